@@ -43,6 +43,15 @@ pub enum ChainDepthError {
     #[msg("Job has not been completed yet")]
     JobNotCompleted,
 
+    #[msg("Job has expired: wait for it to be cleared")]
+    JobExpired,
+
+    #[msg("Job has not expired yet")]
+    JobNotExpired,
+
+    #[msg("Expected [helper_stake, player_account, player_token_account, player] per helper")]
+    InvalidExpireJobAccounts,
+
     #[msg("Too many active jobs: abandon one first")]
     TooManyActiveJobs,
 
@@ -52,18 +61,51 @@ pub enum ChainDepthError {
     #[msg("Invalid item id")]
     InvalidItemId,
 
+    #[msg("Item definition missing from remaining accounts or not registered")]
+    MissingItemDefinition,
+
     #[msg("Invalid item amount")]
     InvalidItemAmount,
 
     #[msg("Not enough items")]
     InsufficientItemAmount,
 
+    #[msg("Item has no use effect")]
+    ItemNotUsable,
+
+    #[msg("Item is not tradeable and cannot be removed")]
+    ItemNotTradeable,
+
+    #[msg("A buff is already active")]
+    BuffAlreadyActive,
+
+    #[msg("No room left for another buff bonus on this job or boss fight")]
+    BuffScheduleFull,
+
+    #[msg("Buff schedule account required while buff bonuses are running")]
+    BuffScheduleMissing,
+
+    #[msg("Room has already been discovered")]
+    RoomAlreadyDiscovered,
+
+    #[msg("This ring has no key chest")]
+    NoKeyChestInRing,
+
+    #[msg("Already in the key chest room")]
+    AlreadyAtKeyChest,
+
+    #[msg("Carried loot is already protected")]
+    LootAlreadyProtected,
+
     #[msg("Missing required key item")]
     MissingRequiredKey,
 
     #[msg("Invalid lock kind")]
     InvalidLockKind,
 
+    #[msg("Seal holds until a boss in this ring is defeated")]
+    BossSealIntact,
+
     // Loot errors
     #[msg("Room has no chest")]
     NoChest,
@@ -71,6 +113,15 @@ pub enum ChainDepthError {
     #[msg("Already looted this chest")]
     AlreadyLooted,
 
+    #[msg("Loot cannot be revealed until its reveal slot has passed")]
+    LootRevealPending,
+
+    #[msg("Loot has already been revealed")]
+    LootAlreadyRevealed,
+
+    #[msg("Invalid SlotHashes sysvar account")]
+    InvalidSlotHashes,
+
     #[msg("Treasury has insufficient SOL to reimburse room rent")]
     TreasuryInsufficientFunds,
 
@@ -86,6 +137,18 @@ pub enum ChainDepthError {
     #[msg("Cannot exit while having active jobs")]
     CannotExitWithActiveJobs,
 
+    #[msg("Player is not at a staircase")]
+    NotAtStairs,
+
+    #[msg("Cannot change floors while having active jobs")]
+    CannotChangeFloorWithActiveJobs,
+
+    #[msg("Cannot recall while having active jobs")]
+    CannotRecallWithActiveJobs,
+
+    #[msg("Player is already at the entrance room")]
+    AlreadyAtEntranceRoom,
+
     #[msg("No boss in this room center")]
     NoBoss,
 
@@ -101,6 +164,12 @@ pub enum ChainDepthError {
     #[msg("Player is not a fighter for this boss")]
     NotBossFighter,
 
+    #[msg("Boss fight already has the maximum number of fighters")]
+    BossFightFull,
+
+    #[msg("Leave the boss fight first")]
+    InBossFight,
+
     #[msg("Invalid center type")]
     InvalidCenterType,
 
@@ -129,6 +198,27 @@ pub enum ChainDepthError {
     #[msg("Season has not ended yet")]
     SeasonNotEnded,
 
+    #[msg("Season seed belongs to the current season")]
+    SeasonStillCurrent,
+
+    #[msg("Player already belongs to the current season")]
+    PlayerAlreadyInCurrentSeason,
+
+    #[msg("Player belongs to an earlier season: respawn first")]
+    PlayerNotInCurrentSeason,
+
+    #[msg("Helper stakes must be recovered before closing")]
+    StaleStakesOutstanding,
+
+    #[msg("Account already uses the current layout")]
+    AccountAlreadyMigrated,
+
+    #[msg("Invalid game config")]
+    InvalidGameConfig,
+
+    #[msg("Invalid season layout: grid size or spawn point out of range")]
+    InvalidSeasonLayout,
+
     #[msg("Unauthorized: only admin can perform this action")]
     Unauthorized,
 
@@ -1,19 +1,24 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{BossContributor, GameConfig, KeyChestBearing, RoomScoutReport, SeasonLayout};
+
 /// Emitted when a job is completed and a wall opens
 #[event]
 pub struct JobCompleted {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
     pub new_depth: u32,
     pub helpers_count: u32,
-    pub reward_per_helper: u64,
+    pub bonus_pool: u64,
+    pub total_contribution: u64,
 }
 
-/// Emitted when a player loots a chest
+/// Emitted when a player's chest loot is revealed
 #[event]
 pub struct ChestLooted {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub player: Pubkey,
@@ -21,6 +26,19 @@ pub struct ChestLooted {
     pub item_amount: u8,
 }
 
+/// Emitted when a chest or boss loot roll is committed (or re-committed
+/// after its reveal window lapsed). `reveal_loot` can grant it after `reveal_slot`.
+#[event]
+pub struct LootCommitted {
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub player: Pubkey,
+    pub kind: u8,
+    pub commit_slot: u64,
+    pub reveal_slot: u64,
+}
+
 /// Emitted when a season resets
 #[event]
 pub struct SeasonReset {
@@ -28,22 +46,29 @@ pub struct SeasonReset {
     pub new_seed: u64,
     pub old_depth: u32,
     pub end_slot: u64,
+    pub layout: SeasonLayout,
 }
 
 /// Emitted when a player joins a job
 #[event]
 pub struct JobJoined {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
     pub player: Pubkey,
     pub helper_count: u32,
+    pub max_helpers: u32,
+    pub work_rate: u64,
+    /// Extra work rate from an active buff, counted until the buff wears off
+    pub buff_bonus: u64,
     pub stake_amount: u64,
 }
 
 /// Emitted when a job is boosted
 #[event]
 pub struct JobBoosted {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
@@ -55,40 +80,137 @@ pub struct JobBoosted {
 /// Emitted when a player abandons a job
 #[event]
 pub struct JobAbandoned {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
     pub player: Pubkey,
     pub refund_amount: u64,
+    pub slash_amount: u64,
+    pub slash_bps: u16,
+}
+
+/// Emitted when an expired job is cranked and its helpers are refunded
+#[event]
+pub struct JobExpired {
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub direction: u8,
+    pub caller: Pubkey,
+    pub helpers_refunded: u32,
+    pub refunded_amount: u64,
+    pub helpers_remaining: u32,
 }
 
 /// Emitted when a helper claims reward from a completed job
 #[event]
 pub struct JobRewardClaimed {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
     pub player: Pubkey,
     pub payout_amount: u64,
+    pub bonus_amount: u64,
+    pub dust_amount: u64,
+}
+
+/// Emitted when a helper recovers a stake stranded by a season reset or by
+/// the floor-aware upgrade (`recover_legacy_stake`)
+#[event]
+pub struct StaleStakeRecovered {
+    pub season_seed: u64,
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub direction: u8,
+    pub player: Pubkey,
+    pub payout_amount: u64,
+    pub job_completed: bool,
+}
+
+/// Emitted when boss counter-damage brings a player to 0 HP. Their scored
+/// loot is gone and they respawn in the start room of the same floor.
+#[event]
+pub struct PlayerDied {
+    pub player: Pubkey,
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub boss_id: u16,
+    pub damage_taken: u64,
+    pub lost_item_stacks: u32,
+    pub lost_item_units: u32,
+    /// A Phoenix Feather kept the carried loot; nothing was lost
+    pub loot_saved: bool,
+}
+
+/// Emitted when a player from a previous season is moved into the new season's start room
+#[event]
+pub struct StalePlayerRespawned {
+    pub player: Pubkey,
+    pub old_season_seed: u64,
+    pub new_season_seed: u64,
+    pub from_floor: u8,
+    pub from_x: i8,
+    pub from_y: i8,
+}
+
+/// Emitted when a previous-season account is closed and its rent returned to the treasury
+#[event]
+pub struct StaleAccountClosed {
+    pub account: Pubkey,
+    pub account_kind: u8,
+    pub season_seed: u64,
+    pub closer: Pubkey,
+    pub rent_reclaimed: u64,
+    pub crank_reward: u64,
+}
+
+/// Emitted when an account is grown to its current layout
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub account_kind: u8,
+    pub old_space: u32,
+    pub new_space: u32,
+    pub rent_paid: u64,
 }
 
 /// Emitted when a player moves to a new room
 #[event]
 pub struct PlayerMoved {
     pub player: Pubkey,
+    pub floor: u8,
     pub from_x: i8,
     pub from_y: i8,
     pub to_x: i8,
     pub to_y: i8,
 }
 
-/// Emitted when a player unlocks a locked door with a key item
+/// Emitted when a player takes the stairs to another floor
+#[event]
+pub struct FloorChanged {
+    pub player: Pubkey,
+    pub from_floor: u8,
+    pub from_x: i8,
+    pub from_y: i8,
+    pub to_floor: u8,
+    pub to_x: i8,
+    pub to_y: i8,
+}
+
+/// Emitted when a player unlocks a locked door with a key item or breaks a boss seal
 #[event]
 pub struct DoorUnlocked {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub direction: u8,
     pub player: Pubkey,
+    pub lock_kind: u8,
+    /// Item consumed to open the door (0 for boss seals)
     pub key_item_id: u16,
 }
 
@@ -112,6 +234,28 @@ pub struct GlobalInitialized {
     pub admin: Pubkey,
     pub skr_mint: Pubkey,
     pub end_slot: u64,
+    pub layout: SeasonLayout,
+}
+
+/// Emitted when the admin changes the game config
+#[event]
+pub struct GameConfigUpdated {
+    pub admin: Pubkey,
+    pub config: GameConfig,
+}
+
+/// Emitted when the admin creates or updates an item definition
+#[event]
+pub struct ItemDefinitionUpserted {
+    pub admin: Pubkey,
+    pub item_id: u16,
+    pub category: u8,
+    pub score_value: u64,
+    pub base_durability: u16,
+    pub combat_dps: u64,
+    pub mining_speed: u16,
+    pub stackable: bool,
+    pub tradeable: bool,
 }
 
 /// Emitted when admin resets a specific player's state for testing.
@@ -144,8 +288,71 @@ pub struct ItemEquipped {
     pub item_id: u16,
 }
 
+/// Emitted when a player consumes a buff item
+#[event]
+pub struct BuffActivated {
+    pub player: Pubkey,
+    pub item_id: u16,
+    pub expires_slot: u64,
+    pub work_rate_bonus_percent: u64,
+    pub dps_bonus_percent: u64,
+}
+
+/// Emitted when a Void Shard recalls a player to the entrance room
+#[event]
+pub struct PlayerRecalled {
+    pub player: Pubkey,
+    pub from_floor: u8,
+    pub from_x: i8,
+    pub from_y: i8,
+}
+
+/// Emitted when a Phoenix Feather protects a player's carried loot
+#[event]
+pub struct LootProtected {
+    pub player: Pubkey,
+}
+
+/// Emitted when a Mystic Orb reveals an undiscovered room
+#[event]
+pub struct RoomScouted {
+    pub player: Pubkey,
+    pub report: RoomScoutReport,
+}
+
+/// Emitted when a Rusted Compass points toward the ring's key chest
+#[event]
+pub struct KeyChestBearingRead {
+    pub player: Pubkey,
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub bearing: KeyChestBearing,
+}
+
+/// Emitted when a worn tool reaches zero durability and is destroyed
+#[event]
+pub struct ItemBroken {
+    pub player: Pubkey,
+    pub item_id: u16,
+    pub unequipped: bool,
+}
+
 #[event]
 pub struct BossFightJoined {
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub player: Pubkey,
+    pub dps: u64,
+    /// Extra DPS from an active buff, counted until the buff wears off
+    pub buff_bonus: u64,
+    pub fighter_count: u32,
+}
+
+#[event]
+pub struct BossFightLeft {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub player: Pubkey,
@@ -155,6 +362,7 @@ pub struct BossFightJoined {
 
 #[event]
 pub struct BossTicked {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub boss_id: u16,
@@ -163,13 +371,31 @@ pub struct BossTicked {
     pub fighter_count: u32,
 }
 
+/// Emitted by the settlement that brings a boss to 0 HP
+#[event]
+pub struct BossDefeated {
+    pub room_floor: u8,
+    pub room_x: i8,
+    pub room_y: i8,
+    pub boss_id: u16,
+    pub defeated_slot: u64,
+    pub fighter_count: u32,
+    /// Highest damage first, at most `BOSS_TOP_CONTRIBUTORS` entries
+    pub top_contributors: Vec<BossContributor>,
+    /// Fighter whose strike took the boss's last HP; only they get the last-hit drop
+    pub last_hitter: Pubkey,
+}
+
 #[event]
 pub struct BossLooted {
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub player: Pubkey,
     pub item_type: u8,
     pub item_amount: u8,
+    /// Extra drop for the fighter who landed the last hit
+    pub last_hit_bonus: bool,
 }
 
 /// Item types for loot
@@ -178,3 +404,20 @@ pub mod item_types {
     pub const TOOL: u8 = 1;
     pub const BUFF: u8 = 2;
 }
+
+/// Account kinds reported by StaleAccountClosed
+pub mod stale_account_kinds {
+    pub const ROOM: u8 = 0;
+    pub const PRESENCE: u8 = 1;
+    pub const LOOT_RECEIPT: u8 = 2;
+    pub const BOSS_FIGHT: u8 = 3;
+    pub const ESCROW: u8 = 4;
+}
+
+/// Account kinds reported by AccountMigrated
+pub mod migrated_account_kinds {
+    pub const GLOBAL: u8 = 0;
+    pub const PLAYER: u8 = 1;
+    pub const HELPER_STAKE: u8 = 2;
+    pub const INVENTORY: u8 = 3;
+}
@@ -5,8 +5,8 @@ use crate::errors::ChainDepthError;
 use crate::events::JobAbandoned;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    session_instruction_bits, GlobalAccount, HelperStake, PlayerAccount, RoomAccount, RoomPresence,
-    SessionAuthority,
+    passed_buff_schedule, session_instruction_bits, BuffSchedule, GlobalAccount, HelperStake,
+    PlayerAccount, RoomAccount, RoomPresence, SessionAuthority, BPS_DENOMINATOR,
 };
 
 #[derive(Accounts)]
@@ -38,6 +38,7 @@ pub struct AbandonJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
@@ -50,6 +51,7 @@ pub struct AbandonJob<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -95,6 +97,14 @@ pub struct AbandonJob<'info> {
     )]
     pub player_token_account: Account<'info, TokenAccount>,
 
+    /// Buff bonuses of this job; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     #[account(
         mut,
         seeds = [
@@ -124,6 +134,7 @@ pub fn handler(ctx: Context<AbandonJob>, direction: u8) -> Result<()> {
     );
 
     let room = &mut ctx.accounts.room;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
     let player_account = &mut ctx.accounts.player_account;
     let player_key = ctx.accounts.player.key();
     let dir_idx = direction as usize;
@@ -134,15 +145,28 @@ pub fn handler(ctx: Context<AbandonJob>, direction: u8) -> Result<()> {
         ChainDepthError::JobAlreadyCompleted
     );
 
+    // Settle at the old rate before this helper's work stops counting,
+    // so the penalty reflects up-to-date progress.
+    let clock = Clock::get()?;
+    room.settle_job_progress(dir_idx, buff_schedule.as_deref_mut(), clock.slot)?;
+
     let stake = ctx.accounts.helper_stake.amount;
+    let slots_since_join = clock
+        .slot
+        .saturating_sub(ctx.accounts.helper_stake.joined_slot);
+    let slash_bps = ctx.accounts.global.config.abandon_slash_bps(
+        slots_since_join,
+        room.progress[dir_idx],
+        room.base_slots[dir_idx],
+    );
+    let slash_amount =
+        (u128::from(stake) * u128::from(slash_bps) / u128::from(BPS_DENOMINATOR)) as u64;
     let refund_amount = stake
-        .checked_mul(RoomAccount::ABANDON_REFUND_PERCENT)
-        .ok_or(ChainDepthError::Overflow)?
-        / 100;
-    let slash_amount = stake
-        .checked_sub(refund_amount)
+        .checked_sub(slash_amount)
         .ok_or(ChainDepthError::Overflow)?;
 
+    room.remove_helper_work(dir_idx, &ctx.accounts.helper_stake, buff_schedule)?;
+
     room.total_staked[dir_idx] = room.total_staked[dir_idx]
         .checked_sub(stake)
         .ok_or(ChainDepthError::Overflow)?;
@@ -151,10 +175,7 @@ pub fn handler(ctx: Context<AbandonJob>, direction: u8) -> Result<()> {
         .ok_or(ChainDepthError::Overflow)?;
 
     if room.helper_counts[dir_idx] == 0 {
-        room.progress[dir_idx] = 0;
-        room.start_slot[dir_idx] = 0;
-        room.bonus_per_helper[dir_idx] = 0;
-        room.job_completed[dir_idx] = false;
+        room.reset_job(dir_idx);
     }
 
     player_account.remove_job(room.x, room.y, direction);
@@ -169,34 +190,41 @@ pub fn handler(ctx: Context<AbandonJob>, direction: u8) -> Result<()> {
     ];
     let escrow_signer = &[&escrow_seeds[..]];
 
-    let refund_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.escrow.to_account_info(),
-            to: ctx.accounts.player_token_account.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
-        escrow_signer,
-    );
-    token::transfer(refund_ctx, refund_amount)?;
-
-    let slash_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.escrow.to_account_info(),
-            to: ctx.accounts.prize_pool.to_account_info(),
-            authority: ctx.accounts.escrow.to_account_info(),
-        },
-        escrow_signer,
-    );
-    token::transfer(slash_ctx, slash_amount)?;
+    if refund_amount > 0 {
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.player_token_account.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            escrow_signer,
+        );
+        token::transfer(refund_ctx, refund_amount)?;
+    }
+
+    if slash_amount > 0 {
+        let slash_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.prize_pool.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            escrow_signer,
+        );
+        token::transfer(slash_ctx, slash_amount)?;
+    }
 
     emit!(JobAbandoned {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
         player: player_key,
         refund_amount,
+        slash_amount,
+        slash_bps,
     });
 
     Ok(())
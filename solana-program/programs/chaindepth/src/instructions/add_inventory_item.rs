@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
 use crate::events::InventoryItemAdded;
-use crate::state::InventoryAccount;
+use crate::state::{InventoryAccount, ItemRegistry};
 
 #[derive(Accounts)]
 pub struct AddInventoryItem<'info> {
@@ -21,7 +21,12 @@ pub struct AddInventoryItem<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<AddInventoryItem>, item_id: u16, amount: u32, durability: u16) -> Result<()> {
+pub fn handler(
+    ctx: Context<AddInventoryItem>,
+    item_id: u16,
+    amount: u32,
+    durability: u16,
+) -> Result<()> {
     require!(item_id > 0, ChainDepthError::InvalidItemId);
     require!(amount > 0, ChainDepthError::InvalidItemAmount);
 
@@ -32,7 +37,8 @@ pub fn handler(ctx: Context<AddInventoryItem>, item_id: u16, amount: u32, durabi
         inventory.bump = ctx.bumps.inventory;
     }
 
-    inventory.add_item(item_id, amount, durability)?;
+    let stats = ItemRegistry::new(ctx.remaining_accounts).stats(item_id)?;
+    inventory.add_item(item_id, amount, durability, &stats)?;
 
     emit!(InventoryItemAdded {
         player: ctx.accounts.player.key(),
@@ -4,7 +4,10 @@ use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use crate::errors::ChainDepthError;
 use crate::events::JobBoosted;
 use crate::instructions::session_auth::authorize_player_action;
-use crate::state::{session_instruction_bits, GlobalAccount, RoomAccount, SessionAuthority};
+use crate::state::{
+    passed_buff_schedule, session_instruction_bits, BuffSchedule, GlobalAccount, RoomAccount,
+    SessionAuthority,
+};
 
 #[derive(Accounts)]
 #[instruction(direction: u8, boost_amount: u64)]
@@ -27,6 +30,7 @@ pub struct BoostJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
@@ -49,6 +53,14 @@ pub struct BoostJob<'info> {
     )]
     pub player_token_account: Account<'info, TokenAccount>,
 
+    /// Buff bonuses of this job; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     #[account(
         mut,
         seeds = [
@@ -86,6 +98,7 @@ pub fn handler(ctx: Context<BoostJob>, direction: u8, boost_amount: u64) -> Resu
 
     let room = &mut ctx.accounts.room;
     let dir_idx = direction as usize;
+    let clock = Clock::get()?;
 
     // Check there's an active job
     require!(
@@ -102,6 +115,13 @@ pub fn handler(ctx: Context<BoostJob>, direction: u8, boost_amount: u64) -> Resu
         .checked_mul(RoomAccount::BOOST_PROGRESS)
         .ok_or(ChainDepthError::Overflow)?;
 
+    // Settle worked progress first so the boost is not lost on the next tick.
+    room.settle_job_progress(
+        dir_idx,
+        passed_buff_schedule(&mut ctx.accounts.buff_schedule),
+        clock.slot,
+    )?;
+
     // Add progress (capped at base_slots)
     room.progress[dir_idx] = room.progress[dir_idx]
         .checked_add(boost_progress)
@@ -120,6 +140,7 @@ pub fn handler(ctx: Context<BoostJob>, direction: u8, boost_amount: u64) -> Resu
     token::transfer(transfer_ctx, boost_amount)?;
 
     emit!(JobBoosted {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
@@ -37,6 +37,7 @@ pub struct ClaimJobReward<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
@@ -49,6 +50,7 @@ pub struct ClaimJobReward<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -77,6 +79,13 @@ pub struct ClaimJobReward<'info> {
     )]
     pub helper_stake: Account<'info, HelperStake>,
 
+    /// Prize pool receives rounding dust from the last claim
+    #[account(
+        mut,
+        constraint = prize_pool.key() == global.prize_pool
+    )]
+    pub prize_pool: Account<'info, TokenAccount>,
+
     #[account(
         mut,
         constraint = player_token_account.mint == global.skr_mint,
@@ -121,11 +130,15 @@ pub fn handler(ctx: Context<ClaimJobReward>, direction: u8) -> Result<()> {
         ChainDepthError::JobNotCompleted
     );
 
-    let stake_amount = ctx.accounts.helper_stake.amount;
-    let bonus_amount = room.bonus_per_helper[dir_idx];
+    let helper_stake = &ctx.accounts.helper_stake;
+    let stake_amount = helper_stake.amount;
+    let bonus_amount = room.helper_bonus_share(dir_idx, helper_stake);
     let payout_amount = stake_amount
         .checked_add(bonus_amount)
         .ok_or(ChainDepthError::Overflow)?;
+    room.bonus_paid[dir_idx] = room.bonus_paid[dir_idx]
+        .checked_add(bonus_amount)
+        .ok_or(ChainDepthError::Overflow)?;
 
     room.total_staked[dir_idx] = room.total_staked[dir_idx]
         .checked_sub(stake_amount)
@@ -134,12 +147,11 @@ pub fn handler(ctx: Context<ClaimJobReward>, direction: u8) -> Result<()> {
         .checked_sub(1)
         .ok_or(ChainDepthError::Overflow)?;
 
+    let mut dust_amount = 0;
     if room.helper_counts[dir_idx] == 0 {
-        room.progress[dir_idx] = 0;
-        room.start_slot[dir_idx] = 0;
+        dust_amount = room.unclaimed_bonus(dir_idx);
+        room.reset_job(dir_idx);
         room.base_slots[dir_idx] = RoomAccount::calculate_base_slots(ctx.accounts.global.depth);
-        room.job_completed[dir_idx] = false;
-        room.bonus_per_helper[dir_idx] = 0;
     }
 
     player_account.remove_job(room.x, room.y, direction);
@@ -165,12 +177,28 @@ pub fn handler(ctx: Context<ClaimJobReward>, direction: u8) -> Result<()> {
     );
     token::transfer(payout_ctx, payout_amount)?;
 
+    if dust_amount > 0 {
+        let dust_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.prize_pool.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            escrow_signer,
+        );
+        token::transfer(dust_ctx, dust_amount)?;
+    }
+
     emit!(JobRewardClaimed {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
         player: ctx.accounts.player.key(),
         payout_amount,
+        bonus_amount,
+        dust_amount,
     });
 
     Ok(())
@@ -0,0 +1,373 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+
+use crate::errors::ChainDepthError;
+use crate::events::{stale_account_kinds, StaleAccountClosed};
+use crate::state::{BossFightAccount, GlobalAccount, LootReceipt, RoomAccount, RoomPresence};
+
+/// Lamports paid to whoever cranks a stale account closed (taken from the reclaimed rent).
+pub const STALE_CLOSE_CRANK_REWARD_LAMPORTS: u64 = 5_000;
+
+/// Close a previous-season room once all helper stakes have been recovered.
+#[derive(Accounts)]
+pub struct CloseStaleRoom<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    /// Global game state - receives reclaimed rent as treasury
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &room.season_seed.to_le_bytes(),
+            &[room.floor],
+            &[room.x as u8],
+            &[room.y as u8]
+        ],
+        bump = room.bump,
+        constraint = room.season_seed != global.season_seed @ ChainDepthError::SeasonStillCurrent
+    )]
+    pub room: Account<'info, RoomAccount>,
+}
+
+pub fn close_stale_room_handler(ctx: Context<CloseStaleRoom>) -> Result<()> {
+    let room = &ctx.accounts.room;
+    require!(
+        room.helper_counts.iter().all(|count| *count == 0),
+        ChainDepthError::StaleStakesOutstanding
+    );
+
+    let room_info = room.to_account_info();
+    let rent_reclaimed = room_info.lamports();
+    let crank_reward = pay_crank_reward(&room_info, &ctx.accounts.closer.to_account_info())?;
+
+    emit!(StaleAccountClosed {
+        account: room.key(),
+        account_kind: stale_account_kinds::ROOM,
+        season_seed: room.season_seed,
+        closer: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        crank_reward,
+    });
+
+    Ok(())
+}
+
+/// Close a previous-season room presence marker.
+#[derive(Accounts)]
+pub struct CloseStalePresence<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &room_presence.season_seed.to_le_bytes(),
+            &[room_presence.room_floor],
+            &[room_presence.room_x as u8],
+            &[room_presence.room_y as u8],
+            room_presence.player.as_ref()
+        ],
+        bump = room_presence.bump,
+        constraint = room_presence.season_seed != global.season_seed @ ChainDepthError::SeasonStillCurrent
+    )]
+    pub room_presence: Account<'info, RoomPresence>,
+}
+
+pub fn close_stale_presence_handler(ctx: Context<CloseStalePresence>) -> Result<()> {
+    let room_presence = &ctx.accounts.room_presence;
+    let presence_info = room_presence.to_account_info();
+    let rent_reclaimed = presence_info.lamports();
+    let crank_reward = pay_crank_reward(&presence_info, &ctx.accounts.closer.to_account_info())?;
+
+    emit!(StaleAccountClosed {
+        account: room_presence.key(),
+        account_kind: stale_account_kinds::PRESENCE,
+        season_seed: room_presence.season_seed,
+        closer: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        crank_reward,
+    });
+
+    Ok(())
+}
+
+/// Close a previous-season chest/boss loot receipt.
+#[derive(Accounts)]
+pub struct CloseStaleLootReceipt<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            LootReceipt::SEED_PREFIX,
+            &loot_receipt.season_seed.to_le_bytes(),
+            &[loot_receipt.room_floor],
+            &[loot_receipt.room_x as u8],
+            &[loot_receipt.room_y as u8],
+            loot_receipt.player.as_ref()
+        ],
+        bump = loot_receipt.bump,
+        constraint = loot_receipt.season_seed != global.season_seed @ ChainDepthError::SeasonStillCurrent
+    )]
+    pub loot_receipt: Account<'info, LootReceipt>,
+}
+
+pub fn close_stale_loot_receipt_handler(ctx: Context<CloseStaleLootReceipt>) -> Result<()> {
+    let loot_receipt = &ctx.accounts.loot_receipt;
+    let receipt_info = loot_receipt.to_account_info();
+    let rent_reclaimed = receipt_info.lamports();
+    let crank_reward = pay_crank_reward(&receipt_info, &ctx.accounts.closer.to_account_info())?;
+
+    emit!(StaleAccountClosed {
+        account: loot_receipt.key(),
+        account_kind: stale_account_kinds::LOOT_RECEIPT,
+        season_seed: loot_receipt.season_seed,
+        closer: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        crank_reward,
+    });
+
+    Ok(())
+}
+
+/// Close a previous-season boss fight marker.
+/// The room is addressed by seed so this still works after the room itself was closed.
+#[derive(Accounts)]
+#[instruction(season_seed: u64, room_floor: u8, room_x: i8, room_y: i8)]
+pub struct CloseStaleBossFight<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// CHECK: only used as a PDA seed; may already be closed
+    #[account(
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &season_seed.to_le_bytes(),
+            &[room_floor],
+            &[room_x as u8],
+            &[room_y as u8]
+        ],
+        bump
+    )]
+    pub room: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = global,
+        seeds = [BossFightAccount::SEED_PREFIX, room.key().as_ref(), boss_fight.player.as_ref()],
+        bump = boss_fight.bump
+    )]
+    pub boss_fight: Account<'info, BossFightAccount>,
+}
+
+pub fn close_stale_boss_fight_handler(
+    ctx: Context<CloseStaleBossFight>,
+    season_seed: u64,
+    _room_floor: u8,
+    _room_x: i8,
+    _room_y: i8,
+) -> Result<()> {
+    require!(
+        season_seed != ctx.accounts.global.season_seed,
+        ChainDepthError::SeasonStillCurrent
+    );
+
+    let boss_fight_info = ctx.accounts.boss_fight.to_account_info();
+    let rent_reclaimed = boss_fight_info.lamports();
+    let crank_reward = pay_crank_reward(&boss_fight_info, &ctx.accounts.closer.to_account_info())?;
+
+    emit!(StaleAccountClosed {
+        account: ctx.accounts.boss_fight.key(),
+        account_kind: stale_account_kinds::BOSS_FIGHT,
+        season_seed,
+        closer: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        crank_reward,
+    });
+
+    Ok(())
+}
+
+/// Close a previous-season job escrow token account.
+/// Leftover tokens (bonus dust) are swept to the prize pool before closing.
+#[derive(Accounts)]
+#[instruction(season_seed: u64, room_floor: u8, room_x: i8, room_y: i8, direction: u8)]
+pub struct CloseStaleEscrow<'info> {
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// CHECK: PDA seed for the escrow; deserialized only if it still exists
+    #[account(
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &season_seed.to_le_bytes(),
+            &[room_floor],
+            &[room_x as u8],
+            &[room_y as u8]
+        ],
+        bump
+    )]
+    pub room: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Prize pool receives any tokens left in the escrow
+    #[account(
+        mut,
+        constraint = prize_pool.key() == global.prize_pool
+    )]
+    pub prize_pool: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn close_stale_escrow_handler(
+    ctx: Context<CloseStaleEscrow>,
+    season_seed: u64,
+    _room_floor: u8,
+    _room_x: i8,
+    _room_y: i8,
+    direction: u8,
+) -> Result<()> {
+    require!(
+        RoomAccount::is_valid_direction(direction),
+        ChainDepthError::InvalidDirection
+    );
+    require!(
+        season_seed != ctx.accounts.global.season_seed,
+        ChainDepthError::SeasonStillCurrent
+    );
+
+    // A closed room guarantees every stake was recovered (close_stale_room checks it).
+    if !ctx.accounts.room.data_is_empty() {
+        require_keys_eq!(
+            *ctx.accounts.room.owner,
+            crate::ID,
+            ChainDepthError::Unauthorized
+        );
+        let room_data = ctx.accounts.room.try_borrow_data()?;
+        let room = RoomAccount::try_deserialize(&mut &room_data[..])?;
+        require!(
+            room.helper_counts[direction as usize] == 0,
+            ChainDepthError::StaleStakesOutstanding
+        );
+    }
+
+    let room_key = ctx.accounts.room.key();
+    let escrow_seeds = &[
+        b"escrow".as_ref(),
+        room_key.as_ref(),
+        &[direction],
+        &[ctx.bumps.escrow],
+    ];
+    let escrow_signer = &[&escrow_seeds[..]];
+
+    let leftover_amount = ctx.accounts.escrow.amount;
+    if leftover_amount > 0 {
+        let sweep_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.prize_pool.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            escrow_signer,
+        );
+        token::transfer(sweep_ctx, leftover_amount)?;
+    }
+
+    let rent_reclaimed = ctx.accounts.escrow.to_account_info().lamports();
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.global.to_account_info(),
+            authority: ctx.accounts.escrow.to_account_info(),
+        },
+        escrow_signer,
+    );
+    token::close_account(close_ctx)?;
+
+    // Rent already landed in the treasury via the CPI, so the reward comes from there.
+    let crank_reward = pay_crank_reward(
+        &ctx.accounts.global.to_account_info(),
+        &ctx.accounts.closer.to_account_info(),
+    )?;
+
+    emit!(StaleAccountClosed {
+        account: ctx.accounts.escrow.key(),
+        account_kind: stale_account_kinds::ESCROW,
+        season_seed,
+        closer: ctx.accounts.closer.key(),
+        rent_reclaimed,
+        crank_reward,
+    });
+
+    Ok(())
+}
+
+/// Move the crank reward out of `source` (manual lamport transfer) before the
+/// remaining rent is swept to the treasury.
+fn pay_crank_reward(source: &AccountInfo, closer: &AccountInfo) -> Result<u64> {
+    let crank_reward = STALE_CLOSE_CRANK_REWARD_LAMPORTS.min(source.lamports());
+    if crank_reward == 0 {
+        return Ok(0);
+    }
+
+    **source.try_borrow_mut_lamports()? = source
+        .lamports()
+        .checked_sub(crank_reward)
+        .ok_or(ChainDepthError::TreasuryInsufficientFunds)?;
+    **closer.try_borrow_mut_lamports()? = closer
+        .lamports()
+        .checked_add(crank_reward)
+        .ok_or(ChainDepthError::Overflow)?;
+
+    Ok(crank_reward)
+}
@@ -3,10 +3,12 @@ use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 use crate::errors::ChainDepthError;
 use crate::events::JobCompleted;
+use crate::instructions::item_wear::wear_equipped_item;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
     calculate_depth, enforce_special_room_topology, initialize_discovered_room,
-    session_instruction_bits, GlobalAccount, HelperStake, PlayerAccount, RoomAccount,
+    passed_buff_schedule, session_instruction_bits, BuffSchedule, FloorParams, GlobalAccount,
+    HelperStake, InventoryAccount, PlayerAccount, RoomAccount, RoomEntrance, RoomPresence,
     SessionAuthority, LOCK_KIND_NONE, WALL_OPEN,
 };
 
@@ -39,6 +41,7 @@ pub struct CompleteJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
@@ -58,6 +61,31 @@ pub struct CompleteJob<'info> {
     )]
     pub helper_stake: Account<'info, HelperStake>,
 
+    /// Inventory holding the completer's equipped tool
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = InventoryAccount::DISCRIMINATOR.len() + InventoryAccount::INIT_SPACE,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump
+    )]
+    pub inventory: Box<Account<'info, InventoryAccount>>,
+
+    /// Completer's presence in their current room (updated if their tool breaks)
+    #[account(
+        mut,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = room_presence.bump
+    )]
+    pub room_presence: Box<Account<'info, RoomPresence>>,
+
     /// Adjacent room that will be opened/initialized
     #[account(
         init_if_needed,
@@ -66,6 +94,7 @@ pub struct CompleteJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[adjacent_x(room.x, direction) as u8],
             &[adjacent_y(room.y, direction) as u8]
         ],
@@ -88,6 +117,14 @@ pub struct CompleteJob<'info> {
     )]
     pub prize_pool: Box<Account<'info, TokenAccount>>,
 
+    /// Buff bonuses of this job; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     #[account(
         mut,
         seeds = [
@@ -136,19 +173,13 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
     let clock = Clock::get()?;
     let dir_idx = direction as usize;
 
-    // Auto-tick: calculate current progress from elapsed slots so the
+    // Auto-tick: settle progress up to the current slot so the
     // client does not need to send a separate TickJob first.
-    {
-        let room = &mut ctx.accounts.room;
-        let helper_count_raw = room.helper_counts[dir_idx] as u64;
-        if helper_count_raw > 0 && room.start_slot[dir_idx] > 0 {
-            let elapsed = clock.slot.saturating_sub(room.start_slot[dir_idx]);
-            let effective = elapsed
-                .checked_mul(helper_count_raw)
-                .ok_or(ChainDepthError::Overflow)?;
-            room.progress[dir_idx] = effective.min(room.base_slots[dir_idx]);
-        }
-    }
+    ctx.accounts.room.settle_job_progress(
+        dir_idx,
+        passed_buff_schedule(&mut ctx.accounts.buff_schedule),
+        clock.slot,
+    )?;
 
     {
         let room = &ctx.accounts.room;
@@ -171,17 +202,19 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
     }
 
     let global_account_info = ctx.accounts.global.to_account_info();
+    let room_floor = ctx.accounts.room.floor;
     let room_x = ctx.accounts.room.x;
     let room_y = ctx.accounts.room.y;
     let helper_count = ctx.accounts.room.helper_counts[dir_idx] as u64;
     let season_seed = ctx.accounts.global.season_seed;
+    let layout = ctx.accounts.global.layout;
     let global_bump = ctx.accounts.global.bump;
 
     {
         let room = &mut ctx.accounts.room;
         room.walls[dir_idx] = WALL_OPEN;
         room.door_lock_kinds[dir_idx] = LOCK_KIND_NONE;
-        room.job_completed[dir_idx] = true;
+        room.record_job_completion(dir_idx, clock.slot)?;
     }
 
     // Free the completer's active job slot immediately so they can join
@@ -191,6 +224,20 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
         .player_account
         .remove_job(room_x, room_y, direction);
 
+    let player_key = ctx.accounts.player.key();
+    let inventory = &mut ctx.accounts.inventory;
+    if inventory.owner == Pubkey::default() {
+        inventory.owner = player_key;
+        inventory.items = Vec::new();
+        inventory.bump = ctx.bumps.inventory;
+    }
+    wear_equipped_item(
+        player_key,
+        inventory,
+        &mut ctx.accounts.player_account,
+        &mut ctx.accounts.room_presence,
+    )?;
+
     let opposite_dir = RoomAccount::opposite_direction(direction);
     let is_new_adjacent_room;
     {
@@ -200,10 +247,13 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
         if is_new_adjacent_room {
             initialize_discovered_room(
                 adjacent,
-                season_seed,
-                adjacent_x(room_x, direction),
-                adjacent_y(room_y, direction),
-                opposite_dir,
+                &FloorParams::new(season_seed, &layout, room_floor),
+                &layout,
+                RoomEntrance {
+                    x: adjacent_x(room_x, direction),
+                    y: adjacent_y(room_y, direction),
+                    direction: opposite_dir,
+                },
                 ctx.accounts.player.key(),
                 clock.slot,
                 ctx.bumps.adjacent_room,
@@ -212,7 +262,7 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
 
         adjacent.walls[opposite_dir as usize] = WALL_OPEN;
         adjacent.door_lock_kinds[opposite_dir as usize] = LOCK_KIND_NONE;
-        enforce_special_room_topology(adjacent);
+        enforce_special_room_topology(adjacent, &layout);
         let return_wall_state = adjacent.walls[opposite_dir as usize];
         msg!(
             "complete_job_topology from=({}, {}) to=({}, {}) dir={} return_dir={} return_wall_state={}",
@@ -231,7 +281,12 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
     }
 
     // --- Token bonus transfer (CPI) BEFORE lamport manipulation ---
-    let new_depth = calculate_depth(ctx.accounts.adjacent_room.x, ctx.accounts.adjacent_room.y);
+    let new_depth = calculate_depth(
+        &layout,
+        room_floor,
+        ctx.accounts.adjacent_room.x,
+        ctx.accounts.adjacent_room.y,
+    );
     {
         let global = &mut ctx.accounts.global;
         if new_depth > global.depth {
@@ -294,19 +349,18 @@ pub fn handler(ctx: Context<CompleteJob>, direction: u8) -> Result<()> {
         );
     }
 
-    let bonus_per_helper = bonus_total / helper_count;
-    {
-        let room = &mut ctx.accounts.room;
-        room.bonus_per_helper[dir_idx] = bonus_per_helper;
-    }
+    // Claimers split the bonus by contributed work; see RoomAccount::helper_bonus_share.
+    ctx.accounts.room.bonus_pool[dir_idx] = bonus_total;
 
     emit!(JobCompleted {
+        room_floor,
         room_x,
         room_y,
         direction,
         new_depth: ctx.accounts.global.depth,
         helpers_count: ctx.accounts.room.helper_counts[dir_idx],
-        reward_per_helper: RoomAccount::STAKE_AMOUNT + bonus_per_helper,
+        bonus_pool: bonus_total,
+        total_contribution: ctx.accounts.room.total_contribution[dir_idx],
     });
 
     Ok(())
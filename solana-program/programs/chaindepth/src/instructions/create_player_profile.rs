@@ -3,8 +3,8 @@ use anchor_lang::prelude::*;
 use crate::errors::ChainDepthError;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    item_ids, session_instruction_bits, GlobalAccount, InventoryAccount, PlayerAccount,
-    PlayerProfile, RoomPresence, SessionAuthority,
+    item_ids, session_instruction_bits, GlobalAccount, InventoryAccount, ItemRegistry,
+    PlayerAccount, PlayerProfile, RoomPresence, SessionAuthority,
 };
 
 #[derive(Accounts)]
@@ -52,6 +52,7 @@ pub struct CreatePlayerProfile<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -74,7 +75,11 @@ pub struct CreatePlayerProfile<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<CreatePlayerProfile>, skin_id: u16, display_name: String) -> Result<()> {
+pub fn handler(
+    ctx: Context<CreatePlayerProfile>,
+    skin_id: u16,
+    display_name: String,
+) -> Result<()> {
     authorize_player_action(
         &ctx.accounts.authority,
         &ctx.accounts.player,
@@ -83,7 +88,10 @@ pub fn handler(ctx: Context<CreatePlayerProfile>, skin_id: u16, display_name: St
         0,
     )?;
 
-    require!(display_name.len() <= 24, ChainDepthError::DisplayNameTooLong);
+    require!(
+        display_name.len() <= 24,
+        ChainDepthError::DisplayNameTooLong
+    );
 
     let player_key = ctx.accounts.player.key();
     let profile = &mut ctx.accounts.profile;
@@ -109,7 +117,13 @@ pub fn handler(ctx: Context<CreatePlayerProfile>, skin_id: u16, display_name: St
     profile.display_name = display_name;
 
     if !profile.starter_pickaxe_granted {
-        inventory.add_item(item_ids::BRONZE_PICKAXE, 1, 80)?;
+        let starter = ItemRegistry::new(ctx.remaining_accounts).stats(item_ids::BRONZE_PICKAXE)?;
+        inventory.add_item(
+            item_ids::BRONZE_PICKAXE,
+            1,
+            starter.base_durability,
+            &starter,
+        )?;
         profile.starter_pickaxe_granted = true;
 
         if player_account.equipped_item_id == 0 {
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
-use crate::state::{GlobalAccount, RoomAccount, CENTER_EMPTY};
+use crate::state::{initialize_start_room, GlobalAccount, RoomAccount, ENTRANCE_FLOOR};
 
 #[derive(Accounts)]
 pub struct EnsureStartRoom<'info> {
@@ -22,8 +22,9 @@ pub struct EnsureStartRoom<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
-            &[GlobalAccount::START_X as u8],
-            &[GlobalAccount::START_Y as u8]
+            &[ENTRANCE_FLOOR],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8]
         ],
         bump
     )]
@@ -43,36 +44,15 @@ pub fn handler(ctx: Context<EnsureStartRoom>) -> Result<()> {
     }
 
     let clock = Clock::get()?;
-    start_room.x = GlobalAccount::START_X;
-    start_room.y = GlobalAccount::START_Y;
-    start_room.season_seed = ctx.accounts.global.season_seed;
-    start_room.walls = RoomAccount::generate_start_walls(
+    initialize_start_room(
+        start_room,
         ctx.accounts.global.season_seed,
-        GlobalAccount::START_X,
-        GlobalAccount::START_Y,
+        &ctx.accounts.global.layout,
+        ENTRANCE_FLOOR,
+        ctx.accounts.authority.key(),
+        clock.slot,
+        ctx.bumps.start_room,
     );
-    start_room.helper_counts = [0; 4];
-    start_room.progress = [0; 4];
-    start_room.start_slot = [0; 4];
-    start_room.base_slots = [RoomAccount::calculate_base_slots(0); 4];
-    start_room.total_staked = [0; 4];
-    start_room.job_completed = [false; 4];
-    start_room.bonus_per_helper = [0; 4];
-    start_room.door_lock_kinds = [0; 4];
-    start_room.has_chest = false;
-    start_room.forced_key_drop = false;
-    start_room.center_type = CENTER_EMPTY;
-    start_room.center_id = 0;
-    start_room.boss_max_hp = 0;
-    start_room.boss_current_hp = 0;
-    start_room.boss_last_update_slot = clock.slot;
-    start_room.boss_total_dps = 0;
-    start_room.boss_fighter_count = 0;
-    start_room.boss_defeated = false;
-    start_room.looted_count = 0;
-    start_room.created_by = ctx.accounts.authority.key();
-    start_room.created_slot = clock.slot;
-    start_room.bump = ctx.bumps.start_room;
 
     Ok(())
 }
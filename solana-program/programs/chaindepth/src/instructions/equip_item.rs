@@ -41,6 +41,7 @@ pub struct EquipItem<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
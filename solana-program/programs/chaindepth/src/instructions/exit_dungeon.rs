@@ -4,9 +4,9 @@ use crate::errors::ChainDepthError;
 use crate::events::DungeonExited;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    compute_time_bonus, is_scored_loot_item, score_value_for_item, session_instruction_bits,
-    GlobalAccount, InventoryAccount, PlayerAccount, RoomAccount, RoomPresence, SessionAuthority,
-    DIRECTION_SOUTH, WALL_ENTRANCE_STAIRS,
+    compute_time_bonus, is_scored_loot_item, session_instruction_bits, GlobalAccount,
+    InventoryAccount, PlayerAccount, RoomAccount, RoomPresence, SessionAuthority, DIRECTION_SOUTH,
+    ENTRANCE_FLOOR, PLAYER_MAX_HP, WALL_ENTRANCE_STAIRS,
 };
 
 #[derive(Accounts)]
@@ -35,6 +35,7 @@ pub struct ExitDungeon<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -56,6 +57,7 @@ pub struct ExitDungeon<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -92,11 +94,11 @@ pub fn handler(ctx: Context<ExitDungeon>) -> Result<()> {
     let player_key = ctx.accounts.player.key();
 
     require!(
-        player.is_at_room(room.x, room.y),
+        player.is_at_room(room.floor, room.x, room.y),
         ChainDepthError::NotInRoom
     );
     require!(
-        room.x == GlobalAccount::START_X && room.y == GlobalAccount::START_Y,
+        room.floor == ENTRANCE_FLOOR && ctx.accounts.global.layout.is_start(room.x, room.y),
         ChainDepthError::NotAtEntranceRoom
     );
     require!(
@@ -107,6 +109,7 @@ pub fn handler(ctx: Context<ExitDungeon>) -> Result<()> {
         player.active_jobs.is_empty(),
         ChainDepthError::CannotExitWithActiveJobs
     );
+    require!(!player.is_in_boss_fight(), ChainDepthError::InBossFight);
 
     let inventory = &mut ctx.accounts.inventory;
     if inventory.owner == Pubkey::default() {
@@ -119,14 +122,15 @@ pub fn handler(ctx: Context<ExitDungeon>) -> Result<()> {
         ChainDepthError::Unauthorized
     );
 
+    // Each stack is scored at the value cached when its units were granted.
     let mut loot_score = 0u64;
     let mut extracted_item_stacks = 0u32;
     let mut extracted_item_units = 0u32;
     let mut kept_items = Vec::with_capacity(inventory.items.len());
     for item in inventory.items.iter() {
         if is_scored_loot_item(item.item_id) {
-            let unit_score = score_value_for_item(item.item_id);
-            let stack_score = unit_score
+            let stack_score = item
+                .score_value
                 .checked_mul(item.amount as u64)
                 .ok_or(ChainDepthError::Overflow)?;
             loot_score = loot_score
@@ -166,6 +170,7 @@ pub fn handler(ctx: Context<ExitDungeon>) -> Result<()> {
         .ok_or(ChainDepthError::Overflow)?;
     player.last_extraction_slot = now_slot;
     player.current_run_start_slot = now_slot;
+    player.hp = PLAYER_MAX_HP;
 
     let room_presence = &mut ctx.accounts.room_presence;
     room_presence.is_current = true;
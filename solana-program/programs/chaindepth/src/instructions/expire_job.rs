@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::ChainDepthError;
+use crate::events::JobExpired;
+use crate::state::{
+    passed_buff_schedule, BuffSchedule, GlobalAccount, HelperStake, PlayerAccount, RoomAccount,
+    RoomPresence,
+};
+
+/// Remaining accounts per refunded helper:
+/// [helper_stake (mut), player_account (mut), player_token_account (mut), player (mut),
+///  room_presence (mut, the helper's presence in their current room)]
+pub const EXPIRE_JOB_ACCOUNTS_PER_HELPER: usize = 5;
+
+#[derive(Accounts)]
+#[instruction(direction: u8)]
+pub struct ExpireJob<'info> {
+    /// Anyone can expire a stale job (permissionless crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// Room with the expired job
+    #[account(
+        mut,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[room.floor],
+            &[room.x as u8],
+            &[room.y as u8]
+        ],
+        bump
+    )]
+    pub room: Account<'info, RoomAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// Buff bonuses of this job; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Refund the helpers passed in `remaining_accounts` in full, drop their
+/// stakes and set their presence back to idle if it still shows this job.
+/// Large jobs can be expired over several calls; the direction's job
+/// state is reset once the last helper is refunded.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExpireJob<'info>>,
+    direction: u8,
+) -> Result<()> {
+    require!(
+        RoomAccount::is_valid_direction(direction),
+        ChainDepthError::InvalidDirection
+    );
+
+    let helper_accounts = ctx.remaining_accounts;
+    require!(
+        !helper_accounts.is_empty() && helper_accounts.len() % EXPIRE_JOB_ACCOUNTS_PER_HELPER == 0,
+        ChainDepthError::InvalidExpireJobAccounts
+    );
+
+    let room = &mut ctx.accounts.room;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
+    let clock = Clock::get()?;
+    let dir_idx = direction as usize;
+
+    require!(room.is_rubble(direction), ChainDepthError::NotRubble);
+    require!(
+        room.is_job_expired(
+            dir_idx,
+            clock.slot,
+            ctx.accounts.global.config.job_ttl_slots
+        ),
+        ChainDepthError::JobNotExpired
+    );
+
+    // Settle so helpers that stay (partial batches) keep consistent accounting.
+    room.settle_job_progress(dir_idx, buff_schedule.as_deref_mut(), clock.slot)?;
+
+    let room_key = room.key();
+    let escrow_seeds = &[
+        b"escrow".as_ref(),
+        room_key.as_ref(),
+        &[direction],
+        &[ctx.bumps.escrow],
+    ];
+    let escrow_signer = &[&escrow_seeds[..]];
+
+    let mut helpers_refunded: u32 = 0;
+    let mut refunded_amount: u64 = 0;
+
+    for accounts in helper_accounts.chunks(EXPIRE_JOB_ACCOUNTS_PER_HELPER) {
+        let [stake_info, player_account_info, token_account_info, player_info, presence_info] =
+            accounts
+        else {
+            return err!(ChainDepthError::InvalidExpireJobAccounts);
+        };
+
+        // A stake that was already closed (e.g. listed twice) fails to deserialize here.
+        let helper_stake = Account::<HelperStake>::try_from(stake_info)?;
+        require_keys_eq!(
+            helper_stake.room,
+            room_key,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+        require!(
+            helper_stake.direction == direction,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+        require_keys_eq!(
+            player_info.key(),
+            helper_stake.player,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+
+        let mut player_account = Account::<PlayerAccount>::try_from(player_account_info)?;
+        require_keys_eq!(
+            player_account.owner,
+            helper_stake.player,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+
+        let player_token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+        require_keys_eq!(
+            player_token_account.mint,
+            ctx.accounts.global.skr_mint,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+        require_keys_eq!(
+            player_token_account.owner,
+            helper_stake.player,
+            ChainDepthError::InvalidExpireJobAccounts
+        );
+
+        let stake_amount = helper_stake.amount;
+        room.remove_helper_work(dir_idx, &helper_stake, buff_schedule.as_deref_mut())?;
+        room.total_staked[dir_idx] = room.total_staked[dir_idx]
+            .checked_sub(stake_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+        room.helper_counts[dir_idx] = room.helper_counts[dir_idx]
+            .checked_sub(1)
+            .ok_or(ChainDepthError::Overflow)?;
+
+        if player_account.season_seed == room.season_seed {
+            player_account.remove_job(room.x, room.y, direction);
+            player_account.exit(&crate::ID)?;
+
+            // Presences only exist at their PDA, so matching the owner and the
+            // player's current room identifies the right one.
+            let mut room_presence = Account::<RoomPresence>::try_from(presence_info)?;
+            require!(
+                room_presence.player == helper_stake.player
+                    && room_presence.season_seed == room.season_seed
+                    && room_presence.room_floor == player_account.current_floor
+                    && room_presence.room_x == player_account.current_room_x
+                    && room_presence.room_y == player_account.current_room_y,
+                ChainDepthError::InvalidExpireJobAccounts
+            );
+            // A helper who moved on may be busy with something else there.
+            if room_presence.is_on_door_job(room.floor, room.x, room.y, direction) {
+                room_presence.set_idle();
+                room_presence.exit(&crate::ID)?;
+            }
+        }
+
+        let refund_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: token_account_info.clone(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            escrow_signer,
+        );
+        token::transfer(refund_ctx, stake_amount)?;
+
+        // Stake rent goes back to the helper who paid it.
+        helper_stake.close(player_info.clone())?;
+
+        helpers_refunded = helpers_refunded
+            .checked_add(1)
+            .ok_or(ChainDepthError::Overflow)?;
+        refunded_amount = refunded_amount
+            .checked_add(stake_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+    }
+
+    if room.helper_counts[dir_idx] == 0 {
+        room.reset_job(dir_idx);
+    }
+
+    emit!(JobExpired {
+        room_floor: room.floor,
+        room_x: room.x,
+        room_y: room.y,
+        direction,
+        caller: ctx.accounts.caller.key(),
+        helpers_refunded,
+        refunded_amount,
+        helpers_remaining: room.helper_counts[dir_idx],
+    });
+
+    Ok(())
+}
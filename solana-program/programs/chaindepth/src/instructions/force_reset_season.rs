@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::errors::ChainDepthError;
 use crate::events::SeasonReset;
 use crate::instructions::reset_season::apply_season_reset;
-use crate::state::GlobalAccount;
+use crate::state::{GlobalAccount, SeasonLayout};
 
 #[derive(Accounts)]
 pub struct ForceResetSeason<'info> {
@@ -19,17 +19,18 @@ pub struct ForceResetSeason<'info> {
     pub global: Account<'info, GlobalAccount>,
 }
 
-pub fn handler(ctx: Context<ForceResetSeason>) -> Result<()> {
+pub fn handler(ctx: Context<ForceResetSeason>, layout: Option<SeasonLayout>) -> Result<()> {
     let global = &mut ctx.accounts.global;
     let clock = Clock::get()?;
 
-    let (old_seed, new_seed, old_depth, end_slot) = apply_season_reset(global, clock.slot);
+    let (old_seed, new_seed, old_depth, end_slot) = apply_season_reset(global, clock.slot, layout)?;
 
     emit!(SeasonReset {
         old_seed,
         new_seed,
         old_depth,
         end_slot,
+        layout: global.layout,
     });
 
     Ok(())
@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
+use crate::errors::ChainDepthError;
 use crate::events::GlobalInitialized;
-use crate::state::{GlobalAccount, RoomAccount, CENTER_EMPTY};
+use crate::state::{
+    initialize_start_room, GameConfig, GlobalAccount, RoomAccount, SeasonLayout, ENTRANCE_FLOOR,
+};
 
 #[derive(Accounts)]
-#[instruction(initial_prize_pool_amount: u64, season_seed: u64)]
+#[instruction(initial_prize_pool_amount: u64, season_seed: u64, layout: Option<SeasonLayout>)]
 pub struct InitGlobal<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -41,7 +44,7 @@ pub struct InitGlobal<'info> {
     )]
     pub admin_token_account: Account<'info, TokenAccount>,
 
-    /// Starting room at (5, 5) - uses season_seed passed as instruction arg
+    /// Starting room at the layout's spawn point - uses season_seed passed as instruction arg
     #[account(
         init,
         payer = admin,
@@ -49,8 +52,9 @@ pub struct InitGlobal<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &season_seed.to_le_bytes(),
-            &[GlobalAccount::START_X as u8],
-            &[GlobalAccount::START_Y as u8]
+            &[ENTRANCE_FLOOR],
+            &[layout.unwrap_or_default().start_x as u8],
+            &[layout.unwrap_or_default().start_y as u8]
         ],
         bump
     )]
@@ -60,8 +64,15 @@ pub struct InitGlobal<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<InitGlobal>, initial_prize_pool_amount: u64, season_seed: u64) -> Result<()> {
+pub fn handler(
+    ctx: Context<InitGlobal>,
+    initial_prize_pool_amount: u64,
+    season_seed: u64,
+    layout: Option<SeasonLayout>,
+) -> Result<()> {
     let clock = Clock::get()?;
+    let layout = layout.unwrap_or_default();
+    require!(layout.is_valid(), ChainDepthError::InvalidSeasonLayout);
     
     // Initialize global state
     let global = &mut ctx.accounts.global;
@@ -72,44 +83,20 @@ pub fn handler(ctx: Context<InitGlobal>, initial_prize_pool_amount: u64, season_
     global.admin = ctx.accounts.admin.key();
     global.end_slot = clock.slot + GlobalAccount::SEASON_DURATION_SLOTS;
     global.jobs_completed = 0;
+    global.config = GameConfig::default();
+    global.layout = layout;
     global.bump = ctx.bumps.global;
 
     // Initialize starting room with fixed extraction topology.
-    let start_room = &mut ctx.accounts.start_room;
-    start_room.x = GlobalAccount::START_X;
-    start_room.y = GlobalAccount::START_Y;
-    start_room.season_seed = season_seed;
-    
-    start_room.walls = RoomAccount::generate_start_walls(
+    initialize_start_room(
+        &mut ctx.accounts.start_room,
         season_seed,
-        GlobalAccount::START_X,
-        GlobalAccount::START_Y,
+        &layout,
+        ENTRANCE_FLOOR,
+        ctx.accounts.admin.key(),
+        clock.slot,
+        ctx.bumps.start_room,
     );
-    
-    // Initialize directional job state
-    start_room.helper_counts = [0; 4];
-    start_room.progress = [0; 4];
-    start_room.start_slot = [0; 4];
-    start_room.base_slots = [RoomAccount::calculate_base_slots(0); 4];
-    start_room.total_staked = [0; 4];
-    start_room.job_completed = [false; 4];
-    start_room.bonus_per_helper = [0; 4];
-    start_room.door_lock_kinds = [0; 4];
-    
-    start_room.has_chest = false;
-    start_room.forced_key_drop = false;
-    start_room.center_type = CENTER_EMPTY;
-    start_room.center_id = 0;
-    start_room.boss_max_hp = 0;
-    start_room.boss_current_hp = 0;
-    start_room.boss_last_update_slot = clock.slot;
-    start_room.boss_total_dps = 0;
-    start_room.boss_fighter_count = 0;
-    start_room.boss_defeated = false;
-    start_room.looted_count = 0;
-    start_room.created_by = ctx.accounts.admin.key();
-    start_room.created_slot = clock.slot;
-    start_room.bump = ctx.bumps.start_room;
 
     // Transfer initial prize pool from admin
     if initial_prize_pool_amount > 0 {
@@ -129,6 +116,7 @@ pub fn handler(ctx: Context<InitGlobal>, initial_prize_pool_amount: u64, season_
         admin: ctx.accounts.admin.key(),
         skr_mint: ctx.accounts.skr_mint.key(),
         end_slot: global.end_slot,
+        layout,
     });
 
     Ok(())
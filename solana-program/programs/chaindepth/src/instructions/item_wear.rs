@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::events::ItemBroken;
+use crate::state::{InventoryAccount, PlayerAccount, RoomPresence, TOOL_WEAR_PER_ACTION};
+
+/// Consume durability on the player's equipped tool for one mining or combat action.
+/// A broken tool is removed from the inventory and unequipped once no units remain.
+pub(crate) fn wear_equipped_item(
+    player_key: Pubkey,
+    inventory: &mut InventoryAccount,
+    player_account: &mut PlayerAccount,
+    room_presence: &mut RoomPresence,
+) -> Result<()> {
+    let item_id = player_account.equipped_item_id;
+    if item_id == 0 {
+        return Ok(());
+    }
+
+    let broken = inventory.consume_durability(item_id, TOOL_WEAR_PER_ACTION)?;
+    if !broken {
+        return Ok(());
+    }
+
+    let unequipped = !inventory.has_item(item_id);
+    if unequipped {
+        player_account.equipped_item_id = 0;
+        room_presence.equipped_item_id = 0;
+    }
+
+    emit!(ItemBroken {
+        player: player_key,
+        item_id,
+        unequipped,
+    });
+
+    Ok(())
+}
@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
-use crate::events::BossFightJoined;
+use crate::events::{BossDefeated, BossFightJoined};
+use crate::instructions::item_wear::wear_equipped_item;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    session_instruction_bits, BossFightAccount, GlobalAccount, PlayerAccount, PlayerProfile,
-    RoomAccount, RoomPresence, SessionAuthority, CENTER_BOSS,
+    last_hitter, passed_buff_schedule, session_instruction_bits, slots_to_defeat, top_contributors,
+    weapon_dps, BossFightAccount, BossRoster, BuffSchedule, GlobalAccount, InventoryAccount,
+    ItemRegistry, PlayerAccount, PlayerProfile, RoomAccount, RoomPresence, SessionAuthority,
+    BARE_HANDS_DPS, BUFF_TARGET_BOSS, CENTER_BOSS, MAX_BOSS_FIGHTERS,
 };
 
 #[derive(Accounts)]
@@ -23,6 +26,7 @@ pub struct JoinBossFight<'info> {
     pub global: Account<'info, GlobalAccount>,
 
     #[account(
+        mut,
         seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
         bump = player_account.bump
     )]
@@ -34,11 +38,21 @@ pub struct JoinBossFight<'info> {
     )]
     pub profile: Account<'info, PlayerProfile>,
 
+    /// Inventory holding the equipped weapon (durability affects DPS and wears down)
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
     #[account(
         mut,
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -51,6 +65,7 @@ pub struct JoinBossFight<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -68,6 +83,27 @@ pub struct JoinBossFight<'info> {
     )]
     pub boss_fight: Account<'info, BossFightAccount>,
 
+    /// Every current fighter of this boss; the first fighter creates it
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = BossRoster::DISCRIMINATOR.len() + BossRoster::INIT_SPACE,
+        seeds = [BossRoster::SEED_PREFIX, room.key().as_ref()],
+        bump
+    )]
+    pub boss_roster: Box<Account<'info, BossRoster>>,
+
+    /// Buff bonuses of this boss fight. Pass it when it exists or the player
+    /// is buffed; the first buffed fighter creates it.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = BuffSchedule::DISCRIMINATOR.len() + BuffSchedule::INIT_SPACE,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[BUFF_TARGET_BOSS]],
+        bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     #[account(
         mut,
         seeds = [
@@ -92,41 +128,82 @@ pub fn handler(ctx: Context<JoinBossFight>) -> Result<()> {
     )?;
 
     let room = &mut ctx.accounts.room;
-    let player_account = &ctx.accounts.player_account;
+    let player_account = &mut ctx.accounts.player_account;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
     let clock = Clock::get()?;
 
+    require!(room.center_type == CENTER_BOSS, ChainDepthError::NoBoss);
     require!(
-        room.center_type == CENTER_BOSS,
-        ChainDepthError::NoBoss
+        player_account.is_at_room(room.floor, room.x, room.y),
+        ChainDepthError::NotInRoom
     );
+
     require!(
-        player_account.is_at_room(room.x, room.y),
-        ChainDepthError::NotInRoom
+        !player_account.is_in_boss_fight(),
+        ChainDepthError::AlreadyFightingBoss
     );
     require!(
-        !room.boss_defeated,
-        ChainDepthError::BossAlreadyDefeated
+        (room.boss_fighter_count as usize) < MAX_BOSS_FIGHTERS,
+        ChainDepthError::BossFightFull
     );
 
-    apply_boss_damage(room, clock.slot)?;
-
-    let fighter_dps = weapon_dps(player_account.equipped_item_id);
+    apply_boss_damage(
+        room,
+        buff_schedule.as_deref_mut(),
+        &ctx.accounts.boss_roster,
+        clock.slot,
+    )?;
+    // Damage due since the last update may have killed the boss already.
+    require!(!room.boss_defeated, ChainDepthError::BossAlreadyDefeated);
 
-    room.boss_fighter_count = room
-        .boss_fighter_count
-        .checked_add(1)
-        .ok_or(ChainDepthError::Overflow)?;
-    room.boss_total_dps = room
-        .boss_total_dps
-        .checked_add(fighter_dps)
-        .ok_or(ChainDepthError::Overflow)?;
+    let equipped_dps = equipped_weapon_dps(
+        &ItemRegistry::new(ctx.remaining_accounts),
+        &ctx.accounts.inventory,
+        player_account.equipped_item_id,
+    )?;
+    // An active buff adds to the DPS only until the buff wears off.
+    let buff_bonus = player_account
+        .buffed_dps(equipped_dps, clock.slot)
+        .saturating_sub(equipped_dps);
+    let buff_expires_slot = if buff_bonus > 0 {
+        player_account.buff_expires_slot
+    } else {
+        0
+    };
+    if buff_bonus > 0 {
+        let schedule = buff_schedule.ok_or(ChainDepthError::BuffScheduleMissing)?;
+        if let Some(bump) = ctx.bumps.buff_schedule {
+            schedule.bind(room.key(), BUFF_TARGET_BOSS, bump);
+        }
+        schedule.add_bonus(
+            &mut room.boss_buffs_scheduled,
+            buff_bonus,
+            buff_expires_slot,
+        )?;
+    }
 
     let boss_fight = &mut ctx.accounts.boss_fight;
     boss_fight.player = ctx.accounts.player.key();
     boss_fight.room = room.key();
-    boss_fight.dps = fighter_dps;
+    boss_fight.dps = equipped_dps;
     boss_fight.joined_slot = clock.slot;
+    boss_fight.buff_bonus = buff_bonus;
+    boss_fight.buff_expires_slot = buff_expires_slot;
     boss_fight.bump = ctx.bumps.boss_fight;
+    player_account.boss_fight_room = room.key();
+
+    let boss_roster = &mut ctx.accounts.boss_roster;
+    boss_roster.bind(room.key(), ctx.bumps.boss_roster);
+    boss_roster.add(room.boss_fighter_count, boss_fight.fighter())?;
+
+    room.boss_fighter_count = room
+        .boss_fighter_count
+        .checked_add(1)
+        .ok_or(ChainDepthError::Overflow)?;
+    room.boss_total_dps = equipped_dps
+        .checked_add(buff_bonus)
+        .and_then(|dps| dps.checked_add(room.boss_total_dps))
+        .ok_or(ChainDepthError::Overflow)?;
 
     let room_presence = &mut ctx.accounts.room_presence;
     room_presence.skin_id = ctx.accounts.profile.skin_id;
@@ -134,47 +211,121 @@ pub fn handler(ctx: Context<JoinBossFight>) -> Result<()> {
     room_presence.set_boss_fight();
     room_presence.is_current = true;
 
+    // DPS is locked in above, so wear only affects the next fight.
+    wear_equipped_item(
+        ctx.accounts.player.key(),
+        &mut ctx.accounts.inventory,
+        player_account,
+        room_presence,
+    )?;
+
     emit!(BossFightJoined {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         player: ctx.accounts.player.key(),
-        dps: fighter_dps,
+        dps: equipped_dps,
+        buff_bonus,
         fighter_count: room.boss_fighter_count,
     });
 
     Ok(())
 }
 
-pub(crate) fn apply_boss_damage(room: &mut Account<RoomAccount>, current_slot: u64) -> Result<()> {
+/// Apply fighter damage since the last update. Buff bonuses that wore off in
+/// between stop counting at their own slot. The fight's `buff_schedule` is
+/// required while any buff bonus is scheduled; the call that brings the boss
+/// to 0 HP settles the kill from the fight's `boss_roster`.
+pub(crate) fn apply_boss_damage(
+    room: &mut RoomAccount,
+    buff_schedule: Option<&mut BuffSchedule>,
+    boss_roster: &BossRoster,
+    current_slot: u64,
+) -> Result<()> {
     if room.center_type != CENTER_BOSS || room.boss_defeated || room.boss_fighter_count == 0 {
         room.boss_last_update_slot = current_slot;
         return Ok(());
     }
 
-    let elapsed_slots = current_slot.saturating_sub(room.boss_last_update_slot);
-    if elapsed_slots == 0 || room.boss_total_dps == 0 {
-        room.boss_last_update_slot = current_slot;
-        return Ok(());
+    if room.boss_buffs_scheduled > 0 {
+        let schedule = buff_schedule.ok_or(ChainDepthError::BuffScheduleMissing)?;
+        while let Some(expired) =
+            schedule.take_expired(&mut room.boss_buffs_scheduled, current_slot)
+        {
+            if let Some(hp_left) = damage_boss_until(room, expired.expires_slot)? {
+                room.boss_last_update_slot = current_slot;
+                settle_boss_defeat(room, boss_roster, hp_left);
+                return Ok(());
+            }
+            room.boss_total_dps = room.boss_total_dps.saturating_sub(expired.bonus);
+        }
+    }
+    if let Some(hp_left) = damage_boss_until(room, current_slot)? {
+        settle_boss_defeat(room, boss_roster, hp_left);
     }
+    Ok(())
+}
 
-    let damage = elapsed_slots
-        .checked_mul(room.boss_total_dps)
-        .ok_or(ChainDepthError::Overflow)?;
+/// Damage the boss at the current total DPS up to `slot`. If the boss dies
+/// on the way, returns the HP it had left going into the slot it died in.
+fn damage_boss_until(room: &mut RoomAccount, slot: u64) -> Result<Option<u64>> {
+    let elapsed_slots = slot.saturating_sub(room.boss_last_update_slot);
+    if elapsed_slots == 0 || room.boss_total_dps == 0 {
+        room.boss_last_update_slot = slot;
+        return Ok(None);
+    }
 
-    room.boss_current_hp = room.boss_current_hp.saturating_sub(damage);
-    room.boss_last_update_slot = current_slot;
-    if room.boss_current_hp == 0 {
-        room.boss_defeated = true;
+    let slots_to_kill = slots_to_defeat(room.boss_current_hp, room.boss_total_dps);
+    if elapsed_slots < slots_to_kill {
+        let damage = elapsed_slots
+            .checked_mul(room.boss_total_dps)
+            .ok_or(ChainDepthError::Overflow)?;
+        room.boss_current_hp = room.boss_current_hp.saturating_sub(damage);
+        room.boss_last_update_slot = slot;
+        return Ok(None);
     }
 
-    Ok(())
+    // Damage stops at the slot the boss dies in, not at the settling slot.
+    let hp_left = room.boss_current_hp - (slots_to_kill - 1) * room.boss_total_dps;
+    room.boss_defeated_slot = room.boss_last_update_slot + slots_to_kill;
+    room.boss_current_hp = 0;
+    room.boss_defeated = true;
+    room.boss_last_update_slot = slot;
+    Ok(Some(hp_left))
+}
+
+/// Pick the last hitter and emit BossDefeated with the fighters' damage.
+/// `hp_left` is the boss HP the killing slot's strikes had to take.
+fn settle_boss_defeat(room: &mut RoomAccount, boss_roster: &BossRoster, hp_left: u64) {
+    let fighters = boss_roster.current_fighters();
+    room.boss_last_hitter =
+        last_hitter(&fighters, room.boss_defeated_slot, hp_left).unwrap_or_default();
+
+    emit!(BossDefeated {
+        room_floor: room.floor,
+        room_x: room.x,
+        room_y: room.y,
+        boss_id: room.center_id,
+        defeated_slot: room.boss_defeated_slot,
+        fighter_count: room.boss_fighter_count,
+        top_contributors: top_contributors(&fighters, room.boss_defeated_slot),
+        last_hitter: room.boss_last_hitter,
+    });
 }
 
-fn weapon_dps(item_id: u16) -> u64 {
-    match item_id {
-        2 => 5,      // tool
-        1001 => 12,  // reserved weapon ids
-        1002 => 20,
-        _ => 1,      // bare hands
+/// DPS of the equipped item; the definition PDA must be passed as a remaining account.
+/// Falls back to bare hands if the equipped item is no longer in the inventory.
+fn equipped_weapon_dps(
+    item_registry: &ItemRegistry,
+    inventory: &InventoryAccount,
+    item_id: u16,
+) -> Result<u64> {
+    if item_id == 0 {
+        return Ok(BARE_HANDS_DPS);
     }
+    let Some(durability) = inventory.equipped_durability(item_id) else {
+        return Ok(BARE_HANDS_DPS);
+    };
+    let stats = item_registry.stats(item_id)?;
+    Ok(weapon_dps(&stats, durability))
 }
@@ -3,7 +3,11 @@ use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 use crate::errors::ChainDepthError;
 use crate::events::JobJoined;
-use crate::state::{GlobalAccount, HelperStake, PlayerAccount, RoomAccount, RoomPresence};
+use crate::instructions::item_wear::wear_equipped_item;
+use crate::state::{
+    calculate_depth, equipped_work_rate, passed_buff_schedule, BuffSchedule, GlobalAccount,
+    HelperStake, InventoryAccount, ItemRegistry, PlayerAccount, RoomAccount, RoomPresence,
+};
 
 #[derive(Accounts)]
 #[instruction(direction: u8)]
@@ -31,6 +35,7 @@ pub struct JoinJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -45,6 +50,7 @@ pub struct JoinJob<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -53,6 +59,16 @@ pub struct JoinJob<'info> {
     )]
     pub room_presence: Box<Account<'info, RoomPresence>>,
 
+    /// Inventory holding the equipped tool (loses durability while mining)
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = InventoryAccount::DISCRIMINATOR.len() + InventoryAccount::INIT_SPACE,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump
+    )]
+    pub inventory: Box<Account<'info, InventoryAccount>>,
+
     /// Escrow token account for this room direction.
     #[account(
         init_if_needed,
@@ -91,11 +107,42 @@ pub struct JoinJob<'info> {
     #[account(constraint = skr_mint.key() == global.skr_mint)]
     pub skr_mint: Box<Account<'info, anchor_spl::token::Mint>>,
 
+    /// Buff bonuses of this job. Pass it when it exists or the player is
+    /// buffed; the first buffed helper creates it.
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = BuffSchedule::DISCRIMINATOR.len() + BuffSchedule::INIT_SPACE,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<JoinJob>, direction: u8) -> Result<()> {
+pub fn handler(mut ctx: Context<JoinJob>, direction: u8) -> Result<()> {
+    let joined = take_helper_slot(&mut ctx, direction)?;
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.player_token_account.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+            authority: ctx.accounts.player.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, RoomAccount::STAKE_AMOUNT)?;
+
+    emit!(joined);
+
+    Ok(())
+}
+
+/// Everything `join_job` does before moving the stake into escrow. Returns
+/// the `JobJoined` event for the handler to emit once the stake has moved.
+pub fn take_helper_slot(ctx: &mut Context<JoinJob>, direction: u8) -> Result<JobJoined> {
     require!(
         RoomAccount::is_valid_direction(direction),
         ChainDepthError::InvalidDirection
@@ -117,12 +164,34 @@ pub fn handler(ctx: Context<JoinJob>, direction: u8) -> Result<()> {
         ChainDepthError::JobAlreadyCompleted
     );
 
+    let max_helpers = ctx
+        .accounts
+        .global
+        .config
+        .max_helpers_for_depth(calculate_depth(
+            &ctx.accounts.global.layout,
+            room.floor,
+            room.x,
+            room.y,
+        ));
+    require!(
+        room.helper_counts[dir_idx] < max_helpers,
+        ChainDepthError::JobFull
+    );
+    require!(
+        !room.is_job_expired(
+            dir_idx,
+            clock.slot,
+            ctx.accounts.global.config.job_ttl_slots
+        ),
+        ChainDepthError::JobExpired
+    );
+
     if room.helper_counts[dir_idx] == 0 {
+        room.reset_job(dir_idx);
         room.start_slot[dir_idx] = clock.slot;
+        room.progress_slot[dir_idx] = clock.slot;
         room.base_slots[dir_idx] = RoomAccount::calculate_base_slots(ctx.accounts.global.depth);
-        room.progress[dir_idx] = 0;
-        room.bonus_per_helper[dir_idx] = 0;
-        room.job_completed[dir_idx] = false;
     }
 
     room.helper_counts[dir_idx] = room.helper_counts[dir_idx]
@@ -137,6 +206,7 @@ pub fn handler(ctx: Context<JoinJob>, direction: u8) -> Result<()> {
     if ctx.accounts.room_presence.player == Pubkey::default() {
         ctx.accounts.room_presence.player = player_key;
         ctx.accounts.room_presence.season_seed = ctx.accounts.global.season_seed;
+        ctx.accounts.room_presence.room_floor = room.floor;
         ctx.accounts.room_presence.room_x = room.x;
         ctx.accounts.room_presence.room_y = room.y;
         ctx.accounts.room_presence.skin_id = 0;
@@ -146,32 +216,62 @@ pub fn handler(ctx: Context<JoinJob>, direction: u8) -> Result<()> {
     }
     ctx.accounts.room_presence.set_door_job(direction);
 
+    let inventory = &mut ctx.accounts.inventory;
+    if inventory.owner == Pubkey::default() {
+        inventory.owner = player_key;
+        inventory.items = Vec::new();
+        inventory.bump = ctx.bumps.inventory;
+    }
+
+    // Snapshot the tool's work rate before wear can break it. An active buff
+    // adds to it only until the buff wears off.
+    let work_rate = equipped_work_rate(
+        &ItemRegistry::new(ctx.remaining_accounts),
+        inventory,
+        player_account.equipped_item_id,
+    )?;
+    let buff_bonus = player_account
+        .buffed_work_rate(work_rate, clock.slot)
+        .saturating_sub(work_rate);
+
     let helper_stake = &mut ctx.accounts.helper_stake;
     helper_stake.player = player_key;
     helper_stake.room = room.key();
     helper_stake.direction = direction;
     helper_stake.amount = RoomAccount::STAKE_AMOUNT;
     helper_stake.joined_slot = clock.slot;
+    helper_stake.work_rate = work_rate;
+    helper_stake.buff_bonus = buff_bonus;
+    helper_stake.buff_expires_slot = if buff_bonus > 0 {
+        player_account.buff_expires_slot
+    } else {
+        0
+    };
     helper_stake.bump = ctx.bumps.helper_stake;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
+    if let (Some(schedule), Some(bump)) = (buff_schedule.as_deref_mut(), ctx.bumps.buff_schedule) {
+        schedule.bind(room.key(), direction, bump);
+    }
+    room.settle_job_progress(dir_idx, buff_schedule.as_deref_mut(), clock.slot)?;
+    room.add_helper_work(dir_idx, helper_stake, buff_schedule)?;
 
-    let transfer_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.player_token_account.to_account_info(),
-            to: ctx.accounts.escrow.to_account_info(),
-            authority: ctx.accounts.player.to_account_info(),
-        },
-    );
-    token::transfer(transfer_ctx, RoomAccount::STAKE_AMOUNT)?;
+    wear_equipped_item(
+        player_key,
+        inventory,
+        player_account,
+        &mut ctx.accounts.room_presence,
+    )?;
 
-    emit!(JobJoined {
+    Ok(JobJoined {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
         player: player_key,
         helper_count: room.helper_counts[dir_idx],
+        max_helpers,
+        work_rate,
+        buff_bonus,
         stake_amount: RoomAccount::STAKE_AMOUNT,
-    });
-
-    Ok(())
+    })
 }
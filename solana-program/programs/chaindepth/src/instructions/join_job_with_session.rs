@@ -3,10 +3,12 @@ use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
 use crate::errors::ChainDepthError;
 use crate::events::JobJoined;
+use crate::instructions::item_wear::wear_equipped_item;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    session_instruction_bits, GlobalAccount, HelperStake, PlayerAccount, RoomAccount, RoomPresence,
-    SessionAuthority,
+    calculate_depth, equipped_work_rate, passed_buff_schedule, session_instruction_bits,
+    BuffSchedule, GlobalAccount, HelperStake, InventoryAccount, ItemRegistry, PlayerAccount,
+    RoomAccount, RoomPresence, SessionAuthority,
 };
 
 #[derive(Accounts)]
@@ -38,6 +40,7 @@ pub struct JoinJobWithSession<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -52,6 +55,7 @@ pub struct JoinJobWithSession<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -60,6 +64,16 @@ pub struct JoinJobWithSession<'info> {
     )]
     pub room_presence: Box<Account<'info, RoomPresence>>,
 
+    /// Inventory holding the equipped tool (loses durability while mining)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = InventoryAccount::DISCRIMINATOR.len() + InventoryAccount::INIT_SPACE,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump
+    )]
+    pub inventory: Box<Account<'info, InventoryAccount>>,
+
     /// Escrow token account for this room direction.
     #[account(
         init_if_needed,
@@ -98,6 +112,17 @@ pub struct JoinJobWithSession<'info> {
     #[account(constraint = skr_mint.key() == global.skr_mint)]
     pub skr_mint: Box<Account<'info, anchor_spl::token::Mint>>,
 
+    /// Buff bonuses of this job. Pass it when it exists or the player is
+    /// buffed; the first buffed helper creates it.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = BuffSchedule::DISCRIMINATOR.len() + BuffSchedule::INIT_SPACE,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
     #[account(
         mut,
         seeds = [
@@ -113,7 +138,31 @@ pub struct JoinJobWithSession<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
+pub fn handler(mut ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
+    let joined = take_helper_slot_with_session(&mut ctx, direction)?;
+
+    let transfer_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.player_token_account.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        },
+    );
+    token::transfer(transfer_context, RoomAccount::STAKE_AMOUNT)?;
+
+    emit!(joined);
+
+    Ok(())
+}
+
+/// Everything `join_job_with_session` does before moving the stake into
+/// escrow. Returns the `JobJoined` event for the handler to emit once the
+/// stake has moved.
+pub fn take_helper_slot_with_session(
+    ctx: &mut Context<JoinJobWithSession>,
+    direction: u8,
+) -> Result<JobJoined> {
     authorize_player_action(
         &ctx.accounts.authority,
         &ctx.accounts.player,
@@ -143,12 +192,35 @@ pub fn handler(ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
         ChainDepthError::JobAlreadyCompleted
     );
 
+    let max_helpers = ctx
+        .accounts
+        .global
+        .config
+        .max_helpers_for_depth(calculate_depth(
+            &ctx.accounts.global.layout,
+            room.floor,
+            room.x,
+            room.y,
+        ));
+    require!(
+        room.helper_counts[direction_index] < max_helpers,
+        ChainDepthError::JobFull
+    );
+    require!(
+        !room.is_job_expired(
+            direction_index,
+            clock.slot,
+            ctx.accounts.global.config.job_ttl_slots
+        ),
+        ChainDepthError::JobExpired
+    );
+
     if room.helper_counts[direction_index] == 0 {
+        room.reset_job(direction_index);
         room.start_slot[direction_index] = clock.slot;
-        room.base_slots[direction_index] = RoomAccount::calculate_base_slots(ctx.accounts.global.depth);
-        room.progress[direction_index] = 0;
-        room.bonus_per_helper[direction_index] = 0;
-        room.job_completed[direction_index] = false;
+        room.progress_slot[direction_index] = clock.slot;
+        room.base_slots[direction_index] =
+            RoomAccount::calculate_base_slots(ctx.accounts.global.depth);
     }
 
     room.helper_counts[direction_index] = room.helper_counts[direction_index]
@@ -159,13 +231,20 @@ pub fn handler(ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
         .checked_add(RoomAccount::STAKE_AMOUNT)
         .ok_or(ChainDepthError::Overflow)?;
 
-    msg!("JoinJob: adding job for player, current jobs={}", player_account.active_jobs.len());
+    msg!(
+        "JoinJob: adding job for player, current jobs={}",
+        player_account.active_jobs.len()
+    );
     player_account.add_job(room.x, room.y, direction)?;
-    msg!("JoinJob: after add_job, jobs={}", player_account.active_jobs.len());
+    msg!(
+        "JoinJob: after add_job, jobs={}",
+        player_account.active_jobs.len()
+    );
 
     if ctx.accounts.room_presence.player == Pubkey::default() {
         ctx.accounts.room_presence.player = player_key;
         ctx.accounts.room_presence.season_seed = ctx.accounts.global.season_seed;
+        ctx.accounts.room_presence.room_floor = room.floor;
         ctx.accounts.room_presence.room_x = room.x;
         ctx.accounts.room_presence.room_y = room.y;
         ctx.accounts.room_presence.skin_id = 0;
@@ -180,32 +259,62 @@ pub fn handler(ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
     );
     ctx.accounts.room_presence.set_door_job(direction);
 
+    let inventory = &mut ctx.accounts.inventory;
+    if inventory.owner == Pubkey::default() {
+        inventory.owner = player_key;
+        inventory.items = Vec::new();
+        inventory.bump = ctx.bumps.inventory;
+    }
+
+    // Snapshot the tool's work rate before wear can break it. An active buff
+    // adds to it only until the buff wears off.
+    let work_rate = equipped_work_rate(
+        &ItemRegistry::new(ctx.remaining_accounts),
+        inventory,
+        player_account.equipped_item_id,
+    )?;
+    let buff_bonus = player_account
+        .buffed_work_rate(work_rate, clock.slot)
+        .saturating_sub(work_rate);
+
     let helper_stake = &mut ctx.accounts.helper_stake;
     helper_stake.player = player_key;
     helper_stake.room = room.key();
     helper_stake.direction = direction;
     helper_stake.amount = RoomAccount::STAKE_AMOUNT;
     helper_stake.joined_slot = clock.slot;
+    helper_stake.work_rate = work_rate;
+    helper_stake.buff_bonus = buff_bonus;
+    helper_stake.buff_expires_slot = if buff_bonus > 0 {
+        player_account.buff_expires_slot
+    } else {
+        0
+    };
     helper_stake.bump = ctx.bumps.helper_stake;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
+    if let (Some(schedule), Some(bump)) = (buff_schedule.as_deref_mut(), ctx.bumps.buff_schedule) {
+        schedule.bind(room.key(), direction, bump);
+    }
+    room.settle_job_progress(direction_index, buff_schedule.as_deref_mut(), clock.slot)?;
+    room.add_helper_work(direction_index, helper_stake, buff_schedule)?;
 
-    let transfer_context = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.player_token_account.to_account_info(),
-            to: ctx.accounts.escrow.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
-        },
-    );
-    token::transfer(transfer_context, RoomAccount::STAKE_AMOUNT)?;
+    wear_equipped_item(
+        player_key,
+        inventory,
+        player_account,
+        &mut ctx.accounts.room_presence,
+    )?;
 
-    emit!(JobJoined {
+    Ok(JobJoined {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
         player: player_key,
         helper_count: room.helper_counts[direction_index],
+        max_helpers,
+        work_rate,
+        buff_bonus,
         stake_amount: RoomAccount::STAKE_AMOUNT,
-    });
-
-    Ok(())
+    })
 }
@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::BossFightLeft;
+use crate::instructions::join_boss_fight::apply_boss_damage;
+use crate::instructions::player_death::{kill_player, move_presence_to_respawn};
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    passed_buff_schedule, session_instruction_bits, BossFightAccount, BossRoster, BuffSchedule,
+    GlobalAccount, InventoryAccount, PlayerAccount, RoomAccount, RoomPresence, SessionAuthority,
+    BUFF_TARGET_BOSS,
+};
+
+#[derive(Accounts)]
+pub struct LeaveBossFight<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8]
+        ],
+        bump
+    )]
+    pub room: Account<'info, RoomAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = room_presence.bump
+    )]
+    pub room_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [BossFightAccount::SEED_PREFIX, room.key().as_ref(), player.key().as_ref()],
+        bump = boss_fight.bump
+    )]
+    pub boss_fight: Account<'info, BossFightAccount>,
+
+    /// Loses the fighter while the boss is still up
+    #[account(
+        mut,
+        seeds = [BossRoster::SEED_PREFIX, room.key().as_ref()],
+        bump = boss_roster.bump
+    )]
+    pub boss_roster: Box<Account<'info, BossRoster>>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    /// Presence in this floor's start room, where the player respawns if the
+    /// boss's counter-damage kills them. Closed again when the player survives
+    /// and did not have one yet.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub respawn_presence: Account<'info, RoomPresence>,
+
+    /// Buff bonuses of this boss fight; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[BUFF_TARGET_BOSS]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Drop out of a boss fight. The damage dealt so far stays on the boss but
+/// the player gives up their loot. Leaving after the kill, including a kill
+/// this leave settles, just forfeits the loot. Counter-damage taken in the
+/// fight is applied on the way out.
+pub fn handler(ctx: Context<LeaveBossFight>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::LEAVE_BOSS_FIGHT,
+        0,
+    )?;
+
+    let room = &mut ctx.accounts.room;
+    let player_account = &mut ctx.accounts.player_account;
+    let boss_fight = &ctx.accounts.boss_fight;
+    let mut buff_schedule = passed_buff_schedule(&mut ctx.accounts.buff_schedule);
+    let player_key = ctx.accounts.player.key();
+    let clock = Clock::get()?;
+
+    apply_boss_damage(
+        room,
+        buff_schedule.as_deref_mut(),
+        &ctx.accounts.boss_roster,
+        clock.slot,
+    )?;
+    if !room.boss_defeated {
+        require!(
+            ctx.accounts.boss_roster.remove(player_key),
+            ChainDepthError::NotBossFighter
+        );
+        room.boss_fighter_count = room
+            .boss_fighter_count
+            .checked_sub(1)
+            .ok_or(ChainDepthError::Overflow)?;
+        room.boss_total_dps = room
+            .boss_total_dps
+            .checked_sub(boss_fight.dps)
+            .ok_or(ChainDepthError::Overflow)?;
+        // A bonus that has not worn off yet is still in the total.
+        if boss_fight.buff_bonus > 0
+            && room.boss_buffs_scheduled > 0
+            && buff_schedule
+                .ok_or(ChainDepthError::BuffScheduleMissing)?
+                .remove_bonus(
+                    &mut room.boss_buffs_scheduled,
+                    boss_fight.buff_bonus,
+                    boss_fight.buff_expires_slot,
+                )
+        {
+            room.boss_total_dps = room
+                .boss_total_dps
+                .checked_sub(boss_fight.buff_bonus)
+                .ok_or(ChainDepthError::Overflow)?;
+        }
+    }
+
+    player_account.boss_fight_room = Pubkey::default();
+    ctx.accounts.room_presence.set_idle();
+
+    emit!(BossFightLeft {
+        room_floor: room.floor,
+        room_x: room.x,
+        room_y: room.y,
+        player: player_key,
+        dps: boss_fight.dps,
+        fighter_count: room.boss_fighter_count,
+    });
+
+    let fight_end_slot = if room.boss_defeated {
+        room.boss_defeated_slot
+    } else {
+        clock.slot
+    };
+    let damage_taken = boss_fight.damage_taken_until(fight_end_slot, room.boss_max_hp);
+    if player_account.take_damage(damage_taken) {
+        kill_player(
+            player_key,
+            player_account,
+            &mut ctx.accounts.inventory,
+            room,
+            damage_taken,
+            &ctx.accounts.global.layout,
+        )?;
+        move_presence_to_respawn(
+            player_key,
+            player_account,
+            &mut ctx.accounts.room_presence,
+            &mut ctx.accounts.respawn_presence,
+            ctx.bumps.respawn_presence,
+        );
+    } else if ctx.accounts.respawn_presence.player == Pubkey::default() {
+        ctx.accounts
+            .respawn_presence
+            .close(ctx.accounts.authority.to_account_info())?;
+    }
+
+    Ok(())
+}
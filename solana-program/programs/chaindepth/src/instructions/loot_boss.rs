@@ -1,11 +1,14 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
-use crate::events::{item_types, BossLooted};
+use crate::events::LootCommitted;
+use crate::instructions::item_wear::wear_equipped_item;
+use crate::instructions::player_death::{kill_player, move_presence_to_respawn};
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    item_ids, session_instruction_bits, BossFightAccount, GlobalAccount, InventoryAccount,
-    LootReceipt, PlayerAccount, RoomAccount, RoomPresence, SessionAuthority, CENTER_BOSS,
+    boss_loot_table, loot_kinds, session_instruction_bits, BossFightAccount, GlobalAccount,
+    InventoryAccount, LootReceipt, PlayerAccount, RoomAccount, RoomPresence, SessionAuthority,
+    CENTER_BOSS,
 };
 
 #[derive(Accounts)]
@@ -36,6 +39,7 @@ pub struct LootBoss<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -48,6 +52,7 @@ pub struct LootBoss<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -56,7 +61,10 @@ pub struct LootBoss<'info> {
     )]
     pub room_presence: Account<'info, RoomPresence>,
 
+    /// Closed on loot, which ends the player's fight
     #[account(
+        mut,
+        close = authority,
         seeds = [BossFightAccount::SEED_PREFIX, room.key().as_ref(), player.key().as_ref()],
         bump = boss_fight.bump
     )]
@@ -79,6 +87,7 @@ pub struct LootBoss<'info> {
         seeds = [
             LootReceipt::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -87,6 +96,25 @@ pub struct LootBoss<'info> {
     )]
     pub loot_receipt: Account<'info, LootReceipt>,
 
+    /// Presence in this floor's start room, where the player respawns if the
+    /// boss's counter-damage kills them. Closed again when the player survives
+    /// and did not have one yet.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub respawn_presence: Account<'info, RoomPresence>,
+
     #[account(
         mut,
         seeds = [
@@ -121,7 +149,7 @@ pub fn handler(ctx: Context<LootBoss>) -> Result<()> {
     require!(room.center_type == CENTER_BOSS, ChainDepthError::NoBoss);
     require!(room.boss_defeated, ChainDepthError::BossNotDefeated);
     require!(
-        player_account.is_at_room(room.x, room.y),
+        player_account.is_at_room(room.floor, room.x, room.y),
         ChainDepthError::NotInRoom
     );
 
@@ -131,144 +159,89 @@ pub fn handler(ctx: Context<LootBoss>) -> Result<()> {
         ChainDepthError::AlreadyLooted
     );
 
+    if inventory.owner == Pubkey::default() {
+        inventory.owner = player_key;
+        inventory.items = Vec::new();
+        inventory.bump = ctx.bumps.inventory;
+    }
+
+    // Counter-damage from the whole fight lands before the loot does.
+    let damage_taken = ctx
+        .accounts
+        .boss_fight
+        .damage_taken_until(room.boss_defeated_slot, room.boss_max_hp);
+    if player_account.take_damage(damage_taken) {
+        kill_player(
+            player_key,
+            player_account,
+            inventory,
+            room,
+            damage_taken,
+            &ctx.accounts.global.layout,
+        )?;
+        move_presence_to_respawn(
+            player_key,
+            player_account,
+            &mut ctx.accounts.room_presence,
+            &mut ctx.accounts.respawn_presence,
+            ctx.bumps.respawn_presence,
+        );
+        return loot_receipt.close(ctx.accounts.authority.to_account_info());
+    }
+    if ctx.accounts.respawn_presence.player == Pubkey::default() {
+        ctx.accounts
+            .respawn_presence
+            .close(ctx.accounts.authority.to_account_info())?;
+    }
+
     // Initialize the loot receipt
     loot_receipt.player = player_key;
     loot_receipt.season_seed = ctx.accounts.global.season_seed;
+    loot_receipt.room_floor = room.floor;
     loot_receipt.room_x = room.x;
     loot_receipt.room_y = room.y;
+    loot_receipt.kind = loot_kinds::BOSS;
+    loot_receipt.source_id = room.center_id;
+    loot_receipt.commit_slot = clock.slot;
+    loot_receipt.damage_share_bps = ctx
+        .accounts
+        .boss_fight
+        .fighter()
+        .damage_share_bps(room.boss_defeated_slot, room.boss_max_hp);
+    loot_receipt.last_hitter = room.boss_last_hitter == player_key;
+    loot_receipt.revealed = false;
     loot_receipt.bump = ctx.bumps.loot_receipt;
 
     // Update room looted count and player stats
     room.looted_count += 1;
     player_account.chests_looted += 1;
+    player_account.boss_fight_room = Pubkey::default();
     ctx.accounts.room_presence.set_idle();
 
-    let loot_hash = generate_loot_hash(clock.slot, &player_key, room.center_id);
-    let (item_type, item_amount) = calculate_boss_loot(loot_hash);
-    let item_id = map_item_type_to_item_id(item_type, loot_hash);
-    let durability = item_durability(item_type, item_id);
-
-    if inventory.owner == Pubkey::default() {
-        inventory.owner = player_key;
-        inventory.items = Vec::new();
-        inventory.bump = ctx.bumps.inventory;
-    }
-    inventory.add_item(item_id, u32::from(item_amount), durability)?;
+    wear_equipped_item(
+        player_key,
+        inventory,
+        player_account,
+        &mut ctx.accounts.room_presence,
+    )?;
 
-    emit!(BossLooted {
+    // The drop itself is rolled by reveal_loot from a slot hash that does not exist yet.
+    emit!(LootCommitted {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         player: player_key,
-        item_type,
-        item_amount,
+        kind: loot_kinds::BOSS,
+        commit_slot: loot_receipt.commit_slot,
+        reveal_slot: loot_receipt.reveal_slot(),
     });
 
     Ok(())
 }
 
-fn generate_loot_hash(slot: u64, player: &Pubkey, boss_id: u16) -> u64 {
-    let player_bytes = player.to_bytes();
-    let mut hash = slot
-        .wrapping_mul(31)
-        .wrapping_add(u64::from(boss_id));
-    for chunk in player_bytes.chunks(8) {
-        let mut bytes = [0u8; 8];
-        bytes[..chunk.len()].copy_from_slice(chunk);
-        let value = u64::from_le_bytes(bytes);
-        hash = hash.wrapping_mul(31).wrapping_add(value);
-    }
-    hash
-}
-
-fn calculate_boss_loot(hash: u64) -> (u8, u8) {
-    let type_roll = hash % 100;
-    let item_type = if type_roll < 35 {
-        item_types::ORE
-    } else if type_roll < 75 {
-        item_types::TOOL
-    } else {
-        item_types::BUFF
-    };
-
-    let amount_hash = (hash >> 32) as u8;
-    let item_amount = match item_type {
-        item_types::ORE => (amount_hash % 8) + 3,
-        item_types::TOOL => 1,
-        item_types::BUFF => (amount_hash % 5) + 2,
-        _ => 1,
-    };
-
-    (item_type, item_amount)
-}
-
-fn map_item_type_to_item_id(item_type: u8, hash: u64) -> u16 {
-    let picker = ((hash >> 16) & 0xFFFF) as usize;
-    match item_type {
-        item_types::TOOL => {
-            // Boss drops: includes rare weapons not found in chests
-            const TOOLS: [u16; 9] = [
-                item_ids::IRON_PICKAXE,
-                item_ids::IRON_SWORD,
-                item_ids::DIAMOND_SWORD,
-                item_ids::NOKIA_3310,
-                item_ids::IRON_SCIMITAR,
-                item_ids::BRONZE_SWORD,
-                item_ids::BRONZE_PICKAXE,
-                item_ids::WOODEN_PIPE,
-                item_ids::WOODEN_TANKARD,
-            ];
-            TOOLS[picker % TOOLS.len()]
-        }
-        item_types::ORE => {
-            // Boss drops: includes rare valuables not found in chests
-            const VALUABLES: [u16; 16] = [
-                item_ids::GOLD_COIN,
-                item_ids::GOLD_BAR,
-                item_ids::GOLD_BAR,     // weighted: more common from bosses
-                item_ids::DIAMOND,
-                item_ids::RUBY,
-                item_ids::SAPPHIRE,
-                item_ids::EMERALD,
-                item_ids::ANCIENT_CROWN,
-                item_ids::DRAGON_SCALE,
-                item_ids::CURSED_AMULET,
-                item_ids::GOLDEN_CHALICE,
-                item_ids::MYSTIC_ORB,
-                item_ids::PHOENIX_FEATHER,
-                item_ids::VOID_SHARD,
-                item_ids::SKELETON_KEY,
-                item_ids::ENCHANTED_SCROLL,
-            ];
-            VALUABLES[picker % VALUABLES.len()]
-        }
-        item_types::BUFF => {
-            // Boss drops more major buffs
-            const BUFFS: [u16; 3] = [
-                item_ids::MINOR_BUFF,
-                item_ids::MAJOR_BUFF,
-                item_ids::MAJOR_BUFF,   // weighted: better from bosses
-            ];
-            BUFFS[picker % BUFFS.len()]
-        }
-        _ => item_ids::GOLD_COIN,
-    }
-}
-
-fn item_durability(item_type: u8, item_id: u16) -> u16 {
-    if item_type == item_types::TOOL {
-        match item_id {
-            // Bronze tier
-            item_ids::BRONZE_PICKAXE | item_ids::BRONZE_SWORD => 80,
-            // Iron tier
-            item_ids::IRON_PICKAXE | item_ids::IRON_SWORD | item_ids::IRON_SCIMITAR => 120,
-            // Diamond tier
-            item_ids::DIAMOND_SWORD => 200,
-            // Fun / novelty weapons
-            item_ids::NOKIA_3310 => 9999,
-            item_ids::WOODEN_PIPE | item_ids::WOODEN_TANKARD => 60,
-            _ => 100,
-        }
-    } else {
-        0
-    }
+/// Roll boss loot from a revealed random value using the table of the
+/// boss with `boss_id` (the room's `center_id`).
+/// Returns (item_type, item_amount, item_id).
+pub(crate) fn roll_boss_loot(hash: u64, boss_id: u16) -> (u8, u8, u16) {
+    boss_loot_table(boss_id).roll(hash)
 }
@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
-use crate::events::{item_types, ChestLooted};
+use crate::events::LootCommitted;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
-    item_ids, session_instruction_bits, GlobalAccount, InventoryAccount, LootReceipt,
-    PlayerAccount, RoomAccount, SessionAuthority, CENTER_CHEST,
+    chest_loot_table, item_ids, loot_kinds, session_instruction_bits, GlobalAccount,
+    InventoryAccount, ItemRegistry, LootReceipt, PlayerAccount, RoomAccount, SessionAuthority,
+    CENTER_CHEST,
 };
 
 #[derive(Accounts)]
@@ -37,6 +38,7 @@ pub struct LootChest<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -61,6 +63,7 @@ pub struct LootChest<'info> {
         seeds = [
             LootReceipt::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -103,7 +106,7 @@ pub fn handler(ctx: Context<LootChest>) -> Result<()> {
 
     // Check player is in this room
     require!(
-        player_account.is_at_room(room.x, room.y),
+        player_account.is_at_room(room.floor, room.x, room.y),
         ChainDepthError::NotInRoom
     );
 
@@ -116,144 +119,51 @@ pub fn handler(ctx: Context<LootChest>) -> Result<()> {
     // Initialize the loot receipt
     loot_receipt.player = player_key;
     loot_receipt.season_seed = ctx.accounts.global.season_seed;
+    loot_receipt.room_floor = room.floor;
     loot_receipt.room_x = room.x;
     loot_receipt.room_y = room.y;
+    loot_receipt.kind = loot_kinds::CHEST;
+    loot_receipt.source_id = 0;
+    loot_receipt.commit_slot = clock.slot;
+    loot_receipt.damage_share_bps = 0;
+    loot_receipt.last_hitter = false;
+    loot_receipt.revealed = false;
     loot_receipt.bump = ctx.bumps.loot_receipt;
 
     // Update room looted count and player stats
     room.looted_count += 1;
     player_account.chests_looted += 1;
 
-    // Generate deterministic loot based on slot + player pubkey
-    let loot_hash = generate_loot_hash(clock.slot, &player_key);
-    let (item_type, item_amount) = calculate_loot(loot_hash);
-    let item_id = map_item_type_to_item_id(item_type, loot_hash);
-    let durability = item_durability(item_type, item_id);
-
     if inventory.owner == Pubkey::default() {
         inventory.owner = player_key;
         inventory.items = Vec::new();
         inventory.bump = ctx.bumps.inventory;
     }
-    inventory.add_item(item_id, u32::from(item_amount), durability)?;
 
+    // The forced key is not random, so it is granted right away. Its
+    // definition PDA must then be passed as a remaining account.
     if room.forced_key_drop {
-        inventory.add_item(item_ids::SKELETON_KEY, 1, 0)?;
+        let key = ItemRegistry::new(ctx.remaining_accounts).stats(item_ids::SKELETON_KEY)?;
+        inventory.add_item(item_ids::SKELETON_KEY, 1, key.base_durability, &key)?;
     }
 
-    emit!(ChestLooted {
+    // The drop itself is rolled by reveal_loot from a slot hash that does not exist yet.
+    emit!(LootCommitted {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         player: player_key,
-        item_type,
-        item_amount,
+        kind: loot_kinds::CHEST,
+        commit_slot: loot_receipt.commit_slot,
+        reveal_slot: loot_receipt.reveal_slot(),
     });
 
     Ok(())
 }
 
-/// Generate deterministic hash for loot
-fn generate_loot_hash(slot: u64, player: &Pubkey) -> u64 {
-    let player_bytes = player.to_bytes();
-    let mut hash = slot;
-    
-    // Mix in player pubkey bytes
-    for chunk in player_bytes.chunks(8) {
-        let mut bytes = [0u8; 8];
-        bytes[..chunk.len()].copy_from_slice(chunk);
-        let val = u64::from_le_bytes(bytes);
-        hash = hash.wrapping_mul(31).wrapping_add(val);
-    }
-    
-    hash
-}
-
-/// Calculate loot item type and amount from hash
-fn calculate_loot(hash: u64) -> (u8, u8) {
-    // Item type: 0=Ore (60%), 1=Tool (25%), 2=Buff (15%)
-    let type_roll = hash % 100;
-    let item_type = if type_roll < 60 {
-        item_types::ORE
-    } else if type_roll < 85 {
-        item_types::TOOL
-    } else {
-        item_types::BUFF
-    };
-
-    // Amount varies by type
-    let amount_hash = (hash >> 32) as u8;
-    let item_amount = match item_type {
-        item_types::ORE => (amount_hash % 5) + 1,    // 1-5 ore
-        item_types::TOOL => 1,                        // Always 1 tool
-        item_types::BUFF => (amount_hash % 3) + 1,   // 1-3 buffs
-        _ => 1,
-    };
-
-    (item_type, item_amount)
-}
-
-fn map_item_type_to_item_id(item_type: u8, hash: u64) -> u16 {
-    let picker = ((hash >> 16) & 0xFFFF) as usize;
-    match item_type {
-        item_types::TOOL => {
-            // Chest drops: common weapons only (no diamond sword from chests)
-            const TOOLS: [u16; 7] = [
-                item_ids::BRONZE_PICKAXE,
-                item_ids::IRON_PICKAXE,
-                item_ids::BRONZE_SWORD,
-                item_ids::IRON_SWORD,
-                item_ids::WOODEN_PIPE,
-                item_ids::IRON_SCIMITAR,
-                item_ids::WOODEN_TANKARD,
-            ];
-            TOOLS[picker % TOOLS.len()]
-        }
-        item_types::ORE => {
-            // Chest drops: common and mid-tier valuables
-            const VALUABLES: [u16; 14] = [
-                item_ids::SILVER_COIN,
-                item_ids::SILVER_COIN,  // weighted: more common
-                item_ids::GOLD_COIN,
-                item_ids::GOLD_COIN,    // weighted: more common
-                item_ids::GOLD_BAR,
-                item_ids::RUBY,
-                item_ids::SAPPHIRE,
-                item_ids::EMERALD,
-                item_ids::GOBLIN_TOOTH,
-                item_ids::DUSTY_TOME,
-                item_ids::SKELETON_KEY,
-                item_ids::RUSTED_COMPASS,
-                item_ids::DWARF_BEARD_RING,
-                item_ids::ENCHANTED_SCROLL,
-            ];
-            VALUABLES[picker % VALUABLES.len()]
-        }
-        item_types::BUFF => {
-            const BUFFS: [u16; 2] = [
-                item_ids::MINOR_BUFF,
-                item_ids::MAJOR_BUFF,
-            ];
-            BUFFS[picker % BUFFS.len()]
-        }
-        _ => item_ids::SILVER_COIN,
-    }
-}
-
-fn item_durability(item_type: u8, item_id: u16) -> u16 {
-    if item_type == item_types::TOOL {
-        match item_id {
-            // Bronze tier
-            item_ids::BRONZE_PICKAXE | item_ids::BRONZE_SWORD => 80,
-            // Iron tier
-            item_ids::IRON_PICKAXE | item_ids::IRON_SWORD | item_ids::IRON_SCIMITAR => 120,
-            // Diamond tier
-            item_ids::DIAMOND_SWORD => 200,
-            // Fun / novelty weapons
-            item_ids::NOKIA_3310 => 9999,
-            item_ids::WOODEN_PIPE | item_ids::WOODEN_TANKARD => 60,
-            _ => 100,
-        }
-    } else {
-        0
-    }
+/// Roll chest loot from a revealed random value, using the drop table for
+/// the chest room's depth.
+/// Returns (item_type, item_amount, item_id).
+pub(crate) fn roll_chest_loot(hash: u64, depth: u32) -> (u8, u8, u16) {
+    chest_loot_table(depth).roll(hash)
 }
@@ -0,0 +1,438 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::errors::ChainDepthError;
+use crate::events::{migrated_account_kinds, AccountMigrated};
+use crate::state::{
+    item_ids, ActiveJob, GameConfig, GlobalAccount, HelperStake, InventoryAccount, InventoryItem,
+    PlayerAccount, SeasonLayout, BARE_HANDS_WORK_RATE, ENTRANCE_FLOOR, MAX_ACTIVE_JOBS,
+    MAX_INVENTORY_SLOTS, PLAYER_MAX_HP,
+};
+
+/// `GlobalAccount` as laid out before `config` and `layout` were appended
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyGlobalAccount {
+    season_seed: u64,
+    depth: u32,
+    skr_mint: Pubkey,
+    prize_pool: Pubkey,
+    admin: Pubkey,
+    end_slot: u64,
+    jobs_completed: u64,
+    bump: u8,
+}
+
+/// `PlayerAccount` as laid out before floors, HP, buffs and boss fights were appended
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyPlayerAccount {
+    owner: Pubkey,
+    current_room_x: i8,
+    current_room_y: i8,
+    #[max_len(MAX_ACTIVE_JOBS)]
+    active_jobs: Vec<ActiveJob>,
+    jobs_completed: u64,
+    chests_looted: u64,
+    equipped_item_id: u16,
+    total_score: u64,
+    current_run_start_slot: u64,
+    runs_extracted: u64,
+    last_extraction_slot: u64,
+    season_seed: u64,
+    bump: u8,
+}
+
+/// `HelperStake` as laid out before work rates and buffs were appended
+#[derive(AnchorDeserialize, InitSpace)]
+pub(crate) struct LegacyHelperStake {
+    pub player: Pubkey,
+    pub room: Pubkey,
+    pub direction: u8,
+    pub amount: u64,
+    pub joined_slot: u64,
+    pub bump: u8,
+}
+
+/// `InventoryItem` as laid out before `score_value` was appended
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyInventoryItem {
+    item_id: u16,
+    amount: u32,
+    durability: u16,
+}
+
+/// `InventoryAccount` as laid out before its items cached their score
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyInventoryAccount {
+    owner: Pubkey,
+    #[max_len(MAX_INVENTORY_SLOTS)]
+    items: Vec<LegacyInventoryItem>,
+    bump: u8,
+}
+
+/// Score per unit the launch program paid for each valuable at extraction
+fn launch_score_value(item_id: u16) -> u64 {
+    match item_id {
+        item_ids::SILVER_COIN => 1,
+        item_ids::GOLD_COIN => 3,
+        item_ids::GOLD_BAR => 8,
+        item_ids::DIAMOND => 12,
+        item_ids::RUBY => 10,
+        item_ids::SAPPHIRE => 9,
+        item_ids::EMERALD => 9,
+        item_ids::ANCIENT_CROWN => 20,
+        item_ids::GOBLIN_TOOTH => 2,
+        item_ids::DRAGON_SCALE => 15,
+        item_ids::CURSED_AMULET => 11,
+        item_ids::DUSTY_TOME => 4,
+        item_ids::ENCHANTED_SCROLL => 7,
+        item_ids::GOLDEN_CHALICE => 14,
+        item_ids::MYSTIC_ORB => 13,
+        item_ids::RUSTED_COMPASS => 3,
+        item_ids::DWARF_BEARD_RING => 8,
+        item_ids::PHOENIX_FEATHER => 18,
+        item_ids::VOID_SHARD => 16,
+        _ => 0,
+    }
+}
+
+/// Grow the global account to the current layout. The account pays for its
+/// own extra rent out of the treasury it holds.
+#[derive(Accounts)]
+pub struct MigrateGlobal<'info> {
+    pub admin: Signer<'info>,
+
+    /// CHECK: still in the legacy layout, so the handler reads it by hand
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump,
+        owner = crate::ID
+    )]
+    pub global: UncheckedAccount<'info>,
+}
+
+pub fn migrate_global_handler(ctx: Context<MigrateGlobal>) -> Result<()> {
+    let global_info = ctx.accounts.global.to_account_info();
+    let old_space = global_info.data_len();
+    let legacy: LegacyGlobalAccount = read_legacy_account(
+        &global_info,
+        GlobalAccount::DISCRIMINATOR,
+        8 + LegacyGlobalAccount::INIT_SPACE,
+    )?;
+    require_keys_eq!(
+        legacy.admin,
+        ctx.accounts.admin.key(),
+        ChainDepthError::Unauthorized
+    );
+
+    let new_space = 8 + GlobalAccount::INIT_SPACE;
+    require!(
+        global_info.lamports() >= Rent::get()?.minimum_balance(new_space),
+        ChainDepthError::TreasuryInsufficientFunds
+    );
+
+    let global = GlobalAccount {
+        season_seed: legacy.season_seed,
+        depth: legacy.depth,
+        skr_mint: legacy.skr_mint,
+        prize_pool: legacy.prize_pool,
+        admin: legacy.admin,
+        end_slot: legacy.end_slot,
+        jobs_completed: legacy.jobs_completed,
+        bump: legacy.bump,
+        config: GameConfig::default(),
+        layout: SeasonLayout::default(),
+    };
+    write_migrated_account(&global_info, &global, new_space)?;
+
+    emit!(AccountMigrated {
+        account: global_info.key(),
+        account_kind: migrated_account_kinds::GLOBAL,
+        old_space: old_space as u32,
+        new_space: new_space as u32,
+        rent_paid: 0,
+    });
+
+    Ok(())
+}
+
+/// Grow a player account to the current layout, reimbursing the extra rent
+/// from the treasury. Run `migrate_global` first.
+#[derive(Accounts)]
+pub struct MigratePlayer<'info> {
+    pub admin: Signer<'info>,
+
+    /// Global game state - pays the extra rent as treasury
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump,
+        constraint = global.admin == admin.key() @ ChainDepthError::Unauthorized
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// CHECK: only used to derive the player account address
+    pub player: UncheckedAccount<'info>,
+
+    /// CHECK: still in the legacy layout, so the handler reads it by hand
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub player_account: UncheckedAccount<'info>,
+}
+
+pub fn migrate_player_handler(ctx: Context<MigratePlayer>) -> Result<()> {
+    let player_info = ctx.accounts.player_account.to_account_info();
+    let old_space = player_info.data_len();
+    let legacy: LegacyPlayerAccount = read_legacy_account(
+        &player_info,
+        PlayerAccount::DISCRIMINATOR,
+        8 + LegacyPlayerAccount::INIT_SPACE,
+    )?;
+
+    let new_space = 8 + PlayerAccount::INIT_SPACE;
+    let rent_paid = reimburse_rent(
+        &ctx.accounts.global.to_account_info(),
+        &player_info,
+        new_space,
+    )?;
+
+    // Legacy players were never below the entrance floor, in a boss fight or buffed.
+    let player_account = PlayerAccount {
+        owner: legacy.owner,
+        current_room_x: legacy.current_room_x,
+        current_room_y: legacy.current_room_y,
+        active_jobs: legacy.active_jobs,
+        jobs_completed: legacy.jobs_completed,
+        chests_looted: legacy.chests_looted,
+        equipped_item_id: legacy.equipped_item_id,
+        total_score: legacy.total_score,
+        current_run_start_slot: legacy.current_run_start_slot,
+        runs_extracted: legacy.runs_extracted,
+        last_extraction_slot: legacy.last_extraction_slot,
+        season_seed: legacy.season_seed,
+        bump: legacy.bump,
+        current_floor: ENTRANCE_FLOOR,
+        boss_fight_room: Pubkey::default(),
+        hp: PLAYER_MAX_HP,
+        active_buff_id: 0,
+        buff_expires_slot: 0,
+        loot_protected: false,
+    };
+    write_migrated_account(&player_info, &player_account, new_space)?;
+
+    emit!(AccountMigrated {
+        account: player_info.key(),
+        account_kind: migrated_account_kinds::PLAYER,
+        old_space: old_space as u32,
+        new_space: new_space as u32,
+        rent_paid,
+    });
+
+    Ok(())
+}
+
+/// Grow a helper stake to the current layout, reimbursing the extra rent from
+/// the treasury, so `claim_job_reward`, `abandon_job` and `recover_stale_stake`
+/// can read it again. Run `migrate_global` first.
+#[derive(Accounts)]
+#[instruction(direction: u8)]
+pub struct MigrateHelperStake<'info> {
+    pub admin: Signer<'info>,
+
+    /// Global game state - pays the extra rent as treasury
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump,
+        constraint = global.admin == admin.key() @ ChainDepthError::Unauthorized
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// CHECK: only used to derive the stake address; may be a room from before
+    /// the upgrade
+    pub room: UncheckedAccount<'info>,
+
+    /// CHECK: only used to derive the stake address
+    pub player: UncheckedAccount<'info>,
+
+    /// CHECK: still in the legacy layout, so the handler reads it by hand
+    #[account(
+        mut,
+        seeds = [
+            HelperStake::SEED_PREFIX,
+            room.key().as_ref(),
+            &[direction],
+            player.key().as_ref()
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub helper_stake: UncheckedAccount<'info>,
+}
+
+pub fn migrate_helper_stake_handler(
+    ctx: Context<MigrateHelperStake>,
+    _direction: u8,
+) -> Result<()> {
+    let stake_info = ctx.accounts.helper_stake.to_account_info();
+    let old_space = stake_info.data_len();
+    let legacy: LegacyHelperStake = read_legacy_account(
+        &stake_info,
+        HelperStake::DISCRIMINATOR,
+        8 + LegacyHelperStake::INIT_SPACE,
+    )?;
+
+    let new_space = 8 + HelperStake::INIT_SPACE;
+    let rent_paid = reimburse_rent(
+        &ctx.accounts.global.to_account_info(),
+        &stake_info,
+        new_space,
+    )?;
+
+    // Launch-layout helpers worked bare-handed and unbuffed.
+    let helper_stake = HelperStake {
+        player: legacy.player,
+        room: legacy.room,
+        direction: legacy.direction,
+        amount: legacy.amount,
+        joined_slot: legacy.joined_slot,
+        bump: legacy.bump,
+        work_rate: BARE_HANDS_WORK_RATE,
+        buff_bonus: 0,
+        buff_expires_slot: 0,
+    };
+    write_migrated_account(&stake_info, &helper_stake, new_space)?;
+
+    emit!(AccountMigrated {
+        account: stake_info.key(),
+        account_kind: migrated_account_kinds::HELPER_STAKE,
+        old_space: old_space as u32,
+        new_space: new_space as u32,
+        rent_paid,
+    });
+
+    Ok(())
+}
+
+/// Grow an inventory to the current layout, reimbursing the extra rent from
+/// the treasury. Launch items keep the score the launch program paid for them.
+/// Run `migrate_global` first.
+#[derive(Accounts)]
+pub struct MigrateInventory<'info> {
+    pub admin: Signer<'info>,
+
+    /// Global game state - pays the extra rent as treasury
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump,
+        constraint = global.admin == admin.key() @ ChainDepthError::Unauthorized
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    /// CHECK: only used to derive the inventory address
+    pub player: UncheckedAccount<'info>,
+
+    /// CHECK: still in the legacy layout, so the handler reads it by hand
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub inventory: UncheckedAccount<'info>,
+}
+
+pub fn migrate_inventory_handler(ctx: Context<MigrateInventory>) -> Result<()> {
+    let inventory_info = ctx.accounts.inventory.to_account_info();
+    let old_space = inventory_info.data_len();
+    let legacy: LegacyInventoryAccount = read_legacy_account(
+        &inventory_info,
+        InventoryAccount::DISCRIMINATOR,
+        8 + LegacyInventoryAccount::INIT_SPACE,
+    )?;
+
+    let new_space = 8 + InventoryAccount::INIT_SPACE;
+    let rent_paid = reimburse_rent(
+        &ctx.accounts.global.to_account_info(),
+        &inventory_info,
+        new_space,
+    )?;
+
+    let inventory = InventoryAccount {
+        owner: legacy.owner,
+        items: legacy
+            .items
+            .into_iter()
+            .map(|item| InventoryItem {
+                item_id: item.item_id,
+                amount: item.amount,
+                durability: item.durability,
+                score_value: launch_score_value(item.item_id),
+            })
+            .collect(),
+        bump: legacy.bump,
+    };
+    write_migrated_account(&inventory_info, &inventory, new_space)?;
+
+    emit!(AccountMigrated {
+        account: inventory_info.key(),
+        account_kind: migrated_account_kinds::INVENTORY,
+        old_space: old_space as u32,
+        new_space: new_space as u32,
+        rent_paid,
+    });
+
+    Ok(())
+}
+
+/// Top `account` up to the rent of `new_space` out of the treasury held by
+/// `global` (manual lamport transfer). Returns the lamports moved.
+fn reimburse_rent(global: &AccountInfo, account: &AccountInfo, new_space: usize) -> Result<u64> {
+    let rent_paid = Rent::get()?
+        .minimum_balance(new_space)
+        .saturating_sub(account.lamports());
+    **global.try_borrow_mut_lamports()? = global
+        .lamports()
+        .checked_sub(rent_paid)
+        .ok_or(ChainDepthError::TreasuryInsufficientFunds)?;
+    **account.try_borrow_mut_lamports()? = account
+        .lamports()
+        .checked_add(rent_paid)
+        .ok_or(ChainDepthError::Overflow)?;
+    Ok(rent_paid)
+}
+
+/// Deserialize an account still sized for its legacy layout. Accounts of any
+/// other size have already been migrated.
+pub(crate) fn read_legacy_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    legacy_space: usize,
+) -> Result<T> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() == legacy_space,
+        ChainDepthError::AccountAlreadyMigrated
+    );
+    require!(
+        data.starts_with(discriminator),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    T::deserialize(&mut &data[discriminator.len()..])
+        .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+}
+
+fn write_migrated_account<T: AccountSerialize>(
+    account: &AccountInfo,
+    migrated: &T,
+    new_space: usize,
+) -> Result<()> {
+    account.resize(new_space)?;
+    let mut data = account.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])
+}
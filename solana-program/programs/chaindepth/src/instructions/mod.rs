@@ -3,52 +3,86 @@ pub mod add_inventory_item;
 pub mod begin_session;
 pub mod boost_job;
 pub mod claim_job_reward;
+pub mod close_stale_accounts;
 pub mod complete_job;
 pub mod create_player_profile;
 pub mod end_session;
 pub mod ensure_start_room;
 pub mod exit_dungeon;
+pub mod expire_job;
 pub mod equip_item;
 pub mod force_reset_season;
 pub mod init_global;
+pub mod item_wear;
 pub mod join_boss_fight;
 pub mod join_job;
 pub mod join_job_with_session;
+pub mod leave_boss_fight;
 pub mod loot_boss;
 pub mod loot_chest;
+pub mod migrate_accounts;
 pub mod move_player;
+pub mod player_death;
+pub mod recover_stale_stake;
 pub mod remove_inventory_item;
 pub mod reset_season;
+pub mod reveal_loot;
 pub mod reset_player_for_testing;
+pub mod respawn_stale_player;
 pub mod session_auth;
 pub mod set_player_skin;
+pub mod stairs;
 pub mod tick_boss_fight;
 pub mod tick_job;
 pub mod unlock_door;
+pub mod update_game_config;
+pub mod upsert_item_definition;
+pub mod use_item;
+pub mod use_mystic_orb;
+pub mod use_phoenix_feather;
+pub mod use_rusted_compass;
+pub mod use_void_shard;
+pub mod view_boss_loot;
 
 pub use abandon_job::*;
 pub use add_inventory_item::*;
 pub use begin_session::*;
 pub use boost_job::*;
 pub use claim_job_reward::*;
+pub use close_stale_accounts::*;
 pub use complete_job::*;
 pub use create_player_profile::*;
 pub use end_session::*;
 pub use ensure_start_room::*;
 pub use exit_dungeon::*;
+pub use expire_job::*;
 pub use equip_item::*;
 pub use force_reset_season::*;
 pub use init_global::*;
 pub use join_boss_fight::*;
 pub use join_job::*;
 pub use join_job_with_session::*;
+pub use leave_boss_fight::*;
 pub use loot_boss::*;
 pub use loot_chest::*;
+pub use migrate_accounts::*;
 pub use move_player::*;
+pub use recover_stale_stake::*;
 pub use remove_inventory_item::*;
 pub use reset_season::*;
+pub use reveal_loot::*;
 pub use reset_player_for_testing::*;
+pub use respawn_stale_player::*;
 pub use set_player_skin::*;
+pub use stairs::*;
 pub use tick_boss_fight::*;
 pub use tick_job::*;
 pub use unlock_door::*;
+pub use update_game_config::*;
+pub use upsert_item_definition::*;
+pub use use_item::*;
+pub use use_mystic_orb::*;
+pub use use_phoenix_feather::*;
+pub use use_rusted_compass::*;
+pub use use_void_shard::*;
+pub use view_boss_loot::*;
@@ -5,8 +5,9 @@ use crate::events::PlayerMoved;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
     calculate_depth, enforce_special_room_topology, initialize_discovered_room,
-    session_instruction_bits, GlobalAccount, PlayerAccount, PlayerProfile, RoomAccount,
-    RoomPresence, SessionAuthority, LOCK_KIND_NONE, WALL_OPEN,
+    session_instruction_bits, FloorParams, GlobalAccount, PlayerAccount, PlayerProfile,
+    RoomAccount, RoomEntrance, RoomPresence, SessionAuthority, ENTRANCE_FLOOR, LOCK_KIND_NONE,
+    PLAYER_MAX_HP, WALL_OPEN,
 };
 
 #[derive(Accounts)]
@@ -49,6 +50,7 @@ pub struct MovePlayer<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -64,6 +66,7 @@ pub struct MovePlayer<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[new_x as u8],
             &[new_y as u8]
         ],
@@ -78,6 +81,7 @@ pub struct MovePlayer<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8],
             player.key().as_ref()
@@ -93,6 +97,7 @@ pub struct MovePlayer<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[new_x as u8],
             &[new_y as u8],
             player.key().as_ref()
@@ -128,6 +133,7 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
     let profile = &mut ctx.accounts.profile;
     let current_room = &ctx.accounts.current_room;
     let season_seed = ctx.accounts.global.season_seed;
+    let layout = ctx.accounts.global.layout;
     let player_key = ctx.accounts.player.key();
     let clock = Clock::get()?;
 
@@ -140,24 +146,23 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
     }
 
     // Check bounds
-    require!(
-        new_x >= GlobalAccount::MIN_COORD && new_x <= GlobalAccount::MAX_COORD,
-        ChainDepthError::OutOfBounds
-    );
-    require!(
-        new_y >= GlobalAccount::MIN_COORD && new_y <= GlobalAccount::MAX_COORD,
-        ChainDepthError::OutOfBounds
-    );
+    require!(layout.contains(new_x, new_y), ChainDepthError::OutOfBounds);
 
     // Initialize player if first time (new player starts at spawn)
     if player_account.owner == Pubkey::default() {
         player_account.owner = player_key;
-        player_account.current_room_x = GlobalAccount::START_X;
-        player_account.current_room_y = GlobalAccount::START_Y;
+        player_account.current_floor = ENTRANCE_FLOOR;
+        player_account.current_room_x = layout.start_x;
+        player_account.current_room_y = layout.start_y;
         player_account.active_jobs = Vec::new();
         player_account.jobs_completed = 0;
         player_account.chests_looted = 0;
         player_account.equipped_item_id = 0;
+        player_account.boss_fight_room = Pubkey::default();
+        player_account.hp = PLAYER_MAX_HP;
+        player_account.active_buff_id = 0;
+        player_account.buff_expires_slot = 0;
+        player_account.loot_protected = false;
         player_account.total_score = 0;
         player_account.current_run_start_slot = clock.slot;
         player_account.runs_extracted = 0;
@@ -166,6 +171,12 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
         player_account.bump = ctx.bumps.player_account;
     }
 
+    require!(
+        !player_account.is_in_boss_fight(),
+        ChainDepthError::InBossFight
+    );
+
+    let floor = player_account.current_floor;
     let from_x = player_account.current_room_x;
     let from_y = player_account.current_room_y;
 
@@ -213,10 +224,13 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
     if is_new_room {
         initialize_discovered_room(
             target_room,
-            season_seed,
-            new_x,
-            new_y,
-            opposite_direction,
+            &FloorParams::new(season_seed, &layout, floor),
+            &layout,
+            RoomEntrance {
+                x: new_x,
+                y: new_y,
+                direction: opposite_direction,
+            },
             player_key,
             clock.slot,
             ctx.bumps.target_room,
@@ -224,7 +238,7 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
     }
     target_room.walls[opposite_direction as usize] = WALL_OPEN;
     target_room.door_lock_kinds[opposite_direction as usize] = LOCK_KIND_NONE;
-    enforce_special_room_topology(target_room);
+    enforce_special_room_topology(target_room, &layout);
     let return_wall_state = target_room.walls[opposite_direction as usize];
     msg!(
         "move_topology target=({}, {}) return_dir={} return_wall_state={}",
@@ -238,7 +252,7 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
         ChainDepthError::WallNotOpen
     );
 
-    let room_depth = calculate_depth(new_x, new_y);
+    let room_depth = calculate_depth(&layout, floor, new_x, new_y);
     if room_depth > ctx.accounts.global.depth {
         ctx.accounts.global.depth = room_depth;
     }
@@ -266,9 +280,12 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
     upsert_presence(
         &mut ctx.accounts.target_presence,
         player_key,
-        season_seed,
-        new_x,
-        new_y,
+        PresenceRoom {
+            season_seed,
+            floor,
+            x: new_x,
+            y: new_y,
+        },
         profile.skin_id,
         player_account.equipped_item_id,
         ctx.bumps.target_presence,
@@ -278,6 +295,7 @@ pub fn handler(ctx: Context<MovePlayer>, new_x: i8, new_y: i8) -> Result<()> {
 
     emit!(PlayerMoved {
         player: player_key,
+        floor,
         from_x,
         from_y,
         to_x: new_x,
@@ -324,8 +342,9 @@ pub struct InitPlayer<'info> {
         seeds = [
             RoomPresence::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
-            &[GlobalAccount::START_X as u8],
-            &[GlobalAccount::START_Y as u8],
+            &[ENTRANCE_FLOOR],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
             player.key().as_ref()
         ],
         bump
@@ -344,12 +363,18 @@ pub fn init_player_handler(ctx: Context<InitPlayer>) -> Result<()> {
     let clock = Clock::get()?;
 
     player_account.owner = player_key;
-    player_account.current_room_x = GlobalAccount::START_X;
-    player_account.current_room_y = GlobalAccount::START_Y;
+    player_account.current_floor = ENTRANCE_FLOOR;
+    player_account.current_room_x = global.layout.start_x;
+    player_account.current_room_y = global.layout.start_y;
     player_account.active_jobs = Vec::new();
     player_account.jobs_completed = 0;
     player_account.chests_looted = 0;
     player_account.equipped_item_id = 0;
+    player_account.boss_fight_room = Pubkey::default();
+    player_account.hp = PLAYER_MAX_HP;
+    player_account.active_buff_id = 0;
+    player_account.buff_expires_slot = 0;
+    player_account.loot_protected = false;
     player_account.total_score = 0;
     player_account.current_run_start_slot = clock.slot;
     player_account.runs_extracted = 0;
@@ -365,8 +390,9 @@ pub fn init_player_handler(ctx: Context<InitPlayer>) -> Result<()> {
 
     room_presence.player = player_key;
     room_presence.season_seed = global.season_seed;
-    room_presence.room_x = GlobalAccount::START_X;
-    room_presence.room_y = GlobalAccount::START_Y;
+    room_presence.room_floor = ENTRANCE_FLOOR;
+    room_presence.room_x = global.layout.start_x;
+    room_presence.room_y = global.layout.start_y;
     room_presence.skin_id = profile.skin_id;
     room_presence.equipped_item_id = 0;
     room_presence.set_idle();
@@ -375,30 +401,38 @@ pub fn init_player_handler(ctx: Context<InitPlayer>) -> Result<()> {
 
     emit!(PlayerMoved {
         player: ctx.accounts.player.key(),
+        floor: ENTRANCE_FLOOR,
         from_x: 0,
         from_y: 0,
-        to_x: GlobalAccount::START_X,
-        to_y: GlobalAccount::START_Y,
+        to_x: global.layout.start_x,
+        to_y: global.layout.start_y,
     });
 
     Ok(())
 }
 
-fn upsert_presence(
+/// Room a presence account is opened for
+pub(crate) struct PresenceRoom {
+    pub season_seed: u64,
+    pub floor: u8,
+    pub x: i8,
+    pub y: i8,
+}
+
+pub(crate) fn upsert_presence(
     presence: &mut Account<RoomPresence>,
     player: Pubkey,
-    season_seed: u64,
-    room_x: i8,
-    room_y: i8,
+    room: PresenceRoom,
     skin_id: u16,
     equipped_item_id: u16,
     bump: u8,
 ) {
     if presence.player == Pubkey::default() {
         presence.player = player;
-        presence.season_seed = season_seed;
-        presence.room_x = room_x;
-        presence.room_y = room_y;
+        presence.season_seed = room.season_seed;
+        presence.room_floor = room.floor;
+        presence.room_x = room.x;
+        presence.room_y = room.y;
         presence.bump = bump;
     }
 
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::events::PlayerDied;
+use crate::instructions::move_player::{upsert_presence, PresenceRoom};
+use crate::state::{InventoryAccount, PlayerAccount, RoomAccount, RoomPresence, SeasonLayout};
+
+/// Kill a player whose HP reached 0 in `room`. Carried scored loot is lost
+/// unless a Phoenix Feather saves it, the run timer restarts and the player
+/// respawns at full HP in the start room of the same floor, so jobs they are
+/// staked in stay reachable.
+pub(crate) fn kill_player(
+    player_key: Pubkey,
+    player_account: &mut PlayerAccount,
+    inventory: &mut InventoryAccount,
+    room: &RoomAccount,
+    damage_taken: u64,
+    layout: &SeasonLayout,
+) -> Result<()> {
+    let lost_loot = player_account.lose_carried_loot(inventory);
+    let (lost_item_stacks, lost_item_units) = lost_loot.unwrap_or_default();
+    player_account.respawn(layout, Clock::get()?.slot);
+
+    emit!(PlayerDied {
+        player: player_key,
+        room_floor: room.floor,
+        room_x: room.x,
+        room_y: room.y,
+        boss_id: room.center_id,
+        damage_taken,
+        lost_item_stacks,
+        lost_item_units,
+        loot_saved: lost_loot.is_none(),
+    });
+
+    Ok(())
+}
+
+/// Hand the player's presence over from the room they died in to the
+/// respawn room they now stand in.
+pub(crate) fn move_presence_to_respawn(
+    player_key: Pubkey,
+    player_account: &PlayerAccount,
+    room_presence: &mut RoomPresence,
+    respawn_presence: &mut Account<RoomPresence>,
+    respawn_presence_bump: u8,
+) {
+    room_presence.is_current = false;
+    room_presence.set_idle();
+
+    upsert_presence(
+        respawn_presence,
+        player_key,
+        PresenceRoom {
+            season_seed: player_account.season_seed,
+            floor: player_account.current_floor,
+            x: player_account.current_room_x,
+            y: player_account.current_room_y,
+        },
+        room_presence.skin_id,
+        player_account.equipped_item_id,
+        respawn_presence_bump,
+    );
+    respawn_presence.is_current = true;
+    respawn_presence.set_idle();
+}
@@ -0,0 +1,417 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::errors::ChainDepthError;
+use crate::events::StaleStakeRecovered;
+use crate::instructions::migrate_accounts::{read_legacy_account, LegacyHelperStake};
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    session_instruction_bits, GlobalAccount, HelperStake, PlayerAccount, RoomAccount,
+    SessionAuthority, ENTRANCE_FLOOR,
+};
+
+#[derive(Accounts)]
+#[instruction(season_seed: u64, direction: u8)]
+pub struct RecoverStaleStake<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose stake is being recovered
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    /// Room from a previous season, addressed by its own season seed
+    #[account(
+        mut,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &season_seed.to_le_bytes(),
+            &[room.floor],
+            &[room.x as u8],
+            &[room.y as u8]
+        ],
+        bump
+    )]
+    pub room: Account<'info, RoomAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = player,
+        seeds = [
+            HelperStake::SEED_PREFIX,
+            room.key().as_ref(),
+            &[direction],
+            player.key().as_ref()
+        ],
+        bump = helper_stake.bump
+    )]
+    pub helper_stake: Account<'info, HelperStake>,
+
+    #[account(
+        mut,
+        constraint = player_token_account.mint == global.skr_mint,
+        constraint = player_token_account.owner == player.key()
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler(ctx: Context<RecoverStaleStake>, season_seed: u64, direction: u8) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::RECOVER_STALE_STAKE,
+        0,
+    )?;
+
+    require!(
+        RoomAccount::is_valid_direction(direction),
+        ChainDepthError::InvalidDirection
+    );
+    require!(
+        season_seed != ctx.accounts.global.season_seed,
+        ChainDepthError::SeasonStillCurrent
+    );
+
+    let room = &mut ctx.accounts.room;
+    let player_account = &mut ctx.accounts.player_account;
+    let dir_idx = direction as usize;
+
+    let job_completed = room.job_completed[dir_idx];
+    let payout_amount = room.release_stale_stake(dir_idx, &ctx.accounts.helper_stake)?;
+
+    // Only drop the active job entry while the player still belongs to the
+    // stale season; after a respawn the coordinates refer to the new season.
+    if player_account.season_seed == season_seed {
+        player_account.remove_job(room.x, room.y, direction);
+    }
+
+    let room_key = room.key();
+    let escrow_seeds = &[
+        b"escrow".as_ref(),
+        room_key.as_ref(),
+        &[direction],
+        &[ctx.bumps.escrow],
+    ];
+    let escrow_signer = &[&escrow_seeds[..]];
+
+    let payout_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.escrow.to_account_info(),
+        },
+        escrow_signer,
+    );
+    token::transfer(payout_ctx, payout_amount)?;
+
+    emit!(StaleStakeRecovered {
+        season_seed,
+        room_floor: room.floor,
+        room_x: room.x,
+        room_y: room.y,
+        direction,
+        player: ctx.accounts.player.key(),
+        payout_amount,
+        job_completed,
+    });
+
+    Ok(())
+}
+
+/// `RoomAccount` as the launch program laid it out, before floors, work rates
+/// and bonus pools. It lives at the floorless `["room", season_seed, x, y]`
+/// seeds, which the current program no longer derives.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+struct LegacyRoomAccount {
+    x: i8,
+    y: i8,
+    season_seed: u64,
+    walls: [u8; 4],
+    door_lock_kinds: [u8; 4],
+    helper_counts: [u32; 4],
+    progress: [u64; 4],
+    start_slot: [u64; 4],
+    base_slots: [u64; 4],
+    total_staked: [u64; 4],
+    job_completed: [bool; 4],
+    bonus_per_helper: [u64; 4],
+    has_chest: bool,
+    forced_key_drop: bool,
+    center_type: u8,
+    center_id: u16,
+    boss_max_hp: u64,
+    boss_current_hp: u64,
+    boss_last_update_slot: u64,
+    boss_total_dps: u64,
+    boss_fighter_count: u32,
+    boss_defeated: bool,
+    looted_count: u32,
+    created_by: Pubkey,
+    created_slot: u64,
+    bump: u8,
+}
+
+impl LegacyRoomAccount {
+    /// Release one stake the way the launch program's `claim_job_reward` did:
+    /// a completed job pays the stake plus the fixed per-helper bonus, an
+    /// unfinished one refunds the stake.
+    fn release_stake(&mut self, dir_idx: usize, stake_amount: u64) -> Result<u64> {
+        let bonus_amount = if self.job_completed[dir_idx] {
+            self.bonus_per_helper[dir_idx]
+        } else {
+            0
+        };
+        let payout_amount = stake_amount
+            .checked_add(bonus_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+
+        self.total_staked[dir_idx] = self.total_staked[dir_idx]
+            .checked_sub(stake_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+        self.helper_counts[dir_idx] = self.helper_counts[dir_idx]
+            .checked_sub(1)
+            .ok_or(ChainDepthError::Overflow)?;
+
+        if self.helper_counts[dir_idx] == 0 {
+            self.progress[dir_idx] = 0;
+            self.start_slot[dir_idx] = 0;
+            self.job_completed[dir_idx] = false;
+            self.bonus_per_helper[dir_idx] = 0;
+        }
+        Ok(payout_amount)
+    }
+}
+
+/// Recover a stake from a room the launch program created. Those rooms and
+/// their escrows sit at the floorless room seeds, and the stakes may still be
+/// in the launch layout.
+#[derive(Accounts)]
+#[instruction(season_seed: u64, room_x: i8, room_y: i8, direction: u8)]
+pub struct RecoverLegacyStake<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose stake is being recovered
+    #[account(mut)]
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    /// CHECK: still in the launch layout, so the handler reads it by hand
+    #[account(
+        mut,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &season_seed.to_le_bytes(),
+            &[room_x as u8],
+            &[room_y as u8]
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub room: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", room.key().as_ref(), &[direction]],
+        bump
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    /// CHECK: in the launch layout unless `migrate_helper_stake` grew it; the
+    /// handler reads the fields both layouts share
+    #[account(
+        mut,
+        seeds = [
+            HelperStake::SEED_PREFIX,
+            room.key().as_ref(),
+            &[direction],
+            player.key().as_ref()
+        ],
+        bump,
+        owner = crate::ID
+    )]
+    pub helper_stake: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = player_token_account.mint == global.skr_mint,
+        constraint = player_token_account.owner == player.key()
+    )]
+    pub player_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn recover_legacy_stake_handler(
+    mut ctx: Context<RecoverLegacyStake>,
+    season_seed: u64,
+    _room_x: i8,
+    _room_y: i8,
+    direction: u8,
+) -> Result<()> {
+    let recovered = release_legacy_stake(&mut ctx, season_seed, direction)?;
+
+    let room_key = ctx.accounts.room.key();
+    let escrow_seeds = &[
+        b"escrow".as_ref(),
+        room_key.as_ref(),
+        &[direction],
+        &[ctx.bumps.escrow],
+    ];
+    let escrow_signer = &[&escrow_seeds[..]];
+
+    let payout_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.player_token_account.to_account_info(),
+            authority: ctx.accounts.escrow.to_account_info(),
+        },
+        escrow_signer,
+    );
+    token::transfer(payout_ctx, recovered.payout_amount)?;
+
+    emit!(recovered);
+
+    Ok(())
+}
+
+/// Everything `recover_legacy_stake` does before paying out of escrow: the
+/// room's books, the player's job list and closing the stake. Returns the
+/// `StaleStakeRecovered` event for the handler to emit once the payout moved.
+pub fn release_legacy_stake(
+    ctx: &mut Context<RecoverLegacyStake>,
+    season_seed: u64,
+    direction: u8,
+) -> Result<StaleStakeRecovered> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::RECOVER_STALE_STAKE,
+        0,
+    )?;
+
+    require!(
+        RoomAccount::is_valid_direction(direction),
+        ChainDepthError::InvalidDirection
+    );
+
+    // Launch rooms stopped taking work at the upgrade, so unlike
+    // `recover_stale_stake` this does not wait for their season to end.
+    let room_info = ctx.accounts.room.to_account_info();
+    let mut room: LegacyRoomAccount = read_legacy_account(
+        &room_info,
+        RoomAccount::DISCRIMINATOR,
+        8 + LegacyRoomAccount::INIT_SPACE,
+    )?;
+    let stake_info = ctx.accounts.helper_stake.to_account_info();
+    let helper_stake = read_launch_stake_fields(&stake_info)?;
+
+    let dir_idx = direction as usize;
+    let job_completed = room.job_completed[dir_idx];
+    let payout_amount = room.release_stake(dir_idx, helper_stake.amount)?;
+    {
+        let mut data = room_info.try_borrow_mut_data()?;
+        room.serialize(&mut &mut data[RoomAccount::DISCRIMINATOR.len()..])
+            .map_err(|_| error!(ErrorCode::AccountDidNotSerialize))?;
+    }
+
+    let player_account = &mut ctx.accounts.player_account;
+    if player_account.season_seed == season_seed {
+        player_account.remove_job(room.x, room.y, direction);
+    }
+
+    // Close the stake to the player by hand, as `close = player` would.
+    let player_info = ctx.accounts.player.to_account_info();
+    **player_info.try_borrow_mut_lamports()? = player_info
+        .lamports()
+        .checked_add(stake_info.lamports())
+        .ok_or(ChainDepthError::Overflow)?;
+    **stake_info.try_borrow_mut_lamports()? = 0;
+    stake_info.assign(&system_program::ID);
+    stake_info.resize(0)?;
+
+    // Every launch room was on what is now the entrance floor.
+    Ok(StaleStakeRecovered {
+        season_seed,
+        room_floor: ENTRANCE_FLOOR,
+        room_x: room.x,
+        room_y: room.y,
+        direction,
+        player: ctx.accounts.player.key(),
+        payout_amount,
+        job_completed,
+    })
+}
+
+/// The fields a helper stake had at launch. Work rates and buffs were appended
+/// after `bump`, so this also reads stakes `migrate_helper_stake` already grew.
+fn read_launch_stake_fields(stake_info: &AccountInfo) -> Result<LegacyHelperStake> {
+    let data = stake_info.try_borrow_data()?;
+    require!(
+        data.starts_with(HelperStake::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    LegacyHelperStake::deserialize(&mut &data[HelperStake::DISCRIMINATOR.len()..])
+        .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+}
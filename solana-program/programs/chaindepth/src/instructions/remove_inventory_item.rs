@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::errors::ChainDepthError;
 use crate::events::InventoryItemRemoved;
 use crate::instructions::session_auth::authorize_player_action;
-use crate::state::{session_instruction_bits, InventoryAccount, SessionAuthority};
+use crate::state::{session_instruction_bits, InventoryAccount, ItemRegistry, SessionAuthority};
 
 #[derive(Accounts)]
 pub struct RemoveInventoryItem<'info> {
@@ -43,6 +43,13 @@ pub fn handler(ctx: Context<RemoveInventoryItem>, item_id: u16, amount: u32) ->
 
     require!(item_id > 0, ChainDepthError::InvalidItemId);
     require!(amount > 0, ChainDepthError::InvalidItemAmount);
+    // The item's definition PDA is passed as a remaining account.
+    require!(
+        ItemRegistry::new(ctx.remaining_accounts)
+            .stats(item_id)?
+            .tradeable,
+        ChainDepthError::ItemNotTradeable
+    );
 
     let inventory = &mut ctx.accounts.inventory;
     inventory.remove_item(item_id, amount)?;
@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
 use crate::events::SeasonReset;
-use crate::state::GlobalAccount;
+use crate::state::{GlobalAccount, SeasonLayout};
 
 #[derive(Accounts)]
 pub struct ResetSeason<'info> {
@@ -18,7 +18,8 @@ pub struct ResetSeason<'info> {
     pub global: Account<'info, GlobalAccount>,
 }
 
-pub fn handler(ctx: Context<ResetSeason>) -> Result<()> {
+/// `layout` sets the grid for the new season; `None` keeps the current one.
+pub fn handler(ctx: Context<ResetSeason>, layout: Option<SeasonLayout>) -> Result<()> {
     let global = &mut ctx.accounts.global;
     let clock = Clock::get()?;
 
@@ -28,23 +29,34 @@ pub fn handler(ctx: Context<ResetSeason>) -> Result<()> {
         ChainDepthError::SeasonNotEnded
     );
 
-    let (old_seed, new_seed, old_depth, end_slot) = apply_season_reset(global, clock.slot);
+    let (old_seed, new_seed, old_depth, end_slot) = apply_season_reset(global, clock.slot, layout)?;
 
     // Note: Room and player accounts from old season become orphaned
     // They use the old season_seed in their PDA, so new rooms will use new PDAs
     // This is a "soft reset" - old data stays on chain but is no longer relevant
+    // Stranded stakes are reclaimed with recover_stale_stake and players are
+    // moved into the new season with respawn_stale_player. Once drained, the
+    // leftover accounts can be reclaimed by anyone via the close_stale_* cranks.
 
     emit!(SeasonReset {
         old_seed,
         new_seed,
         old_depth,
         end_slot,
+        layout: global.layout,
     });
 
     Ok(())
 }
 
-pub(crate) fn apply_season_reset(global: &mut GlobalAccount, current_slot: u64) -> (u64, u64, u32, u64) {
+pub(crate) fn apply_season_reset(
+    global: &mut GlobalAccount,
+    current_slot: u64,
+    layout: Option<SeasonLayout>,
+) -> Result<(u64, u64, u32, u64)> {
+    let layout = layout.unwrap_or(global.layout);
+    require!(layout.is_valid(), ChainDepthError::InvalidSeasonLayout);
+
     let old_seed = global.season_seed;
     let old_depth = global.depth;
     let new_seed = generate_new_seed(old_seed, current_slot);
@@ -54,8 +66,9 @@ pub(crate) fn apply_season_reset(global: &mut GlobalAccount, current_slot: u64)
     global.depth = 0;
     global.jobs_completed = 0;
     global.end_slot = end_slot;
+    global.layout = layout;
 
-    (old_seed, new_seed, old_depth, end_slot)
+    Ok((old_seed, new_seed, old_depth, end_slot))
 }
 
 /// Generate new seed from old seed and current slot
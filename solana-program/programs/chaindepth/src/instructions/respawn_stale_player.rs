@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::StalePlayerRespawned;
+use crate::instructions::move_player::{upsert_presence, PresenceRoom};
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    session_instruction_bits, GlobalAccount, PlayerAccount, PlayerProfile, RoomPresence,
+    SessionAuthority, ENTRANCE_FLOOR, PLAYER_MAX_HP,
+};
+
+#[derive(Accounts)]
+pub struct RespawnStalePlayer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [PlayerProfile::SEED_PREFIX, player.key().as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, PlayerProfile>,
+
+    /// Presence in the current season's start room
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[ENTRANCE_FLOOR],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub start_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RespawnStalePlayer>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::RESPAWN_STALE_PLAYER,
+        0,
+    )?;
+
+    let player_account = &mut ctx.accounts.player_account;
+    let season_seed = ctx.accounts.global.season_seed;
+    let player_key = ctx.accounts.player.key();
+    let clock = Clock::get()?;
+
+    require!(
+        player_account.season_seed != season_seed,
+        ChainDepthError::PlayerAlreadyInCurrentSeason
+    );
+
+    let old_season_seed = player_account.season_seed;
+    let from_floor = player_account.current_floor;
+    let from_x = player_account.current_room_x;
+    let from_y = player_account.current_room_y;
+
+    // Old-season jobs can no longer be addressed through the player's position;
+    // their stakes are reclaimed with recover_stale_stake using the old seed.
+    player_account.active_jobs = Vec::new();
+    player_account.boss_fight_room = Pubkey::default();
+    player_account.hp = PLAYER_MAX_HP;
+    player_account.active_buff_id = 0;
+    player_account.buff_expires_slot = 0;
+    player_account.current_floor = ENTRANCE_FLOOR;
+    player_account.current_room_x = ctx.accounts.global.layout.start_x;
+    player_account.current_room_y = ctx.accounts.global.layout.start_y;
+    player_account.current_run_start_slot = clock.slot;
+    player_account.season_seed = season_seed;
+
+    upsert_presence(
+        &mut ctx.accounts.start_presence,
+        player_key,
+        PresenceRoom {
+            season_seed,
+            floor: ENTRANCE_FLOOR,
+            x: ctx.accounts.global.layout.start_x,
+            y: ctx.accounts.global.layout.start_y,
+        },
+        ctx.accounts.profile.skin_id,
+        player_account.equipped_item_id,
+        ctx.bumps.start_presence,
+    );
+    ctx.accounts.start_presence.is_current = true;
+    ctx.accounts.start_presence.set_idle();
+
+    emit!(StalePlayerRespawned {
+        player: player_key,
+        old_season_seed,
+        new_season_seed: season_seed,
+        from_floor,
+        from_x,
+        from_y,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::errors::ChainDepthError;
+use crate::events::{BossLooted, ChestLooted};
+use crate::instructions::loot_boss::roll_boss_loot;
+use crate::instructions::loot_chest::roll_chest_loot;
+use crate::state::{
+    loot_kinds, mix_entropy, resolve_slot_hash, scale_boss_loot_amount, GlobalAccount,
+    InventoryAccount, ItemRegistry, LootReceipt, SlotHashLookup, SlotHashesProvider,
+    EXPIRED_LOOT_DROP,
+};
+
+/// Settle a committed chest/boss loot roll.
+/// Permissionless, so anyone can settle a receipt for its player; the loot
+/// always goes to the receipt's player. A receipt left until its slot hash
+/// expires settles with `EXPIRED_LOOT_DROP`, so waiting never buys a re-roll.
+#[derive(Accounts)]
+pub struct RevealLoot<'info> {
+    pub revealer: Signer<'info>,
+
+    /// CHECK: wallet owner of the loot receipt
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            LootReceipt::SEED_PREFIX,
+            &loot_receipt.season_seed.to_le_bytes(),
+            &[loot_receipt.room_floor],
+            &[loot_receipt.room_x as u8],
+            &[loot_receipt.room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = loot_receipt.bump,
+        constraint = loot_receipt.player == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub loot_receipt: Account<'info, LootReceipt>,
+
+    /// Created when the loot was committed
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    /// CHECK: SlotHashes sysvar, decoded in place by SlotHashesProvider
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<RevealLoot>) -> Result<()> {
+    let loot_receipt = &mut ctx.accounts.loot_receipt;
+    let player_key = ctx.accounts.player.key();
+
+    require!(!loot_receipt.revealed, ChainDepthError::LootAlreadyRevealed);
+
+    let slot_hashes = ctx.accounts.slot_hashes.to_account_info();
+    let provider = SlotHashesProvider::new(&slot_hashes)?;
+    let slot_hash = match resolve_slot_hash(&provider, loot_receipt.reveal_slot())? {
+        SlotHashLookup::Found(slot_hash) => Some(slot_hash),
+        SlotHashLookup::Pending => return err!(ChainDepthError::LootRevealPending),
+        SlotHashLookup::Expired => None,
+    };
+
+    let depth = ctx.accounts.global.layout.depth(
+        loot_receipt.room_floor,
+        loot_receipt.room_x,
+        loot_receipt.room_y,
+    );
+    for (item_type, item_amount, item_id, last_hit_bonus) in
+        receipt_drops(loot_receipt, slot_hash.as_ref(), depth)
+    {
+        grant_loot(
+            &mut ctx.accounts.inventory,
+            ctx.remaining_accounts,
+            item_id,
+            item_amount,
+        )?;
+
+        if loot_receipt.kind == loot_kinds::BOSS {
+            emit!(BossLooted {
+                room_floor: loot_receipt.room_floor,
+                room_x: loot_receipt.room_x,
+                room_y: loot_receipt.room_y,
+                player: player_key,
+                item_type,
+                item_amount,
+                last_hit_bonus,
+            });
+        } else {
+            emit!(ChestLooted {
+                room_floor: loot_receipt.room_floor,
+                room_x: loot_receipt.room_x,
+                room_y: loot_receipt.room_y,
+                player: player_key,
+                item_type,
+                item_amount,
+            });
+        }
+    }
+    loot_receipt.revealed = true;
+
+    Ok(())
+}
+
+/// Drops owed for a receipt as (item_type, item_amount, item_id, last_hit_bonus).
+/// `slot_hash` is the hash of the receipt's reveal slot, or None once it has
+/// left the SlotHashes window; an expired receipt always settles with the
+/// single `EXPIRED_LOOT_DROP`.
+pub(crate) fn receipt_drops(
+    loot_receipt: &LootReceipt,
+    slot_hash: Option<&[u8; 32]>,
+    depth: u32,
+) -> Vec<(u8, u8, u16, bool)> {
+    let Some(slot_hash) = slot_hash else {
+        let (item_type, item_amount, item_id) = EXPIRED_LOOT_DROP;
+        return vec![(item_type, item_amount, item_id, false)];
+    };
+
+    let loot_hash = mix_entropy(
+        slot_hash,
+        &[
+            loot_receipt.player.as_ref(),
+            &loot_receipt.season_seed.to_le_bytes(),
+            &[
+                loot_receipt.room_floor,
+                loot_receipt.room_x as u8,
+                loot_receipt.room_y as u8,
+            ],
+            &[loot_receipt.kind],
+            &loot_receipt.source_id.to_le_bytes(),
+        ],
+    );
+    if loot_receipt.kind != loot_kinds::BOSS {
+        let (item_type, item_amount, item_id) = roll_chest_loot(loot_hash, depth);
+        return vec![(item_type, item_amount, item_id, false)];
+    }
+
+    let (item_type, item_amount, item_id) = roll_boss_loot(loot_hash, loot_receipt.source_id);
+    let item_amount = scale_boss_loot_amount(item_amount, loot_receipt.damage_share_bps);
+    let mut drops = vec![(item_type, item_amount, item_id, false)];
+    if loot_receipt.last_hitter {
+        let bonus_hash = mix_entropy(slot_hash, &[loot_receipt.player.as_ref(), b"last_hit_drop"]);
+        let (item_type, item_amount, item_id) = roll_boss_loot(bonus_hash, loot_receipt.source_id);
+        drops.push((item_type, item_amount, item_id, true));
+    }
+    drops
+}
+
+/// Add a drop to the inventory. The rolled item's definition PDA must be
+/// passed as a remaining account; the roll is known once the slot hash is.
+fn grant_loot(
+    inventory: &mut InventoryAccount,
+    item_definitions: &[AccountInfo],
+    item_id: u16,
+    item_amount: u8,
+) -> Result<()> {
+    let stats = ItemRegistry::new(item_definitions).stats(item_id)?;
+    inventory.add_item(
+        item_id,
+        u32::from(item_amount),
+        stats.base_durability,
+        &stats,
+    )
+}
@@ -0,0 +1,375 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::FloorChanged;
+use crate::instructions::move_player::{upsert_presence, PresenceRoom};
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    calculate_depth, initialize_start_room, session_instruction_bits, GlobalAccount, PlayerAccount,
+    PlayerProfile, RoomAccount, RoomPresence, SessionAuthority, CENTER_STAIRS_DOWN, ENTRANCE_FLOOR,
+};
+
+/// Take the stairs in the current room down to the start room of the floor below.
+#[derive(Accounts)]
+pub struct DescendStairs<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    /// Global game state - also acts as the SOL treasury for room creation rent
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [PlayerProfile::SEED_PREFIX, player.key().as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, PlayerProfile>,
+
+    /// Room with the stairs down
+    #[account(
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8]
+        ],
+        bump
+    )]
+    pub current_room: Account<'info, RoomAccount>,
+
+    /// Start room of the floor below (initialized by the first player down)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + RoomAccount::INIT_SPACE,
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor.saturating_add(1)],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8]
+        ],
+        bump
+    )]
+    pub target_room: Account<'info, RoomAccount>,
+
+    /// Closed on floor change so rent returns to the treasury (global PDA)
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = current_presence.bump
+    )]
+    pub current_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor.saturating_add(1)],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub target_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn descend_handler(ctx: Context<DescendStairs>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::CHANGE_FLOOR,
+        0,
+    )?;
+
+    let player_account = &mut ctx.accounts.player_account;
+    let current_room = &ctx.accounts.current_room;
+    let layout = ctx.accounts.global.layout;
+    let season_seed = ctx.accounts.global.season_seed;
+    let player_key = ctx.accounts.player.key();
+    let clock = Clock::get()?;
+
+    require!(
+        current_room.center_type == CENTER_STAIRS_DOWN
+            && layout.has_floor_below(current_room.floor),
+        ChainDepthError::NotAtStairs
+    );
+    require!(
+        player_account.active_jobs.is_empty(),
+        ChainDepthError::CannotChangeFloorWithActiveJobs
+    );
+    require!(
+        !player_account.is_in_boss_fight(),
+        ChainDepthError::InBossFight
+    );
+
+    let from_floor = current_room.floor;
+    let (from_x, from_y) = (current_room.x, current_room.y);
+    let to_floor = from_floor + 1;
+
+    let target_room = &mut ctx.accounts.target_room;
+    let is_new_room = target_room.season_seed == 0;
+    if is_new_room {
+        initialize_start_room(
+            target_room,
+            season_seed,
+            &layout,
+            to_floor,
+            player_key,
+            clock.slot,
+            ctx.bumps.target_room,
+        );
+    }
+
+    let floor_depth = calculate_depth(&layout, to_floor, layout.start_x, layout.start_y);
+    if floor_depth > ctx.accounts.global.depth {
+        ctx.accounts.global.depth = floor_depth;
+    }
+
+    // Reimburse authority for room creation rent from treasury (manual lamport transfer)
+    if is_new_room {
+        let room_space = 8 + RoomAccount::INIT_SPACE;
+        let rent_cost = Rent::get()?.minimum_balance(room_space);
+        let global_info = ctx.accounts.global.to_account_info();
+        let authority_info = ctx.accounts.authority.to_account_info();
+        **global_info.try_borrow_mut_lamports()? = global_info
+            .lamports()
+            .checked_sub(rent_cost)
+            .ok_or(ChainDepthError::TreasuryInsufficientFunds)?;
+        **authority_info.try_borrow_mut_lamports()? = authority_info
+            .lamports()
+            .checked_add(rent_cost)
+            .ok_or(ChainDepthError::Overflow)?;
+    }
+
+    player_account.current_floor = to_floor;
+    player_account.current_room_x = layout.start_x;
+    player_account.current_room_y = layout.start_y;
+
+    upsert_presence(
+        &mut ctx.accounts.target_presence,
+        player_key,
+        PresenceRoom {
+            season_seed,
+            floor: to_floor,
+            x: layout.start_x,
+            y: layout.start_y,
+        },
+        ctx.accounts.profile.skin_id,
+        player_account.equipped_item_id,
+        ctx.bumps.target_presence,
+    );
+    ctx.accounts.target_presence.is_current = true;
+    ctx.accounts.target_presence.set_idle();
+
+    emit!(FloorChanged {
+        player: player_key,
+        from_floor,
+        from_x,
+        from_y,
+        to_floor,
+        to_x: layout.start_x,
+        to_y: layout.start_y,
+    });
+
+    Ok(())
+}
+
+/// Climb the entrance stairs of a lower floor's start room back to the
+/// stairs-down room of the floor above.
+#[derive(Accounts)]
+pub struct AscendStairs<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    /// Global game state - receives the closed presence rent
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        seeds = [PlayerProfile::SEED_PREFIX, player.key().as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, PlayerProfile>,
+
+    /// Stairs-down room on the floor above; it was created when the way down
+    /// was first taken.
+    #[account(
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor.saturating_sub(1)],
+            &[target_room.x as u8],
+            &[target_room.y as u8]
+        ],
+        bump
+    )]
+    pub target_room: Account<'info, RoomAccount>,
+
+    /// Closed on floor change so rent returns to the treasury (global PDA)
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = current_presence.bump
+    )]
+    pub current_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor.saturating_sub(1)],
+            &[target_room.x as u8],
+            &[target_room.y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub target_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn ascend_handler(ctx: Context<AscendStairs>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::CHANGE_FLOOR,
+        0,
+    )?;
+
+    let player_account = &mut ctx.accounts.player_account;
+    let target_room = &ctx.accounts.target_room;
+    let layout = ctx.accounts.global.layout;
+    let player_key = ctx.accounts.player.key();
+
+    let from_floor = player_account.current_floor;
+    let (from_x, from_y) = (player_account.current_room_x, player_account.current_room_y);
+    require!(
+        from_floor > ENTRANCE_FLOOR && layout.is_start(from_x, from_y),
+        ChainDepthError::NotAtStairs
+    );
+    require!(
+        target_room.center_type == CENTER_STAIRS_DOWN,
+        ChainDepthError::NotAtStairs
+    );
+    require!(
+        player_account.active_jobs.is_empty(),
+        ChainDepthError::CannotChangeFloorWithActiveJobs
+    );
+    require!(
+        !player_account.is_in_boss_fight(),
+        ChainDepthError::InBossFight
+    );
+
+    let to_floor = target_room.floor;
+    player_account.current_floor = to_floor;
+    player_account.current_room_x = target_room.x;
+    player_account.current_room_y = target_room.y;
+
+    upsert_presence(
+        &mut ctx.accounts.target_presence,
+        player_key,
+        PresenceRoom {
+            season_seed: ctx.accounts.global.season_seed,
+            floor: to_floor,
+            x: target_room.x,
+            y: target_room.y,
+        },
+        ctx.accounts.profile.skin_id,
+        player_account.equipped_item_id,
+        ctx.bumps.target_presence,
+    );
+    ctx.accounts.target_presence.is_current = true;
+    ctx.accounts.target_presence.set_idle();
+
+    emit!(FloorChanged {
+        player: player_key,
+        from_floor,
+        from_x,
+        from_y,
+        to_floor,
+        to_x: target_room.x,
+        to_y: target_room.y,
+    });
+
+    Ok(())
+}
@@ -3,7 +3,10 @@ use anchor_lang::prelude::*;
 use crate::errors::ChainDepthError;
 use crate::events::BossTicked;
 use crate::instructions::join_boss_fight::apply_boss_damage;
-use crate::state::{GlobalAccount, RoomAccount, CENTER_BOSS};
+use crate::state::{
+    passed_buff_schedule, BossRoster, BuffSchedule, GlobalAccount, RoomAccount, BUFF_TARGET_BOSS,
+    CENTER_BOSS,
+};
 
 #[derive(Accounts)]
 pub struct TickBossFight<'info> {
@@ -20,12 +23,28 @@ pub struct TickBossFight<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
         bump
     )]
     pub room: Account<'info, RoomAccount>,
+
+    /// Buff bonuses of this boss fight; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[BUFF_TARGET_BOSS]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
+
+    /// The fighters a kill in this tick is settled for
+    #[account(
+        seeds = [BossRoster::SEED_PREFIX, room.key().as_ref()],
+        bump = boss_roster.bump
+    )]
+    pub boss_roster: Box<Account<'info, BossRoster>>,
 }
 
 pub fn handler(ctx: Context<TickBossFight>) -> Result<()> {
@@ -45,9 +64,15 @@ pub fn handler(ctx: Context<TickBossFight>) -> Result<()> {
         ChainDepthError::NoActiveJob
     );
 
-    apply_boss_damage(room, clock.slot)?;
+    apply_boss_damage(
+        room,
+        passed_buff_schedule(&mut ctx.accounts.buff_schedule),
+        &ctx.accounts.boss_roster,
+        clock.slot,
+    )?;
 
     emit!(BossTicked {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         boss_id: room.center_id,
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
-use crate::state::{GlobalAccount, RoomAccount};
+use crate::state::{passed_buff_schedule, BuffSchedule, GlobalAccount, RoomAccount};
 
 #[derive(Accounts)]
 #[instruction(direction: u8)]
@@ -21,12 +21,21 @@ pub struct TickJob<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[room.x as u8],
             &[room.y as u8]
         ],
         bump
     )]
     pub room: Account<'info, RoomAccount>,
+
+    /// Buff bonuses of this job; required while any is running
+    #[account(
+        mut,
+        seeds = [BuffSchedule::SEED_PREFIX, room.key().as_ref(), &[direction]],
+        bump = buff_schedule.bump
+    )]
+    pub buff_schedule: Option<Box<Account<'info, BuffSchedule>>>,
 }
 
 pub fn handler(ctx: Context<TickJob>, direction: u8) -> Result<()> {
@@ -49,25 +58,13 @@ pub fn handler(ctx: Context<TickJob>, direction: u8) -> Result<()> {
     // Check wall is still rubble
     require!(room.is_rubble(direction), ChainDepthError::NotRubble);
 
-    // Calculate elapsed slots since job started
-    let elapsed_slots = clock.slot.saturating_sub(room.start_slot[dir_idx]);
-
-    // Progress is inversely proportional to helper count
-    // More helpers = faster progress
-    // Each helper contributes: elapsed_slots / helper_count to progress
-    let helper_count = room.helper_counts[dir_idx] as u64;
-    
-    // New progress calculation: 
-    // With more helpers, the work is divided more efficiently
-    // Total effective progress = elapsed_slots * helper_count / base_divider
-    // This makes more helpers speed things up
-    let effective_progress = elapsed_slots
-        .checked_mul(helper_count)
-        .ok_or(ChainDepthError::Overflow)?
-        / 1; // Direct multiplication - more helpers = faster
-
-    // Update progress (capped at base_slots to prevent overflow)
-    room.progress[dir_idx] = effective_progress.min(room.base_slots[dir_idx]);
+    // Progress accrues at the helpers' combined work rate since the last
+    // settlement; better tools mean faster progress.
+    room.settle_job_progress(
+        dir_idx,
+        passed_buff_schedule(&mut ctx.accounts.buff_schedule),
+        clock.slot,
+    )?;
 
     Ok(())
 }
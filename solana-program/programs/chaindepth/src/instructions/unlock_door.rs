@@ -5,8 +5,10 @@ use crate::events::DoorUnlocked;
 use crate::instructions::session_auth::authorize_player_action;
 use crate::state::{
     calculate_depth, enforce_special_room_topology, initialize_discovered_room, item_ids,
-    session_instruction_bits, GlobalAccount, InventoryAccount, PlayerAccount, RoomAccount,
-    SessionAuthority, LOCK_KIND_NONE, LOCK_KIND_SKELETON, WALL_LOCKED, WALL_OPEN,
+    session_instruction_bits, FloorParams, GlobalAccount, InventoryAccount, PlayerAccount,
+    RoomAccount, RoomEntrance, SessionAuthority, CENTER_BOSS, LOCK_KIND_BOSS_SEAL,
+    LOCK_KIND_EMERALD, LOCK_KIND_NONE, LOCK_KIND_RUBY, LOCK_KIND_SAPPHIRE, LOCK_KIND_SKELETON,
+    WALL_LOCKED, WALL_OPEN,
 };
 
 #[derive(Accounts)]
@@ -37,6 +39,7 @@ pub struct UnlockDoor<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
             &[player_account.current_room_x as u8],
             &[player_account.current_room_y as u8]
         ],
@@ -51,6 +54,7 @@ pub struct UnlockDoor<'info> {
         seeds = [
             RoomAccount::SEED_PREFIX,
             &global.season_seed.to_le_bytes(),
+            &[room.floor],
             &[adjacent_x(room.x, direction) as u8],
             &[adjacent_y(room.y, direction) as u8]
         ],
@@ -66,6 +70,19 @@ pub struct UnlockDoor<'info> {
     )]
     pub inventory: Account<'info, InventoryAccount>,
 
+    /// Defeated boss room on the same floor and ring; only needed for boss seals
+    #[account(
+        seeds = [
+            RoomAccount::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[boss_room.floor],
+            &[boss_room.x as u8],
+            &[boss_room.y as u8]
+        ],
+        bump = boss_room.bump
+    )]
+    pub boss_room: Option<Account<'info, RoomAccount>>,
+
     #[account(
         mut,
         seeds = [
@@ -100,7 +117,7 @@ pub fn handler(ctx: Context<UnlockDoor>, direction: u8) -> Result<()> {
     let direction_index = direction as usize;
 
     require!(
-        player_account.is_at_room(room.x, room.y),
+        player_account.is_at_room(room.floor, room.x, room.y),
         ChainDepthError::NotInRoom
     );
     require!(
@@ -109,8 +126,28 @@ pub fn handler(ctx: Context<UnlockDoor>, direction: u8) -> Result<()> {
     );
 
     let lock_kind = room.door_lock_kinds[direction_index];
-    let key_item_id = key_item_id_for_lock_kind(lock_kind)?;
-    ctx.accounts.inventory.remove_item(key_item_id, 1)?;
+    let key_item_id = match lock_requirement(lock_kind)? {
+        LockRequirement::Key { item_id, amount } => {
+            ctx.accounts.inventory.remove_item(item_id, amount)?;
+            item_id
+        }
+        LockRequirement::DefeatedBossInRing => {
+            let layout = &ctx.accounts.global.layout;
+            let boss_room = ctx
+                .accounts
+                .boss_room
+                .as_ref()
+                .ok_or(ChainDepthError::BossSealIntact)?;
+            require!(
+                boss_room.floor == room.floor
+                    && layout.ring(boss_room.x, boss_room.y) == layout.ring(room.x, room.y)
+                    && boss_room.center_type == CENTER_BOSS
+                    && boss_room.boss_defeated,
+                ChainDepthError::BossSealIntact
+            );
+            0
+        }
+    };
 
     room.walls[direction_index] = WALL_OPEN;
     room.door_lock_kinds[direction_index] = LOCK_KIND_NONE;
@@ -119,12 +156,16 @@ pub fn handler(ctx: Context<UnlockDoor>, direction: u8) -> Result<()> {
     let clock = Clock::get()?;
     let adjacent_room = &mut ctx.accounts.adjacent_room;
     if adjacent_room.season_seed == 0 {
+        let global = &ctx.accounts.global;
         initialize_discovered_room(
             adjacent_room,
-            ctx.accounts.global.season_seed,
-            adjacent_x(room.x, direction),
-            adjacent_y(room.y, direction),
-            opposite_direction,
+            &FloorParams::new(global.season_seed, &global.layout, room.floor),
+            &global.layout,
+            RoomEntrance {
+                x: adjacent_x(room.x, direction),
+                y: adjacent_y(room.y, direction),
+                direction: opposite_direction,
+            },
             player_key,
             clock.slot,
             ctx.bumps.adjacent_room,
@@ -133,18 +174,25 @@ pub fn handler(ctx: Context<UnlockDoor>, direction: u8) -> Result<()> {
 
     adjacent_room.walls[opposite_direction as usize] = WALL_OPEN;
     adjacent_room.door_lock_kinds[opposite_direction as usize] = LOCK_KIND_NONE;
-    enforce_special_room_topology(adjacent_room);
+    enforce_special_room_topology(adjacent_room, &ctx.accounts.global.layout);
 
-    let new_depth = calculate_depth(adjacent_room.x, adjacent_room.y);
+    let new_depth = calculate_depth(
+        &ctx.accounts.global.layout,
+        adjacent_room.floor,
+        adjacent_room.x,
+        adjacent_room.y,
+    );
     if new_depth > ctx.accounts.global.depth {
         ctx.accounts.global.depth = new_depth;
     }
 
     emit!(DoorUnlocked {
+        room_floor: room.floor,
         room_x: room.x,
         room_y: room.y,
         direction,
         player: player_key,
+        lock_kind,
         key_item_id: key_item_id,
     });
 
@@ -167,9 +215,23 @@ fn adjacent_y(y: i8, direction: u8) -> i8 {
     }
 }
 
-fn key_item_id_for_lock_kind(lock_kind: u8) -> Result<u16> {
+/// What it takes to open a door of a given lock kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LockRequirement {
+    /// Consumes `amount` of `item_id` from the player's inventory
+    Key { item_id: u16, amount: u32 },
+    /// Consumes nothing, but a boss on the same floor and ring must be dead
+    DefeatedBossInRing,
+}
+
+fn lock_requirement(lock_kind: u8) -> Result<LockRequirement> {
+    let key = |item_id| Ok(LockRequirement::Key { item_id, amount: 1 });
     match lock_kind {
-        LOCK_KIND_SKELETON => Ok(item_ids::SKELETON_KEY),
+        LOCK_KIND_SKELETON => key(item_ids::SKELETON_KEY),
+        LOCK_KIND_RUBY => key(item_ids::RUBY),
+        LOCK_KIND_SAPPHIRE => key(item_ids::SAPPHIRE),
+        LOCK_KIND_EMERALD => key(item_ids::EMERALD),
+        LOCK_KIND_BOSS_SEAL => Ok(LockRequirement::DefeatedBossInRing),
         LOCK_KIND_NONE => err!(ChainDepthError::WallNotLocked),
         _ => err!(ChainDepthError::InvalidLockKind),
     }
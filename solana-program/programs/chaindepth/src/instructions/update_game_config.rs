@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::GameConfigUpdated;
+use crate::state::{GameConfig, GlobalAccount};
+
+#[derive(Accounts)]
+pub struct UpdateGameConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump,
+        constraint = global.admin == admin.key() @ ChainDepthError::Unauthorized
+    )]
+    pub global: Account<'info, GlobalAccount>,
+}
+
+pub fn handler(ctx: Context<UpdateGameConfig>, config: GameConfig) -> Result<()> {
+    require!(config.is_valid(), ChainDepthError::InvalidGameConfig);
+
+    ctx.accounts.global.config = config;
+
+    emit!(GameConfigUpdated {
+        admin: ctx.accounts.admin.key(),
+        config,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::ItemDefinitionUpserted;
+use crate::state::{GlobalAccount, ItemDefinition, ItemStats};
+
+#[derive(Accounts)]
+#[instruction(item_id: u16)]
+pub struct UpsertItemDefinition<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump,
+        constraint = global.admin == admin.key() @ ChainDepthError::Unauthorized
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ItemDefinition::DISCRIMINATOR.len() + ItemDefinition::INIT_SPACE,
+        seeds = [ItemDefinition::SEED_PREFIX, &item_id.to_le_bytes()],
+        bump
+    )]
+    pub item_definition: Account<'info, ItemDefinition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<UpsertItemDefinition>, item_id: u16, stats: ItemStats) -> Result<()> {
+    require!(item_id > 0, ChainDepthError::InvalidItemId);
+
+    let item_definition = &mut ctx.accounts.item_definition;
+    item_definition.item_id = item_id;
+    item_definition.stats = stats;
+    item_definition.bump = ctx.bumps.item_definition;
+
+    emit!(ItemDefinitionUpserted {
+        admin: ctx.accounts.admin.key(),
+        item_id,
+        category: stats.category,
+        score_value: stats.score_value,
+        base_durability: stats.base_durability,
+        combat_dps: stats.combat_dps,
+        mining_speed: stats.mining_speed,
+        stackable: stats.stackable,
+        tradeable: stats.tradeable,
+    });
+
+    Ok(())
+}
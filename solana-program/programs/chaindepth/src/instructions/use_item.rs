@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::BuffActivated;
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    buff_effect, session_instruction_bits, InventoryAccount, PlayerAccount, SessionAuthority,
+};
+
+#[derive(Accounts)]
+pub struct UseItem<'info> {
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+}
+
+pub fn handler(ctx: Context<UseItem>, item_id: u16) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::USE_ITEM,
+        0,
+    )?;
+
+    let buff = buff_effect(item_id).ok_or(ChainDepthError::ItemNotUsable)?;
+    let player_account = &mut ctx.accounts.player_account;
+    let clock = Clock::get()?;
+
+    // One buff at a time; a second one would only overwrite the first.
+    require!(
+        player_account.active_buff(clock.slot).is_none(),
+        ChainDepthError::BuffAlreadyActive
+    );
+
+    ctx.accounts.inventory.remove_item(item_id, 1)?;
+
+    player_account.active_buff_id = item_id;
+    player_account.buff_expires_slot = clock
+        .slot
+        .checked_add(buff.duration_slots)
+        .ok_or(ChainDepthError::Overflow)?;
+
+    emit!(BuffActivated {
+        player: ctx.accounts.player.key(),
+        item_id,
+        expires_slot: player_account.buff_expires_slot,
+        work_rate_bonus_percent: buff.work_rate_bonus_percent,
+        dps_bonus_percent: buff.dps_bonus_percent,
+    });
+
+    Ok(())
+}
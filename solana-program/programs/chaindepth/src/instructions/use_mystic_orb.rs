@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::RoomScouted;
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    item_ids, scout_room, session_instruction_bits, GlobalAccount, InventoryAccount, PlayerAccount,
+    RoomAccount, RoomScoutReport, SessionAuthority,
+};
+
+#[derive(Accounts)]
+pub struct UseMysticOrb<'info> {
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    /// CHECK: address of the room being scouted, checked against the room PDA in
+    /// the handler; it must not exist yet
+    pub target_room: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+}
+
+pub fn handler(ctx: Context<UseMysticOrb>, direction: u8) -> Result<RoomScoutReport> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::USE_MYSTIC_ORB,
+        0,
+    )?;
+
+    let global = &ctx.accounts.global;
+    let player_account = &ctx.accounts.player_account;
+    require!(
+        player_account.season_seed == global.season_seed,
+        ChainDepthError::PlayerNotInCurrentSeason
+    );
+    require!(
+        RoomAccount::is_valid_direction(direction),
+        ChainDepthError::InvalidDirection
+    );
+
+    let (target_x, target_y) = RoomAccount::checked_adjacent_coords(
+        player_account.current_room_x,
+        player_account.current_room_y,
+        direction,
+    )
+    .filter(|&(x, y)| global.layout.contains(x, y))
+    .ok_or(ChainDepthError::OutOfBounds)?;
+    let (target_room, _) = RoomAccount::find_address(
+        global.season_seed,
+        player_account.current_floor,
+        target_x,
+        target_y,
+    );
+    require_keys_eq!(
+        ctx.accounts.target_room.key(),
+        target_room,
+        anchor_lang::error::ErrorCode::ConstraintSeeds
+    );
+    require!(
+        ctx.accounts.target_room.data_is_empty(),
+        ChainDepthError::RoomAlreadyDiscovered
+    );
+
+    ctx.accounts
+        .inventory
+        .remove_item(item_ids::MYSTIC_ORB, 1)?;
+
+    // Reported as if opened from the holder's side, the way they would dig in.
+    let report = scout_room(
+        global.season_seed,
+        &global.layout,
+        player_account.current_floor,
+        target_x,
+        target_y,
+        RoomAccount::opposite_direction(direction),
+    );
+
+    emit!(RoomScouted {
+        player: ctx.accounts.player.key(),
+        report,
+    });
+
+    Ok(report)
+}
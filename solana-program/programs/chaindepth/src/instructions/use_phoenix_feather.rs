@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::LootProtected;
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    item_ids, session_instruction_bits, InventoryAccount, PlayerAccount, SessionAuthority,
+};
+
+#[derive(Accounts)]
+pub struct UsePhoenixFeather<'info> {
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+}
+
+/// Burn a Phoenix Feather so the next death keeps the player's carried loot.
+/// The protection stays until it is spent; see `PlayerAccount::lose_carried_loot`.
+pub fn handler(ctx: Context<UsePhoenixFeather>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::USE_PHOENIX_FEATHER,
+        0,
+    )?;
+
+    let player_account = &mut ctx.accounts.player_account;
+    require!(
+        !player_account.loot_protected,
+        ChainDepthError::LootAlreadyProtected
+    );
+
+    ctx.accounts
+        .inventory
+        .remove_item(item_ids::PHOENIX_FEATHER, 1)?;
+    player_account.loot_protected = true;
+
+    emit!(LootProtected {
+        player: ctx.accounts.player.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::KeyChestBearingRead;
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    bearing_direction, forced_key_chest_coords, item_ids, session_instruction_bits, FloorParams,
+    GlobalAccount, InventoryAccount, KeyChestBearing, PlayerAccount, SessionAuthority,
+};
+
+#[derive(Accounts)]
+pub struct UseRustedCompass<'info> {
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+}
+
+pub fn handler(ctx: Context<UseRustedCompass>) -> Result<KeyChestBearing> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::USE_RUSTED_COMPASS,
+        0,
+    )?;
+
+    let global = &ctx.accounts.global;
+    let player_account = &ctx.accounts.player_account;
+    require!(
+        player_account.season_seed == global.season_seed,
+        ChainDepthError::PlayerNotInCurrentSeason
+    );
+
+    let layout = &global.layout;
+    let here = (player_account.current_room_x, player_account.current_room_y);
+    let ring = layout.ring(here.0, here.1);
+
+    let params = FloorParams::new(global.season_seed, layout, player_account.current_floor);
+    let key_chest =
+        forced_key_chest_coords(&params, layout, ring).ok_or(ChainDepthError::NoKeyChestInRing)?;
+    let direction = bearing_direction(here, key_chest).ok_or(ChainDepthError::AlreadyAtKeyChest)?;
+
+    ctx.accounts
+        .inventory
+        .remove_item(item_ids::RUSTED_COMPASS, 1)?;
+
+    let bearing = KeyChestBearing { ring, direction };
+    emit!(KeyChestBearingRead {
+        player: ctx.accounts.player.key(),
+        room_floor: player_account.current_floor,
+        room_x: here.0,
+        room_y: here.1,
+        bearing,
+    });
+
+    Ok(bearing)
+}
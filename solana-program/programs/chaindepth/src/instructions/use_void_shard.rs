@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::events::PlayerRecalled;
+use crate::instructions::move_player::{upsert_presence, PresenceRoom};
+use crate::instructions::session_auth::authorize_player_action;
+use crate::state::{
+    item_ids, session_instruction_bits, GlobalAccount, InventoryAccount, PlayerAccount,
+    RoomPresence, SessionAuthority, ENTRANCE_FLOOR,
+};
+
+#[derive(Accounts)]
+pub struct UseVoidShard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: wallet owner whose gameplay state is being modified
+    pub player: UncheckedAccount<'info>,
+
+    /// Global game state - receives the closed presence rent
+    #[account(
+        mut,
+        seeds = [GlobalAccount::SEED_PREFIX],
+        bump = global.bump
+    )]
+    pub global: Account<'info, GlobalAccount>,
+
+    #[account(
+        mut,
+        seeds = [PlayerAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = player_account.bump,
+        constraint = player_account.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub player_account: Account<'info, PlayerAccount>,
+
+    #[account(
+        mut,
+        seeds = [InventoryAccount::SEED_PREFIX, player.key().as_ref()],
+        bump = inventory.bump,
+        constraint = inventory.owner == player.key() @ ChainDepthError::Unauthorized
+    )]
+    pub inventory: Account<'info, InventoryAccount>,
+
+    /// Closed on recall so rent returns to the treasury (global PDA)
+    #[account(
+        mut,
+        close = global,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[player_account.current_floor],
+            &[player_account.current_room_x as u8],
+            &[player_account.current_room_y as u8],
+            player.key().as_ref()
+        ],
+        bump = current_presence.bump
+    )]
+    pub current_presence: Account<'info, RoomPresence>,
+
+    /// Presence in the entrance floor's start room
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = RoomPresence::DISCRIMINATOR.len() + RoomPresence::INIT_SPACE,
+        seeds = [
+            RoomPresence::SEED_PREFIX,
+            &global.season_seed.to_le_bytes(),
+            &[ENTRANCE_FLOOR],
+            &[global.layout.start_x as u8],
+            &[global.layout.start_y as u8],
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub start_presence: Account<'info, RoomPresence>,
+
+    #[account(
+        mut,
+        seeds = [
+            SessionAuthority::SEED_PREFIX,
+            player.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump = session_authority.bump
+    )]
+    pub session_authority: Option<Account<'info, SessionAuthority>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<UseVoidShard>) -> Result<()> {
+    authorize_player_action(
+        &ctx.accounts.authority,
+        &ctx.accounts.player,
+        ctx.accounts.session_authority.as_mut(),
+        session_instruction_bits::USE_VOID_SHARD,
+        0,
+    )?;
+
+    let player_account = &mut ctx.accounts.player_account;
+    let player_key = ctx.accounts.player.key();
+    let from_floor = player_account.current_floor;
+    let from_x = player_account.current_room_x;
+    let from_y = player_account.current_room_y;
+
+    player_account.recall_to_entrance(&ctx.accounts.global.layout)?;
+    ctx.accounts
+        .inventory
+        .remove_item(item_ids::VOID_SHARD, 1)?;
+
+    // current_presence is closed by Anchor (close = global).
+    upsert_presence(
+        &mut ctx.accounts.start_presence,
+        player_key,
+        PresenceRoom {
+            season_seed: ctx.accounts.global.season_seed,
+            floor: ENTRANCE_FLOOR,
+            x: player_account.current_room_x,
+            y: player_account.current_room_y,
+        },
+        ctx.accounts.current_presence.skin_id,
+        player_account.equipped_item_id,
+        ctx.bumps.start_presence,
+    );
+    ctx.accounts.start_presence.is_current = true;
+    ctx.accounts.start_presence.set_idle();
+
+    emit!(PlayerRecalled {
+        player: player_key,
+        from_floor,
+        from_x,
+        from_y,
+    });
+
+    Ok(())
+}
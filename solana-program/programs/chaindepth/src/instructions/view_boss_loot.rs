@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{boss_loot_table, BossLootView};
+
+/// Read-only; clients simulate the transaction and decode the return data.
+#[derive(Accounts)]
+pub struct ViewBossLoot {}
+
+pub fn handler(_ctx: Context<ViewBossLoot>, boss_id: u16) -> Result<BossLootView> {
+    Ok(boss_loot_table(boss_id).view())
+}
@@ -6,6 +6,7 @@ pub mod instructions;
 pub mod state;
 
 use instructions::*;
+use state::{BossLootView, GameConfig, ItemStats, KeyChestBearing, RoomScoutReport, SeasonLayout};
 
 declare_id!("3Ctc2FgnNHQtGAcZftMS4ykLhJYjLzBD3hELKy55DnKo");
 
@@ -15,22 +16,62 @@ pub mod chaindepth {
 
     /// Initialize the global game state (admin only, once per season start)
     /// season_seed should be generated client-side (e.g., current slot number)
+    /// layout sets grid size and spawn point (None = 10x10 grid, spawn at (5, 5))
     pub fn init_global(
         ctx: Context<InitGlobal>,
         initial_prize_pool_amount: u64,
         season_seed: u64,
+        layout: Option<SeasonLayout>,
     ) -> Result<()> {
-        instructions::init_global::handler(ctx, initial_prize_pool_amount, season_seed)
+        instructions::init_global::handler(ctx, initial_prize_pool_amount, season_seed, layout)
     }
 
     /// Reset the season (creates new seed, resets depth)
-    pub fn reset_season(ctx: Context<ResetSeason>) -> Result<()> {
-        instructions::reset_season::handler(ctx)
+    /// layout replaces the grid for the new season (None keeps the current one)
+    pub fn reset_season(ctx: Context<ResetSeason>, layout: Option<SeasonLayout>) -> Result<()> {
+        instructions::reset_season::handler(ctx, layout)
     }
 
     /// Force reset the season immediately (admin override).
-    pub fn force_reset_season(ctx: Context<ForceResetSeason>) -> Result<()> {
-        instructions::force_reset_season::handler(ctx)
+    pub fn force_reset_season(
+        ctx: Context<ForceResetSeason>,
+        layout: Option<SeasonLayout>,
+    ) -> Result<()> {
+        instructions::force_reset_season::handler(ctx, layout)
+    }
+
+    /// Update admin-tunable balance parameters (admin only).
+    pub fn update_game_config(ctx: Context<UpdateGameConfig>, config: GameConfig) -> Result<()> {
+        instructions::update_game_config::handler(ctx, config)
+    }
+
+    /// Create or update the on-chain definition for an item id (admin only).
+    pub fn upsert_item_definition(
+        ctx: Context<UpsertItemDefinition>,
+        item_id: u16,
+        stats: ItemStats,
+    ) -> Result<()> {
+        instructions::upsert_item_definition::handler(ctx, item_id, stats)
+    }
+
+    /// Grow the global account from its pre-upgrade layout (admin only).
+    pub fn migrate_global(ctx: Context<MigrateGlobal>) -> Result<()> {
+        instructions::migrate_accounts::migrate_global_handler(ctx)
+    }
+
+    /// Grow a player account from its pre-upgrade layout (admin only).
+    pub fn migrate_player(ctx: Context<MigratePlayer>) -> Result<()> {
+        instructions::migrate_accounts::migrate_player_handler(ctx)
+    }
+
+    /// Grow a helper stake from its pre-upgrade layout (admin only).
+    pub fn migrate_helper_stake(ctx: Context<MigrateHelperStake>, direction: u8) -> Result<()> {
+        instructions::migrate_accounts::migrate_helper_stake_handler(ctx, direction)
+    }
+
+    /// Grow an inventory from its pre-upgrade layout (admin only).
+    pub fn migrate_inventory(ctx: Context<MigrateInventory>) -> Result<()> {
+        instructions::migrate_accounts::migrate_inventory_handler(ctx)
     }
 
     /// Admin-only test helper to reset a specific player's core PDAs.
@@ -38,6 +79,89 @@ pub mod chaindepth {
         instructions::reset_player_for_testing::handler(ctx)
     }
 
+    /// Refund or pay out a helper stake left in a previous season's escrow.
+    pub fn recover_stale_stake(
+        ctx: Context<RecoverStaleStake>,
+        season_seed: u64,
+        direction: u8,
+    ) -> Result<()> {
+        instructions::recover_stale_stake::handler(ctx, season_seed, direction)
+    }
+
+    /// Recover a helper's SKR stake from a room created before the floor-aware
+    /// upgrade, at its floorless room seeds.
+    pub fn recover_legacy_stake(
+        ctx: Context<RecoverLegacyStake>,
+        season_seed: u64,
+        room_x: i8,
+        room_y: i8,
+        direction: u8,
+    ) -> Result<()> {
+        instructions::recover_stale_stake::recover_legacy_stake_handler(
+            ctx,
+            season_seed,
+            room_x,
+            room_y,
+            direction,
+        )
+    }
+
+    /// Move a player whose data belongs to a previous season into the current start room.
+    pub fn respawn_stale_player(ctx: Context<RespawnStalePlayer>) -> Result<()> {
+        instructions::respawn_stale_player::handler(ctx)
+    }
+
+    /// Close a previous-season room and return its rent to the treasury (permissionless).
+    pub fn close_stale_room(ctx: Context<CloseStaleRoom>) -> Result<()> {
+        instructions::close_stale_accounts::close_stale_room_handler(ctx)
+    }
+
+    /// Close a previous-season room presence (permissionless).
+    pub fn close_stale_presence(ctx: Context<CloseStalePresence>) -> Result<()> {
+        instructions::close_stale_accounts::close_stale_presence_handler(ctx)
+    }
+
+    /// Close a previous-season loot receipt (permissionless).
+    pub fn close_stale_loot_receipt(ctx: Context<CloseStaleLootReceipt>) -> Result<()> {
+        instructions::close_stale_accounts::close_stale_loot_receipt_handler(ctx)
+    }
+
+    /// Close a previous-season boss fight marker (permissionless).
+    pub fn close_stale_boss_fight(
+        ctx: Context<CloseStaleBossFight>,
+        season_seed: u64,
+        room_floor: u8,
+        room_x: i8,
+        room_y: i8,
+    ) -> Result<()> {
+        instructions::close_stale_accounts::close_stale_boss_fight_handler(
+            ctx,
+            season_seed,
+            room_floor,
+            room_x,
+            room_y,
+        )
+    }
+
+    /// Sweep and close a previous-season job escrow (permissionless).
+    pub fn close_stale_escrow(
+        ctx: Context<CloseStaleEscrow>,
+        season_seed: u64,
+        room_floor: u8,
+        room_x: i8,
+        room_y: i8,
+        direction: u8,
+    ) -> Result<()> {
+        instructions::close_stale_accounts::close_stale_escrow_handler(
+            ctx,
+            season_seed,
+            room_floor,
+            room_x,
+            room_y,
+            direction,
+        )
+    }
+
     /// Ensure the start room exists for the current season seed.
     pub fn ensure_start_room(ctx: Context<EnsureStartRoom>) -> Result<()> {
         instructions::ensure_start_room::handler(ctx)
@@ -75,7 +199,18 @@ pub mod chaindepth {
         instructions::move_player::handler(ctx, new_x, new_y)
     }
 
-    /// Unlock a locked door using the required key item.
+    /// Take the stairs in the current room down to the next floor's start room.
+    pub fn descend_stairs(ctx: Context<DescendStairs>) -> Result<()> {
+        instructions::stairs::descend_handler(ctx)
+    }
+
+    /// Climb from a lower floor's start room back to the stairs room above.
+    pub fn ascend_stairs(ctx: Context<AscendStairs>) -> Result<()> {
+        instructions::stairs::ascend_handler(ctx)
+    }
+
+    /// Unlock a locked door: skeleton and gem locks consume their key item,
+    /// boss seals need a defeated boss room from the same ring as `boss_room`.
     pub fn unlock_door(ctx: Context<UnlockDoor>, direction: u8) -> Result<()> {
         instructions::unlock_door::handler(ctx, direction)
     }
@@ -91,10 +226,7 @@ pub mod chaindepth {
     }
 
     /// Join a job using a delegated session key.
-    pub fn join_job_with_session(
-        ctx: Context<JoinJobWithSession>,
-        direction: u8,
-    ) -> Result<()> {
+    pub fn join_job_with_session(ctx: Context<JoinJobWithSession>, direction: u8) -> Result<()> {
         instructions::join_job_with_session::handler(ctx, direction)
     }
 
@@ -113,16 +245,37 @@ pub mod chaindepth {
         instructions::complete_job::handler(ctx, direction)
     }
 
-    /// Loot a chest in the current room
+    /// Refund helpers of a job that outlived its TTL (permissionless crank).
+    /// Pass [helper_stake, player_account, player_token_account, player, room_presence]
+    /// per helper as remaining accounts; each helper's presence goes back to idle.
+    pub fn expire_job<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpireJob<'info>>,
+        direction: u8,
+    ) -> Result<()> {
+        instructions::expire_job::handler(ctx, direction)
+    }
+
+    /// Loot a chest in the current room (commits the roll; see reveal_loot)
     pub fn loot_chest(ctx: Context<LootChest>) -> Result<()> {
         instructions::loot_chest::handler(ctx)
     }
 
-    /// Loot boss rewards after boss defeat (fighters only)
+    /// Loot boss rewards after boss defeat (fighters only; commits the roll)
     pub fn loot_boss(ctx: Context<LootBoss>) -> Result<()> {
         instructions::loot_boss::handler(ctx)
     }
 
+    /// Grant committed chest/boss loot once its reveal slot hash exists (permissionless).
+    pub fn reveal_loot(ctx: Context<RevealLoot>) -> Result<()> {
+        instructions::reveal_loot::handler(ctx)
+    }
+
+    /// Possible drops and their odds for a boss id (a boss room's center_id).
+    /// Simulate it and read the return data; nothing is written.
+    pub fn view_boss_loot(ctx: Context<ViewBossLoot>, boss_id: u16) -> Result<BossLootView> {
+        instructions::view_boss_loot::handler(ctx, boss_id)
+    }
+
     /// Abandon a job and receive partial refund
     pub fn abandon_job(ctx: Context<AbandonJob>, direction: u8) -> Result<()> {
         instructions::abandon_job::handler(ctx, direction)
@@ -138,6 +291,35 @@ pub mod chaindepth {
         instructions::equip_item::handler(ctx, item_id)
     }
 
+    /// Consume a buff item for a timed work rate and boss DPS bonus
+    pub fn use_item(ctx: Context<UseItem>, item_id: u16) -> Result<()> {
+        instructions::use_item::handler(ctx, item_id)
+    }
+
+    /// Burn a Mystic Orb to reveal the undiscovered room in `direction`
+    /// without creating it. The report is the RoomScouted event and the return
+    /// data of the landed transaction; simulations do not burn the orb, so
+    /// clients must not read the report from one.
+    pub fn use_mystic_orb(ctx: Context<UseMysticOrb>, direction: u8) -> Result<RoomScoutReport> {
+        instructions::use_mystic_orb::handler(ctx, direction)
+    }
+
+    /// Burn a Rusted Compass for the direction of the current ring's key chest
+    pub fn use_rusted_compass(ctx: Context<UseRustedCompass>) -> Result<KeyChestBearing> {
+        instructions::use_rusted_compass::handler(ctx)
+    }
+
+    /// Burn a Void Shard to return to the entrance room without walking.
+    /// Not allowed in a boss fight or with active jobs.
+    pub fn use_void_shard(ctx: Context<UseVoidShard>) -> Result<()> {
+        instructions::use_void_shard::handler(ctx)
+    }
+
+    /// Burn a Phoenix Feather so the next death keeps carried loot
+    pub fn use_phoenix_feather(ctx: Context<UsePhoenixFeather>) -> Result<()> {
+        instructions::use_phoenix_feather::handler(ctx)
+    }
+
     /// Set player skin id for visual profile
     pub fn set_player_skin(ctx: Context<SetPlayerSkin>, skin_id: u16) -> Result<()> {
         instructions::set_player_skin::handler(ctx, skin_id)
@@ -157,6 +339,11 @@ pub mod chaindepth {
         instructions::join_boss_fight::handler(ctx)
     }
 
+    /// Leave the boss fight in the current room, giving up its loot.
+    pub fn leave_boss_fight(ctx: Context<LeaveBossFight>) -> Result<()> {
+        instructions::leave_boss_fight::handler(ctx)
+    }
+
     /// Tick boss fight progress.
     pub fn tick_boss_fight(ctx: Context<TickBossFight>) -> Result<()> {
         instructions::tick_boss_fight::handler(ctx)
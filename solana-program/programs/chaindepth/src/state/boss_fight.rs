@@ -1,5 +1,24 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::ChainDepthError;
+use crate::state::RoomAccount;
+
+/// Fighters listed in `BossDefeated`
+pub const BOSS_TOP_CONTRIBUTORS: usize = 3;
+
+/// Fighters one boss can hold at once, and the size of its `BossRoster`
+pub const MAX_BOSS_FIGHTERS: usize = 24;
+
+/// Slots between a boss's counter-attacks on each fighter
+pub const BOSS_ATTACK_INTERVAL_SLOTS: u64 = 10;
+
+/// Counter-attack damage per boss HP tier (see `boss_attack_damage`)
+pub const BOSS_ATTACK_DAMAGE_PER_TIER: u64 = 2;
+
+/// Loot amount multiplier for a fighter who dealt no damage, in basis points.
+/// It grows linearly to 100% for a fighter who dealt all of the boss's HP.
+pub const MIN_BOSS_LOOT_SHARE_BPS: u64 = 5_000;
+
 #[account]
 #[derive(InitSpace)]
 pub struct BossFightAccount {
@@ -7,9 +26,382 @@ pub struct BossFightAccount {
     pub room: Pubkey,
     pub dps: u64,
     pub joined_slot: u64,
+
+    /// Extra DPS from the fighter's buff, counted until `buff_expires_slot`
+    pub buff_bonus: u64,
+    pub buff_expires_slot: u64,
+
     pub bump: u8,
 }
 
 impl BossFightAccount {
     pub const SEED_PREFIX: &'static [u8] = b"boss_fight";
+
+    /// This fighter's entry in the fight's `BossRoster`
+    pub fn fighter(&self) -> BossFighter {
+        BossFighter {
+            player: self.player,
+            dps: self.dps,
+            joined_slot: self.joined_slot,
+            buff_bonus: self.buff_bonus,
+            buff_expires_slot: self.buff_expires_slot,
+        }
+    }
+
+    /// Counter-damage taken from joining until `end_slot`: one boss attack
+    /// per full `BOSS_ATTACK_INTERVAL_SLOTS` in the fight.
+    pub fn damage_taken_until(&self, end_slot: u64, boss_max_hp: u64) -> u64 {
+        let attacks = end_slot.saturating_sub(self.joined_slot) / BOSS_ATTACK_INTERVAL_SLOTS;
+        attacks.saturating_mul(boss_attack_damage(boss_max_hp))
+    }
+}
+
+/// What a fighter brings to the boss: enough to work out their damage and
+/// their strike in the killing slot. Entries with the default player are free.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct BossFighter {
+    pub player: Pubkey,
+    pub dps: u64,
+    pub joined_slot: u64,
+    pub buff_bonus: u64,
+    pub buff_expires_slot: u64,
+}
+
+impl BossFighter {
+    /// Damage dealt from joining until `end_slot`. Boss damage is applied
+    /// per slot at the fighters' combined DPS, so each fighter's part of it
+    /// is their own DPS for every slot they were in the fight, plus the buff
+    /// bonus for the slots before it wore off.
+    pub fn damage_until(&self, end_slot: u64) -> u64 {
+        let buff_end_slot = self.buff_expires_slot.min(end_slot);
+        self.dps
+            .saturating_mul(end_slot.saturating_sub(self.joined_slot))
+            .saturating_add(
+                self.buff_bonus
+                    .saturating_mul(buff_end_slot.saturating_sub(self.joined_slot)),
+            )
+    }
+
+    /// DPS in the slot the boss died at `defeated_slot`. A bonus stays in
+    /// the boss's total DPS unless the boss outlived it.
+    pub fn dps_at_kill(&self, defeated_slot: u64) -> u64 {
+        if self.buff_expires_slot >= defeated_slot {
+            self.dps.saturating_add(self.buff_bonus)
+        } else {
+            self.dps
+        }
+    }
+
+    /// Share of the boss's max HP this fighter dealt by the kill at
+    /// `defeated_slot`, in basis points
+    pub fn damage_share_bps(&self, defeated_slot: u64, boss_max_hp: u64) -> u16 {
+        if boss_max_hp == 0 {
+            return 0;
+        }
+        let damage = self.damage_until(defeated_slot);
+        let share = u128::from(damage) * 10_000 / u128::from(boss_max_hp);
+        share.min(10_000) as u16
+    }
+}
+
+/// Every current fighter of a room's boss, so the slot that kills the boss
+/// settles the last hit and the leaderboard from this one account. Created
+/// by the first fighter to join; after the kill it keeps the fighters of
+/// that kill until a new boss spawns.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct BossRoster {
+    pub room: Pubkey,
+    pub fighters: [BossFighter; MAX_BOSS_FIGHTERS],
+    pub bump: u8,
+}
+
+impl BossRoster {
+    pub const SEED_PREFIX: &'static [u8] = b"boss_roster";
+
+    /// Fill in the keys of a roster created by this join.
+    pub fn bind(&mut self, room: Pubkey, bump: u8) {
+        if self.room == Pubkey::default() {
+            self.room = room;
+            self.bump = bump;
+        }
+    }
+
+    /// Add a joining fighter. `fighter_count` is the room's count before the
+    /// join; entries left once it is 0 belong to an earlier boss, so they are
+    /// dropped first.
+    pub fn add(&mut self, fighter_count: u32, fighter: BossFighter) -> Result<()> {
+        if fighter_count == 0 {
+            self.fighters = Default::default();
+        }
+        let entry = self
+            .fighters
+            .iter_mut()
+            .find(|entry| entry.player == Pubkey::default())
+            .ok_or(ChainDepthError::BossFightFull)?;
+        *entry = fighter;
+        Ok(())
+    }
+
+    /// Free a leaving fighter's entry. Returns false if they are not listed.
+    pub fn remove(&mut self, player: Pubkey) -> bool {
+        let Some(entry) = self
+            .fighters
+            .iter_mut()
+            .find(|entry| entry.player != Pubkey::default() && entry.player == player)
+        else {
+            return false;
+        };
+        *entry = BossFighter::default();
+        true
+    }
+
+    /// The listed fighters
+    pub fn current_fighters(&self) -> Vec<BossFighter> {
+        self.fighters
+            .iter()
+            .filter(|entry| entry.player != Pubkey::default())
+            .copied()
+            .collect()
+    }
+}
+
+/// Damage of one boss counter-attack. The tier is the boss's HP over the
+/// base HP, i.e. the depth and boss id multipliers of `boss_hp_for_depth`.
+pub fn boss_attack_damage(boss_max_hp: u64) -> u64 {
+    let tier = (boss_max_hp / RoomAccount::BOSS_BASE_HP).max(1);
+    tier.saturating_mul(BOSS_ATTACK_DAMAGE_PER_TIER)
+}
+
+/// One line of the `BossDefeated` leaderboard
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BossContributor {
+    pub player: Pubkey,
+    pub damage: u64,
+}
+
+/// Slots of fighting it takes `total_dps` to bring `current_hp` to 0
+pub fn slots_to_defeat(current_hp: u64, total_dps: u64) -> u64 {
+    if total_dps == 0 {
+        return u64::MAX;
+    }
+    current_hp.div_ceil(total_dps)
+}
+
+/// The fighter who lands the last hit. In the slot the boss dies, fighters
+/// strike in join order (ties by pubkey) for their DPS at the kill, and the
+/// strike that takes the boss's last `hp_left` points is the last hit, so a
+/// fighter's odds across kills follow their share of the DPS.
+pub fn last_hitter(fighters: &[BossFighter], defeated_slot: u64, hp_left: u64) -> Option<Pubkey> {
+    let mut strike_order: Vec<&BossFighter> = fighters.iter().collect();
+    strike_order.sort_by(|a, b| {
+        a.joined_slot
+            .cmp(&b.joined_slot)
+            .then(a.player.cmp(&b.player))
+    });
+
+    let mut damage: u64 = 0;
+    let mut last_striker = None;
+    for fighter in strike_order {
+        let dps = fighter.dps_at_kill(defeated_slot);
+        if dps == 0 {
+            continue;
+        }
+        damage = damage.saturating_add(dps);
+        last_striker = Some(fighter.player);
+        if damage >= hp_left {
+            break;
+        }
+    }
+    last_striker
+}
+
+/// The `BOSS_TOP_CONTRIBUTORS` fighters with the most damage by the kill at
+/// `defeated_slot`
+pub fn top_contributors(fighters: &[BossFighter], defeated_slot: u64) -> Vec<BossContributor> {
+    let mut contributors: Vec<BossContributor> = fighters
+        .iter()
+        .map(|fighter| BossContributor {
+            player: fighter.player,
+            damage: fighter.damage_until(defeated_slot),
+        })
+        .collect();
+    contributors.sort_by(|a, b| b.damage.cmp(&a.damage).then(a.player.cmp(&b.player)));
+    contributors.truncate(BOSS_TOP_CONTRIBUTORS);
+    contributors
+}
+
+/// Scale a boss drop's stack size by the looter's damage share: from
+/// `MIN_BOSS_LOOT_SHARE_BPS` for no damage to the full amount for a solo kill.
+/// Never drops below one item.
+pub fn scale_boss_loot_amount(item_amount: u8, damage_share_bps: u16) -> u8 {
+    let share_bps = u64::from(damage_share_bps.min(10_000));
+    let multiplier_bps =
+        MIN_BOSS_LOOT_SHARE_BPS + (10_000 - MIN_BOSS_LOOT_SHARE_BPS) * share_bps / 10_000;
+    let scaled = u64::from(item_amount) * multiplier_bps / 10_000;
+    scaled.clamp(1, u64::from(u8::MAX)) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fighter(seed: u8, dps: u64, joined_slot: u64) -> BossFighter {
+        BossFighter {
+            player: Pubkey::new_from_array([seed; 32]),
+            dps,
+            joined_slot,
+            ..BossFighter::default()
+        }
+    }
+
+    #[test]
+    fn fighter_damage_adds_up_to_the_boss_hp() {
+        // 300 HP; a joins at slot 0 with 10 DPS, b at slot 10 with 5 DPS.
+        let fighters = [fighter(1, 10, 0), fighter(2, 5, 10)];
+        let after_a_alone = 300 - 10 * 10;
+        let defeated_slot = 10 + slots_to_defeat(after_a_alone, 15);
+        assert_eq!(defeated_slot, 24);
+
+        assert_eq!(fighters[0].damage_until(defeated_slot), 240);
+        assert_eq!(fighters[1].damage_until(defeated_slot), 70);
+        // The killing slot rounds up, so fighters may overkill by less than one slot.
+        let total: u64 = fighters
+            .iter()
+            .map(|fighter| fighter.damage_until(defeated_slot))
+            .sum();
+        assert!((300..300 + 15).contains(&total));
+
+        assert_eq!(fighters[0].damage_share_bps(defeated_slot, 300), 8_000);
+        assert_eq!(fighters[1].damage_share_bps(defeated_slot, 300), 2_333);
+    }
+
+    #[test]
+    fn damage_share_is_capped_at_the_whole_boss() {
+        let solo = fighter(1, 7, 0);
+        let defeated_slot = slots_to_defeat(300, 7);
+        assert!(solo.damage_until(defeated_slot) > 300);
+        assert_eq!(solo.damage_share_bps(defeated_slot, 300), 10_000);
+        assert_eq!(solo.damage_share_bps(defeated_slot, 0), 0);
+    }
+
+    #[test]
+    fn exactly_one_fighter_lands_the_last_hit() {
+        // a joined first with 3 DPS, then b with 1 DPS; both strike every slot.
+        let fighters = [fighter(2, 1, 5), fighter(1, 3, 0)];
+        let a = fighters[1].player;
+        let b = fighters[0].player;
+
+        // a's strike covers the first 3 points of the killing slot, b's the 4th.
+        for hp_left in 1..=3 {
+            assert_eq!(last_hitter(&fighters, 50, hp_left), Some(a));
+        }
+        assert_eq!(last_hitter(&fighters, 50, 4), Some(b));
+
+        // Same join slot: the lower pubkey strikes first.
+        let tied = [fighter(2, 1, 0), fighter(1, 3, 0)];
+        assert_eq!(last_hitter(&tied, 50, 4), Some(tied[0].player));
+        assert_eq!(last_hitter(&tied, 50, 2), Some(tied[1].player));
+        assert_eq!(last_hitter(&[], 50, 2), None);
+    }
+
+    #[test]
+    fn buff_bonus_counts_until_it_wears_off() {
+        let buffed = BossFighter {
+            buff_bonus: 5,
+            buff_expires_slot: 110,
+            ..fighter(1, 10, 100)
+        };
+        assert_eq!(buffed.damage_until(105), 15 * 5);
+        assert_eq!(buffed.damage_until(130), 10 * 30 + 5 * 10);
+        assert_eq!(buffed.dps_at_kill(110), 15);
+        assert_eq!(buffed.dps_at_kill(111), 10);
+
+        // The bonus only widens the killing strike if it is still active.
+        let partner = fighter(2, 10, 100);
+        let fighters = [buffed, partner];
+        assert_eq!(last_hitter(&fighters, 110, 15), Some(buffed.player));
+        assert_eq!(last_hitter(&fighters, 111, 15), Some(partner.player));
+    }
+
+    #[test]
+    fn top_contributors_are_sorted_by_damage() {
+        // One slot of fighting, so each deals their DPS.
+        let fighters: Vec<BossFighter> = [(1, 50), (2, 300), (3, 120), (4, 120)]
+            .into_iter()
+            .map(|(seed, dps)| fighter(seed, dps, 0))
+            .collect();
+
+        let top = top_contributors(&fighters, 1);
+        assert_eq!(top.len(), BOSS_TOP_CONTRIBUTORS);
+        assert_eq!(
+            top.iter().map(|entry| entry.damage).collect::<Vec<_>>(),
+            vec![300, 120, 120]
+        );
+        assert_eq!(top[1].player, fighters[2].player);
+    }
+
+    #[test]
+    fn roster_frees_leavers_and_drops_an_earlier_boss() {
+        let mut roster = BossRoster::default();
+        for seed in 1..=MAX_BOSS_FIGHTERS as u8 {
+            roster.add(seed as u32 - 1, fighter(seed, 1, 0)).unwrap();
+        }
+        assert_eq!(
+            roster
+                .add(MAX_BOSS_FIGHTERS as u32, fighter(99, 1, 0))
+                .unwrap_err(),
+            ChainDepthError::BossFightFull.into()
+        );
+
+        let leaver = fighter(3, 1, 0).player;
+        assert!(roster.remove(leaver));
+        assert!(!roster.remove(leaver));
+        assert!(!roster.remove(Pubkey::default()));
+        roster
+            .add(MAX_BOSS_FIGHTERS as u32 - 1, fighter(99, 1, 0))
+            .unwrap();
+        assert_eq!(roster.fighters[2], fighter(99, 1, 0));
+
+        // A new boss starts from no fighters.
+        roster.add(0, fighter(7, 4, 50)).unwrap();
+        assert_eq!(roster.current_fighters(), vec![fighter(7, 4, 50)]);
+    }
+
+    #[test]
+    fn counter_damage_scales_with_the_boss_tier() {
+        let goblin = RoomAccount::boss_hp_for_depth(2, 1);
+        let dragon = RoomAccount::boss_hp_for_depth(8, 4);
+        assert_eq!(boss_attack_damage(goblin), 2 * BOSS_ATTACK_DAMAGE_PER_TIER);
+        assert_eq!(boss_attack_damage(dragon), 15 * BOSS_ATTACK_DAMAGE_PER_TIER);
+        assert_eq!(boss_attack_damage(0), BOSS_ATTACK_DAMAGE_PER_TIER);
+
+        let fighter = BossFightAccount {
+            player: Pubkey::new_unique(),
+            room: Pubkey::default(),
+            dps: 10,
+            joined_slot: 100,
+            buff_bonus: 0,
+            buff_expires_slot: 0,
+            bump: 0,
+        };
+        let interval = BOSS_ATTACK_INTERVAL_SLOTS;
+        assert_eq!(fighter.damage_taken_until(100 + interval - 1, dragon), 0);
+        assert_eq!(
+            fighter.damage_taken_until(100 + 3 * interval, dragon),
+            3 * boss_attack_damage(dragon)
+        );
+        assert_eq!(fighter.damage_taken_until(50, dragon), 0);
+    }
+
+    #[test]
+    fn loot_amount_scales_with_damage_share() {
+        assert_eq!(scale_boss_loot_amount(10, 10_000), 10);
+        assert_eq!(scale_boss_loot_amount(10, 5_000), 7);
+        assert_eq!(scale_boss_loot_amount(10, 0), 5);
+        assert_eq!(scale_boss_loot_amount(1, 0), 1);
+        assert_eq!(scale_boss_loot_amount(u8::MAX, u16::MAX), u8::MAX);
+    }
 }
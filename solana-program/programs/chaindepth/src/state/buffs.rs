@@ -0,0 +1,270 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::state::{item_ids, MAX_BOSS_FIGHTERS};
+
+/// Timed effect of a consumable buff, stored on the player by `use_item`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuffEffect {
+    pub item_id: u16,
+
+    /// Slots the buff stays active after use
+    pub duration_slots: u64,
+
+    /// Extra job work rate, in percent of the helper's own rate
+    pub work_rate_bonus_percent: u64,
+
+    /// Extra boss DPS, in percent of the fighter's weapon DPS
+    pub dps_bonus_percent: u64,
+}
+
+impl BuffEffect {
+    pub fn boost_work_rate(&self, work_rate: u64) -> u64 {
+        apply_bonus_percent(work_rate, self.work_rate_bonus_percent)
+    }
+
+    pub fn boost_dps(&self, dps: u64) -> u64 {
+        apply_bonus_percent(dps, self.dps_bonus_percent)
+    }
+}
+
+/// Every usable buff. A slot is ~400ms, so 1_500 slots is about ten minutes.
+pub const BUFF_EFFECTS: [BuffEffect; 2] = [
+    BuffEffect {
+        item_id: item_ids::MINOR_BUFF,
+        duration_slots: 1_500,
+        work_rate_bonus_percent: 25,
+        dps_bonus_percent: 25,
+    },
+    BuffEffect {
+        item_id: item_ids::MAJOR_BUFF,
+        duration_slots: 3_000,
+        work_rate_bonus_percent: 50,
+        dps_bonus_percent: 50,
+    },
+];
+
+pub fn buff_effect(item_id: u16) -> Option<BuffEffect> {
+    BUFF_EFFECTS
+        .iter()
+        .find(|effect| effect.item_id == item_id)
+        .copied()
+}
+
+fn apply_bonus_percent(value: u64, bonus_percent: u64) -> u64 {
+    value.saturating_mul(100 + bonus_percent) / 100
+}
+
+/// A buff's extra work rate or DPS riding on a job or boss fight until the
+/// buff wears off. Entries with `bonus == 0` are free.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub struct BuffBonus {
+    pub bonus: u64,
+    pub expires_slot: u64,
+}
+
+/// Buff bonuses one schedule can hold. Matches the boss fight cap so every
+/// fighter can bring a buff; `MAX_HELPERS_PER_JOB` keeps jobs within it too.
+pub const MAX_SCHEDULED_BUFFS: usize = MAX_BOSS_FIGHTERS;
+
+/// `BuffSchedule::target` of a room's boss fight; jobs use their direction (0-3)
+pub const BUFF_TARGET_BOSS: u8 = 4;
+
+/// Buff bonuses riding on one job or boss fight of a room. Created by the
+/// first buffed helper or fighter to join, so rooms nobody brings a buff to
+/// never pay for it. The room counts the live entries, so settling requires
+/// the schedule only while one is running.
+#[account]
+#[derive(InitSpace, Default)]
+pub struct BuffSchedule {
+    pub room: Pubkey,
+
+    /// Direction of the job (0-3) or `BUFF_TARGET_BOSS`
+    pub target: u8,
+
+    pub bonuses: [BuffBonus; MAX_SCHEDULED_BUFFS],
+
+    pub bump: u8,
+}
+
+impl BuffSchedule {
+    pub const SEED_PREFIX: &'static [u8] = b"buff_schedule";
+
+    /// Fill in the keys of a schedule created by this join.
+    pub fn bind(&mut self, room: Pubkey, target: u8, bump: u8) {
+        if self.room == Pubkey::default() {
+            self.room = room;
+            self.target = target;
+            self.bump = bump;
+        }
+    }
+
+    /// Schedule a bonus and count it in `live_count`, the room's tally of
+    /// running entries. Entries left once the tally is 0 belong to a job
+    /// that has since been reset, so they are dropped first.
+    pub fn add_bonus(&mut self, live_count: &mut u8, bonus: u64, expires_slot: u64) -> Result<()> {
+        if *live_count == 0 {
+            self.bonuses = Default::default();
+        }
+        require!(
+            schedule_buff_bonus(&mut self.bonuses, bonus, expires_slot),
+            ChainDepthError::BuffScheduleFull
+        );
+        *live_count += 1;
+        Ok(())
+    }
+
+    /// Free a bonus that is still running. Returns false if it already wore off.
+    pub fn remove_bonus(&mut self, live_count: &mut u8, bonus: u64, expires_slot: u64) -> bool {
+        if *live_count == 0 || !unschedule_buff_bonus(&mut self.bonuses, bonus, expires_slot) {
+            return false;
+        }
+        *live_count -= 1;
+        true
+    }
+
+    /// Take the earliest bonus that has worn off by `slot`, if any
+    pub fn take_expired(&mut self, live_count: &mut u8, slot: u64) -> Option<BuffBonus> {
+        if *live_count == 0 {
+            return None;
+        }
+        let expired = take_expired_buff_bonus(&mut self.bonuses, slot)?;
+        *live_count -= 1;
+        Some(expired)
+    }
+}
+
+/// The schedule passed to an instruction, if any
+pub fn passed_buff_schedule<'a>(
+    buff_schedule: &'a mut Option<Box<Account<'_, BuffSchedule>>>,
+) -> Option<&'a mut BuffSchedule> {
+    buff_schedule.as_deref_mut().map(|schedule| &mut **schedule)
+}
+
+/// Put a bonus in the first free entry. Returns false if none is free.
+pub fn schedule_buff_bonus(schedule: &mut [BuffBonus], bonus: u64, expires_slot: u64) -> bool {
+    let Some(entry) = schedule.iter_mut().find(|entry| entry.bonus == 0) else {
+        return false;
+    };
+    *entry = BuffBonus {
+        bonus,
+        expires_slot,
+    };
+    true
+}
+
+/// Free the entry of a bonus that is still running.
+/// Returns false if it is not scheduled, i.e. it has already worn off.
+pub fn unschedule_buff_bonus(schedule: &mut [BuffBonus], bonus: u64, expires_slot: u64) -> bool {
+    let Some(entry) = schedule.iter_mut().find(|entry| {
+        entry.bonus > 0 && entry.bonus == bonus && entry.expires_slot == expires_slot
+    }) else {
+        return false;
+    };
+    *entry = BuffBonus::default();
+    true
+}
+
+/// Take the earliest bonus that has worn off by `slot`, if any
+pub fn take_expired_buff_bonus(schedule: &mut [BuffBonus], slot: u64) -> Option<BuffBonus> {
+    let entry = schedule
+        .iter_mut()
+        .filter(|entry| entry.bonus > 0 && entry.expires_slot <= slot)
+        .min_by_key(|entry| entry.expires_slot)?;
+    Some(std::mem::take(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{BARE_HANDS_DPS, BARE_HANDS_WORK_RATE};
+
+    #[test]
+    fn major_buff_is_stronger_and_lasts_longer() {
+        let minor = buff_effect(item_ids::MINOR_BUFF).unwrap();
+        let major = buff_effect(item_ids::MAJOR_BUFF).unwrap();
+        assert!(major.duration_slots > minor.duration_slots);
+        assert!(
+            major.boost_work_rate(BARE_HANDS_WORK_RATE)
+                > minor.boost_work_rate(BARE_HANDS_WORK_RATE)
+        );
+        assert!(major.boost_dps(20) > minor.boost_dps(20));
+
+        assert_eq!(minor.boost_work_rate(BARE_HANDS_WORK_RATE), 125);
+        assert_eq!(major.boost_dps(20), 30);
+        // Rounds down, so bare hands only gain once the bonus adds a whole point.
+        assert_eq!(minor.boost_dps(BARE_HANDS_DPS), BARE_HANDS_DPS);
+    }
+
+    #[test]
+    fn only_buff_items_have_effects() {
+        assert_eq!(buff_effect(item_ids::LEGACY_BUFF), None);
+        assert_eq!(buff_effect(item_ids::MYSTIC_ORB), None);
+        assert_eq!(buff_effect(0), None);
+    }
+
+    #[test]
+    fn expired_bonuses_come_out_earliest_first() {
+        let mut schedule = [BuffBonus::default(); 3];
+        assert!(schedule_buff_bonus(&mut schedule, 25, 300));
+        assert!(schedule_buff_bonus(&mut schedule, 50, 200));
+        assert!(schedule_buff_bonus(&mut schedule, 10, 900));
+        assert!(!schedule_buff_bonus(&mut schedule, 10, 950));
+
+        assert_eq!(take_expired_buff_bonus(&mut schedule, 100), None);
+        assert_eq!(
+            take_expired_buff_bonus(&mut schedule, 400),
+            Some(BuffBonus {
+                bonus: 50,
+                expires_slot: 200
+            })
+        );
+        assert_eq!(
+            take_expired_buff_bonus(&mut schedule, 400).unwrap().bonus,
+            25
+        );
+        assert_eq!(take_expired_buff_bonus(&mut schedule, 400), None);
+
+        assert!(!unschedule_buff_bonus(&mut schedule, 25, 300));
+        assert!(unschedule_buff_bonus(&mut schedule, 10, 900));
+        assert!(schedule.iter().all(|entry| entry.bonus == 0));
+    }
+
+    #[test]
+    fn schedule_account_counts_live_bonuses_and_drops_stale_ones() {
+        let mut schedule = BuffSchedule::default();
+        let mut live_count = 0;
+        for slot in 0..MAX_SCHEDULED_BUFFS as u64 {
+            schedule.add_bonus(&mut live_count, 10, 100 + slot).unwrap();
+        }
+        assert_eq!(
+            schedule.add_bonus(&mut live_count, 10, 900).unwrap_err(),
+            ChainDepthError::BuffScheduleFull.into()
+        );
+        assert_eq!(live_count as usize, MAX_SCHEDULED_BUFFS);
+
+        assert_eq!(
+            schedule.take_expired(&mut live_count, 100).unwrap().bonus,
+            10
+        );
+        assert!(schedule.remove_bonus(&mut live_count, 10, 101));
+        assert!(!schedule.remove_bonus(&mut live_count, 10, 101));
+        assert_eq!(live_count as usize, MAX_SCHEDULED_BUFFS - 2);
+
+        // A reset job zeroes the room's count; the leftovers are dropped.
+        live_count = 0;
+        assert_eq!(schedule.take_expired(&mut live_count, 1_000), None);
+        schedule.add_bonus(&mut live_count, 25, 500).unwrap();
+        assert_eq!(live_count, 1);
+        assert_eq!(
+            schedule
+                .bonuses
+                .iter()
+                .filter(|entry| entry.bonus > 0)
+                .count(),
+            1
+        );
+    }
+}
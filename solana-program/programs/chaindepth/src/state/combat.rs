@@ -0,0 +1,135 @@
+use crate::state::ItemStats;
+
+/// Combat DPS of a player with nothing equipped
+pub const BARE_HANDS_DPS: u64 = 1;
+
+/// Share of base DPS a fully worn weapon still deals, in percent
+pub const WORN_WEAPON_DPS_PERCENT: u64 = 50;
+
+/// Boss DPS for an equipped weapon with `durability` left.
+/// Output scales linearly from `WORN_WEAPON_DPS_PERCENT` at 0 durability up to
+/// full DPS at base durability. Durability 0 on a stack means it is not tracked.
+pub fn weapon_dps(stats: &ItemStats, durability: u16) -> u64 {
+    let base_dps = stats.combat_dps;
+    if stats.base_durability == 0 || durability == 0 {
+        return base_dps.max(BARE_HANDS_DPS);
+    }
+
+    let remaining = u64::from(durability.min(stats.base_durability));
+    let max_durability = u64::from(stats.base_durability);
+    let scale_percent =
+        WORN_WEAPON_DPS_PERCENT + (100 - WORN_WEAPON_DPS_PERCENT) * remaining / max_durability;
+
+    // u128 so a huge definition DPS keeps the curve instead of saturating.
+    let scaled = u128::from(base_dps) * u128::from(scale_percent) / 100;
+    u64::try_from(scaled)
+        .unwrap_or(u64::MAX)
+        .max(BARE_HANDS_DPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::{error, AccountInfo};
+
+    use super::*;
+    use crate::errors::ChainDepthError;
+    use crate::state::item_definition::catalog::{
+        definition_accounts, item_stats, registered_item_stats,
+    };
+    use crate::state::{item_ids, ItemRegistry};
+
+    fn full_dps(item_id: u16) -> u64 {
+        let stats = item_stats(item_id);
+        weapon_dps(&stats, stats.base_durability)
+    }
+
+    #[test]
+    fn weapon_catalog_dps_table() {
+        let expected: &[(u16, u64)] = &[
+            (item_ids::BRONZE_PICKAXE, 3),
+            (item_ids::IRON_PICKAXE, 5),
+            (item_ids::BRONZE_SWORD, 6),
+            (item_ids::IRON_SWORD, 10),
+            (item_ids::DIAMOND_SWORD, 20),
+            (item_ids::NOKIA_3310, 8),
+            (item_ids::WOODEN_PIPE, 4),
+            (item_ids::IRON_SCIMITAR, 10),
+            (item_ids::WOODEN_TANKARD, 4),
+        ];
+
+        // A registry holding every definition the catalog registers
+        let mut definitions = definition_accounts();
+        let mut lamports = vec![1; definitions.len()];
+        let owner = crate::ID;
+        let infos: Vec<AccountInfo> = definitions
+            .iter_mut()
+            .zip(lamports.iter_mut())
+            .map(|((address, data), lamports)| {
+                AccountInfo::new(address, false, false, lamports, data, &owner, false, 0)
+            })
+            .collect();
+        let registry = ItemRegistry::new(&infos);
+
+        // The whole weapon id range: ids the catalog leaves out have no stats
+        // to fall back to, so the registry rejects them.
+        for item_id in 100..=199 {
+            match expected.iter().find(|&&(id, _)| id == item_id) {
+                Some(&(_, expected_dps)) => {
+                    assert_eq!(full_dps(item_id), expected_dps, "item {item_id}");
+                    assert_eq!(registry.stats(item_id).unwrap(), item_stats(item_id));
+                }
+                None => {
+                    assert_eq!(registered_item_stats(item_id), None, "item {item_id}");
+                    assert_eq!(
+                        registry.stats(item_id).unwrap_err(),
+                        error!(ChainDepthError::MissingItemDefinition),
+                        "item {item_id}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pickaxes_are_weaker_than_swords_of_the_same_tier() {
+        assert!(full_dps(item_ids::BRONZE_PICKAXE) < full_dps(item_ids::BRONZE_SWORD));
+        assert!(full_dps(item_ids::IRON_PICKAXE) < full_dps(item_ids::IRON_SWORD));
+        assert!(full_dps(item_ids::IRON_SWORD) < full_dps(item_ids::DIAMOND_SWORD));
+    }
+
+    #[test]
+    fn worn_weapons_deal_less_damage() {
+        let stats = item_stats(item_ids::DIAMOND_SWORD);
+        assert_eq!(weapon_dps(&stats, stats.base_durability), 20);
+        assert_eq!(weapon_dps(&stats, stats.base_durability / 2), 15);
+        assert_eq!(weapon_dps(&stats, 1), 10);
+        assert!(weapon_dps(&item_stats(item_ids::BRONZE_PICKAXE), 1) >= BARE_HANDS_DPS);
+    }
+
+    #[test]
+    fn damage_never_drops_as_durability_rises() {
+        let mut stats = item_stats(item_ids::DIAMOND_SWORD);
+        for combat_dps in [1, 3, 20, u64::MAX / 100, u64::MAX / 2, u64::MAX] {
+            stats.combat_dps = combat_dps;
+            let mut previous = weapon_dps(&stats, 1);
+            for durability in 2..=stats.base_durability {
+                let dps = weapon_dps(&stats, durability);
+                assert!(
+                    dps >= previous,
+                    "dps {combat_dps} at durability {durability}"
+                );
+                previous = dps;
+            }
+            assert_eq!(previous, combat_dps);
+        }
+    }
+
+    #[test]
+    fn oversized_definition_dps_does_not_overflow() {
+        let mut stats = item_stats(item_ids::DIAMOND_SWORD);
+        stats.combat_dps = u64::MAX;
+        assert_eq!(weapon_dps(&stats, 0), u64::MAX);
+        assert_eq!(weapon_dps(&stats, stats.base_durability), u64::MAX);
+        assert_eq!(weapon_dps(&stats, 1), u64::MAX / 2);
+    }
+}
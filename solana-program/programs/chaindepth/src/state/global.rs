@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{generation_modes, MAX_SCHEDULED_BUFFS};
+
 /// Global game state - one per season
 /// PDA seeds: ["global"]
 #[account]
@@ -29,6 +31,14 @@ pub struct GlobalAccount {
 
     /// PDA bump seed
     pub bump: u8,
+
+    // Fields below were appended after launch; `migrate_global` grows older
+    // accounts to this layout.
+    /// Admin-tunable balance parameters
+    pub config: GameConfig,
+
+    /// Grid size, spawn point and generation mode for the current season
+    pub layout: SeasonLayout,
 }
 
 impl GlobalAccount {
@@ -38,12 +48,398 @@ impl GlobalAccount {
     /// 1 week = 7 * 24 * 60 * 60 = 604800 seconds
     /// At 400ms per slot: 604800 / 0.4 = 1,512,000 slots
     pub const SEASON_DURATION_SLOTS: u64 = 1_512_000;
+}
 
-    /// Starting room coordinates (center of 10x10 grid)
-    pub const START_X: i8 = 5;
-    pub const START_Y: i8 = 5;
+/// Dungeon shape for one season. Every floor is a width x height grid with
+/// its start room at the same coordinates; players spawn (and extract) at the
+/// start room of floor 0 and reach lower floors through stairs on the grid edge.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct SeasonLayout {
+    pub width: u8,
+    pub height: u8,
+    pub start_x: i8,
+    pub start_y: i8,
+
+    /// Room wall generation mode (see `generation_modes`)
+    pub generation_mode: u8,
+
+    /// Number of floors; the deepest floor has no stairs down
+    pub floor_count: u8,
+
+    /// Locked doors a room may roll on the entrance floor; lower floors allow one more
+    pub max_locked_doors: u8,
+}
+
+impl Default for SeasonLayout {
+    /// Three floors of 10x10 with spawn at (5, 5)
+    fn default() -> Self {
+        Self {
+            width: 10,
+            height: 10,
+            start_x: 5,
+            start_y: 5,
+            generation_mode: generation_modes::CONNECTED,
+            floor_count: 3,
+            max_locked_doors: 1,
+        }
+    }
+}
+
+impl SeasonLayout {
+    /// Smallest grid that still leaves side rooms around the start room
+    pub const MIN_DIMENSION: u8 = 3;
+
+    /// Largest grid whose coordinates, and those of the rooms one step past
+    /// its edge, fit the i8 room seeds
+    pub const MAX_DIMENSION: u8 = 127;
 
-    /// Grid boundaries
     pub const MIN_COORD: i8 = 0;
-    pub const MAX_COORD: i8 = 9;
+
+    /// A room keeps its entrance open, so at most the other three walls can lock
+    pub const MAX_LOCKED_DOORS: u8 = 3;
+
+    pub fn max_x(&self) -> i8 {
+        (self.width - 1) as i8
+    }
+
+    pub fn max_y(&self) -> i8 {
+        (self.height - 1) as i8
+    }
+
+    pub fn start(&self) -> (i8, i8) {
+        (self.start_x, self.start_y)
+    }
+
+    pub fn is_start(&self, x: i8, y: i8) -> bool {
+        (x, y) == self.start()
+    }
+
+    pub fn contains(&self, x: i8, y: i8) -> bool {
+        (Self::MIN_COORD..=self.max_x()).contains(&x)
+            && (Self::MIN_COORD..=self.max_y()).contains(&y)
+    }
+
+    /// Ring distance (Chebyshev) from the start room of the same floor
+    pub fn ring(&self, x: i8, y: i8) -> u32 {
+        let dx = u32::from(x.abs_diff(self.start_x));
+        let dy = u32::from(y.abs_diff(self.start_y));
+        dx.max(dy)
+    }
+
+    /// Ring of the grid corner furthest from the start room
+    pub fn max_ring(&self) -> u32 {
+        self.ring(Self::MIN_COORD, Self::MIN_COORD)
+            .max(self.ring(Self::MIN_COORD, self.max_y()))
+            .max(self.ring(self.max_x(), Self::MIN_COORD))
+            .max(self.ring(self.max_x(), self.max_y()))
+    }
+
+    /// Depth of the start room on `floor`. Each floor continues one past the
+    /// deepest ring of the floor above, so depth keeps rising past the grid edge.
+    pub fn floor_depth(&self, floor: u8) -> u32 {
+        u32::from(floor) * (self.max_ring() + 1)
+    }
+
+    /// Difficulty depth of a room: the floor's base depth plus its ring
+    pub fn depth(&self, floor: u8, x: i8, y: i8) -> u32 {
+        self.floor_depth(floor) + self.ring(x, y)
+    }
+
+    pub fn has_floor_below(&self, floor: u8) -> bool {
+        u16::from(floor) + 1 < u16::from(self.floor_count)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let dimensions = Self::MIN_DIMENSION..=Self::MAX_DIMENSION;
+        dimensions.contains(&self.width)
+            && dimensions.contains(&self.height)
+            && self.contains(self.start_x, self.start_y)
+            // The start room's south wall holds the entrance stairs, so it
+            // must not sit on the bottom edge where walls are clamped solid.
+            && self.start_y > Self::MIN_COORD
+            && generation_modes::is_valid(self.generation_mode)
+            && self.floor_count > 0
+            && self.max_locked_doors <= Self::MAX_LOCKED_DOORS
+    }
+}
+
+/// Denominator for basis-point config values
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Ceiling for `max_helpers_cap`. A job's buff bonuses share one
+/// `BuffSchedule`, so the cap keeps room in it for every helper's buff.
+pub const MAX_HELPERS_PER_JOB: usize = MAX_SCHEDULED_BUFFS;
+
+/// Admin-tunable balance parameters.
+/// Stored on the global account so designers can adjust them without an upgrade.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct GameConfig {
+    /// Helpers allowed on a single rubble wall at depth 0
+    pub base_max_helpers: u32,
+
+    /// Extra helper slots granted every `helper_depth_step` depth levels
+    pub helpers_per_depth_step: u32,
+
+    /// Depth levels between helper cap increases (0 disables depth scaling)
+    pub helper_depth_step: u32,
+
+    /// Hard ceiling on helpers per wall regardless of depth
+    pub max_helpers_cap: u32,
+
+    /// Slots after joining over which the abandon penalty ramps up
+    pub abandon_ramp_slots: u64,
+
+    /// Share of the stake slashed when abandoning right after joining (basis points)
+    pub abandon_min_slash_bps: u16,
+
+    /// Share of the stake slashed when abandoning a nearly finished job after the ramp (basis points)
+    pub abandon_max_slash_bps: u16,
+
+    /// Grace slots after a job could have finished before anyone may expire it
+    pub job_ttl_slots: u64,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            base_max_helpers: 4,
+            helpers_per_depth_step: 1,
+            helper_depth_step: 2,
+            max_helpers_cap: 8,
+            abandon_ramp_slots: 300,
+            abandon_min_slash_bps: 500,
+            abandon_max_slash_bps: 4_000,
+            job_ttl_slots: 9_000,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Maximum number of helpers that may join one job on a wall at `depth`.
+    pub fn max_helpers_for_depth(&self, depth: u32) -> u32 {
+        // A zero step disables depth scaling.
+        let depth_bonus = depth
+            .checked_div(self.helper_depth_step)
+            .unwrap_or(0)
+            .saturating_mul(self.helpers_per_depth_step);
+        self.base_max_helpers
+            .saturating_add(depth_bonus)
+            .min(self.max_helpers_cap)
+    }
+
+    /// Share of a stake slashed when a helper abandons a job (basis points).
+    /// The penalty follows whichever is further along: the time spent on the
+    /// job (up to `abandon_ramp_slots`) or the job's progress. Leaving a fresh
+    /// job early is cheap; bailing just before completion costs the most, even
+    /// for a helper who only just joined.
+    pub fn abandon_slash_bps(&self, slots_since_join: u64, progress: u64, base_slots: u64) -> u16 {
+        let min_bps = u128::from(self.abandon_min_slash_bps);
+        let max_bps = u128::from(self.abandon_max_slash_bps);
+        if self.abandon_ramp_slots == 0 || base_slots == 0 {
+            return self.abandon_min_slash_bps;
+        }
+
+        // Both shares scaled to `ramp_slots * base_slots`.
+        let time_share =
+            u128::from(slots_since_join.min(self.abandon_ramp_slots)) * u128::from(base_slots);
+        let progress_share =
+            u128::from(progress.min(base_slots)) * u128::from(self.abandon_ramp_slots);
+        let ramp = u128::from(self.abandon_ramp_slots) * u128::from(base_slots);
+        let extra_bps = (max_bps - min_bps) * time_share.max(progress_share) / ramp;
+
+        (min_bps + extra_bps) as u16
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.base_max_helpers > 0
+            && self.max_helpers_cap >= self.base_max_helpers
+            && self.max_helpers_cap as usize <= MAX_HELPERS_PER_JOB
+            && self.abandon_min_slash_bps <= self.abandon_max_slash_bps
+            && self.abandon_max_slash_bps <= BPS_DENOMINATOR
+            && self.job_ttl_slots > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_is_the_classic_grid() {
+        let layout = SeasonLayout::default();
+        assert!(layout.is_valid());
+        assert_eq!((layout.max_x(), layout.max_y()), (9, 9));
+        assert_eq!(layout.ring(5, 5), 0);
+        assert_eq!(layout.ring(0, 9), 5);
+        assert!(!layout.contains(10, 5));
+    }
+
+    #[test]
+    fn lower_floors_start_past_the_deepest_ring() {
+        let layout = SeasonLayout::default();
+        assert_eq!(layout.max_ring(), 5);
+        assert_eq!(layout.depth(0, 0, 0), 5);
+        assert_eq!(layout.depth(1, 5, 5), 6);
+        assert_eq!(layout.depth(2, 0, 0), 17);
+        assert!(layout.has_floor_below(1));
+        assert!(!layout.has_floor_below(2));
+        assert!(!SeasonLayout {
+            floor_count: u8::MAX,
+            ..layout
+        }
+        .has_floor_below(u8::MAX - 1));
+    }
+
+    #[test]
+    fn layout_validation_rejects_unusable_grids() {
+        let valid = SeasonLayout {
+            width: 127,
+            height: 40,
+            start_x: 126,
+            start_y: 1,
+            generation_mode: generation_modes::INDEPENDENT,
+            floor_count: 1,
+            max_locked_doors: 0,
+        };
+        assert!(valid.is_valid());
+        assert!(!SeasonLayout { width: 2, ..valid }.is_valid());
+        assert!(!SeasonLayout {
+            width: 128,
+            start_x: 127,
+            ..valid
+        }
+        .is_valid());
+        assert!(!SeasonLayout {
+            start_y: 0,
+            ..valid
+        }
+        .is_valid());
+        assert!(!SeasonLayout {
+            start_y: 40,
+            ..valid
+        }
+        .is_valid());
+        assert!(!SeasonLayout {
+            generation_mode: 7,
+            ..valid
+        }
+        .is_valid());
+        assert!(!SeasonLayout {
+            floor_count: 0,
+            ..valid
+        }
+        .is_valid());
+        assert!(!SeasonLayout {
+            max_locked_doors: 4,
+            ..valid
+        }
+        .is_valid());
+    }
+
+    #[test]
+    fn default_helper_cap_scales_with_depth() {
+        let config = GameConfig::default();
+        assert_eq!(config.max_helpers_for_depth(0), 4);
+        assert_eq!(config.max_helpers_for_depth(1), 4);
+        assert_eq!(config.max_helpers_for_depth(2), 5);
+        assert_eq!(config.max_helpers_for_depth(5), 6);
+    }
+
+    #[test]
+    fn helper_cap_never_exceeds_ceiling() {
+        let config = GameConfig::default();
+        assert_eq!(config.max_helpers_for_depth(8), 8);
+        assert_eq!(config.max_helpers_for_depth(u32::MAX), 8);
+    }
+
+    #[test]
+    fn zero_depth_step_disables_scaling() {
+        let config = GameConfig {
+            helper_depth_step: 0,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.max_helpers_for_depth(0), 4);
+        assert_eq!(config.max_helpers_for_depth(100), 4);
+    }
+
+    #[test]
+    fn abandoning_a_fresh_job_right_after_joining_is_cheap() {
+        let config = GameConfig::default();
+        assert_eq!(config.abandon_slash_bps(0, 0, 300), 500);
+        assert_eq!(config.abandon_slash_bps(3, 3, 300), 535);
+    }
+
+    #[test]
+    fn late_joiner_bailing_on_a_nearly_done_job_pays_for_its_progress() {
+        let config = GameConfig::default();
+        assert_eq!(config.abandon_slash_bps(0, 297, 300), 3_965);
+        assert_eq!(config.abandon_slash_bps(1, 297, 300), 3_965);
+        assert!(config.abandon_slash_bps(0, 297, 300) > config.abandon_slash_bps(0, 150, 300));
+
+        // Long-standing helpers on a slow job pay for their time instead.
+        assert_eq!(config.abandon_slash_bps(300, 30, 300), 4_000);
+    }
+
+    #[test]
+    fn abandoning_before_completion_costs_the_most() {
+        let config = GameConfig::default();
+        assert_eq!(config.abandon_slash_bps(300, 300, 300), 4_000);
+        assert_eq!(config.abandon_slash_bps(10_000, 300, 300), 4_000);
+
+        let early = config.abandon_slash_bps(60, 60, 300);
+        let midway = config.abandon_slash_bps(150, 150, 300);
+        let late = config.abandon_slash_bps(300, 285, 300);
+        assert!(early < midway && midway < late);
+    }
+
+    #[test]
+    fn zero_ramp_always_uses_min_slash() {
+        let config = GameConfig {
+            abandon_ramp_slots: 0,
+            ..GameConfig::default()
+        };
+        assert_eq!(config.abandon_slash_bps(1_000, 300, 300), 500);
+    }
+
+    #[test]
+    fn config_validation_rejects_unusable_caps() {
+        assert!(GameConfig::default().is_valid());
+        assert!(!GameConfig {
+            base_max_helpers: 0,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(!GameConfig {
+            base_max_helpers: 5,
+            max_helpers_cap: 4,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(GameConfig {
+            max_helpers_cap: MAX_HELPERS_PER_JOB as u32,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(!GameConfig {
+            max_helpers_cap: MAX_HELPERS_PER_JOB as u32 + 1,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(!GameConfig {
+            abandon_min_slash_bps: 5_000,
+            abandon_max_slash_bps: 4_000,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(!GameConfig {
+            job_ttl_slots: 0,
+            ..GameConfig::default()
+        }
+        .is_valid());
+        assert!(!GameConfig {
+            abandon_max_slash_bps: 10_001,
+            ..GameConfig::default()
+        }
+        .is_valid());
+    }
 }
@@ -11,6 +11,13 @@ pub struct HelperStake {
     pub amount: u64,
     pub joined_slot: u64,
     pub bump: u8,
+    // Appended after `bump` so launch-layout stakes migrate in place
+    // (`migrate_helper_stake`).
+    /// Work rate snapshotted from the equipped tool at join time (percent)
+    pub work_rate: u64,
+    /// Extra work rate from the helper's buff, counted until `buff_expires_slot`
+    pub buff_bonus: u64,
+    pub buff_expires_slot: u64,
 }
 
 impl HelperStake {
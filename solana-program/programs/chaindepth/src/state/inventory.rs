@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::ChainDepthError;
+use crate::state::{is_scored_loot_item, ItemStats};
 
 pub const MAX_INVENTORY_SLOTS: usize = 64;
 
+/// Durability lost by the equipped tool per mining or combat action
+pub const TOOL_WEAR_PER_ACTION: u16 = 1;
+
 pub mod item_ids {
     // Legacy IDs (kept for backward compat with existing inventories)
     pub const LEGACY_ORE: u16 = 1;
@@ -53,6 +57,17 @@ pub struct InventoryItem {
     pub item_id: u16,
     pub amount: u32,
     pub durability: u16,
+    /// Score per unit when extracted, fixed from the item's definition when
+    /// the units were granted
+    pub score_value: u64,
+}
+
+impl InventoryItem {
+    fn stacks_with(&self, other: &InventoryItem) -> bool {
+        self.item_id == other.item_id
+            && self.durability == other.durability
+            && self.score_value == other.score_value
+    }
 }
 
 #[account]
@@ -67,18 +82,48 @@ pub struct InventoryAccount {
 impl InventoryAccount {
     pub const SEED_PREFIX: &'static [u8] = b"inventory";
 
-    pub fn add_item(&mut self, item_id: u16, amount: u32, durability: u16) -> Result<()> {
-        require!(item_id > 0, ChainDepthError::InvalidItemId);
-        require!(amount > 0, ChainDepthError::InvalidItemAmount);
+    /// Add `amount` units of `item_id` at `durability`, scored at the
+    /// definition's `score_value`. Stackable units join the stack with the same
+    /// durability and score; non-stackable units each take a slot of their own
+    /// (see `ItemStats::stackable`).
+    pub fn add_item(
+        &mut self,
+        item_id: u16,
+        amount: u32,
+        durability: u16,
+        stats: &ItemStats,
+    ) -> Result<()> {
+        self.add_stack(
+            InventoryItem {
+                item_id,
+                amount,
+                durability,
+                score_value: stats.score_value,
+            },
+            stats.stackable,
+        )
+    }
 
-        if let Some(existing) = self
-            .items
-            .iter_mut()
-            .find(|item| item.item_id == item_id && item.durability == durability)
-        {
+    fn add_stack(&mut self, stack: InventoryItem, stackable: bool) -> Result<()> {
+        require!(stack.item_id > 0, ChainDepthError::InvalidItemId);
+        require!(stack.amount > 0, ChainDepthError::InvalidItemAmount);
+
+        if !stackable {
+            require!(
+                MAX_INVENTORY_SLOTS.saturating_sub(self.items.len()) >= stack.amount as usize,
+                ChainDepthError::InventoryFull
+            );
+            self.items.extend((0..stack.amount).map(|_| InventoryItem {
+                amount: 1,
+                ..stack.clone()
+            }));
+            return Ok(());
+        }
+
+        if let Some(existing) = self.items.iter_mut().find(|item| item.stacks_with(&stack)) {
             existing.amount = existing
                 .amount
-                .checked_add(amount)
+                .checked_add(stack.amount)
                 .ok_or(ChainDepthError::Overflow)?;
             return Ok(());
         }
@@ -88,15 +133,96 @@ impl InventoryAccount {
             ChainDepthError::InventoryFull
         );
 
-        self.items.push(InventoryItem {
-            item_id,
-            amount,
-            durability,
-        });
+        self.items.push(stack);
 
         Ok(())
     }
 
+    /// Durability of the stack currently in use for `item_id`.
+    /// The most worn tracked stack is used first, so that is the one equipped;
+    /// untracked (0 durability) stacks only count when no tracked stack is left.
+    pub fn equipped_durability(&self, item_id: u16) -> Option<u16> {
+        let durabilities = self
+            .items
+            .iter()
+            .filter(|item| item.item_id == item_id && item.amount > 0)
+            .map(|item| item.durability);
+        durabilities
+            .clone()
+            .filter(|durability| *durability > 0)
+            .min()
+            .or_else(|| durabilities.min())
+    }
+
+    /// Wear the equipped unit of `item_id` by `wear` durability.
+    /// The worn unit is split off its stack so the other units keep their
+    /// durability. Stacks with durability 0 do not degrade.
+    /// Returns true if the unit broke and was removed.
+    pub fn consume_durability(&mut self, item_id: u16, wear: u16) -> Result<bool> {
+        let Some(index) = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.item_id == item_id && item.amount > 0 && item.durability > 0)
+            .min_by_key(|(_, item)| item.durability)
+            .map(|(index, _)| index)
+        else {
+            return Ok(false);
+        };
+
+        let durability = self.items[index].durability;
+        if wear == 0 {
+            return Ok(false);
+        }
+        let remaining = durability.saturating_sub(wear);
+        let amount = self.items[index].amount;
+
+        if remaining == 0 {
+            if amount == 1 {
+                self.items.remove(index);
+            } else {
+                self.items[index].amount = amount - 1;
+            }
+            return Ok(true);
+        }
+
+        let worn = InventoryItem {
+            amount: 1,
+            durability: remaining,
+            ..self.items[index].clone()
+        };
+        let can_split = amount > 1
+            && (self.items.len() < MAX_INVENTORY_SLOTS
+                || self.items.iter().any(|item| item.stacks_with(&worn)));
+        if can_split {
+            // Only a stack of several units splits, so the item stacks.
+            self.items[index].amount = amount - 1;
+            self.add_stack(worn, true)?;
+        } else {
+            // Single unit, or no free slot to split it off: the stack wears in place.
+            self.items[index].durability = remaining;
+        }
+        Ok(false)
+    }
+
+    /// Remove every scored loot stack (see `is_scored_loot_item`), as on death.
+    /// Returns (stacks, units) removed.
+    pub fn drop_scored_loot(&mut self) -> (u32, u32) {
+        let (dropped, kept): (Vec<InventoryItem>, Vec<InventoryItem>) = self
+            .items
+            .drain(..)
+            .partition(|item| is_scored_loot_item(item.item_id));
+        self.items = kept;
+        let units = dropped.iter().map(|item| item.amount).sum();
+        (dropped.len() as u32, units)
+    }
+
+    pub fn has_item(&self, item_id: u16) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.item_id == item_id && item.amount > 0)
+    }
+
     pub fn remove_item(&mut self, item_id: u16, amount: u32) -> Result<()> {
         require!(item_id > 0, ChainDepthError::InvalidItemId);
         require!(amount > 0, ChainDepthError::InvalidItemAmount);
@@ -123,3 +249,180 @@ impl InventoryAccount {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::catalog::item_stats;
+
+    fn inventory_with(items: &[(u16, u32, u16)]) -> InventoryAccount {
+        InventoryAccount {
+            owner: Pubkey::default(),
+            items: items
+                .iter()
+                .map(|&(item_id, amount, durability)| InventoryItem {
+                    item_id,
+                    amount,
+                    durability,
+                    score_value: item_stats(item_id).score_value,
+                })
+                .collect(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn wear_splits_one_unit_off_a_stack() {
+        let mut inventory = inventory_with(&[(item_ids::IRON_SWORD, 3, 120)]);
+        assert!(!inventory
+            .consume_durability(item_ids::IRON_SWORD, 1)
+            .unwrap());
+        assert_eq!(inventory.items.len(), 2);
+        assert_eq!(inventory.items[0].amount, 2);
+        assert_eq!(inventory.items[0].durability, 120);
+        assert_eq!(inventory.items[1].amount, 1);
+        assert_eq!(inventory.items[1].durability, 119);
+        assert_eq!(
+            inventory.equipped_durability(item_ids::IRON_SWORD),
+            Some(119)
+        );
+    }
+
+    #[test]
+    fn last_point_of_durability_breaks_the_unit() {
+        let mut inventory = inventory_with(&[(item_ids::BRONZE_PICKAXE, 1, 1)]);
+        assert!(inventory
+            .consume_durability(item_ids::BRONZE_PICKAXE, 1)
+            .unwrap());
+        assert!(!inventory.has_item(item_ids::BRONZE_PICKAXE));
+
+        let mut inventory = inventory_with(&[(item_ids::BRONZE_PICKAXE, 2, 1)]);
+        assert!(inventory
+            .consume_durability(item_ids::BRONZE_PICKAXE, 1)
+            .unwrap());
+        assert_eq!(inventory.items[0].amount, 1);
+    }
+
+    #[test]
+    fn untracked_durability_never_wears() {
+        let mut inventory = inventory_with(&[(item_ids::SKELETON_KEY, 1, 0)]);
+        assert!(!inventory
+            .consume_durability(item_ids::SKELETON_KEY, 5)
+            .unwrap());
+        assert_eq!(inventory.items[0].durability, 0);
+        assert!(!inventory
+            .consume_durability(item_ids::IRON_SWORD, 1)
+            .unwrap());
+    }
+
+    #[test]
+    fn untracked_stack_does_not_shield_a_tracked_one_from_wear() {
+        let mut inventory =
+            inventory_with(&[(item_ids::IRON_SWORD, 1, 0), (item_ids::IRON_SWORD, 1, 50)]);
+        assert_eq!(
+            inventory.equipped_durability(item_ids::IRON_SWORD),
+            Some(50)
+        );
+        assert!(!inventory
+            .consume_durability(item_ids::IRON_SWORD, 5)
+            .unwrap());
+        assert_eq!(inventory.items[0].durability, 0);
+        assert_eq!(inventory.items[1].durability, 45);
+        assert_eq!(
+            inventory.equipped_durability(item_ids::IRON_SWORD),
+            Some(45)
+        );
+
+        assert!(inventory
+            .consume_durability(item_ids::IRON_SWORD, 45)
+            .unwrap());
+        assert_eq!(inventory.equipped_durability(item_ids::IRON_SWORD), Some(0));
+    }
+
+    #[test]
+    fn stackable_units_share_a_slot() {
+        let mut inventory = inventory_with(&[(item_ids::GOLD_COIN, 2, 0)]);
+        inventory
+            .add_item(item_ids::GOLD_COIN, 3, 0, &item_stats(item_ids::GOLD_COIN))
+            .unwrap();
+        assert_eq!(inventory.items.len(), 1);
+        assert_eq!(inventory.items[0].amount, 5);
+    }
+
+    #[test]
+    fn units_keep_the_score_they_were_granted_at() {
+        let mut inventory = inventory_with(&[(item_ids::GOLD_COIN, 2, 0)]);
+        let mut rescored = item_stats(item_ids::GOLD_COIN);
+        rescored.score_value += 1;
+        inventory
+            .add_item(item_ids::GOLD_COIN, 3, 0, &rescored)
+            .unwrap();
+        assert_eq!(inventory.items.len(), 2);
+        assert_eq!(
+            inventory.items[0].score_value,
+            item_stats(item_ids::GOLD_COIN).score_value
+        );
+        assert_eq!(inventory.items[1].score_value, rescored.score_value);
+
+        // A worn unit split off its stack keeps the stack's score.
+        let mut inventory = inventory_with(&[(item_ids::IRON_SWORD, 2, 120)]);
+        inventory.items[0].score_value = 4;
+        inventory
+            .consume_durability(item_ids::IRON_SWORD, 1)
+            .unwrap();
+        assert_eq!(inventory.items.len(), 2);
+        assert!(inventory.items.iter().all(|item| item.score_value == 4));
+    }
+
+    #[test]
+    fn non_stackable_units_take_a_slot_each() {
+        let mut inventory = inventory_with(&[(item_ids::IRON_SWORD, 1, 120)]);
+        inventory
+            .add_item(
+                item_ids::IRON_SWORD,
+                2,
+                120,
+                &item_stats(item_ids::IRON_SWORD),
+            )
+            .unwrap();
+        assert_eq!(inventory.items.len(), 3);
+        assert!(inventory.items.iter().all(|item| item.amount == 1));
+
+        let full = vec![(item_ids::GOLD_COIN, 1, 0); MAX_INVENTORY_SLOTS - 1];
+        let mut inventory = inventory_with(&full);
+        assert_eq!(
+            inventory
+                .add_item(
+                    item_ids::IRON_SWORD,
+                    2,
+                    120,
+                    &item_stats(item_ids::IRON_SWORD)
+                )
+                .unwrap_err(),
+            error!(ChainDepthError::InventoryFull)
+        );
+        assert_eq!(inventory.items.len(), MAX_INVENTORY_SLOTS - 1);
+        inventory
+            .add_item(
+                item_ids::IRON_SWORD,
+                1,
+                120,
+                &item_stats(item_ids::IRON_SWORD),
+            )
+            .unwrap();
+        assert_eq!(inventory.items.len(), MAX_INVENTORY_SLOTS);
+    }
+
+    #[test]
+    fn death_drops_only_scored_loot() {
+        let mut inventory = inventory_with(&[
+            (item_ids::IRON_SWORD, 1, 120),
+            (item_ids::GOLD_COIN, 7, 0),
+            (item_ids::MINOR_BUFF, 2, 0),
+            (item_ids::DRAGON_SCALE, 1, 0),
+        ]);
+        assert_eq!(inventory.drop_scored_loot(), (2, 8));
+        let kept: Vec<u16> = inventory.items.iter().map(|item| item.item_id).collect();
+        assert_eq!(kept, vec![item_ids::IRON_SWORD, item_ids::MINOR_BUFF]);
+        assert_eq!(inventory.drop_scored_loot(), (0, 0));
+    }
+}
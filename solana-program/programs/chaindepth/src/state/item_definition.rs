@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+
+pub mod item_categories {
+    pub const UNKNOWN: u8 = 0;
+    pub const WEAPON: u8 = 1;
+    pub const VALUABLE: u8 = 2;
+    pub const CONSUMABLE: u8 = 3;
+}
+
+/// Gameplay stats for one item id
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct ItemStats {
+    /// One of `item_categories`
+    pub category: u8,
+
+    /// Score per unit when extracted
+    pub score_value: u64,
+
+    /// Durability assigned to freshly dropped stacks (0 = no durability)
+    pub base_durability: u16,
+
+    /// Damage per slot dealt to bosses while equipped
+    pub combat_dps: u64,
+
+    /// Job work rate while equipped, in percent (100 = bare hands)
+    pub mining_speed: u16,
+
+    /// Whether units share one inventory stack; otherwise each takes a slot
+    pub stackable: bool,
+
+    /// Whether the player may remove the item from their inventory
+    pub tradeable: bool,
+}
+
+/// Admin-managed item definition - one per item id
+/// PDA seeds: ["item_def", item_id (2 bytes LE)]
+#[account]
+#[derive(InitSpace)]
+pub struct ItemDefinition {
+    pub item_id: u16,
+    pub stats: ItemStats,
+    pub bump: u8,
+}
+
+impl ItemDefinition {
+    pub const SEED_PREFIX: &'static [u8] = b"item_def";
+
+    pub fn find_address(item_id: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &item_id.to_le_bytes()], &crate::ID)
+    }
+
+    /// This definition's PDA, derived from its stored bump
+    fn address(&self) -> Option<Pubkey> {
+        Pubkey::create_program_address(
+            &[Self::SEED_PREFIX, &self.item_id.to_le_bytes(), &[self.bump]],
+            &crate::ID,
+        )
+        .ok()
+    }
+}
+
+/// Item definitions passed to an instruction through `remaining_accounts`.
+/// Every item an instruction reads stats for needs a registered definition.
+pub struct ItemRegistry<'a, 'info> {
+    accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> ItemRegistry<'a, 'info> {
+    pub fn new(accounts: &'a [AccountInfo<'info>]) -> Self {
+        Self { accounts }
+    }
+
+    /// Stats for `item_id` from its definition PDA, which the caller must pass.
+    pub fn stats(&self, item_id: u16) -> Result<ItemStats> {
+        self.find_definition(item_id)?
+            .ok_or_else(|| error!(ChainDepthError::MissingItemDefinition))
+    }
+
+    fn find_definition(&self, item_id: u16) -> Result<Option<ItemStats>> {
+        for info in self.accounts.iter() {
+            if info.owner != &crate::ID || info.data_is_empty() {
+                continue;
+            }
+            let data = info.try_borrow_data()?;
+            if !data.starts_with(ItemDefinition::DISCRIMINATOR) {
+                continue;
+            }
+            let definition = ItemDefinition::try_deserialize(&mut &data[..])?;
+            // Only the definition at the item's own PDA is authoritative.
+            if definition.item_id == item_id && definition.address() == Some(info.key()) {
+                return Ok(Some(definition.stats));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod catalog {
+    use std::sync::OnceLock;
+
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::AccountSerialize;
+
+    use super::{ItemDefinition, ItemStats};
+
+    fn catalog_items() -> &'static [serde_json::Value] {
+        static CATALOG: OnceLock<serde_json::Value> = OnceLock::new();
+        let catalog = CATALOG
+            .get_or_init(|| serde_json::from_str(include_str!("../../item-catalog.json")).unwrap());
+        catalog["items"].as_array().unwrap()
+    }
+
+    /// Stats `item-catalog.json` registers for `item_id`
+    pub fn item_stats(item_id: u16) -> ItemStats {
+        registered_item_stats(item_id)
+            .unwrap_or_else(|| panic!("item {item_id} is not in the catalog"))
+    }
+
+    /// Stats `item-catalog.json` registers for `item_id`, if it has any
+    pub fn registered_item_stats(item_id: u16) -> Option<ItemStats> {
+        let item = catalog_items()
+            .iter()
+            .find(|item| item["itemId"] == item_id)?;
+        let field = |name: &str| item[name].as_u64().unwrap();
+        let flag = |name: &str| item[name].as_bool().unwrap();
+        Some(ItemStats {
+            category: field("category") as u8,
+            score_value: field("scoreValue"),
+            base_durability: field("baseDurability") as u16,
+            combat_dps: field("combatDps"),
+            mining_speed: field("miningSpeed") as u16,
+            stackable: flag("stackable"),
+            tradeable: flag("tradeable"),
+        })
+    }
+
+    /// Every definition `item-catalog.json` registers, as (address, account
+    /// data) of its PDA
+    pub fn definition_accounts() -> Vec<(Pubkey, Vec<u8>)> {
+        catalog_items()
+            .iter()
+            .map(|item| {
+                let item_id = item["itemId"].as_u64().unwrap() as u16;
+                let (address, bump) = ItemDefinition::find_address(item_id);
+                let mut data = Vec::new();
+                ItemDefinition {
+                    item_id,
+                    stats: item_stats(item_id),
+                    bump,
+                }
+                .try_serialize(&mut data)
+                .unwrap();
+                (address, data)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::catalog::item_stats;
+    use super::*;
+    use crate::state::{is_scored_loot_item, item_ids, BARE_HANDS_DPS};
+
+    fn definition_info<'a>(
+        key: &'a Pubkey,
+        lamports: &'a mut u64,
+        data: &'a mut [u8],
+        owner: &'a Pubkey,
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(key, false, false, lamports, data, owner, false, 0)
+    }
+
+    #[test]
+    fn registry_requires_a_registered_definition() {
+        let (address, bump) = ItemDefinition::find_address(item_ids::IRON_SWORD);
+        let mut stats = item_stats(item_ids::IRON_SWORD);
+        stats.combat_dps = 42;
+        let mut data = Vec::new();
+        ItemDefinition {
+            item_id: item_ids::IRON_SWORD,
+            stats,
+            bump,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        let (mut lamports, mut empty_lamports) = (1, 0);
+        let registered = [definition_info(
+            &address,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+        )];
+        assert_eq!(
+            ItemRegistry::new(&registered)
+                .stats(item_ids::IRON_SWORD)
+                .unwrap(),
+            stats
+        );
+        assert_eq!(
+            ItemRegistry::new(&registered)
+                .stats(item_ids::GOLD_BAR)
+                .unwrap_err(),
+            error!(ChainDepthError::MissingItemDefinition)
+        );
+
+        // The same definition anywhere but its PDA is not trusted.
+        let elsewhere = Pubkey::new_unique();
+        let misplaced = [definition_info(
+            &elsewhere,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+        )];
+        assert_eq!(
+            ItemRegistry::new(&misplaced)
+                .stats(item_ids::IRON_SWORD)
+                .unwrap_err(),
+            error!(ChainDepthError::MissingItemDefinition)
+        );
+
+        // A PDA nobody registered yet has no stats to fall back to.
+        let system_program = Pubkey::default();
+        let unregistered = [definition_info(
+            &address,
+            &mut empty_lamports,
+            &mut [],
+            &system_program,
+        )];
+        assert_eq!(
+            ItemRegistry::new(&unregistered)
+                .stats(item_ids::IRON_SWORD)
+                .unwrap_err(),
+            error!(ChainDepthError::MissingItemDefinition)
+        );
+    }
+
+    #[test]
+    fn catalog_weapons_have_durability() {
+        for item_id in item_ids::BRONZE_PICKAXE..=item_ids::WOODEN_TANKARD {
+            let stats = item_stats(item_id);
+            assert_eq!(stats.category, item_categories::WEAPON, "item {item_id}");
+            assert!(stats.base_durability > 0, "item {item_id}");
+            assert!(stats.combat_dps >= BARE_HANDS_DPS, "item {item_id}");
+            assert!(!stats.stackable, "item {item_id}");
+        }
+    }
+
+    #[test]
+    fn catalog_valuables_are_scored_items() {
+        for item_id in item_ids::SILVER_COIN..=item_ids::VOID_SHARD {
+            let stats = item_stats(item_id);
+            assert_eq!(stats.category, item_categories::VALUABLE, "item {item_id}");
+            assert!(is_scored_loot_item(item_id));
+            assert_eq!(stats.base_durability, 0);
+            assert!(stats.stackable && stats.tradeable, "item {item_id}");
+        }
+        assert_eq!(item_stats(item_ids::ANCIENT_CROWN).score_value, 20);
+        assert_eq!(item_stats(item_ids::SKELETON_KEY).score_value, 0);
+    }
+
+    #[test]
+    fn catalog_registers_the_reserved_weapons() {
+        for item_id in [1001, 1002] {
+            let stats = item_stats(item_id);
+            assert_eq!(stats.category, item_categories::WEAPON, "item {item_id}");
+            assert!(stats.combat_dps > 0);
+        }
+    }
+}
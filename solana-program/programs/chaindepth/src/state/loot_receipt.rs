@@ -1,18 +1,51 @@
 use anchor_lang::prelude::*;
 
+use crate::state::LOOT_REVEAL_DELAY_SLOTS;
+
+pub mod loot_kinds {
+    pub const CHEST: u8 = 0;
+    pub const BOSS: u8 = 1;
+}
+
 /// Per-player loot receipt for a specific room.
 /// Existence of this PDA proves the player has already looted the chest.
-/// PDA seeds: ["loot_receipt", season_seed (8 bytes), room_x (1 byte), room_y (1 byte), player_pubkey]
+/// Looting only commits to a roll; `reveal_loot` later mixes in the hash of a
+/// slot after `commit_slot` and grants the item.
+/// PDA seeds: ["loot_receipt", season_seed (8 bytes), room_floor (1 byte), room_x (1 byte), room_y (1 byte), player_pubkey]
 #[account]
 #[derive(InitSpace)]
 pub struct LootReceipt {
     pub player: Pubkey,
     pub season_seed: u64,
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
+
+    /// One of `loot_kinds`
+    pub kind: u8,
+
+    /// Boss id for boss loot (0 for chests)
+    pub source_id: u16,
+
+    /// Slot the roll was committed in
+    pub commit_slot: u64,
+
+    /// Boss loot: the looter's share of the boss HP, scales stack sizes
+    pub damage_share_bps: u16,
+
+    /// Boss loot: the looter landed the last hit and gets a second drop,
+    /// rolled at reveal from the same slot hash as the drop itself
+    pub last_hitter: bool,
+
+    pub revealed: bool,
     pub bump: u8,
 }
 
 impl LootReceipt {
     pub const SEED_PREFIX: &'static [u8] = b"loot_receipt";
+
+    /// Slot whose hash decides the roll
+    pub fn reveal_slot(&self) -> u64 {
+        self.commit_slot.saturating_add(LOOT_REVEAL_DELAY_SLOTS)
+    }
 }
@@ -0,0 +1,677 @@
+use anchor_lang::prelude::*;
+
+use crate::events::item_types;
+use crate::state::{boss_ids, item_ids};
+
+/// One entry of a weighted drop list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightedItem {
+    pub item_id: u16,
+    pub weight: u16,
+}
+
+const fn item(item_id: u16, weight: u16) -> WeightedItem {
+    WeightedItem { item_id, weight }
+}
+
+/// Drop table for one loot source: which item type drops, which item of
+/// that type, and how many.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LootTable {
+    /// Weights of valuable, tool and buff drops, indexed by `item_types`
+    pub type_weights: [u16; 3],
+    pub valuables: &'static [WeightedItem],
+    pub tools: &'static [WeightedItem],
+    pub buffs: &'static [WeightedItem],
+    /// Inclusive (min, max) stack size, indexed by `item_types`
+    pub amounts: [(u8, u8); 3],
+}
+
+impl LootTable {
+    pub fn items(&self, item_type: u8) -> &'static [WeightedItem] {
+        match item_type {
+            item_types::TOOL => self.tools,
+            item_types::BUFF => self.buffs,
+            _ => self.valuables,
+        }
+    }
+
+    /// Roll a drop from a revealed random value.
+    /// Separate bit ranges of `hash` pick the type (bits 0-15), the item
+    /// (16-31) and the amount (32-39).
+    /// Returns (item_type, item_amount, item_id).
+    pub fn roll(&self, hash: u64) -> (u8, u8, u16) {
+        let item_type = pick_weighted(&self.type_weights, hash as u16) as u8;
+
+        let items = self.items(item_type);
+        let weights: Vec<u16> = items.iter().map(|entry| entry.weight).collect();
+        let item_id = items[pick_weighted(&weights, (hash >> 16) as u16)].item_id;
+
+        let (min_amount, max_amount) = self.amounts[item_type as usize];
+        let amount_span = max_amount - min_amount + 1;
+        let item_amount = min_amount + ((hash >> 32) as u8) % amount_span;
+
+        (item_type, item_amount, item_id)
+    }
+}
+
+/// Index of the weight `roll` lands on, with each index taking a share of
+/// `roll % total` proportional to its weight.
+fn pick_weighted(weights: &[u16], roll: u16) -> usize {
+    let total: u32 = weights.iter().map(|weight| u32::from(*weight)).sum();
+    let mut remaining = u32::from(roll) % total;
+    for (index, weight) in weights.iter().enumerate() {
+        let weight = u32::from(*weight);
+        if remaining < weight {
+            return index;
+        }
+        remaining -= weight;
+    }
+    weights.len() - 1
+}
+
+/// Chest drops from `min_depth` until the next tier starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChestLootTier {
+    pub min_depth: u32,
+    pub table: LootTable,
+}
+
+const COMMON_TOOLS: &[WeightedItem] = &[
+    item(item_ids::BRONZE_PICKAXE, 1),
+    item(item_ids::IRON_PICKAXE, 1),
+    item(item_ids::BRONZE_SWORD, 1),
+    item(item_ids::IRON_SWORD, 1),
+    item(item_ids::WOODEN_PIPE, 1),
+    item(item_ids::IRON_SCIMITAR, 1),
+    item(item_ids::WOODEN_TANKARD, 1),
+];
+
+// Chests never drop the diamond sword or the Nokia; those stay boss-only.
+const IRON_TOOLS: &[WeightedItem] = &[
+    item(item_ids::BRONZE_PICKAXE, 1),
+    item(item_ids::BRONZE_SWORD, 1),
+    item(item_ids::IRON_PICKAXE, 2),
+    item(item_ids::IRON_SWORD, 2),
+    item(item_ids::IRON_SCIMITAR, 2),
+];
+
+const EVEN_BUFFS: &[WeightedItem] = &[item(item_ids::MINOR_BUFF, 1), item(item_ids::MAJOR_BUFF, 1)];
+
+const MAJOR_BUFFS: &[WeightedItem] =
+    &[item(item_ids::MINOR_BUFF, 1), item(item_ids::MAJOR_BUFF, 2)];
+
+/// Chest loot by depth, shallowest first. Each tier trades junk for
+/// higher-value valuables and bigger stacks; the crown only appears from
+/// depth 5 and the phoenix feather and void shard from depth 8.
+pub const CHEST_LOOT_TIERS: [ChestLootTier; 4] = [
+    ChestLootTier {
+        min_depth: 0,
+        table: LootTable {
+            type_weights: [60, 25, 15],
+            valuables: &[
+                item(item_ids::SILVER_COIN, 4),
+                item(item_ids::GOLD_COIN, 3),
+                item(item_ids::GOBLIN_TOOTH, 2),
+                item(item_ids::DUSTY_TOME, 2),
+                item(item_ids::RUSTED_COMPASS, 2),
+                item(item_ids::SKELETON_KEY, 2),
+                item(item_ids::GOLD_BAR, 1),
+                item(item_ids::DWARF_BEARD_RING, 1),
+            ],
+            tools: COMMON_TOOLS,
+            buffs: EVEN_BUFFS,
+            amounts: [(1, 3), (1, 1), (1, 2)],
+        },
+    },
+    ChestLootTier {
+        min_depth: 2,
+        table: LootTable {
+            type_weights: [60, 25, 15],
+            valuables: &[
+                item(item_ids::SILVER_COIN, 3),
+                item(item_ids::GOLD_COIN, 3),
+                item(item_ids::GOLD_BAR, 2),
+                item(item_ids::SKELETON_KEY, 2),
+                item(item_ids::RUBY, 1),
+                item(item_ids::SAPPHIRE, 1),
+                item(item_ids::EMERALD, 1),
+                item(item_ids::GOBLIN_TOOTH, 1),
+                item(item_ids::DUSTY_TOME, 1),
+                item(item_ids::RUSTED_COMPASS, 1),
+                item(item_ids::DWARF_BEARD_RING, 1),
+                item(item_ids::ENCHANTED_SCROLL, 1),
+            ],
+            tools: COMMON_TOOLS,
+            buffs: EVEN_BUFFS,
+            amounts: [(1, 5), (1, 1), (1, 3)],
+        },
+    },
+    ChestLootTier {
+        min_depth: 5,
+        table: LootTable {
+            type_weights: [65, 20, 15],
+            valuables: &[
+                item(item_ids::GOLD_COIN, 3),
+                item(item_ids::GOLD_BAR, 3),
+                item(item_ids::RUBY, 2),
+                item(item_ids::SAPPHIRE, 2),
+                item(item_ids::EMERALD, 2),
+                item(item_ids::SKELETON_KEY, 2),
+                item(item_ids::ENCHANTED_SCROLL, 2),
+                item(item_ids::DWARF_BEARD_RING, 1),
+                item(item_ids::DIAMOND, 1),
+                item(item_ids::GOLDEN_CHALICE, 1),
+                item(item_ids::CURSED_AMULET, 1),
+                item(item_ids::MYSTIC_ORB, 1),
+                item(item_ids::ANCIENT_CROWN, 1),
+            ],
+            tools: IRON_TOOLS,
+            buffs: MAJOR_BUFFS,
+            amounts: [(2, 6), (1, 1), (1, 3)],
+        },
+    },
+    ChestLootTier {
+        min_depth: 8,
+        table: LootTable {
+            type_weights: [70, 15, 15],
+            valuables: &[
+                item(item_ids::GOLD_BAR, 3),
+                item(item_ids::RUBY, 2),
+                item(item_ids::SAPPHIRE, 2),
+                item(item_ids::EMERALD, 2),
+                item(item_ids::SKELETON_KEY, 2),
+                item(item_ids::DIAMOND, 2),
+                item(item_ids::GOLDEN_CHALICE, 2),
+                item(item_ids::CURSED_AMULET, 2),
+                item(item_ids::MYSTIC_ORB, 2),
+                item(item_ids::DRAGON_SCALE, 1),
+                item(item_ids::ANCIENT_CROWN, 1),
+                item(item_ids::PHOENIX_FEATHER, 1),
+                item(item_ids::VOID_SHARD, 1),
+            ],
+            tools: IRON_TOOLS,
+            buffs: MAJOR_BUFFS,
+            amounts: [(3, 8), (1, 1), (2, 4)],
+        },
+    },
+];
+
+/// Drop table for a chest in a room at `depth`
+pub fn chest_loot_table(depth: u32) -> &'static LootTable {
+    let tier = CHEST_LOOT_TIERS
+        .iter()
+        .rev()
+        .find(|tier| depth >= tier.min_depth)
+        .unwrap_or(&CHEST_LOOT_TIERS[0]);
+    &tier.table
+}
+
+/// A drop rolled ahead of a boss table, so its odds stay fixed however the
+/// table itself is weighted. Always a single item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JackpotDrop {
+    pub item_type: u8,
+    pub item_id: u16,
+    pub chance_percent: u8,
+}
+
+/// Drop table for one boss variant (`center_id` of a boss room)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BossLootTable {
+    pub boss_id: u16,
+    pub table: LootTable,
+    pub jackpot: Option<JackpotDrop>,
+}
+
+impl BossLootTable {
+    /// Roll a drop from a revealed random value. Bits 48-63 of `hash` roll
+    /// the jackpot; otherwise the drop comes from `table`.
+    /// Returns (item_type, item_amount, item_id).
+    pub fn roll(&self, hash: u64) -> (u8, u8, u16) {
+        if let Some(jackpot) = self.jackpot {
+            if ((hash >> 48) as u16) % 100 < u16::from(jackpot.chance_percent) {
+                return (jackpot.item_type, 1, jackpot.item_id);
+            }
+        }
+        self.table.roll(hash)
+    }
+
+    /// Every possible drop with its chance, jackpot first.
+    pub fn drops(&self) -> Vec<LootDropView> {
+        let jackpot_bps = self
+            .jackpot
+            .map_or(0, |jackpot| u32::from(jackpot.chance_percent) * 100);
+        let mut drops: Vec<LootDropView> = self
+            .jackpot
+            .iter()
+            .map(|jackpot| LootDropView {
+                item_type: jackpot.item_type,
+                item_id: jackpot.item_id,
+                chance_bps: jackpot_bps as u16,
+                min_amount: 1,
+                max_amount: 1,
+            })
+            .collect();
+
+        let table = &self.table;
+        let type_total: u32 = table.type_weights.iter().map(|w| u32::from(*w)).sum();
+        for item_type in [item_types::ORE, item_types::TOOL, item_types::BUFF] {
+            let items = table.items(item_type);
+            let item_total: u32 = items.iter().map(|entry| u32::from(entry.weight)).sum();
+            let (min_amount, max_amount) = table.amounts[item_type as usize];
+            for entry in items {
+                let chance_bps = u64::from(10_000 - jackpot_bps)
+                    * u64::from(table.type_weights[item_type as usize])
+                    * u64::from(entry.weight)
+                    / (u64::from(type_total) * u64::from(item_total));
+                drops.push(LootDropView {
+                    item_type,
+                    item_id: entry.item_id,
+                    chance_bps: chance_bps as u16,
+                    min_amount,
+                    max_amount,
+                });
+            }
+        }
+        drops
+    }
+
+    pub fn view(&self) -> BossLootView {
+        BossLootView {
+            boss_id: self.boss_id,
+            drops: self.drops(),
+        }
+    }
+}
+
+/// One possible drop as shown to clients
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LootDropView {
+    pub item_type: u8,
+    pub item_id: u16,
+    /// Chance of this exact drop in basis points (rounded down)
+    pub chance_bps: u16,
+    pub min_amount: u8,
+    pub max_amount: u8,
+}
+
+/// Drop odds of a boss, returned by view_boss_loot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BossLootView {
+    pub boss_id: u16,
+    pub drops: Vec<LootDropView>,
+}
+
+/// Per-boss drops, weakest boss first. Each boss has signature valuables;
+/// only the dragon, the boss with the most HP, can drop the diamond sword.
+pub const BOSS_LOOT_TABLES: [BossLootTable; 4] = [
+    BossLootTable {
+        boss_id: boss_ids::GOBLIN_CHIEF,
+        table: LootTable {
+            type_weights: [50, 35, 15],
+            valuables: &[
+                item(item_ids::GOBLIN_TOOTH, 6),
+                item(item_ids::SILVER_COIN, 3),
+                item(item_ids::GOLD_COIN, 3),
+                item(item_ids::DWARF_BEARD_RING, 2),
+                item(item_ids::RUSTED_COMPASS, 1),
+                item(item_ids::GOLD_BAR, 1),
+            ],
+            tools: &[
+                item(item_ids::WOODEN_PIPE, 2),
+                item(item_ids::WOODEN_TANKARD, 2),
+                item(item_ids::BRONZE_SWORD, 2),
+                item(item_ids::IRON_SCIMITAR, 2),
+                item(item_ids::NOKIA_3310, 1),
+            ],
+            buffs: EVEN_BUFFS,
+            amounts: [(3, 8), (1, 1), (2, 4)],
+        },
+        jackpot: None,
+    },
+    BossLootTable {
+        boss_id: boss_ids::SKELETON_LORD,
+        table: LootTable {
+            type_weights: [50, 30, 20],
+            valuables: &[
+                item(item_ids::SKELETON_KEY, 4),
+                item(item_ids::DUSTY_TOME, 3),
+                item(item_ids::CURSED_AMULET, 3),
+                item(item_ids::GOLD_COIN, 2),
+                item(item_ids::GOLD_BAR, 2),
+                item(item_ids::ENCHANTED_SCROLL, 1),
+            ],
+            tools: &[
+                item(item_ids::IRON_SWORD, 2),
+                item(item_ids::IRON_SCIMITAR, 2),
+                item(item_ids::IRON_PICKAXE, 1),
+                item(item_ids::BRONZE_SWORD, 1),
+            ],
+            buffs: MAJOR_BUFFS,
+            amounts: [(3, 9), (1, 1), (2, 5)],
+        },
+        jackpot: None,
+    },
+    BossLootTable {
+        boss_id: boss_ids::LICH,
+        table: LootTable {
+            type_weights: [45, 25, 30],
+            valuables: &[
+                item(item_ids::MYSTIC_ORB, 4),
+                item(item_ids::ENCHANTED_SCROLL, 3),
+                item(item_ids::RUBY, 2),
+                item(item_ids::SAPPHIRE, 2),
+                item(item_ids::EMERALD, 2),
+                item(item_ids::CURSED_AMULET, 2),
+                item(item_ids::GOLDEN_CHALICE, 1),
+                item(item_ids::VOID_SHARD, 1),
+            ],
+            tools: IRON_TOOLS,
+            buffs: MAJOR_BUFFS,
+            amounts: [(3, 10), (1, 1), (2, 6)],
+        },
+        jackpot: None,
+    },
+    BossLootTable {
+        boss_id: boss_ids::DRAGON,
+        table: LootTable {
+            type_weights: [60, 20, 20],
+            valuables: &[
+                item(item_ids::DRAGON_SCALE, 5),
+                item(item_ids::GOLD_BAR, 4),
+                item(item_ids::DIAMOND, 3),
+                item(item_ids::GOLDEN_CHALICE, 2),
+                item(item_ids::ANCIENT_CROWN, 2),
+                item(item_ids::RUBY, 1),
+                item(item_ids::PHOENIX_FEATHER, 1),
+            ],
+            tools: IRON_TOOLS,
+            buffs: MAJOR_BUFFS,
+            amounts: [(4, 10), (1, 1), (3, 6)],
+        },
+        jackpot: Some(JackpotDrop {
+            item_type: item_types::TOOL,
+            item_id: item_ids::DIAMOND_SWORD,
+            chance_percent: 10,
+        }),
+    },
+];
+
+/// Boss drops for a `center_id` outside `BOSS_LOOT_TABLES`
+pub const GENERIC_BOSS_LOOT: BossLootTable = BossLootTable {
+    boss_id: 0,
+    table: LootTable {
+        type_weights: [35, 40, 25],
+        valuables: &[
+            item(item_ids::GOLD_COIN, 1),
+            item(item_ids::GOLD_BAR, 2),
+            item(item_ids::DIAMOND, 1),
+            item(item_ids::RUBY, 1),
+            item(item_ids::SAPPHIRE, 1),
+            item(item_ids::EMERALD, 1),
+            item(item_ids::ANCIENT_CROWN, 1),
+            item(item_ids::DRAGON_SCALE, 1),
+            item(item_ids::CURSED_AMULET, 1),
+            item(item_ids::GOLDEN_CHALICE, 1),
+            item(item_ids::MYSTIC_ORB, 1),
+            item(item_ids::PHOENIX_FEATHER, 1),
+            item(item_ids::VOID_SHARD, 1),
+            item(item_ids::SKELETON_KEY, 1),
+            item(item_ids::ENCHANTED_SCROLL, 1),
+        ],
+        tools: IRON_TOOLS,
+        buffs: MAJOR_BUFFS,
+        amounts: [(3, 10), (1, 1), (2, 6)],
+    },
+    jackpot: None,
+};
+
+/// Drop table for the boss with `boss_id`
+pub fn boss_loot_table(boss_id: u16) -> &'static BossLootTable {
+    BOSS_LOOT_TABLES
+        .iter()
+        .find(|table| table.boss_id == boss_id)
+        .unwrap_or(&GENERIC_BOSS_LOOT)
+}
+
+/// Fixed drop for a receipt whose reveal slot hash has expired: a single
+/// Silver Coin, the lowest-scoring treasure. Waiting out the SlotHashes
+/// window therefore cannot buy a different roll.
+/// Returns (item_type, item_amount, item_id).
+pub const EXPIRED_LOOT_DROP: (u8, u8, u16) = (item_types::ORE, 1, item_ids::SILVER_COIN);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::item_definition::catalog::item_stats;
+    use crate::state::{item_categories, RoomAccount};
+    use std::collections::BTreeMap;
+
+    const SAMPLES: u64 = 40_000;
+
+    /// splitmix64, standing in for revealed slot-hash entropy
+    fn sample_hash(index: u64) -> u64 {
+        let mut z = index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn total_weight(items: &[WeightedItem]) -> u32 {
+        items.iter().map(|entry| u32::from(entry.weight)).sum()
+    }
+
+    #[test]
+    fn tiers_start_at_the_surface_and_deepen() {
+        assert_eq!(CHEST_LOOT_TIERS[0].min_depth, 0);
+        for pair in CHEST_LOOT_TIERS.windows(2) {
+            assert!(pair[0].min_depth < pair[1].min_depth);
+        }
+        assert_eq!(chest_loot_table(0), &CHEST_LOOT_TIERS[0].table);
+        assert_eq!(chest_loot_table(4), &CHEST_LOOT_TIERS[1].table);
+        assert_eq!(chest_loot_table(u32::MAX), &CHEST_LOOT_TIERS[3].table);
+    }
+
+    #[test]
+    fn tables_only_list_items_of_their_category() {
+        for tier in &CHEST_LOOT_TIERS {
+            let table = &tier.table;
+            for (item_type, category) in [
+                (item_types::ORE, item_categories::VALUABLE),
+                (item_types::TOOL, item_categories::WEAPON),
+                (item_types::BUFF, item_categories::CONSUMABLE),
+            ] {
+                let items = table.items(item_type);
+                assert!(!items.is_empty());
+                for entry in items {
+                    assert!(entry.weight > 0, "item {}", entry.item_id);
+                    assert_eq!(item_stats(entry.item_id).category, category);
+                }
+                let (min_amount, max_amount) = table.amounts[item_type as usize];
+                assert!(0 < min_amount && min_amount <= max_amount);
+            }
+        }
+    }
+
+    #[test]
+    fn rare_valuables_only_drop_deep() {
+        let drops_at = |depth: u32, item_id: u16| {
+            chest_loot_table(depth)
+                .valuables
+                .iter()
+                .any(|entry| entry.item_id == item_id)
+        };
+
+        for depth in 0..5 {
+            assert!(!drops_at(depth, item_ids::ANCIENT_CROWN), "depth {depth}");
+        }
+        for depth in 0..8 {
+            assert!(!drops_at(depth, item_ids::PHOENIX_FEATHER), "depth {depth}");
+            assert!(!drops_at(depth, item_ids::VOID_SHARD), "depth {depth}");
+        }
+        assert!(drops_at(5, item_ids::ANCIENT_CROWN));
+        for item_id in [
+            item_ids::ANCIENT_CROWN,
+            item_ids::PHOENIX_FEATHER,
+            item_ids::VOID_SHARD,
+        ] {
+            assert!(drops_at(8, item_id));
+        }
+    }
+
+    #[test]
+    fn roll_rates_match_table_weights() {
+        for tier in &CHEST_LOOT_TIERS {
+            let table = &tier.table;
+            let mut counts = BTreeMap::<(u8, u16), u64>::new();
+            for index in 0..SAMPLES {
+                let (item_type, item_amount, item_id) = table.roll(sample_hash(index));
+                let (min_amount, max_amount) = table.amounts[item_type as usize];
+                assert!((min_amount..=max_amount).contains(&item_amount));
+                *counts.entry((item_type, item_id)).or_default() += 1;
+            }
+
+            let type_total: u32 = table.type_weights.iter().map(|w| u32::from(*w)).sum();
+            for item_type in [item_types::ORE, item_types::TOOL, item_types::BUFF] {
+                let items = table.items(item_type);
+                let type_share =
+                    f64::from(table.type_weights[item_type as usize]) / f64::from(type_total);
+                for entry in items {
+                    let expected =
+                        type_share * f64::from(entry.weight) / f64::from(total_weight(items));
+                    let observed = counts
+                        .get(&(item_type, entry.item_id))
+                        .copied()
+                        .unwrap_or_default() as f64
+                        / SAMPLES as f64;
+                    assert!(
+                        (observed - expected).abs() < 0.01,
+                        "depth {} item {}: observed {observed:.4}, expected {expected:.4}",
+                        tier.min_depth,
+                        entry.item_id
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deeper_chests_drop_bigger_valuable_stacks() {
+        let mean_valuable_stack = |table: &LootTable| {
+            let (total, drops) = (0..SAMPLES)
+                .map(|index| table.roll(sample_hash(index)))
+                .filter(|(item_type, _, _)| *item_type == item_types::ORE)
+                .fold((0u64, 0u64), |(total, drops), (_, amount, _)| {
+                    (total + u64::from(amount), drops + 1)
+                });
+            total as f64 / drops as f64
+        };
+
+        let means: Vec<f64> = CHEST_LOOT_TIERS
+            .iter()
+            .map(|tier| mean_valuable_stack(&tier.table))
+            .collect();
+        for pair in means.windows(2) {
+            assert!(pair[0] < pair[1], "{means:?}");
+        }
+    }
+
+    #[test]
+    fn each_boss_has_its_own_table_with_a_signature_drop() {
+        for (boss_id, signature) in [
+            (boss_ids::GOBLIN_CHIEF, item_ids::GOBLIN_TOOTH),
+            (boss_ids::SKELETON_LORD, item_ids::SKELETON_KEY),
+            (boss_ids::LICH, item_ids::MYSTIC_ORB),
+            (boss_ids::DRAGON, item_ids::DRAGON_SCALE),
+        ] {
+            let table = boss_loot_table(boss_id);
+            assert_eq!(table.boss_id, boss_id);
+            let top = table
+                .table
+                .valuables
+                .iter()
+                .max_by_key(|entry| entry.weight)
+                .unwrap();
+            assert_eq!(top.item_id, signature, "boss {boss_id}");
+        }
+        assert_eq!(boss_loot_table(0), &GENERIC_BOSS_LOOT);
+        assert_eq!(boss_loot_table(99), &GENERIC_BOSS_LOOT);
+    }
+
+    #[test]
+    fn only_the_toughest_boss_drops_the_diamond_sword() {
+        let toughest = BOSS_LOOT_TABLES
+            .iter()
+            .max_by_key(|table| RoomAccount::boss_hp_for_depth(0, table.boss_id))
+            .unwrap();
+        assert_eq!(toughest.boss_id, boss_ids::DRAGON);
+
+        for table in BOSS_LOOT_TABLES.iter().chain([&GENERIC_BOSS_LOOT]) {
+            let drops_sword = table
+                .drops()
+                .iter()
+                .any(|drop| drop.item_id == item_ids::DIAMOND_SWORD);
+            assert_eq!(drops_sword, table == toughest, "boss {}", table.boss_id);
+        }
+
+        let swords = (0..SAMPLES)
+            .filter(|index| toughest.roll(sample_hash(*index)).2 == item_ids::DIAMOND_SWORD)
+            .count() as f64;
+        assert!((swords / SAMPLES as f64 - 0.10).abs() < 0.01);
+    }
+
+    #[test]
+    fn boss_tables_only_list_items_of_their_category() {
+        for table in BOSS_LOOT_TABLES.iter().chain([&GENERIC_BOSS_LOOT]) {
+            for drop in table.drops() {
+                let category = match drop.item_type {
+                    item_types::ORE => item_categories::VALUABLE,
+                    item_types::TOOL => item_categories::WEAPON,
+                    _ => item_categories::CONSUMABLE,
+                };
+                assert_eq!(item_stats(drop.item_id).category, category);
+                assert!(0 < drop.min_amount && drop.min_amount <= drop.max_amount);
+            }
+        }
+    }
+
+    #[test]
+    fn boss_loot_view_matches_roll_rates() {
+        for table in BOSS_LOOT_TABLES.iter().chain([&GENERIC_BOSS_LOOT]) {
+            let drops = table.drops();
+            let listed_bps: u32 = drops.iter().map(|drop| u32::from(drop.chance_bps)).sum();
+            assert!(
+                (9_950..=10_000).contains(&listed_bps),
+                "boss {}",
+                table.boss_id
+            );
+
+            let mut counts = BTreeMap::<(u8, u16), u64>::new();
+            for index in 0..SAMPLES {
+                let (item_type, item_amount, item_id) = table.roll(sample_hash(index));
+                let drop = drops
+                    .iter()
+                    .find(|drop| (drop.item_type, drop.item_id) == (item_type, item_id))
+                    .expect("rolled a drop the view does not list");
+                assert!((drop.min_amount..=drop.max_amount).contains(&item_amount));
+                *counts.entry((item_type, item_id)).or_default() += 1;
+            }
+
+            for drop in &drops {
+                let expected = f64::from(drop.chance_bps) / 10_000.0;
+                let observed = counts
+                    .get(&(drop.item_type, drop.item_id))
+                    .copied()
+                    .unwrap_or_default() as f64
+                    / SAMPLES as f64;
+                assert!(
+                    (observed - expected).abs() < 0.01,
+                    "boss {} item {}: observed {observed:.4}, expected {expected:.4}",
+                    table.boss_id,
+                    drop.item_id
+                );
+            }
+        }
+    }
+}
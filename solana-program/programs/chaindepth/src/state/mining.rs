@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ChainDepthError;
+use crate::state::{InventoryAccount, ItemRegistry};
+
+/// Work rate of a helper with no tool equipped, in percent.
+/// Base slots are calibrated so one bare-handed helper clears a wall in `base_slots`.
+pub const BARE_HANDS_WORK_RATE: u64 = 100;
+
+/// Work rate (percent) contributed by a helper with `item_id` equipped.
+/// The definition PDA of the equipped item must be passed as a remaining account.
+pub fn equipped_work_rate(
+    item_registry: &ItemRegistry,
+    inventory: &InventoryAccount,
+    item_id: u16,
+) -> Result<u64> {
+    if item_id == 0 || !inventory.has_item(item_id) {
+        return Ok(BARE_HANDS_WORK_RATE);
+    }
+    let mining_speed = u64::from(item_registry.stats(item_id)?.mining_speed);
+    Ok(mining_speed.max(BARE_HANDS_WORK_RATE))
+}
+
+/// Progress earned over `elapsed_slots` at `total_work_rate`.
+/// Sub-slot work is carried in `remainder` so frequent settlement never loses progress.
+/// Returns (progress in slots, new remainder).
+pub fn accrue_job_progress(
+    elapsed_slots: u64,
+    total_work_rate: u64,
+    remainder: u64,
+) -> Result<(u64, u64)> {
+    let work = elapsed_slots
+        .checked_mul(total_work_rate)
+        .and_then(|work| work.checked_add(remainder))
+        .ok_or(ChainDepthError::Overflow)?;
+    Ok((work / BARE_HANDS_WORK_RATE, work % BARE_HANDS_WORK_RATE))
+}
+
+/// Work a helper contributed to a job: their rate times the slots they were
+/// on it, counting both the join and the completion slot.
+pub fn helper_contribution(work_rate: u64, joined_slot: u64, completed_slot: u64) -> u64 {
+    let slots_worked = completed_slot.saturating_sub(joined_slot).saturating_add(1);
+    work_rate.saturating_mul(slots_worked)
+}
+
+/// Work a helper's buff bonus contributed: the bonus for every slot from the
+/// join until the buff wore off or the job completed, whichever came first.
+pub fn buff_contribution(
+    buff_bonus: u64,
+    joined_slot: u64,
+    buff_expires_slot: u64,
+    completed_slot: u64,
+) -> u64 {
+    let end_slot = buff_expires_slot.min(completed_slot.saturating_add(1));
+    buff_bonus.saturating_mul(end_slot.saturating_sub(joined_slot))
+}
+
+/// Pro-rata share of `bonus_pool` for `contribution` out of `total_contribution`.
+/// Rounds down; the caller returns the leftover dust to the prize pool.
+pub fn contribution_share(bonus_pool: u64, contribution: u64, total_contribution: u64) -> u64 {
+    if total_contribution == 0 {
+        return 0;
+    }
+    let share = u128::from(bonus_pool) * u128::from(contribution) / u128::from(total_contribution);
+    share.min(u128::from(bonus_pool)) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::item_definition::catalog::item_stats;
+    use crate::state::{item_ids, BuffSchedule, HelperStake, RoomAccount};
+
+    fn slots_to_clear(base_slots: u64, total_work_rate: u64) -> u64 {
+        let mut progress = 0;
+        let mut remainder = 0;
+        let mut slots = 0;
+        while progress < base_slots {
+            let (gained, carry) = accrue_job_progress(1, total_work_rate, remainder).unwrap();
+            progress += gained;
+            remainder = carry;
+            slots += 1;
+        }
+        slots
+    }
+
+    fn tool_rate(item_id: u16) -> u64 {
+        u64::from(item_stats(item_id).mining_speed).max(BARE_HANDS_WORK_RATE)
+    }
+
+    #[test]
+    fn iron_pickaxe_clears_faster_than_bare_hands() {
+        let base_slots = RoomAccount::calculate_base_slots(0);
+        let bare_hands = slots_to_clear(base_slots, BARE_HANDS_WORK_RATE);
+        let bronze = slots_to_clear(base_slots, tool_rate(item_ids::BRONZE_PICKAXE));
+        let iron = slots_to_clear(base_slots, tool_rate(item_ids::IRON_PICKAXE));
+
+        assert_eq!(bare_hands, base_slots);
+        assert!(bronze < bare_hands);
+        assert!(iron < bronze);
+        assert_eq!(iron, base_slots / 2);
+    }
+
+    #[test]
+    fn swords_mine_like_bare_hands() {
+        assert_eq!(tool_rate(item_ids::DIAMOND_SWORD), BARE_HANDS_WORK_RATE);
+    }
+
+    #[test]
+    fn late_joiner_earns_a_smaller_share() {
+        let completed_slot = 1_300;
+        let early = helper_contribution(BARE_HANDS_WORK_RATE, 1_000, completed_slot);
+        let late = helper_contribution(BARE_HANDS_WORK_RATE, 1_299, completed_slot);
+        let total = early + late;
+        let bonus_pool = 1_000_000;
+
+        let early_share = contribution_share(bonus_pool, early, total);
+        let late_share = contribution_share(bonus_pool, late, total);
+        assert!(late_share * 100 < early_share);
+        assert!(early_share + late_share <= bonus_pool);
+    }
+
+    #[test]
+    fn shares_never_exceed_pool_and_dust_is_small() {
+        let completed_slot = 5_000;
+        let helpers = [(100, 4_000), (150, 4_321), (200, 4_999), (100, 5_000)];
+        let total: u64 = helpers
+            .iter()
+            .map(|&(rate, joined)| helper_contribution(rate, joined, completed_slot))
+            .sum();
+        let bonus_pool = 999_999;
+        let paid: u64 = helpers
+            .iter()
+            .map(|&(rate, joined)| {
+                contribution_share(
+                    bonus_pool,
+                    helper_contribution(rate, joined, completed_slot),
+                    total,
+                )
+            })
+            .sum();
+        assert!(paid <= bonus_pool);
+        assert!(bonus_pool - paid < helpers.len() as u64);
+    }
+
+    #[test]
+    fn settling_every_slot_matches_one_settlement() {
+        let rate = tool_rate(item_ids::BRONZE_PICKAXE);
+        let (bulk, bulk_remainder) = accrue_job_progress(7, rate, 0).unwrap();
+
+        let mut progress = 0;
+        let mut remainder = 0;
+        for _ in 0..7 {
+            let (gained, carry) = accrue_job_progress(1, rate, remainder).unwrap();
+            progress += gained;
+            remainder = carry;
+        }
+        assert_eq!((progress, remainder), (bulk, bulk_remainder));
+    }
+
+    #[test]
+    fn buff_bonus_stops_when_the_buff_wears_off() {
+        let stake = |joined_slot, buff_bonus, buff_expires_slot| HelperStake {
+            player: Pubkey::new_unique(),
+            room: Pubkey::default(),
+            direction: 0,
+            amount: RoomAccount::STAKE_AMOUNT,
+            joined_slot,
+            work_rate: BARE_HANDS_WORK_RATE,
+            buff_bonus,
+            buff_expires_slot,
+            bump: 0,
+        };
+        let buffed = stake(100, 50, 110);
+        let plain = stake(100, 0, 0);
+        let late_buffed = stake(120, 50, 200);
+
+        let mut room = RoomAccount {
+            base_slots: [1_000; 4],
+            progress_slot: [100; 4],
+            ..RoomAccount::default()
+        };
+        let mut schedule = BuffSchedule::default();
+        room.add_helper_work(0, &buffed, Some(&mut schedule))
+            .unwrap();
+        room.add_helper_work(0, &plain, None).unwrap();
+        assert_eq!(
+            room.settle_job_progress(0, None, 120).unwrap_err(),
+            ChainDepthError::BuffScheduleMissing.into()
+        );
+        room.settle_job_progress(0, Some(&mut schedule), 120)
+            .unwrap();
+        assert_eq!(room.job_buffs_scheduled[0], 0);
+        room.add_helper_work(0, &late_buffed, Some(&mut schedule))
+            .unwrap();
+
+        // 250% until slot 110, 200% until 120, then 350% with the late buff.
+        room.settle_job_progress(0, Some(&mut schedule), 130)
+            .unwrap();
+        assert_eq!(room.progress[0], 25 + 20 + 35);
+        assert_eq!(room.total_work_rate[0], 350);
+        assert_eq!(room.job_buffs_scheduled[0], 1);
+
+        room.record_job_completion(0, 130).unwrap();
+        room.bonus_pool[0] = 1_000_000;
+        let shares =
+            [&buffed, &plain, &late_buffed].map(|helper| room.helper_bonus_share(0, helper));
+        assert_eq!(
+            room.total_contribution[0],
+            (100 * 31 + 50 * 10) + 100 * 31 + (100 * 11 + 50 * 11)
+        );
+        assert!(shares[0] > shares[1]);
+        assert!(shares.iter().sum::<u64>() <= room.bonus_pool[0]);
+
+        // Leaving after the buff wore off takes its expired work out too.
+        let mut room = RoomAccount {
+            base_slots: [1_000; 4],
+            progress_slot: [100; 4],
+            ..RoomAccount::default()
+        };
+        let mut schedule = BuffSchedule::default();
+        room.add_helper_work(0, &buffed, Some(&mut schedule))
+            .unwrap();
+        room.settle_job_progress(0, Some(&mut schedule), 150)
+            .unwrap();
+        room.remove_helper_work(0, &buffed, None).unwrap();
+        assert_eq!(room.total_work_rate[0], 0);
+        assert_eq!(room.weighted_join_slot_sum[0], 0);
+        assert_eq!(room.expired_buff_slot_sum[0], 0);
+    }
+}
@@ -1,10 +1,16 @@
 pub mod boss_fight;
+pub mod buffs;
+pub mod combat;
 pub mod global;
 pub mod helper_stake;
 pub mod inventory;
+pub mod item_definition;
 pub mod loot_receipt;
+pub mod loot_tables;
+pub mod mining;
 pub mod player;
 pub mod player_profile;
+pub mod randomness;
 pub mod room;
 pub mod room_generation;
 pub mod room_presence;
@@ -12,12 +18,18 @@ pub mod scoring;
 pub mod session_authority;
 
 pub use boss_fight::*;
+pub use buffs::*;
+pub use combat::*;
 pub use global::*;
 pub use helper_stake::*;
 pub use inventory::*;
+pub use item_definition::*;
 pub use loot_receipt::*;
+pub use loot_tables::*;
+pub use mining::*;
 pub use player::*;
 pub use player_profile::*;
+pub use randomness::*;
 pub use room::*;
 pub use room_generation::*;
 pub use room_presence::*;
@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::ChainDepthError;
+use crate::state::{buff_effect, BuffEffect, InventoryAccount, SeasonLayout, ENTRANCE_FLOOR};
+
 /// Maximum number of active jobs a player can have at once
 pub const MAX_ACTIVE_JOBS: usize = 4;
 
+/// HP a player starts every run with
+pub const PLAYER_MAX_HP: u16 = 100;
+
 /// Player account - one per wallet
 /// PDA seeds: ["player", user_pubkey]
 #[account]
@@ -17,6 +23,7 @@ pub struct PlayerAccount {
 
     /// Active jobs: [(room_x, room_y, direction)]
     /// Direction: 0=North, 1=South, 2=East, 3=West
+    /// Always on `current_floor`: players can't change floors with active jobs.
     #[max_len(MAX_ACTIVE_JOBS)]
     pub active_jobs: Vec<ActiveJob>,
 
@@ -46,6 +53,27 @@ pub struct PlayerAccount {
 
     /// PDA bump seed
     pub bump: u8,
+
+    // Fields below were appended after launch; `migrate_player` grows older
+    // accounts to this layout.
+    /// Current dungeon floor (0 = entrance floor)
+    pub current_floor: u8,
+
+    /// Room whose boss the player is fighting (default = not in a fight).
+    /// Movement is locked until the player leaves or loots the fight.
+    pub boss_fight_room: Pubkey,
+
+    /// Remaining HP this run (see `PLAYER_MAX_HP`); the player dies at 0
+    pub hp: u16,
+
+    /// Buff item consumed by `use_item` (0 = none); see `active_buff`
+    pub active_buff_id: u16,
+
+    /// Slot the active buff wears off
+    pub buff_expires_slot: u64,
+
+    /// Set by a Phoenix Feather: the next death keeps carried loot
+    pub loot_protected: bool,
 }
 
 /// Represents an active job the player is working on
@@ -60,8 +88,78 @@ impl PlayerAccount {
     pub const SEED_PREFIX: &'static [u8] = b"player";
 
     /// Check if player is at the given room
-    pub fn is_at_room(&self, x: i8, y: i8) -> bool {
-        self.current_room_x == x && self.current_room_y == y
+    pub fn is_at_room(&self, floor: u8, x: i8, y: i8) -> bool {
+        self.current_floor == floor && self.current_room_x == x && self.current_room_y == y
+    }
+
+    /// Apply damage and report whether it was lethal
+    pub fn take_damage(&mut self, damage: u64) -> bool {
+        let damage = u16::try_from(damage).unwrap_or(u16::MAX);
+        self.hp = self.hp.saturating_sub(damage);
+        self.hp == 0
+    }
+
+    /// Reset after death: back to full HP in the start room of the current
+    /// floor, out of any boss fight, with a fresh run timer.
+    pub fn respawn(&mut self, layout: &SeasonLayout, slot: u64) {
+        self.hp = PLAYER_MAX_HP;
+        self.boss_fight_room = Pubkey::default();
+        (self.current_room_x, self.current_room_y) = layout.start();
+        self.current_run_start_slot = slot;
+    }
+
+    /// Effect of the buff in use at `slot`, if it has not worn off
+    pub fn active_buff(&self, slot: u64) -> Option<BuffEffect> {
+        if slot >= self.buff_expires_slot {
+            return None;
+        }
+        buff_effect(self.active_buff_id)
+    }
+
+    /// Job work rate with the active buff applied
+    pub fn buffed_work_rate(&self, work_rate: u64, slot: u64) -> u64 {
+        self.active_buff(slot)
+            .map_or(work_rate, |buff| buff.boost_work_rate(work_rate))
+    }
+
+    /// Boss DPS with the active buff applied
+    pub fn buffed_dps(&self, dps: u64, slot: u64) -> u64 {
+        self.active_buff(slot)
+            .map_or(dps, |buff| buff.boost_dps(dps))
+    }
+
+    /// Loot loss of a run-ending event such as death: carried scored loot is
+    /// dropped, unless a Phoenix Feather protection is up, which is spent
+    /// instead. Returns the (stacks, units) dropped, or None if the loot was saved.
+    pub fn lose_carried_loot(&mut self, inventory: &mut InventoryAccount) -> Option<(u32, u32)> {
+        if self.loot_protected {
+            self.loot_protected = false;
+            return None;
+        }
+        Some(inventory.drop_scored_loot())
+    }
+
+    /// Void Shard recall to the entrance floor's start room, where the player
+    /// can extract. Not allowed mid-fight or with jobs still staked.
+    pub fn recall_to_entrance(&mut self, layout: &SeasonLayout) -> Result<()> {
+        require!(!self.is_in_boss_fight(), ChainDepthError::InBossFight);
+        require!(
+            self.active_jobs.is_empty(),
+            ChainDepthError::CannotRecallWithActiveJobs
+        );
+        let (start_x, start_y) = layout.start();
+        require!(
+            !self.is_at_room(ENTRANCE_FLOOR, start_x, start_y),
+            ChainDepthError::AlreadyAtEntranceRoom
+        );
+
+        self.current_floor = ENTRANCE_FLOOR;
+        (self.current_room_x, self.current_room_y) = (start_x, start_y);
+        Ok(())
+    }
+
+    pub fn is_in_boss_fight(&self) -> bool {
+        self.boss_fight_room != Pubkey::default()
     }
 
     /// Check if player is already working on a job at given room/direction
@@ -75,7 +173,7 @@ impl PlayerAccount {
     pub fn add_job(&mut self, room_x: i8, room_y: i8, direction: u8) -> Result<()> {
         require!(
             self.active_jobs.len() < MAX_ACTIVE_JOBS,
-            crate::errors::ChainDepthError::TooManyActiveJobs
+            ChainDepthError::TooManyActiveJobs
         );
         self.active_jobs.push(ActiveJob {
             room_x,
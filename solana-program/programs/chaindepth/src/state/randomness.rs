@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use solana_sha256_hasher::hashv;
+
+use crate::errors::ChainDepthError;
+
+/// Slots between a loot commit and the slot whose hash decides the roll.
+/// The hash of a later slot does not exist when the commit is submitted, so
+/// the outcome cannot be simulated in advance.
+pub const LOOT_REVEAL_DELAY_SLOTS: u64 = 1;
+
+/// Source of recent slot hashes, newest first (the `SlotHashes` sysvar order).
+/// On-chain this is `SlotHashesProvider`; tests plug in a local mock.
+pub trait RandomnessProvider {
+    fn len(&self) -> usize;
+
+    /// (slot, hash) at `index`, where index 0 is the newest slot
+    fn entry(&self, index: usize) -> Result<(u64, [u8; 32])>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Result of looking up the entropy for a committed roll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotHashLookup {
+    /// The target slot has not been hashed yet
+    Pending,
+    /// Hash of the first slot at or after the target (skipped slots have no hash)
+    Found([u8; 32]),
+    /// The target slot has fallen out of the recent-hash window
+    Expired,
+}
+
+/// Find the hash of the first slot at or after `target_slot`.
+pub fn resolve_slot_hash<P: RandomnessProvider>(
+    provider: &P,
+    target_slot: u64,
+) -> Result<SlotHashLookup> {
+    if provider.is_empty() {
+        return Ok(SlotHashLookup::Pending);
+    }
+
+    let (newest_slot, _) = provider.entry(0)?;
+    if newest_slot < target_slot {
+        return Ok(SlotHashLookup::Pending);
+    }
+    let (oldest_slot, _) = provider.entry(provider.len() - 1)?;
+    if oldest_slot > target_slot {
+        // The earliest slot at or after the target may already be gone, so no
+        // hash in the window is guaranteed to be the one fixed at commit time.
+        return Ok(SlotHashLookup::Expired);
+    }
+
+    // Entries are sorted by descending slot: find the last index with slot >= target.
+    let (mut low, mut high) = (0, provider.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if provider.entry(mid)?.0 >= target_slot {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(SlotHashLookup::Found(provider.entry(low)?.1))
+}
+
+/// Mix a slot hash with per-roll context into a 64-bit loot roll.
+pub fn mix_entropy(slot_hash: &[u8; 32], context: &[&[u8]]) -> u64 {
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(context.len() + 1);
+    parts.push(slot_hash);
+    parts.extend_from_slice(context);
+
+    let digest = hashv(&parts).to_bytes();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Reads the `SlotHashes` sysvar in place. The sysvar is too large to
+/// deserialize on-chain, so entries are decoded straight from account data.
+pub struct SlotHashesProvider<'a, 'info> {
+    account: &'a AccountInfo<'info>,
+    len: usize,
+}
+
+impl<'a, 'info> SlotHashesProvider<'a, 'info> {
+    const LEN_SIZE: usize = 8;
+    const ENTRY_SIZE: usize = 8 + 32;
+
+    pub fn new(account: &'a AccountInfo<'info>) -> Result<Self> {
+        require_keys_eq!(
+            account.key(),
+            slot_hashes::ID,
+            ChainDepthError::InvalidSlotHashes
+        );
+
+        let data = account.try_borrow_data()?;
+        let len_bytes = data
+            .get(..Self::LEN_SIZE)
+            .ok_or(ChainDepthError::InvalidSlotHashes)?;
+        let len = u64::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+        require!(
+            data.len() >= Self::LEN_SIZE + len * Self::ENTRY_SIZE,
+            ChainDepthError::InvalidSlotHashes
+        );
+
+        Ok(Self { account, len })
+    }
+}
+
+impl RandomnessProvider for SlotHashesProvider<'_, '_> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn entry(&self, index: usize) -> Result<(u64, [u8; 32])> {
+        require!(index < self.len, ChainDepthError::InvalidSlotHashes);
+        let data = self.account.try_borrow_data()?;
+        let offset = Self::LEN_SIZE + index * Self::ENTRY_SIZE;
+
+        let mut slot = [0u8; 8];
+        slot.copy_from_slice(&data[offset..offset + 8]);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&data[offset + 8..offset + Self::ENTRY_SIZE]);
+        Ok((u64::from_le_bytes(slot), hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::loot_chest::roll_chest_loot;
+
+    /// Local stand-in for the SlotHashes sysvar
+    struct MockRandomness {
+        entries: Vec<(u64, [u8; 32])>,
+    }
+
+    impl MockRandomness {
+        /// Hashes for `slots` (any order); each hash is derived from `salt` so
+        /// different "futures" can be simulated for the same commit.
+        fn with_slots(slots: &[u64], salt: u8) -> Self {
+            let mut entries: Vec<(u64, [u8; 32])> = slots
+                .iter()
+                .map(|slot| (*slot, hashv(&[&slot.to_le_bytes(), &[salt]]).to_bytes()))
+                .collect();
+            entries.sort_by_key(|(slot, _)| std::cmp::Reverse(*slot));
+            Self { entries }
+        }
+    }
+
+    impl RandomnessProvider for MockRandomness {
+        fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        fn entry(&self, index: usize) -> Result<(u64, [u8; 32])> {
+            Ok(self.entries[index])
+        }
+    }
+
+    fn roll(provider: &MockRandomness, commit_slot: u64, player: &Pubkey) -> u64 {
+        match resolve_slot_hash(provider, commit_slot + LOOT_REVEAL_DELAY_SLOTS).unwrap() {
+            SlotHashLookup::Found(hash) => mix_entropy(&hash, &[player.as_ref()]),
+            other => panic!("expected a slot hash, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn outcome_is_unknown_at_commit_time() {
+        // At submission the newest known hash is for the commit slot itself.
+        let commit_slot = 1_000;
+        let known = MockRandomness::with_slots(&[998, 999, 1_000], 0);
+        assert_eq!(
+            resolve_slot_hash(&known, commit_slot + LOOT_REVEAL_DELAY_SLOTS).unwrap(),
+            SlotHashLookup::Pending
+        );
+    }
+
+    #[test]
+    fn same_commit_rolls_differently_per_future_hash() {
+        // Identical commits (same slot, same player) whose reveal hashes differ
+        // produce different loot, so nothing known at submission fixes the drop.
+        let player = Pubkey::new_unique();
+        let mut item_ids = std::collections::BTreeSet::new();
+        let mut rolls = std::collections::BTreeSet::new();
+        for salt in 0..64 {
+            let future = MockRandomness::with_slots(&[1_000, 1_001, 1_002], salt);
+            let roll = roll(&future, 1_000, &player);
+            rolls.insert(roll);
+            item_ids.insert(roll_chest_loot(roll, 0).2);
+        }
+        assert_eq!(rolls.len(), 64);
+        assert!(item_ids.len() > 5);
+    }
+
+    #[test]
+    fn skipped_target_slot_uses_next_hashed_slot() {
+        let provider = MockRandomness::with_slots(&[1_000, 1_003, 1_004], 7);
+        let expected = provider.entries[1].1;
+        assert_eq!(
+            resolve_slot_hash(&provider, 1_001).unwrap(),
+            SlotHashLookup::Found(expected)
+        );
+    }
+
+    #[test]
+    fn expired_receipt_settles_with_the_same_fixed_drop() {
+        use crate::instructions::reveal_loot::receipt_drops;
+        use crate::state::{loot_kinds, LootReceipt, EXPIRED_LOOT_DROP};
+
+        let mut receipt = LootReceipt {
+            player: Pubkey::new_unique(),
+            season_seed: 42,
+            room_floor: 0,
+            room_x: 3,
+            room_y: 4,
+            kind: loot_kinds::CHEST,
+            source_id: 0,
+            commit_slot: 1_000,
+            damage_share_bps: 10_000,
+            last_hitter: true,
+            revealed: false,
+            bump: 0,
+        };
+        let (item_type, item_amount, item_id) = EXPIRED_LOOT_DROP;
+        let fallback = vec![(item_type, item_amount, item_id, false)];
+
+        for kind in [loot_kinds::CHEST, loot_kinds::BOSS] {
+            receipt.kind = kind;
+            receipt.source_id = if kind == loot_kinds::BOSS { 4 } else { 0 };
+            for salt in 0..16 {
+                // Whatever the window holds by the time it is revealed, the
+                // receipt is never re-committed and settles the same way.
+                let window = MockRandomness::with_slots(&[1_600 + u64::from(salt), 1_700], salt);
+                let slot_hash = match resolve_slot_hash(&window, receipt.reveal_slot()).unwrap() {
+                    SlotHashLookup::Found(hash) => Some(hash),
+                    SlotHashLookup::Expired => None,
+                    SlotHashLookup::Pending => panic!("reveal slot is in the past"),
+                };
+                assert_eq!(slot_hash, None);
+                assert_eq!(receipt_drops(&receipt, slot_hash.as_ref(), 9), fallback);
+                assert_eq!(receipt.commit_slot, 1_000);
+            }
+        }
+    }
+
+    #[test]
+    fn only_the_last_hitter_gets_the_bonus_drop() {
+        use crate::instructions::reveal_loot::receipt_drops;
+        use crate::state::{loot_kinds, LootReceipt};
+
+        let mut receipt = LootReceipt {
+            player: Pubkey::new_unique(),
+            season_seed: 42,
+            room_floor: 0,
+            room_x: 3,
+            room_y: 4,
+            kind: loot_kinds::BOSS,
+            source_id: 4,
+            commit_slot: 1_000,
+            damage_share_bps: 5_000,
+            last_hitter: true,
+            revealed: false,
+            bump: 0,
+        };
+        let reveal = |receipt: &LootReceipt, salt: u8| {
+            let window = MockRandomness::with_slots(&[1_001], salt);
+            receipt_drops(receipt, Some(&window.entries[0].1), 9)
+        };
+
+        // The bonus item is only known once the reveal slot is hashed.
+        let mut bonus_items = std::collections::BTreeSet::new();
+        for salt in 0..64 {
+            let drops = reveal(&receipt, salt);
+            assert_eq!(drops.len(), 2);
+            assert!(!drops[0].3 && drops[1].3);
+            bonus_items.insert(drops[1].2);
+        }
+        assert!(bonus_items.len() > 1);
+
+        receipt.last_hitter = false;
+        assert!((0..64).all(|salt| reveal(&receipt, salt).len() == 1));
+    }
+
+    #[test]
+    fn same_coordinates_on_another_floor_roll_differently() {
+        use crate::instructions::reveal_loot::receipt_drops;
+        use crate::state::{loot_kinds, LootReceipt};
+
+        let mut receipt = LootReceipt {
+            player: Pubkey::new_unique(),
+            season_seed: 42,
+            room_floor: 0,
+            room_x: 3,
+            room_y: 4,
+            kind: loot_kinds::CHEST,
+            source_id: 0,
+            commit_slot: 1_000,
+            damage_share_bps: 10_000,
+            last_hitter: false,
+            revealed: false,
+            bump: 0,
+        };
+        let drops = |receipt: &LootReceipt| {
+            (0..32u8)
+                .map(|salt| {
+                    let window = MockRandomness::with_slots(&[1_001], salt);
+                    receipt_drops(receipt, Some(&window.entries[0].1), 9)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Rooms at the same x/y on different floors share a reveal slot hash
+        // but must not share a drop.
+        let ground_floor = drops(&receipt);
+        receipt.room_floor = 1;
+        assert_ne!(drops(&receipt), ground_floor);
+    }
+
+    #[test]
+    fn evicted_target_slot_is_expired() {
+        let provider = MockRandomness::with_slots(&[1_600, 1_601], 3);
+        assert_eq!(
+            resolve_slot_hash(&provider, 1_001).unwrap(),
+            SlotHashLookup::Expired
+        );
+    }
+}
@@ -1,6 +1,12 @@
 use anchor_lang::prelude::*;
 
-use super::global::GlobalAccount;
+use super::buffs::BuffSchedule;
+use super::global::SeasonLayout;
+use super::helper_stake::HelperStake;
+use super::mining::{
+    accrue_job_progress, buff_contribution, contribution_share, helper_contribution,
+};
+use crate::errors::ChainDepthError;
 
 pub const MAX_BOSS_HP: u64 = 100_000;
 
@@ -20,17 +26,38 @@ pub const WALL_ENTRANCE_STAIRS: u8 = 4;
 /// Door lock kind constants
 pub const LOCK_KIND_NONE: u8 = 0;
 pub const LOCK_KIND_SKELETON: u8 = 1;
+pub const LOCK_KIND_RUBY: u8 = 2;
+pub const LOCK_KIND_SAPPHIRE: u8 = 3;
+pub const LOCK_KIND_EMERALD: u8 = 4;
+/// Opens without a key once a boss on the same floor and ring is defeated
+pub const LOCK_KIND_BOSS_SEAL: u8 = 5;
 
 /// Center state constants
 pub const CENTER_EMPTY: u8 = 0;
 pub const CENTER_CHEST: u8 = 1;
 pub const CENTER_BOSS: u8 = 2;
+pub const CENTER_STAIRS_DOWN: u8 = 3;
+
+/// Boss variants stored in `center_id` of boss rooms. Higher ids have more
+/// HP (see `boss_hp_for_depth`) and better loot (see `BOSS_LOOT_TABLES`).
+pub mod boss_ids {
+    pub const GOBLIN_CHIEF: u16 = 1;
+    pub const SKELETON_LORD: u16 = 2;
+    pub const LICH: u16 = 3;
+    pub const DRAGON: u16 = 4;
+}
+
+/// Floor with the entrance stairs; the only floor players can extract from
+pub const ENTRANCE_FLOOR: u8 = 0;
 
-/// Room account - one per coordinate pair per season
-/// PDA seeds: ["room", season_seed (8 bytes), x (1 byte), y (1 byte)]
+/// Room account - one per floor and coordinate pair per season
+/// PDA seeds: ["room", season_seed (8 bytes), floor (1 byte), x (1 byte), y (1 byte)]
 #[account]
-#[derive(InitSpace)]
+#[derive(InitSpace, Default)]
 pub struct RoomAccount {
+    /// Dungeon floor (0 = entrance floor)
+    pub floor: u8,
+
     /// Room coordinates
     pub x: i8,
     pub y: i8,
@@ -40,7 +67,8 @@ pub struct RoomAccount {
 
     /// Wall states: [North, South, East, West]
     /// 0 = solid wall (impassable), 1 = rubble (can clear), 2 = open (passable),
-    /// 3 = locked, 4 = entrance stairs (extract at spawn room south)
+    /// 3 = locked, 4 = entrance stairs (spawn room south: extract on floor 0,
+    /// climb to the floor above otherwise)
     pub walls: [u8; 4],
 
     /// Per-door lock kind: 0 = none, 1 = skeleton lock
@@ -52,6 +80,15 @@ pub struct RoomAccount {
     /// Progress towards completion for each direction (in slots)
     pub progress: [u64; 4],
 
+    /// Sum of helper work rates per direction (percent, 100 = one bare-handed helper)
+    pub total_work_rate: [u64; 4],
+
+    /// Slot progress was last settled at for each direction
+    pub progress_slot: [u64; 4],
+
+    /// Sub-slot work carried between settlements (percent of a slot)
+    pub progress_remainder: [u64; 4],
+
     /// Slot when job started for each direction
     pub start_slot: [u64; 4],
 
@@ -64,8 +101,27 @@ pub struct RoomAccount {
     /// Whether each directional job has been completed and is in claim phase
     pub job_completed: [bool; 4],
 
-    /// Bonus allocated per helper after completion
-    pub bonus_per_helper: [u64; 4],
+    /// Bonus moved from the prize pool into escrow at completion, split by contribution
+    pub bonus_pool: [u64; 4],
+
+    /// Bonus already paid out to claimers per direction
+    pub bonus_paid: [u64; 4],
+
+    /// Sum of work_rate * joined_slot over active helpers per direction
+    pub weighted_join_slot_sum: [u64; 4],
+
+    /// Slot each directional job was completed at
+    pub completed_slot: [u64; 4],
+
+    /// Total helper contribution per direction, fixed at completion
+    pub total_contribution: [u64; 4],
+
+    /// Helpers' buff bonuses still counted in `total_work_rate`, per direction.
+    /// The bonuses themselves live in the job's `BuffSchedule`.
+    pub job_buffs_scheduled: [u8; 4],
+
+    /// Sum of bonus * expires_slot over buff bonuses that wore off, per direction
+    pub expired_buff_slot_sum: [u64; 4],
 
     /// Whether this room has a chest
     pub has_chest: bool,
@@ -73,7 +129,7 @@ pub struct RoomAccount {
     /// If true, this chest always grants SkeletonKey x1 in addition to normal chest loot.
     pub forced_key_drop: bool,
 
-    /// What is in the room center (empty/chest/boss)
+    /// What is in the room center (empty/chest/boss/stairs down)
     pub center_type: u8,
 
     /// Identifier used by Unity to pick boss prefab/variant
@@ -91,12 +147,22 @@ pub struct RoomAccount {
     /// Total DPS from current fighters
     pub boss_total_dps: u64,
 
+    /// Fighters' buff bonuses still counted in `boss_total_dps`.
+    /// The bonuses themselves live in the boss fight's `BuffSchedule`.
+    pub boss_buffs_scheduled: u8,
+
     /// Number of current fighters
     pub boss_fighter_count: u32,
 
     /// Whether boss has been defeated
     pub boss_defeated: bool,
 
+    /// Slot the boss HP reached 0; fighters' damage counts up to it
+    pub boss_defeated_slot: u64,
+
+    /// Fighter who landed the last hit (default until the boss is defeated)
+    pub boss_last_hitter: Pubkey,
+
     /// Number of players who have looted this chest (loot tracking moved to LootReceipt PDAs)
     pub looted_count: u32,
 
@@ -125,8 +191,6 @@ impl RoomAccount {
     /// Boost progress per tip (in slots)
     pub const BOOST_PROGRESS: u64 = 30; // ~12 seconds worth
 
-    /// Refund percentage when abandoning (80%)
-    pub const ABANDON_REFUND_PERCENT: u64 = 80;
     pub const BOSS_BASE_HP: u64 = 300;
 
     /// Get opposite direction
@@ -151,12 +215,272 @@ impl RoomAccount {
         }
     }
 
+    /// Adjacent room coordinates for a direction, or None for an invalid
+    /// direction or a step past the i8 range
+    pub fn checked_adjacent_coords(x: i8, y: i8, direction: u8) -> Option<(i8, i8)> {
+        match direction {
+            DIRECTION_NORTH => Some((x, y.checked_add(1)?)),
+            DIRECTION_SOUTH => Some((x, y.checked_sub(1)?)),
+            DIRECTION_EAST => Some((x.checked_add(1)?, y)),
+            DIRECTION_WEST => Some((x.checked_sub(1)?, y)),
+            _ => None,
+        }
+    }
+
+    pub fn find_address(season_seed: u64, floor: u8, x: i8, y: i8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED_PREFIX,
+                &season_seed.to_le_bytes(),
+                &[floor],
+                &[x as u8],
+                &[y as u8],
+            ],
+            &crate::ID,
+        )
+    }
+
     /// Calculate base slots based on global depth
     pub fn calculate_base_slots(depth: u32) -> u64 {
         // Base increases by 10% every 10 depth levels
         Self::BASE_SLOTS_DEPTH_0 * ((depth / 10) as u64 + 1)
     }
 
+    /// Accrue progress for a directional job up to `current_slot`.
+    /// Must run before the direction's total work rate changes.
+    /// Buff bonuses that wore off in between stop counting at their own slot,
+    /// so the job's `buff_schedule` is required while any is scheduled.
+    pub fn settle_job_progress(
+        &mut self,
+        dir_idx: usize,
+        buff_schedule: Option<&mut BuffSchedule>,
+        current_slot: u64,
+    ) -> Result<()> {
+        if self.job_completed[dir_idx] {
+            self.progress_slot[dir_idx] = current_slot;
+            return Ok(());
+        }
+        if self.job_buffs_scheduled[dir_idx] == 0 {
+            return self.accrue_progress_until(dir_idx, current_slot);
+        }
+
+        let schedule = buff_schedule.ok_or(ChainDepthError::BuffScheduleMissing)?;
+        while let Some(expired) =
+            schedule.take_expired(&mut self.job_buffs_scheduled[dir_idx], current_slot)
+        {
+            self.accrue_progress_until(dir_idx, expired.expires_slot)?;
+            self.total_work_rate[dir_idx] =
+                self.total_work_rate[dir_idx].saturating_sub(expired.bonus);
+            self.expired_buff_slot_sum[dir_idx] = expired
+                .bonus
+                .checked_mul(expired.expires_slot)
+                .and_then(|weighted| weighted.checked_add(self.expired_buff_slot_sum[dir_idx]))
+                .ok_or(ChainDepthError::Overflow)?;
+        }
+        self.accrue_progress_until(dir_idx, current_slot)
+    }
+
+    fn accrue_progress_until(&mut self, dir_idx: usize, slot: u64) -> Result<()> {
+        let elapsed_slots = slot.saturating_sub(self.progress_slot[dir_idx]);
+        self.progress_slot[dir_idx] = slot;
+        if elapsed_slots == 0 {
+            return Ok(());
+        }
+
+        let (gained, remainder) = accrue_job_progress(
+            elapsed_slots,
+            self.total_work_rate[dir_idx],
+            self.progress_remainder[dir_idx],
+        )?;
+        self.progress[dir_idx] = self.progress[dir_idx]
+            .saturating_add(gained)
+            .min(self.base_slots[dir_idx]);
+        self.progress_remainder[dir_idx] = remainder;
+        Ok(())
+    }
+
+    /// Register a helper's work rate, buff bonus and join slot for
+    /// contribution weighting. Progress must be settled up to the join slot.
+    /// A buffed helper needs the job's `buff_schedule`.
+    pub fn add_helper_work(
+        &mut self,
+        dir_idx: usize,
+        helper_stake: &HelperStake,
+        buff_schedule: Option<&mut BuffSchedule>,
+    ) -> Result<()> {
+        let work_rate = helper_stake
+            .work_rate
+            .checked_add(helper_stake.buff_bonus)
+            .ok_or(ChainDepthError::Overflow)?;
+        if helper_stake.buff_bonus > 0 {
+            buff_schedule
+                .ok_or(ChainDepthError::BuffScheduleMissing)?
+                .add_bonus(
+                    &mut self.job_buffs_scheduled[dir_idx],
+                    helper_stake.buff_bonus,
+                    helper_stake.buff_expires_slot,
+                )?;
+        }
+        self.total_work_rate[dir_idx] = self.total_work_rate[dir_idx]
+            .checked_add(work_rate)
+            .ok_or(ChainDepthError::Overflow)?;
+        self.weighted_join_slot_sum[dir_idx] = work_rate
+            .checked_mul(helper_stake.joined_slot)
+            .and_then(|weighted| weighted.checked_add(self.weighted_join_slot_sum[dir_idx]))
+            .ok_or(ChainDepthError::Overflow)?;
+        Ok(())
+    }
+
+    /// Remove a helper that leaves before completion.
+    /// Progress must be settled up to the current slot. The job's
+    /// `buff_schedule` is required while any buff bonus is scheduled.
+    pub fn remove_helper_work(
+        &mut self,
+        dir_idx: usize,
+        helper_stake: &HelperStake,
+        buff_schedule: Option<&mut BuffSchedule>,
+    ) -> Result<()> {
+        let work_rate = helper_stake
+            .work_rate
+            .saturating_add(helper_stake.buff_bonus);
+        self.total_work_rate[dir_idx] =
+            self.total_work_rate[dir_idx].saturating_sub(helper_stake.work_rate);
+        self.weighted_join_slot_sum[dir_idx] = self.weighted_join_slot_sum[dir_idx]
+            .saturating_sub(work_rate.saturating_mul(helper_stake.joined_slot));
+
+        if helper_stake.buff_bonus == 0 {
+            return Ok(());
+        }
+        let still_running = self.job_buffs_scheduled[dir_idx] > 0
+            && buff_schedule
+                .ok_or(ChainDepthError::BuffScheduleMissing)?
+                .remove_bonus(
+                    &mut self.job_buffs_scheduled[dir_idx],
+                    helper_stake.buff_bonus,
+                    helper_stake.buff_expires_slot,
+                );
+        if still_running {
+            self.total_work_rate[dir_idx] =
+                self.total_work_rate[dir_idx].saturating_sub(helper_stake.buff_bonus);
+        } else {
+            self.expired_buff_slot_sum[dir_idx] = self.expired_buff_slot_sum[dir_idx]
+                .saturating_sub(
+                    helper_stake
+                        .buff_bonus
+                        .saturating_mul(helper_stake.buff_expires_slot),
+                );
+        }
+        Ok(())
+    }
+
+    /// Mark a directional job completed and fix the contribution total.
+    /// Every helper's contribution is work_rate * (completed_slot - joined_slot + 1),
+    /// plus buff_bonus * (slots until the buff wore off or the job completed),
+    /// so the total is derived from the running sums without iterating helpers.
+    pub fn record_job_completion(&mut self, dir_idx: usize, completed_slot: u64) -> Result<()> {
+        let total_contribution = completed_slot
+            .checked_add(1)
+            .and_then(|slot| slot.checked_mul(self.total_work_rate[dir_idx]))
+            .and_then(|total| total.checked_add(self.expired_buff_slot_sum[dir_idx]))
+            .and_then(|total| total.checked_sub(self.weighted_join_slot_sum[dir_idx]))
+            .ok_or(ChainDepthError::Overflow)?;
+        self.job_completed[dir_idx] = true;
+        self.completed_slot[dir_idx] = completed_slot;
+        self.total_contribution[dir_idx] = total_contribution;
+        Ok(())
+    }
+
+    /// Bonus owed to a helper of a completed job, weighted by contributed work.
+    /// Rounding dust stays in escrow until the last claim (see `unclaimed_bonus`).
+    pub fn helper_bonus_share(&self, dir_idx: usize, helper_stake: &HelperStake) -> u64 {
+        let completed_slot = self.completed_slot[dir_idx];
+        let contribution = helper_contribution(
+            helper_stake.work_rate,
+            helper_stake.joined_slot,
+            completed_slot,
+        )
+        .saturating_add(buff_contribution(
+            helper_stake.buff_bonus,
+            helper_stake.joined_slot,
+            helper_stake.buff_expires_slot,
+            completed_slot,
+        ));
+        contribution_share(
+            self.bonus_pool[dir_idx],
+            contribution,
+            self.total_contribution[dir_idx],
+        )
+    }
+
+    /// Release a helper's stake from a job a season reset cut short and return
+    /// what escrow owes them. Completed jobs pay stake + contribution-weighted
+    /// bonus exactly like claim_job_reward; unfinished jobs refund the stake in
+    /// full. Rounding dust is left in escrow for close_stale_escrow to sweep.
+    pub fn release_stale_stake(
+        &mut self,
+        dir_idx: usize,
+        helper_stake: &HelperStake,
+    ) -> Result<u64> {
+        let bonus_amount = if self.job_completed[dir_idx] {
+            self.helper_bonus_share(dir_idx, helper_stake)
+        } else {
+            0
+        };
+        let payout_amount = helper_stake
+            .amount
+            .checked_add(bonus_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+        self.bonus_paid[dir_idx] = self.bonus_paid[dir_idx]
+            .checked_add(bonus_amount)
+            .ok_or(ChainDepthError::Overflow)?;
+
+        self.total_staked[dir_idx] = self.total_staked[dir_idx]
+            .checked_sub(helper_stake.amount)
+            .ok_or(ChainDepthError::Overflow)?;
+        self.helper_counts[dir_idx] = self.helper_counts[dir_idx]
+            .checked_sub(1)
+            .ok_or(ChainDepthError::Overflow)?;
+
+        if self.helper_counts[dir_idx] == 0 {
+            self.reset_job(dir_idx);
+        }
+        Ok(payout_amount)
+    }
+
+    /// Bonus left in escrow that has not been paid to any helper.
+    pub fn unclaimed_bonus(&self, dir_idx: usize) -> u64 {
+        self.bonus_pool[dir_idx].saturating_sub(self.bonus_paid[dir_idx])
+    }
+
+    /// Whether an unfinished job has outlived its TTL. Any helper works at least
+    /// at bare-hands speed, so the job could have finished by `start_slot + base_slots`;
+    /// the TTL is a grace period on top of that.
+    pub fn is_job_expired(&self, dir_idx: usize, current_slot: u64, ttl_slots: u64) -> bool {
+        self.helper_counts[dir_idx] > 0
+            && !self.job_completed[dir_idx]
+            && current_slot
+                >= self.start_slot[dir_idx]
+                    .saturating_add(self.base_slots[dir_idx])
+                    .saturating_add(ttl_slots)
+    }
+
+    /// Clear all per-direction job state once the last helper has left.
+    pub fn reset_job(&mut self, dir_idx: usize) {
+        self.progress[dir_idx] = 0;
+        self.total_work_rate[dir_idx] = 0;
+        self.progress_slot[dir_idx] = 0;
+        self.progress_remainder[dir_idx] = 0;
+        self.start_slot[dir_idx] = 0;
+        self.job_completed[dir_idx] = false;
+        self.bonus_pool[dir_idx] = 0;
+        self.bonus_paid[dir_idx] = 0;
+        self.weighted_join_slot_sum[dir_idx] = 0;
+        self.completed_slot[dir_idx] = 0;
+        self.total_contribution[dir_idx] = 0;
+        self.job_buffs_scheduled[dir_idx] = 0;
+        self.expired_buff_slot_sum[dir_idx] = 0;
+    }
+
     /// Check if a direction is valid (0-3)
     pub fn is_valid_direction(direction: u8) -> bool {
         direction <= DIRECTION_WEST
@@ -177,7 +501,10 @@ impl RoomAccount {
     }
 
     pub fn is_valid_center_type(center_type: u8) -> bool {
-        center_type == CENTER_EMPTY || center_type == CENTER_CHEST || center_type == CENTER_BOSS
+        matches!(
+            center_type,
+            CENTER_EMPTY | CENTER_CHEST | CENTER_BOSS | CENTER_STAIRS_DOWN
+        )
     }
 
     pub fn boss_hp_for_depth(depth: u32, boss_id: u16) -> u64 {
@@ -189,26 +516,26 @@ impl RoomAccount {
         hp.min(MAX_BOSS_HP)
     }
 
-    pub fn generate_start_walls(_season_seed: u64, x: i8, y: i8) -> [u8; 4] {
+    pub fn generate_start_walls(_season_seed: u64, layout: &SeasonLayout) -> [u8; 4] {
         let mut walls = [WALL_OPEN; 4];
         walls[DIRECTION_SOUTH as usize] = WALL_ENTRANCE_STAIRS;
-        Self::clamp_boundary_walls(&mut walls, x, y);
+        Self::clamp_boundary_walls(&mut walls, layout, layout.start_x, layout.start_y);
         walls
     }
 
     /// Force walls that face outside the grid boundary to Solid so
     /// players never see interactable doors that lead nowhere.
-    pub fn clamp_boundary_walls(walls: &mut [u8; 4], x: i8, y: i8) {
-        if y >= GlobalAccount::MAX_COORD {
+    pub fn clamp_boundary_walls(walls: &mut [u8; 4], layout: &SeasonLayout, x: i8, y: i8) {
+        if y >= layout.max_y() {
             walls[DIRECTION_NORTH as usize] = WALL_SOLID;
         }
-        if y <= GlobalAccount::MIN_COORD {
+        if y <= SeasonLayout::MIN_COORD {
             walls[DIRECTION_SOUTH as usize] = WALL_SOLID;
         }
-        if x >= GlobalAccount::MAX_COORD {
+        if x >= layout.max_x() {
             walls[DIRECTION_EAST as usize] = WALL_SOLID;
         }
-        if x <= GlobalAccount::MIN_COORD {
+        if x <= SeasonLayout::MIN_COORD {
             walls[DIRECTION_WEST as usize] = WALL_SOLID;
         }
     }
@@ -1,19 +1,79 @@
 use anchor_lang::prelude::*;
 
 use super::{
-    GlobalAccount, RoomAccount, CENTER_BOSS, CENTER_CHEST, CENTER_EMPTY, DIRECTION_NORTH,
-    DIRECTION_WEST, LOCK_KIND_NONE, LOCK_KIND_SKELETON, WALL_LOCKED, WALL_OPEN, WALL_RUBBLE,
-    WALL_SOLID,
+    RoomAccount, SeasonLayout, CENTER_BOSS, CENTER_CHEST, CENTER_EMPTY, CENTER_STAIRS_DOWN,
+    DIRECTION_EAST, DIRECTION_NORTH, DIRECTION_SOUTH, DIRECTION_WEST, ENTRANCE_FLOOR,
+    LOCK_KIND_BOSS_SEAL, LOCK_KIND_EMERALD, LOCK_KIND_NONE, LOCK_KIND_RUBY, LOCK_KIND_SAPPHIRE,
+    LOCK_KIND_SKELETON, WALL_LOCKED, WALL_OPEN, WALL_RUBBLE, WALL_SOLID,
 };
 
+/// How a season lays out room walls. Stored on `GlobalAccount` and fixed for the season.
+pub mod generation_modes {
+    /// Every wall is rolled independently from the room hash
+    pub const INDEPENDENT: u8 = 0;
+    /// Independent rolls plus a seed-derived spanning tree rooted at the start
+    /// room whose edges are never solid, so every room stays reachable
+    pub const CONNECTED: u8 = 1;
+
+    pub fn is_valid(generation_mode: u8) -> bool {
+        generation_mode <= CONNECTED
+    }
+}
+
 const LOCK_MIN_DEPTH: u32 = 2;
-const MAX_LOCKED_DOORS_PER_ROOM: usize = 1;
+const GEM_LOCK_MIN_DEPTH: u32 = 4;
+const GEM_LOCK_PERCENT: u64 = 40;
+const BOSS_SEAL_MIN_DEPTH: u32 = 6;
+const BOSS_SEAL_PERCENT: u64 = 20;
+const GEM_LOCK_KINDS: [u8; 3] = [LOCK_KIND_RUBY, LOCK_KIND_SAPPHIRE, LOCK_KIND_EMERALD];
 const FORCED_KEY_CHEST_MIN_DEPTH: u32 = 2;
+const BOSS_PERCENT: u64 = 50;
+const BOSS_PERCENT_PER_FLOOR: u64 = 10;
+const MAX_BOSS_PERCENT: u64 = 80;
+
+/// Generation parameters for one floor. The entrance floor keeps the original
+/// tuning; each floor below rolls from its own seed with more bosses and one
+/// more locked door per room than the season's `max_locked_doors`. Depth-scaled values (boss HP, job length) grow with
+/// `base_depth` on top of that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloorParams {
+    pub season_seed: u64,
+
+    pub floor: u8,
+
+    /// Seed all room hashes on this floor derive from
+    pub seed: u64,
+
+    /// Depth of the floor's start room
+    pub base_depth: u32,
+
+    /// Chance (percent) that a room past depth 1 holds a boss
+    pub boss_percent: u64,
 
-pub fn calculate_depth(x: i8, y: i8) -> u32 {
-    let dx = (x - GlobalAccount::START_X).abs() as u32;
-    let dy = (y - GlobalAccount::START_Y).abs() as u32;
-    dx.max(dy)
+    pub max_locked_doors: usize,
+}
+
+impl FloorParams {
+    pub fn new(season_seed: u64, layout: &SeasonLayout, floor: u8) -> Self {
+        let is_entrance_floor = floor == ENTRANCE_FLOOR;
+        Self {
+            season_seed,
+            floor,
+            seed: season_seed.wrapping_add(u64::from(floor).wrapping_mul(0x9E37_79B9_7F4A_7C15)),
+            base_depth: layout.floor_depth(floor),
+            boss_percent: (BOSS_PERCENT + BOSS_PERCENT_PER_FLOOR * u64::from(floor))
+                .min(MAX_BOSS_PERCENT),
+            max_locked_doors: if is_entrance_floor {
+                usize::from(layout.max_locked_doors)
+            } else {
+                usize::from((layout.max_locked_doors + 1).min(SeasonLayout::MAX_LOCKED_DOORS))
+            },
+        }
+    }
+}
+
+pub fn calculate_depth(layout: &SeasonLayout, floor: u8, x: i8, y: i8) -> u32 {
+    layout.depth(floor, x, y)
 }
 
 pub fn generate_room_hash(seed: u64, x: i8, y: i8) -> u64 {
@@ -45,22 +105,120 @@ pub fn generate_walls(hash: u64, entrance_direction: u8) -> [u8; 4] {
     walls
 }
 
-pub fn generate_room_center(season_seed: u64, room_x: i8, room_y: i8, depth: u32) -> (u8, u16, bool) {
-    let room_hash = generate_room_hash(season_seed, room_x, room_y);
-    let forced_key_drop = is_forced_key_chest(season_seed, room_x, room_y, depth);
+/// Parent of a room in the season's spanning tree (None for the start room).
+/// Each room hangs off a neighbour one step closer to the start room, so
+/// following parents from any room ends at spawn. The choice only depends on
+/// the seed and the room's own coordinates, so it can be evaluated lazily
+/// whenever a room is discovered.
+pub fn spanning_parent(season_seed: u64, layout: &SeasonLayout, x: i8, y: i8) -> Option<(i8, i8)> {
+    let start = layout.start();
+    if (x, y) == start {
+        return None;
+    }
+
+    let pick_first = (generate_room_hash(season_seed, x, y) >> 40) & 1 == 0;
+
+    // The start room's south wall is the entrance stairs, so the room below it
+    // hangs off a side neighbour instead, and nothing else routes through it
+    // unless it is the only step towards spawn.
+    let below_start = (start.0, start.1 - 1);
+    if (x, y) == below_start {
+        let west = (x - 1, y);
+        let east = (x + 1, y);
+        let use_west =
+            layout.contains(west.0, west.1) && (pick_first || !layout.contains(east.0, east.1));
+        return Some(if use_west { west } else { east });
+    }
+
+    let step_x = (x != start.0).then(|| (x - (x - start.0).signum(), y));
+    let step_y = (y != start.1).then(|| (x, y - (y - start.1).signum()));
+    match (step_x, step_y) {
+        (Some(along_x), Some(along_y)) if along_x == below_start => Some(along_y),
+        (Some(along_x), Some(along_y)) if along_y == below_start => Some(along_x),
+        (Some(along_x), Some(along_y)) => Some(if pick_first { along_x } else { along_y }),
+        (along_x, along_y) => along_x.or(along_y),
+    }
+}
+
+/// Open up (as rubble) any solid wall leading to one of this room's
+/// spanning-tree children.
+fn connect_spanning_children(
+    walls: &mut [u8; 4],
+    season_seed: u64,
+    layout: &SeasonLayout,
+    room_x: i8,
+    room_y: i8,
+) {
+    for direction in 0..=DIRECTION_WEST {
+        let (child_x, child_y) = RoomAccount::adjacent_coords(room_x, room_y, direction);
+        if !layout.contains(child_x, child_y) || walls[direction as usize] != WALL_SOLID {
+            continue;
+        }
+        if spanning_parent(season_seed, layout, child_x, child_y) == Some((room_x, room_y)) {
+            walls[direction as usize] = WALL_RUBBLE;
+        }
+    }
+}
+
+/// Walls and lock kinds of a newly discovered room entered from `entrance_direction`.
+pub fn generate_room_walls(
+    params: &FloorParams,
+    layout: &SeasonLayout,
+    room_x: i8,
+    room_y: i8,
+    entrance_direction: u8,
+) -> ([u8; 4], [u8; 4]) {
+    let mut walls = generate_walls(
+        generate_room_hash(params.seed, room_x, room_y),
+        entrance_direction,
+    );
+    if layout.generation_mode == generation_modes::CONNECTED {
+        connect_spanning_children(&mut walls, params.seed, layout, room_x, room_y);
+    }
+    RoomAccount::clamp_boundary_walls(&mut walls, layout, room_x, room_y);
+
+    let mut door_lock_kinds = [LOCK_KIND_NONE; 4];
+    apply_locked_doors(
+        &mut walls,
+        &mut door_lock_kinds,
+        params,
+        layout,
+        room_x,
+        room_y,
+        entrance_direction,
+    );
+    (walls, door_lock_kinds)
+}
+
+pub fn generate_room_center(
+    params: &FloorParams,
+    layout: &SeasonLayout,
+    room_x: i8,
+    room_y: i8,
+) -> (u8, u16, bool) {
+    let room_hash = generate_room_hash(params.seed, room_x, room_y);
+    let ring = layout.ring(room_x, room_y);
+    let depth = params.base_depth + ring;
+
+    if select_stairs_down_coords(params, layout) == Some((room_x, room_y)) {
+        return (CENTER_STAIRS_DOWN, 0, false);
+    }
 
     if depth == 1 {
-        if is_forced_depth_one_chest(season_seed, room_x, room_y) || (room_hash % 100) < 50 {
+        if is_forced_depth_one_chest(params.seed, layout, room_x, room_y) || (room_hash % 100) < 50
+        {
             return (CENTER_CHEST, 1, false);
         }
         return (CENTER_EMPTY, 0, false);
     }
 
-    if forced_key_drop {
+    // Every floor gets a guaranteed key chest per ring, placed by ring rather
+    // than depth so lower floors mirror the entrance floor.
+    if is_forced_key_chest(params.seed, layout, room_x, room_y, ring) {
         return (CENTER_CHEST, 1, true);
     }
 
-    if depth >= 2 && (room_hash % 100) < 50 {
+    if depth >= 2 && rolls_boss(params, room_x, room_y) {
         let boss_id = ((room_hash % 4) + 1) as u16;
         return (CENTER_BOSS, boss_id, false);
     }
@@ -68,45 +226,149 @@ pub fn generate_room_center(season_seed: u64, room_x: i8, room_y: i8, depth: u32
     (CENTER_EMPTY, 0, false)
 }
 
-pub fn initialize_discovered_room(
+fn rolls_boss(params: &FloorParams, room_x: i8, room_y: i8) -> bool {
+    (generate_room_hash(params.seed, room_x, room_y) % 100) < params.boss_percent
+}
+
+/// Whether any room on `ring` (ring 2 or deeper) holds a boss, i.e. whether a
+/// boss seal there can ever be broken. Mirrors `generate_room_center` without
+/// re-deriving the special rooms for every cell.
+fn ring_has_boss(params: &FloorParams, layout: &SeasonLayout, ring: u32) -> bool {
+    let stairs_down = select_stairs_down_coords(params, layout);
+    let key_chest = select_forced_key_chest_coords(params.seed, layout, ring);
+    ring_coords(layout, ring).any(|coords| {
+        Some(coords) != stairs_down
+            && Some(coords) != key_chest
+            && rolls_boss(params, coords.0, coords.1)
+    })
+}
+
+/// Whether the door on `direction` of room (x, y) is a spanning-tree edge.
+/// Boss seals stay off those edges, so every room (and with it every ring's
+/// boss) can be reached without breaking a seal first.
+fn is_spanning_edge(season_seed: u64, layout: &SeasonLayout, x: i8, y: i8, direction: u8) -> bool {
+    let (next_x, next_y) = RoomAccount::adjacent_coords(x, y, direction);
+    layout.contains(next_x, next_y)
+        && (spanning_parent(season_seed, layout, next_x, next_y) == Some((x, y))
+            || spanning_parent(season_seed, layout, x, y) == Some((next_x, next_y)))
+}
+
+/// Lock kind for a door rolled at `depth`. Deeper doors may take a gem
+/// instead of a skeleton key, and the deepest may be sealed until a boss in
+/// the ring falls.
+fn roll_lock_kind(depth: u32, lock_hash: u64, seal_allowed: bool) -> u8 {
+    let roll = lock_hash % 100;
+    if depth >= BOSS_SEAL_MIN_DEPTH && seal_allowed && roll < BOSS_SEAL_PERCENT {
+        return LOCK_KIND_BOSS_SEAL;
+    }
+    if depth >= GEM_LOCK_MIN_DEPTH && roll >= 100 - GEM_LOCK_PERCENT {
+        return GEM_LOCK_KINDS[((lock_hash / 100) % GEM_LOCK_KINDS.len() as u64) as usize];
+    }
+    LOCK_KIND_SKELETON
+}
+
+/// Set up the start room of `floor`: open on every in-bounds side, with the
+/// entrance stairs (extraction on floor 0, the way up below it) to the south.
+pub fn initialize_start_room(
     room: &mut RoomAccount,
     season_seed: u64,
-    room_x: i8,
-    room_y: i8,
-    entrance_direction: u8,
+    layout: &SeasonLayout,
+    floor: u8,
+    created_by: Pubkey,
+    created_slot: u64,
+    bump: u8,
+) {
+    room.floor = floor;
+    room.x = layout.start_x;
+    room.y = layout.start_y;
+    room.season_seed = season_seed;
+    room.walls = RoomAccount::generate_start_walls(season_seed, layout);
+    room.door_lock_kinds = [LOCK_KIND_NONE; 4];
+    room.helper_counts = [0; 4];
+    room.progress = [0; 4];
+    room.total_work_rate = [0; 4];
+    room.progress_slot = [0; 4];
+    room.progress_remainder = [0; 4];
+    room.start_slot = [0; 4];
+    room.base_slots = [RoomAccount::calculate_base_slots(layout.floor_depth(floor)); 4];
+    room.total_staked = [0; 4];
+    room.job_completed = [false; 4];
+    room.bonus_pool = [0; 4];
+    room.bonus_paid = [0; 4];
+    room.weighted_join_slot_sum = [0; 4];
+    room.completed_slot = [0; 4];
+    room.total_contribution = [0; 4];
+    room.job_buffs_scheduled = [0; 4];
+    room.expired_buff_slot_sum = [0; 4];
+    room.has_chest = false;
+    room.forced_key_drop = false;
+    room.center_type = CENTER_EMPTY;
+    room.center_id = 0;
+    room.boss_max_hp = 0;
+    room.boss_current_hp = 0;
+    room.boss_last_update_slot = created_slot;
+    room.boss_total_dps = 0;
+    room.boss_buffs_scheduled = 0;
+    room.boss_fighter_count = 0;
+    room.boss_defeated = false;
+    room.boss_defeated_slot = 0;
+    room.boss_last_hitter = Pubkey::default();
+    room.looted_count = 0;
+    room.created_by = created_by;
+    room.created_slot = created_slot;
+    room.bump = bump;
+}
+
+/// Coordinates of a room on its floor and the side it is entered from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoomEntrance {
+    pub x: i8,
+    pub y: i8,
+    pub direction: u8,
+}
+
+pub fn initialize_discovered_room(
+    room: &mut RoomAccount,
+    params: &FloorParams,
+    layout: &SeasonLayout,
+    entrance: RoomEntrance,
     created_by: Pubkey,
     created_slot: u64,
     bump: u8,
 ) {
-    let room_depth = calculate_depth(room_x, room_y);
-    let room_hash = generate_room_hash(season_seed, room_x, room_y);
+    let RoomEntrance {
+        x: room_x,
+        y: room_y,
+        direction: entrance_direction,
+    } = entrance;
+    let room_depth = layout.depth(params.floor, room_x, room_y);
 
+    room.floor = params.floor;
     room.x = room_x;
     room.y = room_y;
-    room.season_seed = season_seed;
-    room.walls = generate_walls(room_hash, entrance_direction);
-    RoomAccount::clamp_boundary_walls(&mut room.walls, room_x, room_y);
-    room.door_lock_kinds = [LOCK_KIND_NONE; 4];
-    apply_locked_doors(
-        &mut room.walls,
-        &mut room.door_lock_kinds,
-        season_seed,
-        room_x,
-        room_y,
-        room_depth,
-        entrance_direction,
-    );
-    enforce_special_room_topology(room);
+    room.season_seed = params.season_seed;
+    (room.walls, room.door_lock_kinds) =
+        generate_room_walls(params, layout, room_x, room_y, entrance_direction);
+    enforce_special_room_topology(room, layout);
     room.helper_counts = [0; 4];
     room.progress = [0; 4];
+    room.total_work_rate = [0; 4];
+    room.progress_slot = [0; 4];
+    room.progress_remainder = [0; 4];
     room.start_slot = [0; 4];
     room.base_slots = [RoomAccount::calculate_base_slots(room_depth); 4];
     room.total_staked = [0; 4];
     room.job_completed = [false; 4];
-    room.bonus_per_helper = [0; 4];
+    room.bonus_pool = [0; 4];
+    room.bonus_paid = [0; 4];
+    room.weighted_join_slot_sum = [0; 4];
+    room.completed_slot = [0; 4];
+    room.total_contribution = [0; 4];
+    room.job_buffs_scheduled = [0; 4];
+    room.expired_buff_slot_sum = [0; 4];
 
     let (center_type, center_id, forced_key_drop) =
-        generate_room_center(season_seed, room_x, room_y, room_depth);
+        generate_room_center(params, layout, room_x, room_y);
     let boss_max_hp = if center_type == CENTER_BOSS {
         RoomAccount::boss_hp_for_depth(room_depth, center_id)
     } else {
@@ -121,18 +383,75 @@ pub fn initialize_discovered_room(
     room.boss_current_hp = boss_max_hp;
     room.boss_last_update_slot = created_slot;
     room.boss_total_dps = 0;
+    room.boss_buffs_scheduled = 0;
     room.boss_fighter_count = 0;
     room.boss_defeated = false;
+    room.boss_defeated_slot = 0;
+    room.boss_last_hitter = Pubkey::default();
     room.looted_count = 0;
     room.created_by = created_by;
     room.created_slot = created_slot;
     room.bump = bump;
 }
 
-pub fn enforce_special_room_topology(room: &mut RoomAccount) {
+/// Contents of a room that has not been discovered yet, as the Mystic Orb
+/// reveals them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoomScoutReport {
+    pub floor: u8,
+    pub x: i8,
+    pub y: i8,
+    pub walls: [u8; 4],
+    pub door_lock_kinds: [u8; 4],
+    pub center_type: u8,
+    pub center_id: u16,
+    pub boss_max_hp: u64,
+    pub forced_key_drop: bool,
+}
+
+/// Generate a room without creating its account. Walls depend on the side
+/// the room is opened from, so the report holds when it is entered through
+/// `entrance_direction`.
+pub fn scout_room(
+    season_seed: u64,
+    layout: &SeasonLayout,
+    floor: u8,
+    room_x: i8,
+    room_y: i8,
+    entrance_direction: u8,
+) -> RoomScoutReport {
+    let mut room = Box::<RoomAccount>::default();
+    initialize_discovered_room(
+        &mut room,
+        &FloorParams::new(season_seed, layout, floor),
+        layout,
+        RoomEntrance {
+            x: room_x,
+            y: room_y,
+            direction: entrance_direction,
+        },
+        Pubkey::default(),
+        0,
+        0,
+    );
+
+    RoomScoutReport {
+        floor,
+        x: room_x,
+        y: room_y,
+        walls: room.walls,
+        door_lock_kinds: room.door_lock_kinds,
+        center_type: room.center_type,
+        center_id: room.center_id,
+        boss_max_hp: room.boss_max_hp,
+        forced_key_drop: room.forced_key_drop,
+    }
+}
+
+pub fn enforce_special_room_topology(room: &mut RoomAccount, layout: &SeasonLayout) {
     // Reserve start-room south edge for entrance stairs/extraction only:
-    // room (5,4) north wall must never be passable or lockable.
-    if room.x == GlobalAccount::START_X && room.y == GlobalAccount::START_Y - 1 {
+    // the north wall of the room below spawn must never be passable or lockable.
+    if room.x == layout.start_x && room.y == layout.start_y - 1 {
         room.walls[DIRECTION_NORTH as usize] = WALL_SOLID;
         room.door_lock_kinds[DIRECTION_NORTH as usize] = LOCK_KIND_NONE;
     }
@@ -141,13 +460,14 @@ pub fn enforce_special_room_topology(room: &mut RoomAccount) {
 fn apply_locked_doors(
     walls: &mut [u8; 4],
     door_lock_kinds: &mut [u8; 4],
-    season_seed: u64,
+    params: &FloorParams,
+    layout: &SeasonLayout,
     room_x: i8,
     room_y: i8,
-    room_depth: u32,
     entrance_direction: u8,
 ) {
-    if room_depth < LOCK_MIN_DEPTH {
+    let room_ring = layout.ring(room_x, room_y);
+    if room_ring < LOCK_MIN_DEPTH {
         return;
     }
 
@@ -165,8 +485,10 @@ fn apply_locked_doors(
         return;
     }
 
-    let room_hash = generate_room_hash(season_seed, room_x, room_y);
-    let lock_limit = MAX_LOCKED_DOORS_PER_ROOM.min(eligible_directions.len());
+    let room_hash = generate_room_hash(params.seed, room_x, room_y);
+    let room_depth = params.base_depth + room_ring;
+    let mut boss_in_ring = None;
+    let lock_limit = params.max_locked_doors.min(eligible_directions.len());
     let mut locked_count = 0usize;
 
     for lock_attempt in 0..lock_limit {
@@ -186,69 +508,348 @@ fn apply_locked_doors(
             continue;
         }
 
+        // The room hash is a weak mix of the coordinates, so spread it before
+        // reading low bits for the kind.
+        let lock_hash =
+            (room_hash ^ u64::from(lock_direction)).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+        let seal_allowed = room_depth >= BOSS_SEAL_MIN_DEPTH
+            && !is_spanning_edge(params.seed, layout, room_x, room_y, lock_direction)
+            && *boss_in_ring.get_or_insert_with(|| ring_has_boss(params, layout, room_ring));
+
         walls[lock_direction as usize] = WALL_LOCKED;
-        door_lock_kinds[lock_direction as usize] = LOCK_KIND_SKELETON;
+        door_lock_kinds[lock_direction as usize] =
+            roll_lock_kind(room_depth, lock_hash, seal_allowed);
         locked_count += 1;
-        if locked_count >= MAX_LOCKED_DOORS_PER_ROOM {
+        if locked_count >= params.max_locked_doors {
             break;
         }
     }
 }
 
-fn is_forced_depth_one_chest(season_seed: u64, room_x: i8, room_y: i8) -> bool {
+fn is_forced_depth_one_chest(
+    season_seed: u64,
+    layout: &SeasonLayout,
+    room_x: i8,
+    room_y: i8,
+) -> bool {
     let forced_direction = (season_seed % 4) as u8;
-    let expected = match forced_direction {
-        0 => (GlobalAccount::START_X, GlobalAccount::START_Y + 1),
-        1 => (GlobalAccount::START_X, GlobalAccount::START_Y - 1),
-        2 => (GlobalAccount::START_X + 1, GlobalAccount::START_Y),
-        _ => (GlobalAccount::START_X - 1, GlobalAccount::START_Y),
-    };
+    let expected = RoomAccount::adjacent_coords(layout.start_x, layout.start_y, forced_direction);
     room_x == expected.0 && room_y == expected.1
 }
 
-fn is_forced_key_chest(season_seed: u64, room_x: i8, room_y: i8, depth: u32) -> bool {
-    if depth < FORCED_KEY_CHEST_MIN_DEPTH {
+fn is_forced_key_chest(
+    season_seed: u64,
+    layout: &SeasonLayout,
+    room_x: i8,
+    room_y: i8,
+    ring: u32,
+) -> bool {
+    if ring < FORCED_KEY_CHEST_MIN_DEPTH {
         return false;
     }
 
-    if let Some((forced_x, forced_y)) = select_forced_key_chest_coords(season_seed, depth) {
+    if let Some((forced_x, forced_y)) = select_forced_key_chest_coords(season_seed, layout, ring) {
         return room_x == forced_x && room_y == forced_y;
     }
 
     false
 }
 
-fn select_forced_key_chest_coords(season_seed: u64, depth: u32) -> Option<(i8, i8)> {
-    let target_depth = depth as i8;
-    let mut ring_coords = Vec::<(i8, i8)>::new();
+/// The forced key chest of `ring` on the floor, if the ring is deep enough to have one.
+pub fn forced_key_chest_coords(
+    params: &FloorParams,
+    layout: &SeasonLayout,
+    ring: u32,
+) -> Option<(i8, i8)> {
+    if ring < FORCED_KEY_CHEST_MIN_DEPTH {
+        return None;
+    }
+    select_forced_key_chest_coords(params.seed, layout, ring)
+}
 
-    for x in GlobalAccount::MIN_COORD..=GlobalAccount::MAX_COORD {
-        for y in GlobalAccount::MIN_COORD..=GlobalAccount::MAX_COORD {
-            let candidate_depth = calculate_depth(x, y) as i8;
-            if candidate_depth == target_depth {
-                ring_coords.push((x, y));
-            }
-        }
+/// Where the Rusted Compass points: the key chest of the holder's ring
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChestBearing {
+    pub ring: u32,
+    /// One of the `DIRECTION_*` values
+    pub direction: u8,
+}
+
+/// Compass direction from one room toward another along the axis with the
+/// longer distance; north/south wins ties. None when both are the same room.
+pub fn bearing_direction(from: (i8, i8), to: (i8, i8)) -> Option<u8> {
+    let dx = i16::from(to.0) - i16::from(from.0);
+    let dy = i16::from(to.1) - i16::from(from.1);
+    if dx == 0 && dy == 0 {
+        return None;
     }
+    Some(if dy.abs() >= dx.abs() {
+        if dy > 0 {
+            DIRECTION_NORTH
+        } else {
+            DIRECTION_SOUTH
+        }
+    } else if dx > 0 {
+        DIRECTION_EAST
+    } else {
+        DIRECTION_WEST
+    })
+}
 
-    if ring_coords.is_empty() {
+/// The room on `ring` whose chest always drops a skeleton key.
+pub fn select_forced_key_chest_coords(
+    season_seed: u64,
+    layout: &SeasonLayout,
+    ring: u32,
+) -> Option<(i8, i8)> {
+    let ring_size = ring_coords(layout, ring).count();
+    if ring_size == 0 {
         return None;
     }
 
     let ring_hash = season_seed
         .wrapping_mul(53)
-        .wrapping_add(depth as u64)
+        .wrapping_add(ring as u64)
         .wrapping_mul(97);
-    let selected_index = (ring_hash as usize) % ring_coords.len();
-    Some(ring_coords[selected_index])
+    let selected_index = (ring_hash as usize) % ring_size;
+    ring_coords(layout, ring).nth(selected_index)
+}
+
+/// The floor's stairs-down room: a seed-picked room on the grid edge that is
+/// neither the start room nor one of the forced chests. None on the deepest floor.
+pub fn select_stairs_down_coords(params: &FloorParams, layout: &SeasonLayout) -> Option<(i8, i8)> {
+    if !layout.has_floor_below(params.floor) {
+        return None;
+    }
+
+    let edge_size = edge_coords(layout).count();
+    let edge_hash = params
+        .seed
+        .wrapping_mul(71)
+        .wrapping_add(u64::from(params.floor))
+        .wrapping_mul(113);
+    let first_index = (edge_hash as usize) % edge_size;
+    edge_coords(layout)
+        .cycle()
+        .skip(first_index)
+        .take(edge_size)
+        .find(|&(x, y)| {
+            !layout.is_start(x, y)
+                && !is_forced_depth_one_chest(params.seed, layout, x, y)
+                && !is_forced_key_chest(params.seed, layout, x, y, layout.ring(x, y))
+        })
+}
+
+/// Rooms on the outer edge of the grid, ordered by x then y.
+fn edge_coords(layout: &SeasonLayout) -> impl Iterator<Item = (i8, i8)> + Clone + '_ {
+    let (max_x, max_y) = (layout.max_x(), layout.max_y());
+    (SeasonLayout::MIN_COORD..=max_x).flat_map(move |x| {
+        let y_step = if x == SeasonLayout::MIN_COORD || x == max_x {
+            1
+        } else {
+            max_y as usize
+        };
+        (SeasonLayout::MIN_COORD..=max_y)
+            .step_by(y_step)
+            .map(move |y| (x, y))
+    })
+}
+
+/// In-bounds rooms at exactly `ring` from the start room, ordered by x then y.
+/// Walks only the ring's perimeter so large grids stay cheap.
+fn ring_coords(layout: &SeasonLayout, ring: u32) -> impl Iterator<Item = (i8, i8)> + '_ {
+    let ring = i32::try_from(ring).unwrap_or(i32::MAX / 4);
+    let (start_x, start_y) = (i32::from(layout.start_x), i32::from(layout.start_y));
+    let (min_x, max_x) = (start_x - ring, start_x + ring);
+    let (min_y, max_y) = (start_y - ring, start_y + ring);
+
+    (min_x..=max_x).flat_map(move |x| {
+        // Edge columns cover the whole side; inner columns only the top and bottom cells.
+        let y_step = if x == min_x || x == max_x {
+            1
+        } else {
+            (max_y - min_y) as usize
+        };
+        (min_y..=max_y).step_by(y_step.max(1)).filter_map(move |y| {
+            let (x, y) = (i8::try_from(x).ok()?, i8::try_from(y).ok()?);
+            layout.contains(x, y).then_some((x, y))
+        })
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::WALL_ENTRANCE_STAIRS;
+
+    fn empty_room() -> RoomAccount {
+        RoomAccount {
+            floor: 0,
+            x: 0,
+            y: 0,
+            season_seed: 0,
+            walls: [WALL_SOLID; 4],
+            door_lock_kinds: [LOCK_KIND_NONE; 4],
+            helper_counts: [0; 4],
+            progress: [0; 4],
+            total_work_rate: [0; 4],
+            progress_slot: [0; 4],
+            progress_remainder: [0; 4],
+            start_slot: [0; 4],
+            base_slots: [0; 4],
+            total_staked: [0; 4],
+            job_completed: [false; 4],
+            bonus_pool: [0; 4],
+            bonus_paid: [0; 4],
+            weighted_join_slot_sum: [0; 4],
+            completed_slot: [0; 4],
+            total_contribution: [0; 4],
+            job_buffs_scheduled: [0; 4],
+            expired_buff_slot_sum: [0; 4],
+            has_chest: false,
+            forced_key_drop: false,
+            center_type: CENTER_EMPTY,
+            center_id: 0,
+            boss_max_hp: 0,
+            boss_current_hp: 0,
+            boss_last_update_slot: 0,
+            boss_total_dps: 0,
+            boss_buffs_scheduled: 0,
+            boss_fighter_count: 0,
+            boss_defeated: false,
+            boss_defeated_slot: 0,
+            boss_last_hitter: Pubkey::default(),
+            looted_count: 0,
+            created_by: Pubkey::default(),
+            created_slot: 0,
+            bump: 0,
+        }
+    }
+
+    /// A lopsided grid with spawn near a corner
+    fn small_layout() -> SeasonLayout {
+        SeasonLayout {
+            width: 7,
+            height: 15,
+            start_x: 1,
+            start_y: 3,
+            generation_mode: generation_modes::CONNECTED,
+            floor_count: 3,
+            max_locked_doors: 1,
+        }
+    }
+
+    /// Rooms reachable from a floor's start room when players explore every
+    /// passable wall (rubble can be cleared and keyed doors opened),
+    /// discovering rooms the same way move_player does. A boss seal only opens
+    /// once a boss on the sealed room's ring has been reached.
+    fn explore_floor(season_seed: u64, layout: &SeasonLayout, floor: u8) -> Vec<RoomAccount> {
+        let width = usize::from(layout.width);
+        let index = |x: i8, y: i8| y as usize * width + x as usize;
+
+        let mut rooms = vec![None::<RoomAccount>; width * usize::from(layout.height)];
+        let mut start_room = empty_room();
+        initialize_start_room(
+            &mut start_room,
+            season_seed,
+            layout,
+            floor,
+            Pubkey::default(),
+            0,
+            0,
+        );
+        rooms[index(layout.start_x, layout.start_y)] = Some(start_room);
+        let params = FloorParams::new(season_seed, layout, floor);
+        let mut queue = std::collections::VecDeque::from([layout.start()]);
+        let mut boss_rings = std::collections::BTreeSet::new();
+        let mut sealed_rooms = Vec::new();
+
+        loop {
+            let Some((x, y)) = queue.pop_front() else {
+                // Revisit sealed rooms whose ring has a boss by now.
+                let (opened, still_sealed): (Vec<_>, Vec<_>) = sealed_rooms
+                    .into_iter()
+                    .partition(|(x, y)| boss_rings.contains(&layout.ring(*x, *y)));
+                if opened.is_empty() {
+                    break;
+                }
+                queue.extend(opened);
+                sealed_rooms = still_sealed;
+                continue;
+            };
+            let room = rooms[index(x, y)].as_ref().unwrap();
+            let (room_walls, room_lock_kinds) = (room.walls, room.door_lock_kinds);
+            for direction in 0..=DIRECTION_WEST {
+                let wall = room_walls[direction as usize];
+                if wall == WALL_SOLID || wall == WALL_ENTRANCE_STAIRS {
+                    continue;
+                }
+                let (next_x, next_y) = RoomAccount::adjacent_coords(x, y, direction);
+                if rooms[index(next_x, next_y)].is_some() {
+                    continue;
+                }
+                if wall == WALL_LOCKED
+                    && room_lock_kinds[direction as usize] == LOCK_KIND_BOSS_SEAL
+                    && !boss_rings.contains(&layout.ring(x, y))
+                {
+                    sealed_rooms.push((x, y));
+                    continue;
+                }
+
+                let entrance = RoomAccount::opposite_direction(direction);
+                let mut room = empty_room();
+                initialize_discovered_room(
+                    &mut room,
+                    &params,
+                    layout,
+                    RoomEntrance {
+                        x: next_x,
+                        y: next_y,
+                        direction: entrance,
+                    },
+                    Pubkey::default(),
+                    0,
+                    0,
+                );
+                room.walls[entrance as usize] = WALL_OPEN;
+                enforce_special_room_topology(&mut room, layout);
+
+                if room.center_type == CENTER_BOSS {
+                    boss_rings.insert(layout.ring(next_x, next_y));
+                }
+                rooms[index(next_x, next_y)] = Some(room);
+                queue.push_back((next_x, next_y));
+            }
+        }
+
+        rooms.into_iter().flatten().collect()
+    }
+
+    fn assert_parents_lead_back_to_start(season_seed: u64, layout: &SeasonLayout) {
+        for x in SeasonLayout::MIN_COORD..=layout.max_x() {
+            for y in SeasonLayout::MIN_COORD..=layout.max_y() {
+                let (mut room_x, mut room_y) = (x, y);
+                let mut steps = 0;
+                while let Some(parent) = spanning_parent(season_seed, layout, room_x, room_y) {
+                    assert!(layout.contains(parent.0, parent.1));
+                    let dx = (parent.0 - room_x).abs();
+                    let dy = (parent.1 - room_y).abs();
+                    assert_eq!(dx + dy, 1, "parent must be adjacent");
+                    (room_x, room_y) = parent;
+                    steps += 1;
+                    assert!(
+                        steps <= usize::from(layout.width) + usize::from(layout.height),
+                        "cycle from ({x}, {y}) seed {season_seed}"
+                    );
+                }
+                assert_eq!((room_x, room_y), layout.start());
+            }
+        }
+    }
 
     #[test]
     fn room_generation_is_deterministic() {
+        let layout = SeasonLayout::default();
         let seed = 12345u64;
         let x = 7i8;
         let y = 6i8;
@@ -262,74 +863,347 @@ mod tests {
         let second_walls = generate_walls(second_hash, entrance);
         assert_eq!(first_walls, second_walls);
 
-        let depth = calculate_depth(x, y);
-        let first_center = generate_room_center(seed, x, y, depth);
-        let second_center = generate_room_center(seed, x, y, depth);
+        let params = FloorParams::new(seed, &layout, 0);
+        let first_center = generate_room_center(&params, &layout, x, y);
+        let second_center = generate_room_center(&params, &layout, x, y);
         assert_eq!(first_center, second_center);
     }
 
     #[test]
     fn no_locked_doors_before_depth_two() {
+        let layout = SeasonLayout::default();
         let seed = 12345u64;
-        let x = GlobalAccount::START_X;
-        let y = GlobalAccount::START_Y + 1;
-        let depth = calculate_depth(x, y);
+        let x = layout.start_x;
+        let y = layout.start_y + 1;
+        let depth = calculate_depth(&layout, 0, x, y);
         assert_eq!(depth, 1);
 
         let mut walls = generate_walls(generate_room_hash(seed, x, y), 1);
         let mut lock_kinds = [LOCK_KIND_NONE; 4];
-        apply_locked_doors(&mut walls, &mut lock_kinds, seed, x, y, depth, 1);
+        let params = FloorParams::new(seed, &layout, 0);
+        apply_locked_doors(&mut walls, &mut lock_kinds, &params, &layout, x, y, 1);
 
         assert!(walls.iter().all(|wall| *wall != WALL_LOCKED));
-        assert!(lock_kinds.iter().all(|lock_kind| *lock_kind == LOCK_KIND_NONE));
+        assert!(lock_kinds
+            .iter()
+            .all(|lock_kind| *lock_kind == LOCK_KIND_NONE));
     }
 
     #[test]
     fn forced_key_chest_exists_for_depth_ring() {
+        let layout = SeasonLayout::default();
         let seed = 424242u64;
         let depth = 3u32;
-        let forced_coords = select_forced_key_chest_coords(seed, depth);
+        let forced_coords = select_forced_key_chest_coords(seed, &layout, depth);
         assert!(forced_coords.is_some());
 
         let (forced_x, forced_y) = forced_coords.unwrap();
-        assert_eq!(calculate_depth(forced_x, forced_y), depth);
-        assert!(is_forced_key_chest(seed, forced_x, forced_y, depth));
+        assert_eq!(calculate_depth(&layout, 0, forced_x, forced_y), depth);
+        assert!(is_forced_key_chest(
+            seed, &layout, forced_x, forced_y, depth
+        ));
+    }
+
+    #[test]
+    fn ring_walk_matches_full_grid_scan() {
+        for layout in [SeasonLayout::default(), small_layout()] {
+            for depth in 0..20u32 {
+                let mut expected = Vec::new();
+                for x in SeasonLayout::MIN_COORD..=layout.max_x() {
+                    for y in SeasonLayout::MIN_COORD..=layout.max_y() {
+                        if layout.ring(x, y) == depth {
+                            expected.push((x, y));
+                        }
+                    }
+                }
+                assert_eq!(ring_coords(&layout, depth).collect::<Vec<_>>(), expected);
+            }
+        }
     }
 
     #[test]
     fn room_below_start_never_opens_north() {
-        let seed = 999u64;
+        let layout = SeasonLayout::default();
         let mut room = RoomAccount {
-            x: GlobalAccount::START_X,
-            y: GlobalAccount::START_Y - 1,
-            season_seed: seed,
+            x: layout.start_x,
+            y: layout.start_y - 1,
+            season_seed: 999,
             walls: [WALL_OPEN; 4],
             door_lock_kinds: [LOCK_KIND_SKELETON; 4],
-            helper_counts: [0; 4],
-            progress: [0; 4],
-            start_slot: [0; 4],
-            base_slots: [0; 4],
-            total_staked: [0; 4],
-            job_completed: [false; 4],
-            bonus_per_helper: [0; 4],
-            has_chest: false,
-            forced_key_drop: false,
-            center_type: CENTER_EMPTY,
-            center_id: 0,
-            boss_max_hp: 0,
-            boss_current_hp: 0,
-            boss_last_update_slot: 0,
-            boss_total_dps: 0,
-            boss_fighter_count: 0,
-            boss_defeated: false,
-            looted_count: 0,
-            created_by: Pubkey::default(),
-            created_slot: 0,
-            bump: 0,
+            ..empty_room()
         };
 
-        enforce_special_room_topology(&mut room);
+        enforce_special_room_topology(&mut room, &layout);
         assert_eq!(room.walls[DIRECTION_NORTH as usize], WALL_SOLID);
-        assert_eq!(room.door_lock_kinds[DIRECTION_NORTH as usize], LOCK_KIND_NONE);
+        assert_eq!(
+            room.door_lock_kinds[DIRECTION_NORTH as usize],
+            LOCK_KIND_NONE
+        );
+    }
+
+    #[test]
+    fn spanning_parents_lead_back_to_start() {
+        let edge_spawn = SeasonLayout {
+            start_x: 0,
+            ..small_layout()
+        };
+        for season_seed in 0..2_000u64 {
+            assert_parents_lead_back_to_start(season_seed, &SeasonLayout::default());
+            assert_parents_lead_back_to_start(season_seed, &small_layout());
+            assert_parents_lead_back_to_start(season_seed, &edge_spawn);
+        }
+    }
+
+    #[test]
+    fn connected_mode_reaches_every_room() {
+        for layout in [SeasonLayout::default(), small_layout()] {
+            let room_count = usize::from(layout.width) * usize::from(layout.height);
+            for season_seed in 0..5_000u64 {
+                let seed = season_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                assert_eq!(
+                    explore_floor(seed, &layout, 0).len(),
+                    room_count,
+                    "seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn widest_grid_generates_up_to_its_edges() {
+        let widest = SeasonLayout {
+            width: SeasonLayout::MAX_DIMENSION,
+            height: 5,
+            start_x: 60,
+            start_y: 2,
+            ..small_layout()
+        };
+        let east_edge_spawn = SeasonLayout {
+            start_x: widest.max_x(),
+            ..widest
+        };
+        for layout in [widest, east_edge_spawn] {
+            assert!(layout.is_valid());
+            let room_count = usize::from(layout.width) * usize::from(layout.height);
+            for season_seed in 0..10u64 {
+                assert_parents_lead_back_to_start(season_seed, &layout);
+                for floor in 0..layout.floor_count {
+                    let rooms = explore_floor(season_seed, &layout, floor);
+                    assert_eq!(rooms.len(), room_count, "seed {season_seed} floor {floor}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn entrance_floor_keeps_the_original_tuning() {
+        let layout = SeasonLayout::default();
+        let entrance = FloorParams::new(77, &layout, ENTRANCE_FLOOR);
+        assert_eq!(entrance.seed, 77);
+        assert_eq!(entrance.base_depth, 0);
+        assert_eq!(entrance.boss_percent, BOSS_PERCENT);
+        assert_eq!(entrance.max_locked_doors, 1);
+
+        let below = FloorParams::new(77, &layout, 1);
+        assert_ne!(below.seed, entrance.seed);
+        assert_eq!(below.base_depth, layout.max_ring() + 1);
+        assert!(below.boss_percent > entrance.boss_percent);
+        assert_eq!(below.max_locked_doors, 2);
+        assert!(FloorParams::new(77, &layout, u8::MAX).boss_percent <= MAX_BOSS_PERCENT);
+    }
+
+    #[test]
+    fn every_floor_but_the_deepest_has_reachable_edge_stairs() {
+        for layout in [SeasonLayout::default(), small_layout()] {
+            let room_count = usize::from(layout.width) * usize::from(layout.height);
+            for season_seed in 0..500u64 {
+                let seed = season_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                for floor in 0..layout.floor_count {
+                    let rooms = explore_floor(seed, &layout, floor);
+                    assert_eq!(rooms.len(), room_count, "seed {seed} floor {floor}");
+                    assert!(rooms.iter().all(|room| room.floor == floor
+                        && calculate_depth(&layout, floor, room.x, room.y)
+                            >= layout.floor_depth(floor)));
+
+                    let stairs: Vec<_> = rooms
+                        .iter()
+                        .filter(|room| room.center_type == CENTER_STAIRS_DOWN)
+                        .collect();
+                    if !layout.has_floor_below(floor) {
+                        assert!(stairs.is_empty());
+                        continue;
+                    }
+                    let [stairs_room] = stairs[..] else {
+                        panic!("expected one stairs room, seed {seed} floor {floor}");
+                    };
+                    let (x, y) = (stairs_room.x, stairs_room.y);
+                    assert!(
+                        x == SeasonLayout::MIN_COORD
+                            || y == SeasonLayout::MIN_COORD
+                            || x == layout.max_x()
+                            || y == layout.max_y()
+                    );
+                    assert!(!layout.is_start(x, y));
+                    assert!(!stairs_room.has_chest && stairs_room.boss_max_hp == 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn independent_mode_can_strand_rooms() {
+        let layout = SeasonLayout {
+            generation_mode: generation_modes::INDEPENDENT,
+            ..SeasonLayout::default()
+        };
+        let room_count = usize::from(layout.width) * usize::from(layout.height);
+        let stranded_seeds = (0..1_000u64)
+            .filter(|seed| explore_floor(*seed, &layout, 0).len() < room_count)
+            .count();
+        assert!(stranded_seeds > 0);
+    }
+
+    #[test]
+    fn lock_kinds_deepen_with_depth() {
+        let layout = SeasonLayout::default();
+        let room_count = usize::from(layout.width) * usize::from(layout.height);
+        let mut seen_kinds = std::collections::BTreeSet::new();
+        let seeds = (0..100u64)
+            .map(|season_seed| season_seed.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            // Once sealed its only ring boss off behind a spanning-tree door.
+            .chain([11_978_766_303_238_853_878]);
+        for seed in seeds {
+            for floor in 0..layout.floor_count {
+                let floor_seed = FloorParams::new(seed, &layout, floor).seed;
+                // Seals are enforced, so a seal no boss can break strands rooms.
+                let rooms = explore_floor(seed, &layout, floor);
+                assert_eq!(rooms.len(), room_count, "seed {seed} floor {floor}");
+                for room in &rooms {
+                    let depth = layout.depth(floor, room.x, room.y);
+                    for (direction, lock_kind) in (0..=DIRECTION_WEST).zip(room.door_lock_kinds) {
+                        seen_kinds.insert(lock_kind);
+                        if GEM_LOCK_KINDS.contains(&lock_kind) {
+                            assert!(depth >= GEM_LOCK_MIN_DEPTH);
+                        }
+                        if lock_kind == LOCK_KIND_BOSS_SEAL {
+                            assert!(depth >= BOSS_SEAL_MIN_DEPTH);
+                            assert!(!is_spanning_edge(
+                                floor_seed, &layout, room.x, room.y, direction
+                            ));
+                            let ring = layout.ring(room.x, room.y);
+                            assert!(rooms.iter().any(|boss_room| {
+                                boss_room.center_type == CENTER_BOSS
+                                    && layout.ring(boss_room.x, boss_room.y) == ring
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+
+        for lock_kind in [
+            LOCK_KIND_SKELETON,
+            LOCK_KIND_RUBY,
+            LOCK_KIND_SAPPHIRE,
+            LOCK_KIND_EMERALD,
+            LOCK_KIND_BOSS_SEAL,
+        ] {
+            assert!(seen_kinds.contains(&lock_kind), "lock kind {lock_kind}");
+        }
+    }
+
+    #[test]
+    fn locked_door_cap_follows_the_season_layout() {
+        let max_locks_per_room = |layout: &SeasonLayout| {
+            (0..50u64)
+                .flat_map(|seed| explore_floor(seed, layout, ENTRANCE_FLOOR))
+                .map(|room| {
+                    room.walls
+                        .iter()
+                        .filter(|wall| **wall == WALL_LOCKED)
+                        .count()
+                })
+                .max()
+                .unwrap()
+        };
+
+        let no_locks = SeasonLayout {
+            max_locked_doors: 0,
+            ..SeasonLayout::default()
+        };
+        assert_eq!(max_locks_per_room(&no_locks), 0);
+        assert_eq!(FloorParams::new(1, &no_locks, 1).max_locked_doors, 1);
+
+        assert_eq!(max_locks_per_room(&SeasonLayout::default()), 1);
+
+        let many_locks = SeasonLayout {
+            max_locked_doors: SeasonLayout::MAX_LOCKED_DOORS,
+            ..SeasonLayout::default()
+        };
+        assert!(max_locks_per_room(&many_locks) > 1);
+        assert_eq!(
+            FloorParams::new(1, &many_locks, 1).max_locked_doors,
+            usize::from(SeasonLayout::MAX_LOCKED_DOORS)
+        );
+    }
+
+    #[test]
+    fn scouting_matches_the_discovered_room() {
+        let layout = SeasonLayout::default();
+        for season_seed in [3u64, 77, 9001] {
+            for (x, y, entrance) in [
+                (5, 7, DIRECTION_SOUTH),
+                (8, 2, DIRECTION_WEST),
+                (0, 9, DIRECTION_EAST),
+            ] {
+                let report = scout_room(season_seed, &layout, 1, x, y, entrance);
+                let mut room = empty_room();
+                initialize_discovered_room(
+                    &mut room,
+                    &FloorParams::new(season_seed, &layout, 1),
+                    &layout,
+                    RoomEntrance {
+                        x,
+                        y,
+                        direction: entrance,
+                    },
+                    Pubkey::default(),
+                    123,
+                    0,
+                );
+                assert_eq!(report.walls, room.walls);
+                assert_eq!(report.door_lock_kinds, room.door_lock_kinds);
+                assert_eq!(
+                    (report.center_type, report.center_id, report.boss_max_hp),
+                    (room.center_type, room.center_id, room.boss_max_hp)
+                );
+                assert_eq!(report.forced_key_drop, room.forced_key_drop);
+            }
+        }
+    }
+
+    #[test]
+    fn compass_points_along_the_longer_axis() {
+        assert_eq!(bearing_direction((5, 5), (5, 5)), None);
+        assert_eq!(bearing_direction((5, 5), (6, 9)), Some(DIRECTION_NORTH));
+        assert_eq!(bearing_direction((5, 5), (4, 1)), Some(DIRECTION_SOUTH));
+        assert_eq!(bearing_direction((5, 5), (9, 4)), Some(DIRECTION_EAST));
+        assert_eq!(bearing_direction((5, 5), (0, 7)), Some(DIRECTION_WEST));
+        assert_eq!(bearing_direction((5, 5), (7, 3)), Some(DIRECTION_SOUTH));
+    }
+
+    #[test]
+    fn only_deep_rings_have_a_key_chest_to_find() {
+        let layout = SeasonLayout::default();
+        let params = FloorParams::new(42, &layout, 1);
+        assert_eq!(forced_key_chest_coords(&params, &layout, 0), None);
+        assert_eq!(forced_key_chest_coords(&params, &layout, 1), None);
+
+        let (x, y) = forced_key_chest_coords(&params, &layout, 3).unwrap();
+        assert_eq!(layout.ring(x, y), 3);
+        assert_eq!(
+            generate_room_center(&params, &layout, x, y),
+            (CENTER_CHEST, 1, true)
+        );
     }
 }
@@ -5,6 +5,7 @@ use anchor_lang::prelude::*;
 pub struct RoomPresence {
     pub player: Pubkey,
     pub season_seed: u64,
+    pub room_floor: u8,
     pub room_x: i8,
     pub room_y: i8,
     pub skin_id: u16,
@@ -32,6 +33,16 @@ impl RoomPresence {
         self.activity_direction = direction;
     }
 
+    /// Whether this presence shows its player working the door job at
+    /// `direction` of the room at (`floor`, `x`, `y`)
+    pub fn is_on_door_job(&self, floor: u8, x: i8, y: i8, direction: u8) -> bool {
+        self.room_floor == floor
+            && self.room_x == x
+            && self.room_y == y
+            && self.activity == Self::ACTIVITY_DOOR_JOB
+            && self.activity_direction == direction
+    }
+
     pub fn set_boss_fight(&mut self) {
         self.activity = Self::ACTIVITY_BOSS_FIGHT;
         self.activity_direction = 255;
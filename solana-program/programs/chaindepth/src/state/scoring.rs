@@ -4,32 +4,8 @@ pub const TIME_BONUS_FIRST_HOUR_SLOTS: u64 = 60 * 60 * 5; // 9000 slots at 400ms
 pub const TIME_BONUS_CAP_DIVISOR: u64 = 4;
 pub const TIME_BONUS_MIN_CAP: u64 = 5;
 
-pub fn score_value_for_item(item_id: u16) -> u64 {
-    match item_id {
-        200 => 1,  // Silver Coin
-        201 => 3,  // Gold Coin
-        202 => 8,  // Gold Bar
-        203 => 12, // Diamond
-        204 => 10, // Ruby
-        205 => 9,  // Sapphire
-        206 => 9,  // Emerald
-        207 => 20, // Ancient Crown
-        208 => 2,  // Goblin Tooth
-        209 => 15, // Dragon Scale
-        210 => 11, // Cursed Amulet
-        211 => 4,  // Dusty Tome
-        212 => 7,  // Enchanted Scroll
-        213 => 14, // Golden Chalice
-        214 => 0,  // Skeleton Key
-        215 => 13, // Mystic Orb
-        216 => 3,  // Rusted Compass
-        217 => 8,  // Dwarf Beard Ring
-        218 => 18, // Phoenix Feather
-        219 => 16, // Void Shard
-        _ => 0,
-    }
-}
-
+/// Items in the valuables range are converted to score on extraction.
+/// Per-item values come from the item registry.
 pub fn is_scored_loot_item(item_id: u16) -> bool {
     (200..=299).contains(&item_id)
 }
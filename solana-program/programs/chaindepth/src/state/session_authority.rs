@@ -34,4 +34,13 @@ pub mod session_instruction_bits {
     pub const LOOT_BOSS: u64 = 1 << 12;
     pub const UNLOCK_DOOR: u64 = 1 << 13;
     pub const EXIT_DUNGEON: u64 = 1 << 14;
+    pub const RECOVER_STALE_STAKE: u64 = 1 << 15;
+    pub const RESPAWN_STALE_PLAYER: u64 = 1 << 16;
+    pub const CHANGE_FLOOR: u64 = 1 << 17;
+    pub const LEAVE_BOSS_FIGHT: u64 = 1 << 18;
+    pub const USE_ITEM: u64 = 1 << 19;
+    pub const USE_MYSTIC_ORB: u64 = 1 << 20;
+    pub const USE_RUSTED_COMPASS: u64 = 1 << 21;
+    pub const USE_VOID_SHARD: u64 = 1 << 22;
+    pub const USE_PHOENIX_FEATHER: u64 = 1 << 23;
 }
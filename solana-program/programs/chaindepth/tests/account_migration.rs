@@ -0,0 +1,874 @@
+//! `migrate_global`, `migrate_player`, `migrate_helper_stake` and
+//! `migrate_inventory` on accounts written in the layout the program launched
+//! with, before floors, HP, buffs, work rates, the game config, the season
+//! layout and cached item scores were appended, and
+//! `recover_legacy_stake` on launch rooms, which keep their layout and their
+//! floorless seeds. The runtime cannot run the payout transfer, so stake
+//! recovery stops at `release_legacy_stake`.
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::{borsh, Context, Error, ProgramError, Pubkey, Rent};
+use anchor_lang::{
+    Accounts, AccountsExit, AnchorSerialize, Discriminator, InitSpace, Space, ToAccountMetas,
+};
+use anchor_spl::token::spl_token;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::events::StaleStakeRecovered;
+use chaindepth::instructions::recover_stale_stake::{
+    release_legacy_stake, RecoverLegacyStake, RecoverLegacyStakeBumps,
+};
+use chaindepth::state::{
+    item_ids, ActiveJob, GameConfig, GlobalAccount, HelperStake, InventoryAccount, PlayerAccount,
+    RoomAccount, SeasonLayout, BARE_HANDS_WORK_RATE, DIRECTION_EAST, ENTRANCE_FLOOR,
+    MAX_ACTIVE_JOBS, MAX_INVENTORY_SLOTS, PLAYER_MAX_HP, WALL_RUBBLE,
+};
+use chaindepth::{accounts, instruction};
+use common::{program_error, TestAccount, TestRuntime, CLOCK_SLOT};
+
+const SEASON_SEED: u64 = 7;
+const TREASURY_LAMPORTS: u64 = 1_000_000_000;
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchGlobalAccount {
+    season_seed: u64,
+    depth: u32,
+    skr_mint: Pubkey,
+    prize_pool: Pubkey,
+    admin: Pubkey,
+    end_slot: u64,
+    jobs_completed: u64,
+    bump: u8,
+}
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchPlayerAccount {
+    owner: Pubkey,
+    current_room_x: i8,
+    current_room_y: i8,
+    #[max_len(MAX_ACTIVE_JOBS)]
+    active_jobs: Vec<ActiveJob>,
+    jobs_completed: u64,
+    chests_looted: u64,
+    equipped_item_id: u16,
+    total_score: u64,
+    current_run_start_slot: u64,
+    runs_extracted: u64,
+    last_extraction_slot: u64,
+    season_seed: u64,
+    bump: u8,
+}
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchHelperStake {
+    player: Pubkey,
+    room: Pubkey,
+    direction: u8,
+    amount: u64,
+    joined_slot: u64,
+    bump: u8,
+}
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchInventoryItem {
+    item_id: u16,
+    amount: u32,
+    durability: u16,
+}
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchInventoryAccount {
+    owner: Pubkey,
+    #[max_len(MAX_INVENTORY_SLOTS)]
+    items: Vec<LaunchInventoryItem>,
+    bump: u8,
+}
+
+#[derive(AnchorSerialize, InitSpace)]
+struct LaunchRoomAccount {
+    x: i8,
+    y: i8,
+    season_seed: u64,
+    walls: [u8; 4],
+    door_lock_kinds: [u8; 4],
+    helper_counts: [u32; 4],
+    progress: [u64; 4],
+    start_slot: [u64; 4],
+    base_slots: [u64; 4],
+    total_staked: [u64; 4],
+    job_completed: [bool; 4],
+    bonus_per_helper: [u64; 4],
+    has_chest: bool,
+    forced_key_drop: bool,
+    center_type: u8,
+    center_id: u16,
+    boss_max_hp: u64,
+    boss_current_hp: u64,
+    boss_last_update_slot: u64,
+    boss_total_dps: u64,
+    boss_fighter_count: u32,
+    boss_defeated: bool,
+    looted_count: u32,
+    created_by: Pubkey,
+    created_slot: u64,
+    bump: u8,
+}
+
+/// A rent-exempt account holding `account` in a launch layout, plus `extra_lamports`
+fn launch_account<T: AnchorSerialize + Space>(
+    discriminator: &[u8],
+    account: &T,
+    extra_lamports: u64,
+) -> TestAccount {
+    let mut data = discriminator.to_vec();
+    account.serialize(&mut data).unwrap();
+    data.resize(8 + T::INIT_SPACE, 0);
+    TestAccount {
+        lamports: Rent::default().minimum_balance(data.len()) + extra_lamports,
+        owner: chaindepth::ID,
+        data,
+        executable: false,
+    }
+}
+
+struct Upgrade {
+    runtime: TestRuntime,
+    admin: Pubkey,
+    global: Pubkey,
+    launch_global: LaunchGlobalAccount,
+}
+
+impl Upgrade {
+    /// A launch-layout global holding `TREASURY_LAMPORTS` over its rent
+    fn new() -> Self {
+        let mut runtime = TestRuntime::new();
+        let admin = Pubkey::new_unique();
+        runtime.set_account(admin, TestAccount::wallet(1_000_000_000));
+
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        let launch_global = LaunchGlobalAccount {
+            season_seed: SEASON_SEED,
+            depth: 6,
+            skr_mint: Pubkey::new_unique(),
+            prize_pool: Pubkey::new_unique(),
+            admin,
+            end_slot: CLOCK_SLOT + 5_000,
+            jobs_completed: 31,
+            bump: global_bump,
+        };
+        runtime.set_account(
+            global,
+            launch_account(
+                GlobalAccount::DISCRIMINATOR,
+                &launch_global,
+                TREASURY_LAMPORTS,
+            ),
+        );
+
+        Self {
+            runtime,
+            admin,
+            global,
+            launch_global,
+        }
+    }
+
+    fn migrate_global(&mut self, admin: Pubkey) -> Result<(), ProgramError> {
+        self.runtime.process(
+            accounts::MigrateGlobal {
+                admin,
+                global: self.global,
+            },
+            instruction::MigrateGlobal {},
+        )
+    }
+
+    /// A launch-layout player account for a new wallet. Returns the wallet,
+    /// the player account and what it holds.
+    fn put_launch_player(&mut self) -> (Pubkey, Pubkey, LaunchPlayerAccount) {
+        let wallet = Pubkey::new_unique();
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        let launch_player = LaunchPlayerAccount {
+            owner: wallet,
+            current_room_x: 3,
+            current_room_y: 7,
+            active_jobs: vec![ActiveJob {
+                room_x: 3,
+                room_y: 7,
+                direction: DIRECTION_EAST,
+            }],
+            jobs_completed: 12,
+            chests_looted: 5,
+            equipped_item_id: 1001,
+            total_score: 4_200,
+            current_run_start_slot: 9_000,
+            runs_extracted: 3,
+            last_extraction_slot: 8_500,
+            season_seed: SEASON_SEED,
+            bump: player_bump,
+        };
+        self.runtime.set_account(
+            player_account,
+            launch_account(PlayerAccount::DISCRIMINATOR, &launch_player, 0),
+        );
+        (wallet, player_account, launch_player)
+    }
+
+    fn migrate_player(
+        &mut self,
+        admin: Pubkey,
+        wallet: Pubkey,
+        player_account: Pubkey,
+    ) -> Result<(), ProgramError> {
+        self.runtime.process(
+            accounts::MigratePlayer {
+                admin,
+                global: self.global,
+                player: wallet,
+                player_account,
+            },
+            instruction::MigratePlayer {},
+        )
+    }
+
+    /// A launch-layout stake of a new wallet on the east door of a room
+    /// created before the upgrade. Returns the wallet, the room, the stake
+    /// account and what it holds.
+    fn put_launch_stake(&mut self) -> (Pubkey, Pubkey, Pubkey, LaunchHelperStake) {
+        let wallet = Pubkey::new_unique();
+        let room = Pubkey::new_unique();
+        let (helper_stake, launch_stake) = self.put_launch_stake_of(wallet, room);
+        (wallet, room, helper_stake, launch_stake)
+    }
+
+    /// A launch-layout stake of `wallet` on the east door of `room`. Returns
+    /// the stake account and what it holds.
+    fn put_launch_stake_of(&mut self, wallet: Pubkey, room: Pubkey) -> (Pubkey, LaunchHelperStake) {
+        let (helper_stake, stake_bump) = Pubkey::find_program_address(
+            &[
+                HelperStake::SEED_PREFIX,
+                room.as_ref(),
+                &[DIRECTION_EAST],
+                wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let launch_stake = LaunchHelperStake {
+            player: wallet,
+            room,
+            direction: DIRECTION_EAST,
+            amount: RoomAccount::STAKE_AMOUNT,
+            joined_slot: 9_400,
+            bump: stake_bump,
+        };
+        self.runtime.set_account(
+            helper_stake,
+            launch_account(HelperStake::DISCRIMINATOR, &launch_stake, 0),
+        );
+        (helper_stake, launch_stake)
+    }
+
+    /// A launch room at the player's job, (3, 7), at its floorless seeds,
+    /// with `helpers` staked on the east door, and that door's escrow
+    fn put_launch_room(&mut self, helpers: u32, completed: bool) -> Pubkey {
+        let (room, room_bump) = Pubkey::find_program_address(
+            &[
+                RoomAccount::SEED_PREFIX,
+                &SEASON_SEED.to_le_bytes(),
+                &[3],
+                &[7],
+            ],
+            &chaindepth::ID,
+        );
+        let east = DIRECTION_EAST as usize;
+        let mut launch_room = LaunchRoomAccount {
+            x: 3,
+            y: 7,
+            season_seed: SEASON_SEED,
+            walls: [WALL_RUBBLE; 4],
+            door_lock_kinds: [0; 4],
+            helper_counts: [0; 4],
+            progress: [0; 4],
+            start_slot: [0; 4],
+            base_slots: [RoomAccount::BASE_SLOTS_DEPTH_0; 4],
+            total_staked: [0; 4],
+            job_completed: [false; 4],
+            bonus_per_helper: [0; 4],
+            has_chest: false,
+            forced_key_drop: false,
+            center_type: 0,
+            center_id: 0,
+            boss_max_hp: 0,
+            boss_current_hp: 0,
+            boss_last_update_slot: 0,
+            boss_total_dps: 0,
+            boss_fighter_count: 0,
+            boss_defeated: false,
+            looted_count: 0,
+            created_by: Pubkey::new_unique(),
+            created_slot: 8_000,
+            bump: room_bump,
+        };
+        launch_room.helper_counts[east] = helpers;
+        launch_room.total_staked[east] = u64::from(helpers) * RoomAccount::STAKE_AMOUNT;
+        launch_room.start_slot[east] = 9_000;
+        if completed {
+            launch_room.progress[east] = RoomAccount::BASE_SLOTS_DEPTH_0;
+            launch_room.job_completed[east] = true;
+            launch_room.bonus_per_helper[east] = 2_500_000;
+        } else {
+            launch_room.progress[east] = 120;
+        }
+        self.runtime.set_account(
+            room,
+            launch_account(RoomAccount::DISCRIMINATOR, &launch_room, 0),
+        );
+
+        let escrow = Self::escrow(room);
+        self.runtime.set_account(
+            escrow,
+            TestAccount::token_account(
+                self.launch_global.skr_mint,
+                escrow,
+                launch_room.total_staked[east] + 2 * launch_room.bonus_per_helper[east],
+            ),
+        );
+        room
+    }
+
+    fn escrow(room: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"escrow", room.as_ref(), &[DIRECTION_EAST]],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn migrate_helper_stake(
+        &mut self,
+        admin: Pubkey,
+        wallet: Pubkey,
+        room: Pubkey,
+        helper_stake: Pubkey,
+    ) -> Result<(), ProgramError> {
+        self.runtime.process(
+            accounts::MigrateHelperStake {
+                admin,
+                global: self.global,
+                room,
+                player: wallet,
+                helper_stake,
+            },
+            instruction::MigrateHelperStake {
+                direction: DIRECTION_EAST,
+            },
+        )
+    }
+
+    /// A launch-layout inventory of a new wallet: a worn weapon and two
+    /// valuable stacks. Returns the wallet and the inventory account.
+    fn put_launch_inventory(&mut self) -> (Pubkey, Pubkey) {
+        let wallet = Pubkey::new_unique();
+        let (inventory, inventory_bump) = Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        let launch_inventory = LaunchInventoryAccount {
+            owner: wallet,
+            items: vec![
+                LaunchInventoryItem {
+                    item_id: item_ids::BRONZE_PICKAXE,
+                    amount: 1,
+                    durability: 40,
+                },
+                LaunchInventoryItem {
+                    item_id: item_ids::GOLD_BAR,
+                    amount: 3,
+                    durability: 0,
+                },
+                LaunchInventoryItem {
+                    item_id: item_ids::ANCIENT_CROWN,
+                    amount: 1,
+                    durability: 0,
+                },
+            ],
+            bump: inventory_bump,
+        };
+        self.runtime.set_account(
+            inventory,
+            launch_account(InventoryAccount::DISCRIMINATOR, &launch_inventory, 0),
+        );
+        (wallet, inventory)
+    }
+
+    fn migrate_inventory(
+        &mut self,
+        admin: Pubkey,
+        wallet: Pubkey,
+        inventory: Pubkey,
+    ) -> Result<(), ProgramError> {
+        self.runtime.process(
+            accounts::MigrateInventory {
+                admin,
+                global: self.global,
+                player: wallet,
+                inventory,
+            },
+            instruction::MigrateInventory {},
+        )
+    }
+
+    /// Runs `recover_legacy_stake` for `wallet`'s east stake in the launch
+    /// room up to the payout, which needs a CPI
+    fn recover_legacy_stake(
+        &mut self,
+        wallet: Pubkey,
+        room: Pubkey,
+    ) -> Result<StaleStakeRecovered, ProgramError> {
+        let player_token_account = Pubkey::new_unique();
+        self.runtime.set_account(
+            player_token_account,
+            TestAccount::token_account(self.launch_global.skr_mint, wallet, 0),
+        );
+        let metas = accounts::RecoverLegacyStake {
+            authority: wallet,
+            player: wallet,
+            global: self.global,
+            player_account: Pubkey::find_program_address(
+                &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+                &chaindepth::ID,
+            )
+            .0,
+            room,
+            escrow: Self::escrow(room),
+            helper_stake: Pubkey::find_program_address(
+                &[
+                    HelperStake::SEED_PREFIX,
+                    room.as_ref(),
+                    &[DIRECTION_EAST],
+                    wallet.as_ref(),
+                ],
+                &chaindepth::ID,
+            )
+            .0,
+            player_token_account,
+            session_authority: None,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        let args = borsh::to_vec(&instruction::RecoverLegacyStake {
+            season_seed: SEASON_SEED,
+            room_x: 3,
+            room_y: 7,
+            direction: DIRECTION_EAST,
+        })
+        .unwrap();
+
+        self.runtime.run(&metas, |mut infos| {
+            let mut bumps = RecoverLegacyStakeBumps::default();
+            let mut accounts = RecoverLegacyStake::try_accounts(
+                &chaindepth::ID,
+                &mut infos,
+                &args,
+                &mut bumps,
+                &mut BTreeSet::new(),
+            )?;
+            let mut ctx = Context::new(&chaindepth::ID, &mut accounts, &[], bumps);
+            let recovered = release_legacy_stake(&mut ctx, SEASON_SEED, DIRECTION_EAST)?;
+            accounts.exit(&chaindepth::ID)?;
+            Ok(recovered)
+        })
+    }
+
+    fn lamports(&self, key: &Pubkey) -> u64 {
+        self.runtime.account(key).lamports
+    }
+}
+
+/// The launch room's books, read at the launch layout's offsets
+fn launch_room_east_door(upgrade: &Upgrade, room: Pubkey) -> (u32, u64, bool, u64) {
+    let data = upgrade.runtime.account(&room).data;
+    assert_eq!(data.len(), 8 + LaunchRoomAccount::INIT_SPACE);
+    let east = DIRECTION_EAST as usize;
+    // discriminator, x, y, season_seed, walls, door_lock_kinds
+    let helper_counts = 8 + 1 + 1 + 8 + 4 + 4;
+    let total_staked = helper_counts + 16 + 3 * 32;
+    let job_completed = total_staked + 32;
+    let bonus_per_helper = job_completed + 4;
+    let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+    (
+        u32::from_le_bytes(
+            data[helper_counts + 4 * east..helper_counts + 4 * east + 4]
+                .try_into()
+                .unwrap(),
+        ),
+        read_u64(total_staked + 8 * east),
+        data[job_completed + east] == 1,
+        read_u64(bonus_per_helper + 8 * east),
+    )
+}
+
+#[test]
+fn launch_layouts_have_the_deployed_account_sizes() {
+    // Sizes of the accounts the launch program allocated
+    assert_eq!(8 + LaunchGlobalAccount::INIT_SPACE, 133);
+    assert_eq!(8 + LaunchPlayerAccount::INIT_SPACE, 117);
+    assert_eq!(8 + LaunchHelperStake::INIT_SPACE, 90);
+    assert_eq!(8 + LaunchInventoryAccount::INIT_SPACE, 557);
+    assert_eq!(8 + LaunchRoomAccount::INIT_SPACE, 293);
+}
+
+#[test]
+fn migrate_global_keeps_the_season_and_appends_default_config_and_layout() {
+    let mut upgrade = Upgrade::new();
+    let lamports = upgrade.lamports(&upgrade.global);
+
+    upgrade.migrate_global(upgrade.admin).unwrap();
+
+    let account = upgrade.runtime.account(&upgrade.global);
+    assert_eq!(account.data.len(), 8 + GlobalAccount::INIT_SPACE);
+    assert_eq!(account.lamports, lamports);
+    let global: GlobalAccount = upgrade.runtime.fetch(&upgrade.global);
+    let launch = &upgrade.launch_global;
+    assert_eq!(global.season_seed, launch.season_seed);
+    assert_eq!(global.depth, launch.depth);
+    assert_eq!(global.skr_mint, launch.skr_mint);
+    assert_eq!(global.prize_pool, launch.prize_pool);
+    assert_eq!(global.admin, launch.admin);
+    assert_eq!(global.end_slot, launch.end_slot);
+    assert_eq!(global.jobs_completed, launch.jobs_completed);
+    assert_eq!(global.bump, launch.bump);
+    assert_eq!(global.config, GameConfig::default());
+    assert_eq!(global.layout, SeasonLayout::default());
+}
+
+#[test]
+fn migrate_global_is_admin_only_and_runs_once() {
+    let mut upgrade = Upgrade::new();
+    let launch_account = upgrade.runtime.account(&upgrade.global);
+
+    let stranger = Pubkey::new_unique();
+    assert_eq!(
+        upgrade.migrate_global(stranger).unwrap_err(),
+        program_error(ChainDepthError::Unauthorized)
+    );
+    assert_eq!(upgrade.runtime.account(&upgrade.global), launch_account);
+
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let migrated_account = upgrade.runtime.account(&upgrade.global);
+    assert_eq!(
+        upgrade.migrate_global(upgrade.admin).unwrap_err(),
+        program_error(ChainDepthError::AccountAlreadyMigrated)
+    );
+    assert_eq!(upgrade.runtime.account(&upgrade.global), migrated_account);
+}
+
+#[test]
+fn migrate_global_needs_rent_for_the_larger_account() {
+    let mut upgrade = Upgrade::new();
+    let mut global = upgrade.runtime.account(&upgrade.global);
+    global.lamports = Rent::default().minimum_balance(global.data.len());
+    upgrade.runtime.set_account(upgrade.global, global.clone());
+
+    assert_eq!(
+        upgrade.migrate_global(upgrade.admin).unwrap_err(),
+        program_error(ChainDepthError::TreasuryInsufficientFunds)
+    );
+    assert_eq!(upgrade.runtime.account(&upgrade.global), global);
+}
+
+#[test]
+fn migrate_player_keeps_progress_and_starts_at_full_hp_on_the_entrance_floor() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, player_account, launch) = upgrade.put_launch_player();
+    let treasury = upgrade.lamports(&upgrade.global);
+    let launch_rent = upgrade.lamports(&player_account);
+
+    upgrade
+        .migrate_player(upgrade.admin, wallet, player_account)
+        .unwrap();
+
+    // The treasury pays the rent for the appended fields.
+    let account = upgrade.runtime.account(&player_account);
+    let new_space = 8 + PlayerAccount::INIT_SPACE;
+    assert_eq!(account.data.len(), new_space);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(new_space));
+    assert_eq!(
+        upgrade.lamports(&upgrade.global),
+        treasury - (account.lamports - launch_rent)
+    );
+
+    let player: PlayerAccount = upgrade.runtime.fetch(&player_account);
+    assert_eq!(player.owner, launch.owner);
+    assert_eq!(
+        (player.current_room_x, player.current_room_y),
+        (launch.current_room_x, launch.current_room_y)
+    );
+    assert_eq!(player.active_jobs.len(), 1);
+    assert_eq!(player.active_jobs[0].direction, DIRECTION_EAST);
+    assert_eq!(player.jobs_completed, launch.jobs_completed);
+    assert_eq!(player.chests_looted, launch.chests_looted);
+    assert_eq!(player.equipped_item_id, launch.equipped_item_id);
+    assert_eq!(player.total_score, launch.total_score);
+    assert_eq!(player.current_run_start_slot, launch.current_run_start_slot);
+    assert_eq!(player.runs_extracted, launch.runs_extracted);
+    assert_eq!(player.last_extraction_slot, launch.last_extraction_slot);
+    assert_eq!(player.season_seed, launch.season_seed);
+    assert_eq!(player.bump, launch.bump);
+
+    assert_eq!(player.current_floor, ENTRANCE_FLOOR);
+    assert_eq!(player.boss_fight_room, Pubkey::default());
+    assert!(!player.is_in_boss_fight());
+    assert_eq!(player.hp, PLAYER_MAX_HP);
+    assert_eq!(player.active_buff_id, 0);
+    assert_eq!(player.buff_expires_slot, 0);
+    assert!(!player.loot_protected);
+}
+
+#[test]
+fn migrate_player_is_admin_only_and_runs_once() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, player_account, _) = upgrade.put_launch_player();
+    let launch_account = upgrade.runtime.account(&player_account);
+
+    let stranger = Pubkey::new_unique();
+    assert_eq!(
+        upgrade
+            .migrate_player(stranger, wallet, player_account)
+            .unwrap_err(),
+        program_error(ChainDepthError::Unauthorized)
+    );
+    assert_eq!(upgrade.runtime.account(&player_account), launch_account);
+
+    upgrade
+        .migrate_player(upgrade.admin, wallet, player_account)
+        .unwrap();
+    let migrated_account = upgrade.runtime.account(&player_account);
+    assert_eq!(
+        upgrade
+            .migrate_player(upgrade.admin, wallet, player_account)
+            .unwrap_err(),
+        program_error(ChainDepthError::AccountAlreadyMigrated)
+    );
+    assert_eq!(upgrade.runtime.account(&player_account), migrated_account);
+}
+
+#[test]
+fn migrate_player_needs_the_global_migrated_first() {
+    let mut upgrade = Upgrade::new();
+    let (wallet, player_account, _) = upgrade.put_launch_player();
+    let launch_account = upgrade.runtime.account(&player_account);
+
+    assert_eq!(
+        upgrade
+            .migrate_player(upgrade.admin, wallet, player_account)
+            .unwrap_err(),
+        Error::from(anchor_lang::error::ErrorCode::AccountDidNotDeserialize).into()
+    );
+    assert_eq!(upgrade.runtime.account(&player_account), launch_account);
+}
+
+#[test]
+fn migrate_helper_stake_keeps_the_stake_and_counts_bare_hands() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, room, helper_stake, launch) = upgrade.put_launch_stake();
+    let treasury = upgrade.lamports(&upgrade.global);
+    let launch_rent = upgrade.lamports(&helper_stake);
+
+    upgrade
+        .migrate_helper_stake(upgrade.admin, wallet, room, helper_stake)
+        .unwrap();
+
+    let account = upgrade.runtime.account(&helper_stake);
+    let new_space = 8 + HelperStake::INIT_SPACE;
+    assert_eq!(account.data.len(), new_space);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(new_space));
+    assert_eq!(
+        upgrade.lamports(&upgrade.global),
+        treasury - (account.lamports - launch_rent)
+    );
+
+    let stake: HelperStake = upgrade.runtime.fetch(&helper_stake);
+    assert_eq!(stake.player, launch.player);
+    assert_eq!(stake.room, launch.room);
+    assert_eq!(stake.direction, launch.direction);
+    assert_eq!(stake.amount, launch.amount);
+    assert_eq!(stake.joined_slot, launch.joined_slot);
+    assert_eq!(stake.bump, launch.bump);
+    assert_eq!(stake.work_rate, BARE_HANDS_WORK_RATE);
+    assert_eq!(stake.buff_bonus, 0);
+    assert_eq!(stake.buff_expires_slot, 0);
+}
+
+#[test]
+fn migrate_helper_stake_is_admin_only_and_runs_once() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, room, helper_stake, _) = upgrade.put_launch_stake();
+    let launch_account = upgrade.runtime.account(&helper_stake);
+
+    let stranger = Pubkey::new_unique();
+    assert_eq!(
+        upgrade
+            .migrate_helper_stake(stranger, wallet, room, helper_stake)
+            .unwrap_err(),
+        program_error(ChainDepthError::Unauthorized)
+    );
+    assert_eq!(upgrade.runtime.account(&helper_stake), launch_account);
+
+    upgrade
+        .migrate_helper_stake(upgrade.admin, wallet, room, helper_stake)
+        .unwrap();
+    let migrated_account = upgrade.runtime.account(&helper_stake);
+    assert_eq!(
+        upgrade
+            .migrate_helper_stake(upgrade.admin, wallet, room, helper_stake)
+            .unwrap_err(),
+        program_error(ChainDepthError::AccountAlreadyMigrated)
+    );
+    assert_eq!(upgrade.runtime.account(&helper_stake), migrated_account);
+}
+
+#[test]
+fn migrate_inventory_scores_launch_items_at_the_launch_values() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, inventory) = upgrade.put_launch_inventory();
+    let treasury = upgrade.lamports(&upgrade.global);
+    let launch_rent = upgrade.lamports(&inventory);
+
+    upgrade
+        .migrate_inventory(upgrade.admin, wallet, inventory)
+        .unwrap();
+
+    let account = upgrade.runtime.account(&inventory);
+    let new_space = 8 + InventoryAccount::INIT_SPACE;
+    assert_eq!(account.data.len(), new_space);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(new_space));
+    assert_eq!(
+        upgrade.lamports(&upgrade.global),
+        treasury - (account.lamports - launch_rent)
+    );
+
+    let migrated: InventoryAccount = upgrade.runtime.fetch(&inventory);
+    assert_eq!(migrated.owner, wallet);
+    let items: Vec<(u16, u32, u16, u64)> = migrated
+        .items
+        .iter()
+        .map(|item| (item.item_id, item.amount, item.durability, item.score_value))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (item_ids::BRONZE_PICKAXE, 1, 40, 0),
+            (item_ids::GOLD_BAR, 3, 0, 8),
+            (item_ids::ANCIENT_CROWN, 1, 0, 20),
+        ]
+    );
+}
+
+#[test]
+fn migrate_inventory_is_admin_only_and_runs_once() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, inventory) = upgrade.put_launch_inventory();
+    let launch_account = upgrade.runtime.account(&inventory);
+
+    let stranger = Pubkey::new_unique();
+    assert_eq!(
+        upgrade
+            .migrate_inventory(stranger, wallet, inventory)
+            .unwrap_err(),
+        program_error(ChainDepthError::Unauthorized)
+    );
+    assert_eq!(upgrade.runtime.account(&inventory), launch_account);
+
+    upgrade
+        .migrate_inventory(upgrade.admin, wallet, inventory)
+        .unwrap();
+    let migrated_account = upgrade.runtime.account(&inventory);
+    assert_eq!(
+        upgrade
+            .migrate_inventory(upgrade.admin, wallet, inventory)
+            .unwrap_err(),
+        program_error(ChainDepthError::AccountAlreadyMigrated)
+    );
+    assert_eq!(upgrade.runtime.account(&inventory), migrated_account);
+}
+
+#[test]
+fn recover_legacy_stake_refunds_a_launch_layout_stake() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, player_account, _) = upgrade.put_launch_player();
+    upgrade
+        .migrate_player(upgrade.admin, wallet, player_account)
+        .unwrap();
+    let room = upgrade.put_launch_room(2, false);
+    let (helper_stake, _) = upgrade.put_launch_stake_of(wallet, room);
+    let wallet_lamports = upgrade.lamports(&wallet);
+    let stake_rent = upgrade.lamports(&helper_stake);
+
+    let recovered = upgrade.recover_legacy_stake(wallet, room).unwrap();
+
+    assert_eq!(recovered.payout_amount, RoomAccount::STAKE_AMOUNT);
+    assert!(!recovered.job_completed);
+    assert_eq!(recovered.season_seed, SEASON_SEED);
+    assert_eq!(recovered.room_floor, ENTRANCE_FLOOR);
+    assert_eq!((recovered.room_x, recovered.room_y), (3, 7));
+    assert_eq!(recovered.player, wallet);
+
+    // The other helper's stake stays on the books; this one's rent goes home.
+    assert_eq!(
+        launch_room_east_door(&upgrade, room),
+        (1, RoomAccount::STAKE_AMOUNT, false, 0)
+    );
+    assert!(upgrade.runtime.account(&helper_stake).is_closed());
+    assert_eq!(upgrade.lamports(&wallet), wallet_lamports + stake_rent);
+    let player: PlayerAccount = upgrade.runtime.fetch(&player_account);
+    assert!(player.active_jobs.is_empty());
+
+    // The stake is gone, so it cannot be recovered twice.
+    assert_eq!(
+        upgrade
+            .recover_legacy_stake(wallet, room)
+            .map(|_| ())
+            .unwrap_err(),
+        Error::from(anchor_lang::error::ErrorCode::ConstraintOwner).into()
+    );
+}
+
+#[test]
+fn recover_legacy_stake_pays_the_launch_bonus_of_a_completed_job_to_a_migrated_stake() {
+    let mut upgrade = Upgrade::new();
+    upgrade.migrate_global(upgrade.admin).unwrap();
+    let (wallet, player_account, _) = upgrade.put_launch_player();
+    upgrade
+        .migrate_player(upgrade.admin, wallet, player_account)
+        .unwrap();
+    let room = upgrade.put_launch_room(1, true);
+    let (helper_stake, _) = upgrade.put_launch_stake_of(wallet, room);
+    upgrade
+        .migrate_helper_stake(upgrade.admin, wallet, room, helper_stake)
+        .unwrap();
+
+    let recovered = upgrade.recover_legacy_stake(wallet, room).unwrap();
+
+    assert_eq!(
+        recovered.payout_amount,
+        RoomAccount::STAKE_AMOUNT + 2_500_000
+    );
+    assert!(recovered.job_completed);
+    // The last helper out clears the job, as the launch claim did.
+    assert_eq!(launch_room_east_door(&upgrade, room), (0, 0, false, 0));
+    assert!(upgrade.runtime.account(&helper_stake).is_closed());
+}
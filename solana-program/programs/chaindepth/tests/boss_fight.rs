@@ -0,0 +1,610 @@
+//! Boss fights driven through the program's instructions: the killing tick,
+//! `leave_boss_fight`, and the movement lock while fighting.
+
+mod common;
+
+use anchor_lang::prelude::{ProgramError, Pubkey};
+use anchor_lang::solana_program::system_program;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::state::{
+    item_ids, BossFightAccount, BossRoster, BuffBonus, BuffSchedule, GameConfig, GlobalAccount,
+    InventoryAccount, PlayerAccount, PlayerProfile, RoomAccount, RoomPresence, SeasonLayout,
+    BUFF_TARGET_BOSS, CENTER_BOSS, DIRECTION_SOUTH, ENTRANCE_FLOOR, MAX_BOSS_FIGHTERS,
+    PLAYER_MAX_HP, WALL_ENTRANCE_STAIRS,
+};
+use chaindepth::{accounts, instruction};
+use common::{program_error, TestAccount, TestRuntime, CLOCK_SLOT};
+
+const SEASON_SEED: u64 = 41;
+
+/// Accounts of one player in the fight
+#[derive(Clone, Copy)]
+struct Fighter {
+    wallet: Pubkey,
+    player_account: Pubkey,
+    boss_fight: Pubkey,
+    presence: Pubkey,
+    inventory: Pubkey,
+    respawn_presence: Pubkey,
+}
+
+struct Fight {
+    runtime: TestRuntime,
+    global: Pubkey,
+    room: Pubkey,
+    boss_roster: Pubkey,
+    caller: Pubkey,
+    floor: u8,
+    x: i8,
+    y: i8,
+    /// Fighters in join order
+    fighters: Vec<Fighter>,
+}
+
+impl Fight {
+    /// Boss damage was last applied at this slot
+    const LAST_UPDATE_SLOT: u64 = CLOCK_SLOT - 10;
+
+    /// A boss room below the entrance with `boss_hp` left and no fighters yet
+    fn new(boss_hp: u64) -> Self {
+        Self::at(1, 4, 6, boss_hp)
+    }
+
+    fn at(floor: u8, x: i8, y: i8, boss_hp: u64) -> Self {
+        let mut runtime = TestRuntime::new();
+        let caller = Pubkey::new_unique();
+        runtime.set_account(caller, TestAccount::wallet(1_000_000_000));
+
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        runtime.set_account(
+            global,
+            TestAccount::program_owned(&GlobalAccount {
+                season_seed: SEASON_SEED,
+                depth: 0,
+                skr_mint: Pubkey::new_unique(),
+                prize_pool: Pubkey::new_unique(),
+                admin: Pubkey::new_unique(),
+                end_slot: CLOCK_SLOT + GlobalAccount::SEASON_DURATION_SLOTS,
+                jobs_completed: 0,
+                config: GameConfig::default(),
+                layout: SeasonLayout::default(),
+                bump: global_bump,
+            }),
+        );
+
+        let (room, room_bump) = RoomAccount::find_address(SEASON_SEED, floor, x, y);
+        runtime.set_account(
+            room,
+            TestAccount::program_owned(&RoomAccount {
+                floor,
+                x,
+                y,
+                season_seed: SEASON_SEED,
+                center_type: CENTER_BOSS,
+                center_id: 1,
+                boss_max_hp: boss_hp,
+                boss_current_hp: boss_hp,
+                boss_last_update_slot: Self::LAST_UPDATE_SLOT,
+                bump: room_bump,
+                ..RoomAccount::default()
+            }),
+        );
+
+        // The first join creates the roster.
+        let (boss_roster, roster_bump) = Pubkey::find_program_address(
+            &[BossRoster::SEED_PREFIX, room.as_ref()],
+            &chaindepth::ID,
+        );
+        runtime.set_account(
+            boss_roster,
+            TestAccount::program_owned(&BossRoster {
+                room,
+                bump: roster_bump,
+                ..BossRoster::default()
+            }),
+        );
+
+        Self {
+            runtime,
+            global,
+            room,
+            boss_roster,
+            caller,
+            floor,
+            x,
+            y,
+            fighters: Vec::new(),
+        }
+    }
+
+    /// Put a new player with `dps` in the fight, joined at `joined_slot`
+    fn join(&mut self, dps: u64, joined_slot: u64) -> Fighter {
+        self.join_buffed(dps, joined_slot, BuffBonus::default())
+    }
+
+    /// `join` for a fighter whose buff adds `buff.bonus` DPS until
+    /// `buff.expires_slot`
+    fn join_buffed(&mut self, dps: u64, joined_slot: u64, buff: BuffBonus) -> Fighter {
+        let wallet = Pubkey::new_unique();
+        self.runtime
+            .set_account(wallet, TestAccount::wallet(1_000_000_000));
+
+        let (boss_fight, boss_fight_bump) = Pubkey::find_program_address(
+            &[
+                BossFightAccount::SEED_PREFIX,
+                self.room.as_ref(),
+                wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let fight_account = BossFightAccount {
+            player: wallet,
+            room: self.room,
+            dps,
+            joined_slot,
+            buff_bonus: buff.bonus,
+            buff_expires_slot: buff.expires_slot,
+            bump: boss_fight_bump,
+        };
+        self.runtime
+            .set_account(boss_fight, TestAccount::program_owned(&fight_account));
+
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            player_account,
+            TestAccount::program_owned(&PlayerAccount {
+                owner: wallet,
+                current_room_x: self.x,
+                current_room_y: self.y,
+                active_jobs: Vec::new(),
+                jobs_completed: 0,
+                chests_looted: 0,
+                equipped_item_id: item_ids::IRON_SWORD,
+                total_score: 0,
+                current_run_start_slot: 0,
+                runs_extracted: 0,
+                last_extraction_slot: 0,
+                season_seed: SEASON_SEED,
+                bump: player_bump,
+                current_floor: self.floor,
+                boss_fight_room: self.room,
+                hp: PLAYER_MAX_HP,
+                active_buff_id: 0,
+                buff_expires_slot: 0,
+                loot_protected: false,
+            }),
+        );
+
+        let (inventory, inventory_bump) = Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            inventory,
+            TestAccount::program_owned(&InventoryAccount {
+                owner: wallet,
+                items: Vec::new(),
+                bump: inventory_bump,
+            }),
+        );
+
+        let (presence, presence_bump) = presence_address(wallet, self.floor, self.x, self.y);
+        let mut fighting = RoomPresence {
+            player: wallet,
+            season_seed: SEASON_SEED,
+            room_floor: self.floor,
+            room_x: self.x,
+            room_y: self.y,
+            skin_id: 0,
+            equipped_item_id: item_ids::IRON_SWORD,
+            activity: RoomPresence::ACTIVITY_IDLE,
+            activity_direction: 0,
+            is_current: true,
+            bump: presence_bump,
+        };
+        fighting.set_boss_fight();
+        self.runtime
+            .set_account(presence, TestAccount::program_owned(&fighting));
+
+        // The test runtime cannot create accounts, so `leave_boss_fight`
+        // finds an empty respawn presence already there.
+        let layout = SeasonLayout::default();
+        let (respawn_presence, _) =
+            presence_address(wallet, self.floor, layout.start_x, layout.start_y);
+        if respawn_presence != presence {
+            self.runtime.set_account(
+                respawn_presence,
+                TestAccount::program_owned(&unused_presence()),
+            );
+        }
+
+        let mut room = self.room_account();
+        let mut roster = self.roster();
+        roster
+            .add(room.boss_fighter_count, fight_account.fighter())
+            .unwrap();
+        self.runtime
+            .set_account(self.boss_roster, TestAccount::program_owned(&roster));
+        room.boss_total_dps += dps + buff.bonus;
+        room.boss_fighter_count += 1;
+        if buff.bonus > 0 {
+            let (address, bump) = self.buff_schedule_address();
+            let mut schedule = match self.buff_schedule() {
+                Some(_) => self.runtime.fetch(&address),
+                None => BuffSchedule {
+                    room: self.room,
+                    target: BUFF_TARGET_BOSS,
+                    bump,
+                    ..BuffSchedule::default()
+                },
+            };
+            schedule
+                .add_bonus(
+                    &mut room.boss_buffs_scheduled,
+                    buff.bonus,
+                    buff.expires_slot,
+                )
+                .unwrap();
+            self.runtime
+                .set_account(address, TestAccount::program_owned(&schedule));
+        }
+        self.runtime
+            .set_account(self.room, TestAccount::program_owned(&room));
+
+        let fighter = Fighter {
+            wallet,
+            player_account,
+            boss_fight,
+            presence,
+            inventory,
+            respawn_presence,
+        };
+        self.fighters.push(fighter);
+        fighter
+    }
+
+    fn buff_schedule_address(&self) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                BuffSchedule::SEED_PREFIX,
+                self.room.as_ref(),
+                &[BUFF_TARGET_BOSS],
+            ],
+            &chaindepth::ID,
+        )
+    }
+
+    /// The fight's buff schedule, once a buffed fighter has created it
+    fn buff_schedule(&self) -> Option<Pubkey> {
+        let address = self.buff_schedule_address().0;
+        (!self.runtime.account(&address).data.is_empty()).then_some(address)
+    }
+
+    fn tick(&mut self) -> Result<(), ProgramError> {
+        let buff_schedule = self.buff_schedule();
+        self.tick_with_schedule(buff_schedule)
+    }
+
+    fn tick_with_schedule(&mut self, buff_schedule: Option<Pubkey>) -> Result<(), ProgramError> {
+        self.runtime.process(
+            accounts::TickBossFight {
+                caller: self.caller,
+                global: self.global,
+                room: self.room,
+                buff_schedule,
+                boss_roster: self.boss_roster,
+            },
+            instruction::TickBossFight {},
+        )
+    }
+
+    fn leave(&mut self, fighter: Fighter) -> Result<(), ProgramError> {
+        let buff_schedule = self.buff_schedule();
+        self.runtime.process(
+            accounts::LeaveBossFight {
+                authority: fighter.wallet,
+                player: fighter.wallet,
+                global: self.global,
+                player_account: fighter.player_account,
+                room: self.room,
+                room_presence: fighter.presence,
+                boss_fight: fighter.boss_fight,
+                boss_roster: self.boss_roster,
+                inventory: fighter.inventory,
+                respawn_presence: fighter.respawn_presence,
+                buff_schedule,
+                session_authority: None,
+                system_program: system_program::ID,
+            },
+            instruction::LeaveBossFight {},
+        )
+    }
+
+    fn room_account(&self) -> RoomAccount {
+        self.runtime.fetch(&self.room)
+    }
+
+    fn roster(&self) -> BossRoster {
+        self.runtime.fetch(&self.boss_roster)
+    }
+
+    fn player(&self, fighter: Fighter) -> PlayerAccount {
+        self.runtime.fetch(&fighter.player_account)
+    }
+}
+
+/// A presence as `init_if_needed` leaves it before the handler fills it in
+fn unused_presence() -> RoomPresence {
+    RoomPresence {
+        player: Pubkey::default(),
+        season_seed: 0,
+        room_floor: 0,
+        room_x: 0,
+        room_y: 0,
+        skin_id: 0,
+        equipped_item_id: 0,
+        activity: 0,
+        activity_direction: 0,
+        is_current: false,
+        bump: 0,
+    }
+}
+
+fn presence_address(wallet: Pubkey, floor: u8, x: i8, y: i8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            RoomPresence::SEED_PREFIX,
+            &SEASON_SEED.to_le_bytes(),
+            &[floor],
+            &[x as u8],
+            &[y as u8],
+            wallet.as_ref(),
+        ],
+        &chaindepth::ID,
+    )
+}
+
+#[test]
+fn killing_tick_records_the_one_fighter_whose_strike_took_the_last_hp() {
+    // 4 DPS kills 30 HP in the 8th slot with 2 HP left: the earlier
+    // joiner strikes first in that slot and takes it with 3 DPS.
+    let mut fight = Fight::new(30);
+    let first = fight.join(3, Fight::LAST_UPDATE_SLOT - 10);
+    fight.join(1, Fight::LAST_UPDATE_SLOT);
+    fight.tick().unwrap();
+
+    let room = fight.room_account();
+    assert!(room.boss_defeated);
+    assert_eq!(room.boss_defeated_slot, Fight::LAST_UPDATE_SLOT + 8);
+    assert_eq!(room.boss_last_hitter, first.wallet);
+
+    // With 4 HP left going into the 8th slot, the first strike leaves 1 HP
+    // and the later joiner takes it.
+    let mut fight = Fight::new(32);
+    fight.join(3, Fight::LAST_UPDATE_SLOT - 10);
+    let second = fight.join(1, Fight::LAST_UPDATE_SLOT);
+    fight.tick().unwrap();
+
+    let room = fight.room_account();
+    assert!(room.boss_defeated);
+    assert_eq!(room.boss_last_hitter, second.wallet);
+}
+
+#[test]
+fn killing_tick_settles_a_full_fight_from_the_roster() {
+    // Every fighter strikes 1 HP per slot; the 10 slots since the last
+    // update take 240 HP, so the last of them in strike order lands the kill.
+    let mut fight = Fight::new(10 * MAX_BOSS_FIGHTERS as u64);
+    for slot in 0..MAX_BOSS_FIGHTERS as u64 {
+        fight.join(1, Fight::LAST_UPDATE_SLOT - MAX_BOSS_FIGHTERS as u64 + slot);
+    }
+    let last_joiner = *fight.fighters.last().unwrap();
+    fight.tick().unwrap();
+
+    let room = fight.room_account();
+    assert!(room.boss_defeated);
+    assert_eq!(room.boss_defeated_slot, CLOCK_SLOT);
+    assert_eq!(room.boss_last_hitter, last_joiner.wallet);
+    assert_eq!(room.boss_fighter_count as usize, MAX_BOSS_FIGHTERS);
+    assert_eq!(fight.roster().current_fighters().len(), MAX_BOSS_FIGHTERS);
+}
+
+#[test]
+fn leave_takes_the_fighters_dps_and_live_buff_bonus_off_the_boss() {
+    let mut fight = Fight::new(300);
+    let buffed = fight.join_buffed(
+        3,
+        Fight::LAST_UPDATE_SLOT,
+        BuffBonus {
+            bonus: 2,
+            expires_slot: CLOCK_SLOT + 50,
+        },
+    );
+    // This bonus wears off mid-way and leaves the total on its own.
+    let worn_off = fight.join_buffed(
+        1,
+        Fight::LAST_UPDATE_SLOT,
+        BuffBonus {
+            bonus: 4,
+            expires_slot: Fight::LAST_UPDATE_SLOT + 5,
+        },
+    );
+    assert_eq!(fight.room_account().boss_total_dps, 10);
+
+    fight.leave(buffed).unwrap();
+    let staying: BossFightAccount = fight.runtime.fetch(&worn_off.boss_fight);
+    assert_eq!(fight.roster().current_fighters(), vec![staying.fighter()]);
+
+    // 5 slots at 10 DPS, then 5 at 6 once the worn-off bonus dropped out.
+    let room = fight.room_account();
+    assert_eq!(room.boss_current_hp, 300 - 50 - 30);
+    assert_eq!(room.boss_fighter_count, 1);
+    assert_eq!(room.boss_total_dps, 1);
+    assert_eq!(room.boss_buffs_scheduled, 0);
+    let schedule: BuffSchedule = fight.runtime.fetch(&fight.buff_schedule().unwrap());
+    assert!(schedule.bonuses.iter().all(|entry| entry.bonus == 0));
+    assert!(!room.boss_defeated);
+
+    assert!(fight.runtime.account(&buffed.boss_fight).is_closed());
+    let player = fight.player(buffed);
+    assert!(!player.is_in_boss_fight());
+    assert!(player.is_at_room(1, 4, 6));
+    // One counter-attack in 10 slots.
+    assert_eq!(player.hp, PLAYER_MAX_HP - 2);
+    let presence: RoomPresence = fight.runtime.fetch(&buffed.presence);
+    assert_eq!(presence.activity, RoomPresence::ACTIVITY_IDLE);
+    assert!(presence.is_current);
+    // The unused respawn presence is closed again.
+    assert!(fight.runtime.account(&buffed.respawn_presence).is_closed());
+
+    fight.leave(worn_off).unwrap();
+
+    let room = fight.room_account();
+    assert_eq!(room.boss_current_hp, 220);
+    assert_eq!(room.boss_fighter_count, 0);
+    assert_eq!(room.boss_total_dps, 0);
+}
+
+#[test]
+fn tick_needs_the_buff_schedule_until_the_last_bonus_wears_off() {
+    let mut fight = Fight::new(300);
+    fight.join_buffed(
+        3,
+        Fight::LAST_UPDATE_SLOT,
+        BuffBonus {
+            bonus: 2,
+            expires_slot: Fight::LAST_UPDATE_SLOT + 5,
+        },
+    );
+    let buff_schedule = fight.buff_schedule();
+    assert!(buff_schedule.is_some());
+
+    // Without it the bonus would keep counting after it wore off.
+    assert_eq!(
+        fight.tick_with_schedule(None).unwrap_err(),
+        program_error(ChainDepthError::BuffScheduleMissing)
+    );
+
+    fight.tick_with_schedule(buff_schedule).unwrap();
+    let room = fight.room_account();
+    assert_eq!(room.boss_current_hp, 300 - 5 * 5 - 5 * 3);
+    assert_eq!(room.boss_buffs_scheduled, 0);
+
+    fight.tick_with_schedule(None).unwrap();
+}
+
+#[test]
+fn leave_that_kills_the_boss_settles_the_kill_and_forfeits_the_loot() {
+    let mut fight = Fight::new(30);
+    let first = fight.join(3, Fight::LAST_UPDATE_SLOT);
+    let second = fight.join(1, Fight::LAST_UPDATE_SLOT);
+
+    // 4 DPS since the last update had the boss dead before this leave.
+    fight.leave(first).unwrap();
+    let room = fight.room_account();
+    assert!(room.boss_defeated);
+    assert_eq!(room.boss_defeated_slot, Fight::LAST_UPDATE_SLOT + 8);
+    assert_eq!(room.boss_last_hitter, first.wallet);
+    assert_eq!(room.boss_fighter_count, 2);
+    assert!(fight.runtime.account(&first.boss_fight).is_closed());
+    assert!(!fight.player(first).is_in_boss_fight());
+
+    // After the kill, leaving only forfeits the loot.
+    fight.leave(second).unwrap();
+    let room = fight.room_account();
+    assert_eq!(room.boss_fighter_count, 2);
+    assert_eq!(room.boss_last_hitter, first.wallet);
+    assert_eq!(fight.roster().current_fighters().len(), 2);
+    assert!(!fight.player(second).is_in_boss_fight());
+}
+
+#[test]
+fn fighters_cannot_move_or_exit_the_dungeon() {
+    let layout = SeasonLayout::default();
+    let mut fight = Fight::at(ENTRANCE_FLOOR, layout.start_x, layout.start_y, 300);
+    let mut room = fight.room_account();
+    room.walls[DIRECTION_SOUTH as usize] = WALL_ENTRANCE_STAIRS;
+    fight
+        .runtime
+        .set_account(fight.room, TestAccount::program_owned(&room));
+    let fighter = fight.join(1, Fight::LAST_UPDATE_SLOT);
+    let player_account = fight.runtime.account(&fighter.player_account);
+
+    let exit = accounts::ExitDungeon {
+        authority: fighter.wallet,
+        player: fighter.wallet,
+        global: fight.global,
+        player_account: fighter.player_account,
+        room: fight.room,
+        inventory: fighter.inventory,
+        room_presence: fighter.presence,
+        session_authority: None,
+        system_program: system_program::ID,
+    };
+    assert_eq!(
+        fight
+            .runtime
+            .process(exit, instruction::ExitDungeon {})
+            .unwrap_err(),
+        program_error(ChainDepthError::InBossFight)
+    );
+
+    // Every account `move_player` could create is already there, so the
+    // move gets as far as the lock.
+    let (new_x, new_y) = (layout.start_x, layout.start_y + 1);
+    let (profile, profile_bump) = Pubkey::find_program_address(
+        &[PlayerProfile::SEED_PREFIX, fighter.wallet.as_ref()],
+        &chaindepth::ID,
+    );
+    fight.runtime.set_account(
+        profile,
+        TestAccount::program_owned(&PlayerProfile {
+            owner: fighter.wallet,
+            skin_id: 0,
+            display_name: String::new(),
+            starter_pickaxe_granted: true,
+            bump: profile_bump,
+        }),
+    );
+    let (target_room, _) = RoomAccount::find_address(SEASON_SEED, ENTRANCE_FLOOR, new_x, new_y);
+    fight.runtime.set_account(
+        target_room,
+        TestAccount::program_owned(&RoomAccount::default()),
+    );
+    let (target_presence, _) = presence_address(fighter.wallet, ENTRANCE_FLOOR, new_x, new_y);
+    fight.runtime.set_account(
+        target_presence,
+        TestAccount::program_owned(&unused_presence()),
+    );
+
+    let step = accounts::MovePlayer {
+        authority: fighter.wallet,
+        player: fighter.wallet,
+        global: fight.global,
+        player_account: fighter.player_account,
+        profile,
+        current_room: fight.room,
+        target_room,
+        current_presence: fighter.presence,
+        target_presence,
+        session_authority: None,
+        system_program: system_program::ID,
+    };
+    assert_eq!(
+        fight
+            .runtime
+            .process(step, instruction::MovePlayer { new_x, new_y })
+            .unwrap_err(),
+        program_error(ChainDepthError::InBossFight)
+    );
+
+    assert_eq!(
+        fight.runtime.account(&fighter.player_account),
+        player_account
+    );
+    assert!(!fight.runtime.account(&fighter.presence).is_closed());
+}
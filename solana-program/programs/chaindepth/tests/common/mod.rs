@@ -0,0 +1,315 @@
+//! In-process runtime for driving real program instructions from `cargo test`.
+//!
+//! Accounts are serialized into the same input buffer layout the loader hands
+//! the on-chain entrypoint, so account constraints, `close` and account
+//! resizing behave as they do on chain. The clock and rent sysvars come from
+//! syscall stubs. Off chain, Anchor cannot make CPIs or log events, so
+//! instructions that create accounts need them to exist already, and events
+//! are not observable here; `tests/chaindepth.ts` covers both on a validator.
+//! `TestRuntime::run` drives a handler directly for instructions whose `init`
+//! or token transfer would otherwise stop them before the logic under test.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::entrypoint::{deserialize, BPF_ALIGN_OF_U128, NON_DUP_MARKER};
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::{bpf_loader_upgradeable, system_program};
+use anchor_lang::{InstructionData, Space, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_option::COption;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use chaindepth::errors::ChainDepthError;
+use solana_sysvar::program_stubs::{set_syscall_stubs, SyscallStubs};
+
+/// Slot and unix timestamp every instruction sees
+pub const CLOCK_SLOT: u64 = 10_000;
+pub const CLOCK_UNIX_TIMESTAMP: i64 = 1_700_000_000;
+
+/// `code` as a handler returns it
+pub fn error(code: ChainDepthError) -> Error {
+    code.into()
+}
+
+/// `code` as `TestRuntime::process` reports it
+pub fn program_error(code: ChainDepthError) -> ProgramError {
+    error(code).into()
+}
+
+const NATIVE_LOADER_ID: Pubkey = pubkey!("NativeLoader1111111111111111111111111111111");
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TestAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    /// A rent-exempt account owned by the program holding `account`,
+    /// sized for its `InitSpace` like `init` would allocate it
+    pub fn program_owned<T: AccountSerialize + Space>(account: &T) -> Self {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.resize(8 + T::INIT_SPACE, 0);
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: chaindepth::ID,
+            data,
+            executable: false,
+        }
+    }
+
+    /// A `T` account as `init` leaves it: the discriminator, then zeroes
+    pub fn initialized<T: Discriminator + Space>() -> Self {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.resize(8 + T::INIT_SPACE, 0);
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: chaindepth::ID,
+            data,
+            executable: false,
+        }
+    }
+
+    /// A rent-exempt, initialized SPL mint
+    pub fn mint(decimals: u8) -> Self {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: spl_token::ID,
+            data,
+            executable: false,
+        }
+    }
+
+    /// A rent-exempt SPL token account holding `amount` of `mint`
+    pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        Self {
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: spl_token::ID,
+            data,
+            executable: false,
+        }
+    }
+
+    pub fn wallet(lamports: u64) -> Self {
+        Self {
+            lamports,
+            owner: system_program::ID,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lamports == 0 && self.owner == system_program::ID && self.data.is_empty()
+    }
+}
+
+/// Accounts of the cluster under test. Instructions apply atomically: a
+/// failed instruction leaves every account as it was.
+pub struct TestRuntime {
+    accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl TestRuntime {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(RuntimeStubs));
+        });
+
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            chaindepth::ID,
+            TestAccount {
+                lamports: 1,
+                owner: bpf_loader_upgradeable::ID,
+                data: Vec::new(),
+                executable: true,
+            },
+        );
+        accounts.insert(
+            system_program::ID,
+            TestAccount {
+                lamports: 1,
+                owner: NATIVE_LOADER_ID,
+                data: Vec::new(),
+                executable: true,
+            },
+        );
+        accounts.insert(
+            spl_token::ID,
+            TestAccount {
+                lamports: 1,
+                owner: bpf_loader_upgradeable::ID,
+                data: Vec::new(),
+                executable: true,
+            },
+        );
+        Self { accounts }
+    }
+
+    pub fn set_account(&mut self, key: Pubkey, account: TestAccount) {
+        self.accounts.insert(key, account);
+    }
+
+    /// The account at `key`; missing accounts read as empty system accounts
+    pub fn account(&self, key: &Pubkey) -> TestAccount {
+        self.accounts
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| TestAccount::wallet(0))
+    }
+
+    pub fn fetch<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        T::try_deserialize(&mut self.account(key).data.as_slice()).unwrap()
+    }
+
+    pub fn process(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        args: impl InstructionData,
+    ) -> std::result::Result<(), ProgramError> {
+        self.process_with_remaining_accounts(accounts, &[], args)
+    }
+
+    /// `process` with `remaining_accounts` appended after the named accounts
+    pub fn process_with_remaining_accounts(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        remaining_accounts: &[AccountMeta],
+        args: impl InstructionData,
+    ) -> std::result::Result<(), ProgramError> {
+        let mut metas = accounts.to_account_metas(None);
+        metas.extend_from_slice(remaining_accounts);
+        let mut input = self.serialize_input(&metas, &args.data());
+
+        // SAFETY: `input` is 8-byte aligned and laid out as the loader does it,
+        // and outlives the account infos borrowed from it.
+        let (program_id, infos, data) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        chaindepth::entry(program_id, &infos, data)?;
+        self.store(&infos);
+        Ok(())
+    }
+
+    /// Runs `handler` on the account infos the loader would pass for `metas`,
+    /// without the entrypoint's account validation. The handler builds its
+    /// `Context` from them; account changes are kept only if it succeeds.
+    pub fn run<T>(
+        &mut self,
+        metas: &[AccountMeta],
+        handler: impl for<'a> FnOnce(&'a [AccountInfo<'a>]) -> anchor_lang::Result<T>,
+    ) -> std::result::Result<T, ProgramError> {
+        let mut input = self.serialize_input(metas, &[]);
+
+        // SAFETY: as in `process_with_remaining_accounts`.
+        let (_, infos, _) = unsafe { deserialize(input.as_mut_ptr() as *mut u8) };
+        let output = handler(&infos)?;
+        self.store(&infos);
+        Ok(output)
+    }
+
+    fn store(&mut self, infos: &[AccountInfo]) {
+        for info in infos {
+            self.accounts.insert(
+                *info.key,
+                TestAccount {
+                    lamports: info.lamports(),
+                    owner: *info.owner,
+                    data: info.data.borrow().to_vec(),
+                    executable: info.executable,
+                },
+            );
+        }
+    }
+
+    fn serialize_input(&self, metas: &[AccountMeta], data: &[u8]) -> Vec<u64> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+        for (index, meta) in metas.iter().enumerate() {
+            if let Some(first) = metas[..index].iter().position(|m| m.pubkey == meta.pubkey) {
+                buffer.push(first as u8);
+                buffer.extend_from_slice(&[0; 7]);
+                continue;
+            }
+
+            // The runtime grants an account the union of its metas' privileges.
+            let same_key = || metas.iter().filter(|m| m.pubkey == meta.pubkey);
+            let account = self.account(&meta.pubkey);
+            buffer.push(NON_DUP_MARKER);
+            buffer.push(same_key().any(|m| m.is_signer) as u8);
+            buffer.push(same_key().any(|m| m.is_writable) as u8);
+            buffer.push(account.executable as u8);
+            buffer.extend_from_slice(&[0; 4]);
+            buffer.extend_from_slice(meta.pubkey.as_ref());
+            buffer.extend_from_slice(account.owner.as_ref());
+            buffer.extend_from_slice(&account.lamports.to_le_bytes());
+            buffer.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            buffer.extend_from_slice(&account.data);
+            buffer.resize(buffer.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            buffer.resize(buffer.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            buffer.extend_from_slice(&u64::MAX.to_le_bytes());
+        }
+        buffer.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        buffer.extend_from_slice(data);
+        buffer.extend_from_slice(chaindepth::ID.as_ref());
+
+        let mut input = vec![0u64; buffer.len().div_ceil(8)];
+        for (word, chunk) in input.iter_mut().zip(buffer.chunks(8)) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *word = u64::from_le_bytes(bytes);
+        }
+        input
+    }
+}
+
+struct RuntimeStubs;
+
+impl SyscallStubs for RuntimeStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: CLOCK_SLOT,
+            unix_timestamp: CLOCK_UNIX_TIMESTAMP,
+            ..Clock::default()
+        };
+        // SAFETY: the sysvar getter passes a pointer to a `Clock`.
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the sysvar getter passes a pointer to a `Rent`.
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+}
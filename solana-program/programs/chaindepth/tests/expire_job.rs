@@ -0,0 +1,390 @@
+//! `expire_job`: which presence the crank resets, then the instruction's
+//! checks on its five-accounts-per-helper `remaining_accounts` run on the test
+//! runtime. The runtime cannot run the escrow refund, so a valid batch is not
+//! run to completion here.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_spl::token::spl_token;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::instructions::expire_job::EXPIRE_JOB_ACCOUNTS_PER_HELPER;
+use chaindepth::state::{
+    item_ids, ActiveJob, GameConfig, GlobalAccount, HelperStake, PlayerAccount, RoomAccount,
+    RoomPresence, SeasonLayout, DIRECTION_EAST, DIRECTION_NORTH, PLAYER_MAX_HP, WALL_RUBBLE,
+};
+use chaindepth::{accounts, instruction};
+use common::{program_error, TestAccount, TestRuntime, CLOCK_SLOT};
+
+const SEASON_SEED: u64 = 1;
+const NORTH: usize = DIRECTION_NORTH as usize;
+
+fn presence_at(floor: u8, x: i8, y: i8) -> RoomPresence {
+    RoomPresence {
+        player: Pubkey::new_unique(),
+        season_seed: SEASON_SEED,
+        room_floor: floor,
+        room_x: x,
+        room_y: y,
+        skin_id: 0,
+        equipped_item_id: 0,
+        activity: RoomPresence::ACTIVITY_IDLE,
+        activity_direction: 255,
+        is_current: true,
+        bump: 0,
+    }
+}
+
+#[test]
+fn only_a_presence_still_on_the_expired_job_is_reset() {
+    let mut presence = presence_at(1, 4, 6);
+    presence.set_door_job(DIRECTION_NORTH);
+    assert!(presence.is_on_door_job(1, 4, 6, DIRECTION_NORTH));
+
+    // Another door of the same room, or the same door one floor up.
+    assert!(!presence.is_on_door_job(1, 4, 6, DIRECTION_EAST));
+    assert!(!presence.is_on_door_job(0, 4, 6, DIRECTION_NORTH));
+
+    // Moved on to a boss fight in the room the job is in.
+    presence.set_boss_fight();
+    assert!(!presence.is_on_door_job(1, 4, 6, DIRECTION_NORTH));
+    presence.set_idle();
+    assert!(!presence.is_on_door_job(1, 4, 6, DIRECTION_NORTH));
+}
+
+/// A floor-1 room with an unfinished rubble job on its north door
+struct Job {
+    runtime: TestRuntime,
+    global: Pubkey,
+    skr_mint: Pubkey,
+    room: Pubkey,
+    caller: Pubkey,
+}
+
+/// The five accounts `expire_job` takes for one helper
+#[derive(Clone, Copy)]
+struct Helper {
+    stake: Pubkey,
+    player_account: Pubkey,
+    token_account: Pubkey,
+    wallet: Pubkey,
+    presence: Pubkey,
+}
+
+impl Helper {
+    fn metas(&self) -> Vec<AccountMeta> {
+        [
+            self.stake,
+            self.player_account,
+            self.token_account,
+            self.wallet,
+            self.presence,
+        ]
+        .into_iter()
+        .map(|key| AccountMeta::new(key, false))
+        .collect()
+    }
+}
+
+impl Job {
+    const FLOOR: u8 = 1;
+    const X: i8 = 4;
+    const Y: i8 = 6;
+
+    /// A job started at `start_slot` that nobody finished
+    fn new(start_slot: u64) -> Self {
+        let mut runtime = TestRuntime::new();
+        let caller = Pubkey::new_unique();
+        runtime.set_account(caller, TestAccount::wallet(1_000_000_000));
+
+        let skr_mint = Pubkey::new_unique();
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        runtime.set_account(
+            global,
+            TestAccount::program_owned(&GlobalAccount {
+                season_seed: SEASON_SEED,
+                depth: 0,
+                skr_mint,
+                prize_pool: Pubkey::new_unique(),
+                admin: Pubkey::new_unique(),
+                end_slot: CLOCK_SLOT + GlobalAccount::SEASON_DURATION_SLOTS,
+                jobs_completed: 0,
+                config: GameConfig::default(),
+                layout: SeasonLayout::default(),
+                bump: global_bump,
+            }),
+        );
+
+        let (room, room_bump) =
+            RoomAccount::find_address(SEASON_SEED, Self::FLOOR, Self::X, Self::Y);
+        let mut room_account = RoomAccount {
+            floor: Self::FLOOR,
+            x: Self::X,
+            y: Self::Y,
+            season_seed: SEASON_SEED,
+            bump: room_bump,
+            ..RoomAccount::default()
+        };
+        room_account.walls[NORTH] = WALL_RUBBLE;
+        room_account.start_slot[NORTH] = start_slot;
+        room_account.progress_slot[NORTH] = start_slot;
+        room_account.base_slots[NORTH] = RoomAccount::BASE_SLOTS_DEPTH_0;
+        runtime.set_account(room, TestAccount::program_owned(&room_account));
+        runtime.set_account(
+            Self::escrow(room),
+            TestAccount::token_account(skr_mint, Self::escrow(room), 0),
+        );
+
+        Self {
+            runtime,
+            global,
+            skr_mint,
+            room,
+            caller,
+        }
+    }
+
+    fn escrow(room: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"escrow", room.as_ref(), &[DIRECTION_NORTH]],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn room_account(&self) -> RoomAccount {
+        self.runtime.fetch(&self.room)
+    }
+
+    /// A helper staked on the room's `direction` door who is still standing
+    /// in the room
+    fn add_helper(&mut self, direction: u8) -> Helper {
+        let wallet = Pubkey::new_unique();
+        self.runtime
+            .set_account(wallet, TestAccount::wallet(1_000_000_000));
+
+        let (stake, stake_bump) = Pubkey::find_program_address(
+            &[
+                HelperStake::SEED_PREFIX,
+                self.room.as_ref(),
+                &[direction],
+                wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let helper_stake = HelperStake {
+            player: wallet,
+            room: self.room,
+            direction,
+            amount: RoomAccount::STAKE_AMOUNT,
+            joined_slot: 0,
+            work_rate: 100,
+            buff_bonus: 0,
+            buff_expires_slot: 0,
+            bump: stake_bump,
+        };
+        let mut room_account = self.room_account();
+        let dir_idx = direction as usize;
+        room_account
+            .add_helper_work(dir_idx, &helper_stake, None)
+            .unwrap();
+        room_account.helper_counts[dir_idx] += 1;
+        room_account.total_staked[dir_idx] += RoomAccount::STAKE_AMOUNT;
+        self.runtime
+            .set_account(self.room, TestAccount::program_owned(&room_account));
+        self.runtime
+            .set_account(stake, TestAccount::program_owned(&helper_stake));
+
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            player_account,
+            TestAccount::program_owned(&PlayerAccount {
+                owner: wallet,
+                current_floor: Self::FLOOR,
+                current_room_x: Self::X,
+                current_room_y: Self::Y,
+                active_jobs: vec![ActiveJob {
+                    room_x: Self::X,
+                    room_y: Self::Y,
+                    direction,
+                }],
+                jobs_completed: 0,
+                chests_looted: 0,
+                equipped_item_id: item_ids::BRONZE_PICKAXE,
+                boss_fight_room: Pubkey::default(),
+                hp: PLAYER_MAX_HP,
+                active_buff_id: 0,
+                buff_expires_slot: 0,
+                loot_protected: false,
+                total_score: 0,
+                current_run_start_slot: 0,
+                runs_extracted: 0,
+                last_extraction_slot: 0,
+                season_seed: SEASON_SEED,
+                bump: player_bump,
+            }),
+        );
+
+        let token_account = Pubkey::new_unique();
+        self.runtime.set_account(
+            token_account,
+            TestAccount::token_account(self.skr_mint, wallet, 0),
+        );
+
+        let (presence, presence_bump) = Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &SEASON_SEED.to_le_bytes(),
+                &[Self::FLOOR],
+                &[Self::X as u8],
+                &[Self::Y as u8],
+                wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let mut room_presence = RoomPresence {
+            player: wallet,
+            bump: presence_bump,
+            ..presence_at(Self::FLOOR, Self::X, Self::Y)
+        };
+        room_presence.set_door_job(direction);
+        self.runtime
+            .set_account(presence, TestAccount::program_owned(&room_presence));
+
+        Helper {
+            stake,
+            player_account,
+            token_account,
+            wallet,
+            presence,
+        }
+    }
+
+    fn expire_job(&mut self, remaining_accounts: &[AccountMeta]) -> Result<(), ProgramError> {
+        let accounts = accounts::ExpireJob {
+            caller: self.caller,
+            global: self.global,
+            room: self.room,
+            escrow: Self::escrow(self.room),
+            buff_schedule: None,
+            token_program: spl_token::ID,
+        };
+        self.runtime.process_with_remaining_accounts(
+            accounts,
+            remaining_accounts,
+            instruction::ExpireJob {
+                direction: DIRECTION_NORTH,
+            },
+        )
+    }
+}
+
+#[test]
+fn expire_job_takes_whole_groups_of_helper_accounts() {
+    let mut job = Job::new(0);
+    let helper = job.add_helper(DIRECTION_NORTH);
+    let room_before = job.runtime.account(&job.room);
+
+    assert_eq!(
+        job.expire_job(&[]).unwrap_err(),
+        program_error(ChainDepthError::InvalidExpireJobAccounts)
+    );
+
+    let metas = helper.metas();
+    assert_eq!(metas.len(), EXPIRE_JOB_ACCOUNTS_PER_HELPER);
+    assert_eq!(
+        job.expire_job(&metas[..EXPIRE_JOB_ACCOUNTS_PER_HELPER - 1])
+            .unwrap_err(),
+        program_error(ChainDepthError::InvalidExpireJobAccounts)
+    );
+    let mut one_and_a_bit = metas.clone();
+    one_and_a_bit.push(metas[0].clone());
+    assert_eq!(
+        job.expire_job(&one_and_a_bit).unwrap_err(),
+        program_error(ChainDepthError::InvalidExpireJobAccounts)
+    );
+    assert_eq!(job.runtime.account(&job.room), room_before);
+}
+
+#[test]
+fn expire_job_waits_for_the_ttl_after_the_base_time() {
+    let config = GameConfig::default();
+    // Expires at start + base slots + TTL; one slot short of that here.
+    let start_slot = CLOCK_SLOT + 1 - RoomAccount::BASE_SLOTS_DEPTH_0 - config.job_ttl_slots;
+    let mut job = Job::new(start_slot);
+    let helper = job.add_helper(DIRECTION_NORTH);
+
+    assert_eq!(
+        job.expire_job(&helper.metas()).unwrap_err(),
+        program_error(ChainDepthError::JobNotExpired)
+    );
+    assert!(!job.runtime.account(&helper.stake).is_closed());
+}
+
+#[test]
+fn expire_job_checks_each_helper_group_belongs_to_the_job() {
+    let mut job = Job::new(0);
+    let helper = job.add_helper(DIRECTION_NORTH);
+    let other = job.add_helper(DIRECTION_NORTH);
+    let east = job.add_helper(DIRECTION_EAST);
+    let room_before = job.runtime.account(&job.room);
+
+    let mismatched = [
+        // A stake on another door of the room.
+        east.metas(),
+        // Someone else's player account, wallet or token account.
+        Helper {
+            player_account: other.player_account,
+            ..helper
+        }
+        .metas(),
+        Helper {
+            wallet: other.wallet,
+            ..helper
+        }
+        .metas(),
+        Helper {
+            token_account: other.token_account,
+            ..helper
+        }
+        .metas(),
+        // The presence of another helper.
+        Helper {
+            presence: other.presence,
+            ..helper
+        }
+        .metas(),
+    ];
+    for metas in mismatched {
+        assert_eq!(
+            job.expire_job(&metas).unwrap_err(),
+            program_error(ChainDepthError::InvalidExpireJobAccounts)
+        );
+    }
+
+    assert_eq!(job.runtime.account(&job.room), room_before);
+    assert!(!job.runtime.account(&helper.stake).is_closed());
+}
+
+#[test]
+fn expire_job_checks_the_presence_is_in_the_helpers_current_room() {
+    let mut job = Job::new(0);
+    let helper = job.add_helper(DIRECTION_NORTH);
+
+    // The helper walked on; their presence in the job room is no longer current.
+    let mut player: PlayerAccount = job.runtime.fetch(&helper.player_account);
+    player.current_room_x = Job::X + 1;
+    job.runtime
+        .set_account(helper.player_account, TestAccount::program_owned(&player));
+
+    assert_eq!(
+        job.expire_job(&helper.metas()).unwrap_err(),
+        program_error(ChainDepthError::InvalidExpireJobAccounts)
+    );
+}
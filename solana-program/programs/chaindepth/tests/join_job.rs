@@ -0,0 +1,467 @@
+//! `join_job` and `join_job_with_session` up to the door's helper cap, which
+//! grows with the room's depth (`GameConfig::max_helpers_for_depth`).
+//!
+//! Both handlers create the helper stake and move SKR into escrow through
+//! CPIs the in-process runtime cannot make, so these tests run them through
+//! `TestRuntime::run` with the created accounts already in place. A
+//! successful join stops at `take_helper_slot` (or its session twin), before
+//! the stake transfer.
+
+mod common;
+
+use anchor_lang::prelude::{
+    Account, AccountInfo, Context, Program, ProgramError, Pubkey, Signer, UncheckedAccount,
+};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{AccountsExit, ToAccountMetas};
+use anchor_spl::token::spl_token;
+use chaindepth::accounts;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::events::JobJoined;
+use chaindepth::instructions::join_job::{self, JoinJob, JoinJobBumps};
+use chaindepth::instructions::join_job_with_session::{
+    self, JoinJobWithSession, JoinJobWithSessionBumps,
+};
+use chaindepth::state::{
+    session_instruction_bits, GameConfig, GlobalAccount, HelperStake, InventoryAccount,
+    PlayerAccount, RoomAccount, RoomPresence, SeasonLayout, SessionAuthority, BARE_HANDS_WORK_RATE,
+    DIRECTION_NORTH, PLAYER_MAX_HP, WALL_RUBBLE,
+};
+use common::{program_error, TestAccount, TestRuntime, CLOCK_SLOT, CLOCK_UNIX_TIMESTAMP};
+
+const SEASON_SEED: u64 = 1;
+const NORTH: usize = DIRECTION_NORTH as usize;
+const TOKEN_BALANCE: u64 = 5 * RoomAccount::STAKE_AMOUNT;
+
+/// A player standing in the job's room with SKR to stake
+#[derive(Clone, Copy)]
+struct Joiner {
+    wallet: Pubkey,
+    player_account: Pubkey,
+    token_account: Pubkey,
+}
+
+/// A floor-1 room whose north rubble already has `helpers` on it
+struct Door {
+    runtime: TestRuntime,
+    global: Pubkey,
+    skr_mint: Pubkey,
+    room: Pubkey,
+}
+
+impl Door {
+    const FLOOR: u8 = 1;
+    const X: i8 = 4;
+    const Y: i8 = 6;
+
+    fn new(helpers: u32) -> Self {
+        let mut runtime = TestRuntime::new();
+
+        let skr_mint = Pubkey::new_unique();
+        runtime.set_account(skr_mint, TestAccount::mint(9));
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        runtime.set_account(
+            global,
+            TestAccount::program_owned(&GlobalAccount {
+                season_seed: SEASON_SEED,
+                depth: 0,
+                skr_mint,
+                prize_pool: Pubkey::new_unique(),
+                admin: Pubkey::new_unique(),
+                end_slot: CLOCK_SLOT + GlobalAccount::SEASON_DURATION_SLOTS,
+                jobs_completed: 0,
+                config: GameConfig::default(),
+                layout: SeasonLayout::default(),
+                bump: global_bump,
+            }),
+        );
+
+        let (room, room_bump) =
+            RoomAccount::find_address(SEASON_SEED, Self::FLOOR, Self::X, Self::Y);
+        let mut room_account = RoomAccount {
+            floor: Self::FLOOR,
+            x: Self::X,
+            y: Self::Y,
+            season_seed: SEASON_SEED,
+            bump: room_bump,
+            ..RoomAccount::default()
+        };
+        room_account.walls[NORTH] = WALL_RUBBLE;
+        room_account.helper_counts[NORTH] = helpers;
+        room_account.total_staked[NORTH] = u64::from(helpers) * RoomAccount::STAKE_AMOUNT;
+        room_account.total_work_rate[NORTH] = u64::from(helpers) * BARE_HANDS_WORK_RATE;
+        room_account.start_slot[NORTH] = CLOCK_SLOT - 10;
+        room_account.progress_slot[NORTH] = CLOCK_SLOT - 10;
+        room_account.base_slots[NORTH] = RoomAccount::BASE_SLOTS_DEPTH_0;
+        runtime.set_account(room, TestAccount::program_owned(&room_account));
+
+        Self {
+            runtime,
+            global,
+            skr_mint,
+            room,
+        }
+    }
+
+    /// Helpers the door takes at its depth: 4 at depth 0, one more every
+    /// 2 levels, so 7 at depth 7
+    fn max_helpers() -> u32 {
+        let layout = SeasonLayout::default();
+        let depth = layout.depth(Self::FLOOR, Self::X, Self::Y);
+        assert_eq!(depth, 7);
+        GameConfig::default().max_helpers_for_depth(depth)
+    }
+
+    fn joiner(&mut self) -> Joiner {
+        let wallet = Pubkey::new_unique();
+        self.runtime
+            .set_account(wallet, TestAccount::wallet(1_000_000_000));
+
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            player_account,
+            TestAccount::program_owned(&PlayerAccount {
+                owner: wallet,
+                current_floor: Self::FLOOR,
+                current_room_x: Self::X,
+                current_room_y: Self::Y,
+                active_jobs: Vec::new(),
+                jobs_completed: 0,
+                chests_looted: 0,
+                equipped_item_id: 0,
+                boss_fight_room: Pubkey::default(),
+                hp: PLAYER_MAX_HP,
+                active_buff_id: 0,
+                buff_expires_slot: 0,
+                loot_protected: false,
+                total_score: 0,
+                current_run_start_slot: 0,
+                runs_extracted: 0,
+                last_extraction_slot: 0,
+                season_seed: SEASON_SEED,
+                bump: player_bump,
+            }),
+        );
+
+        let token_account = Pubkey::new_unique();
+        self.runtime.set_account(
+            token_account,
+            TestAccount::token_account(self.skr_mint, wallet, TOKEN_BALANCE),
+        );
+
+        Joiner {
+            wallet,
+            player_account,
+            token_account,
+        }
+    }
+
+    fn room_account(&self) -> RoomAccount {
+        self.runtime.fetch(&self.room)
+    }
+
+    fn escrow(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"escrow", self.room.as_ref(), &[DIRECTION_NORTH]],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn helper_stake(&self, joiner: Joiner) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                HelperStake::SEED_PREFIX,
+                self.room.as_ref(),
+                &[DIRECTION_NORTH],
+                joiner.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn presence(&self, joiner: Joiner) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &SEASON_SEED.to_le_bytes(),
+                &[Self::FLOOR],
+                &[Self::X as u8],
+                &[Self::Y as u8],
+                joiner.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn inventory(joiner: Joiner) -> Pubkey {
+        Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, joiner.wallet.as_ref()],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    /// Creates what the join's `init` and `init_if_needed` accounts would,
+    /// and returns the instruction's accounts
+    fn join_job_accounts(&mut self, joiner: Joiner) -> accounts::JoinJob {
+        self.create_join_accounts(joiner);
+        accounts::JoinJob {
+            player: joiner.wallet,
+            global: self.global,
+            player_account: joiner.player_account,
+            room: self.room,
+            room_presence: self.presence(joiner),
+            inventory: Self::inventory(joiner),
+            escrow: self.escrow(),
+            helper_stake: self.helper_stake(joiner),
+            player_token_account: joiner.token_account,
+            skr_mint: self.skr_mint,
+            buff_schedule: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    fn create_join_accounts(&mut self, joiner: Joiner) {
+        let escrow = self.escrow();
+        if self.runtime.account(&escrow).data.is_empty() {
+            self.runtime
+                .set_account(escrow, TestAccount::token_account(self.skr_mint, escrow, 0));
+        }
+        let presence = self.presence(joiner);
+        if self.runtime.account(&presence).data.is_empty() {
+            self.runtime
+                .set_account(presence, TestAccount::initialized::<RoomPresence>());
+        }
+        let inventory = Self::inventory(joiner);
+        if self.runtime.account(&inventory).data.is_empty() {
+            self.runtime
+                .set_account(inventory, TestAccount::initialized::<InventoryAccount>());
+        }
+        self.runtime.set_account(
+            self.helper_stake(joiner),
+            TestAccount::initialized::<HelperStake>(),
+        );
+    }
+
+    /// Runs `join_job` up to the stake transfer, which needs a CPI
+    fn join(&mut self, joiner: Joiner) -> Result<JobJoined, ProgramError> {
+        let metas = self.join_job_accounts(joiner).to_account_metas(None);
+        self.runtime.run(&metas, |infos| {
+            let mut accounts = join_job_context(infos)?;
+            let mut ctx =
+                Context::new(&chaindepth::ID, &mut accounts, &[], JoinJobBumps::default());
+            let joined = join_job::take_helper_slot(&mut ctx, DIRECTION_NORTH)?;
+            accounts.exit(&chaindepth::ID)?;
+            Ok(joined)
+        })
+    }
+
+    /// Runs the whole `join_job` handler; only for joins that fail before
+    /// the stake transfer
+    fn join_rejected(&mut self, joiner: Joiner) -> ProgramError {
+        let metas = self.join_job_accounts(joiner).to_account_metas(None);
+        self.runtime
+            .run(&metas, |infos| {
+                let mut accounts = join_job_context(infos)?;
+                let ctx =
+                    Context::new(&chaindepth::ID, &mut accounts, &[], JoinJobBumps::default());
+                join_job::handler(ctx, DIRECTION_NORTH)
+            })
+            .unwrap_err()
+    }
+
+    /// A session key the player allowed to join jobs, and the instruction's
+    /// accounts for joining through it
+    fn join_job_with_session_accounts(&mut self, joiner: Joiner) -> accounts::JoinJobWithSession {
+        let session_key = Pubkey::new_unique();
+        self.runtime
+            .set_account(session_key, TestAccount::wallet(1_000_000_000));
+        let (session_authority, session_bump) = Pubkey::find_program_address(
+            &[
+                SessionAuthority::SEED_PREFIX,
+                joiner.wallet.as_ref(),
+                session_key.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            session_authority,
+            TestAccount::program_owned(&SessionAuthority {
+                player: joiner.wallet,
+                session_key,
+                expires_at_slot: CLOCK_SLOT + 100,
+                expires_at_unix_timestamp: CLOCK_UNIX_TIMESTAMP + 100,
+                instruction_allowlist: session_instruction_bits::JOIN_JOB,
+                max_token_spend: TOKEN_BALANCE,
+                spent_token_amount: 0,
+                is_active: true,
+                bump: session_bump,
+            }),
+        );
+        self.create_join_accounts(joiner);
+
+        accounts::JoinJobWithSession {
+            authority: session_key,
+            player: joiner.wallet,
+            global: self.global,
+            player_account: joiner.player_account,
+            room: self.room,
+            room_presence: self.presence(joiner),
+            inventory: Self::inventory(joiner),
+            escrow: self.escrow(),
+            helper_stake: self.helper_stake(joiner),
+            player_token_account: joiner.token_account,
+            skr_mint: self.skr_mint,
+            buff_schedule: None,
+            session_authority,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Runs `join_job_with_session` up to the stake transfer
+    fn join_with_session(&mut self, joiner: Joiner) -> Result<JobJoined, ProgramError> {
+        let metas = self
+            .join_job_with_session_accounts(joiner)
+            .to_account_metas(None);
+        self.runtime.run(&metas, |infos| {
+            let mut accounts = join_job_with_session_context(infos)?;
+            let mut ctx = Context::new(
+                &chaindepth::ID,
+                &mut accounts,
+                &[],
+                JoinJobWithSessionBumps::default(),
+            );
+            let joined =
+                join_job_with_session::take_helper_slot_with_session(&mut ctx, DIRECTION_NORTH)?;
+            accounts.exit(&chaindepth::ID)?;
+            Ok(joined)
+        })
+    }
+
+    /// Runs the whole `join_job_with_session` handler; only for joins that
+    /// fail before the stake transfer
+    fn join_with_session_rejected(&mut self, joiner: Joiner) -> ProgramError {
+        let metas = self
+            .join_job_with_session_accounts(joiner)
+            .to_account_metas(None);
+        self.runtime
+            .run(&metas, |infos| {
+                let mut accounts = join_job_with_session_context(infos)?;
+                let ctx = Context::new(
+                    &chaindepth::ID,
+                    &mut accounts,
+                    &[],
+                    JoinJobWithSessionBumps::default(),
+                );
+                join_job_with_session::handler(ctx, DIRECTION_NORTH)
+            })
+            .unwrap_err()
+    }
+}
+
+fn join_job_context<'a>(infos: &'a [AccountInfo<'a>]) -> anchor_lang::Result<JoinJob<'a>> {
+    Ok(JoinJob {
+        player: Signer::try_from(&infos[0])?,
+        global: Box::new(Account::try_from(&infos[1])?),
+        player_account: Box::new(Account::try_from(&infos[2])?),
+        room: Box::new(Account::try_from(&infos[3])?),
+        room_presence: Box::new(Account::try_from(&infos[4])?),
+        inventory: Box::new(Account::try_from(&infos[5])?),
+        escrow: Box::new(Account::try_from(&infos[6])?),
+        helper_stake: Box::new(Account::try_from(&infos[7])?),
+        player_token_account: Box::new(Account::try_from(&infos[8])?),
+        skr_mint: Box::new(Account::try_from(&infos[9])?),
+        buff_schedule: None,
+        token_program: Program::try_from(&infos[11])?,
+        system_program: Program::try_from(&infos[12])?,
+    })
+}
+
+fn join_job_with_session_context<'a>(
+    infos: &'a [AccountInfo<'a>],
+) -> anchor_lang::Result<JoinJobWithSession<'a>> {
+    Ok(JoinJobWithSession {
+        authority: Signer::try_from(&infos[0])?,
+        player: UncheckedAccount::try_from(&infos[1]),
+        global: Box::new(Account::try_from(&infos[2])?),
+        player_account: Box::new(Account::try_from(&infos[3])?),
+        room: Box::new(Account::try_from(&infos[4])?),
+        room_presence: Box::new(Account::try_from(&infos[5])?),
+        inventory: Box::new(Account::try_from(&infos[6])?),
+        escrow: Box::new(Account::try_from(&infos[7])?),
+        helper_stake: Box::new(Account::try_from(&infos[8])?),
+        player_token_account: Box::new(Account::try_from(&infos[9])?),
+        skr_mint: Box::new(Account::try_from(&infos[10])?),
+        buff_schedule: None,
+        session_authority: Box::new(Account::try_from(&infos[12])?),
+        token_program: Program::try_from(&infos[13])?,
+        system_program: Program::try_from(&infos[14])?,
+    })
+}
+
+#[test]
+fn join_job_takes_the_last_slot_and_reports_the_depth_cap() {
+    let max_helpers = Door::max_helpers();
+    assert_eq!(max_helpers, 7);
+    let mut door = Door::new(max_helpers - 1);
+    let joiner = door.joiner();
+
+    let joined = door.join(joiner).unwrap();
+
+    assert_eq!(joined.player, joiner.wallet);
+    assert_eq!(joined.helper_count, max_helpers);
+    assert_eq!(joined.max_helpers, max_helpers);
+    assert_eq!(joined.work_rate, BARE_HANDS_WORK_RATE);
+    assert_eq!(door.room_account().helper_counts[NORTH], max_helpers);
+    let helper_stake: HelperStake = door.runtime.fetch(&door.helper_stake(joiner));
+    assert_eq!(helper_stake.player, joiner.wallet);
+    assert_eq!(helper_stake.amount, RoomAccount::STAKE_AMOUNT);
+    let player: PlayerAccount = door.runtime.fetch(&joiner.player_account);
+    assert!(player.has_active_job(Door::X, Door::Y, DIRECTION_NORTH));
+}
+
+#[test]
+fn join_job_on_a_full_door_fails_with_job_full() {
+    let mut door = Door::new(Door::max_helpers());
+    let joiner = door.joiner();
+    let room = door.runtime.account(&door.room);
+
+    assert_eq!(
+        door.join_rejected(joiner),
+        program_error(ChainDepthError::JobFull)
+    );
+    assert_eq!(door.runtime.account(&door.room), room);
+    let player: PlayerAccount = door.runtime.fetch(&joiner.player_account);
+    assert!(player.active_jobs.is_empty());
+}
+
+#[test]
+fn join_job_with_session_applies_the_same_cap() {
+    let max_helpers = Door::max_helpers();
+    let mut door = Door::new(max_helpers - 1);
+    let first = door.joiner();
+
+    let joined = door.join_with_session(first).unwrap();
+
+    assert_eq!(joined.player, first.wallet);
+    assert_eq!(joined.helper_count, max_helpers);
+    assert_eq!(joined.max_helpers, max_helpers);
+    assert_eq!(door.room_account().helper_counts[NORTH], max_helpers);
+
+    let second = door.joiner();
+    let room = door.runtime.account(&door.room);
+    assert_eq!(
+        door.join_with_session_rejected(second),
+        program_error(ChainDepthError::JobFull)
+    );
+    assert_eq!(door.runtime.account(&door.room), room);
+}
@@ -0,0 +1,582 @@
+//! Void Shard recall and Phoenix Feather loot protection: the `PlayerAccount`
+//! / `InventoryAccount` rules, then the `use_void_shard` and
+//! `use_phoenix_feather` instructions, and extracting the recalled loot with
+//! `exit_dungeon`, run end to end on the test runtime.
+
+mod common;
+
+use anchor_lang::prelude::{ProgramError, Pubkey};
+use anchor_lang::solana_program::system_program;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::state::{
+    compute_time_bonus, item_categories, item_ids, session_instruction_bits, ActiveJob, GameConfig,
+    GlobalAccount, InventoryAccount, ItemStats, PlayerAccount, RoomAccount, RoomPresence,
+    SeasonLayout, SessionAuthority, DIRECTION_SOUTH, ENTRANCE_FLOOR, PLAYER_MAX_HP,
+    WALL_ENTRANCE_STAIRS,
+};
+use chaindepth::{accounts, instruction};
+use common::{error, program_error, TestAccount, TestRuntime, CLOCK_SLOT, CLOCK_UNIX_TIMESTAMP};
+
+fn player_at(floor: u8, x: i8, y: i8) -> PlayerAccount {
+    PlayerAccount {
+        owner: Pubkey::new_unique(),
+        current_floor: floor,
+        current_room_x: x,
+        current_room_y: y,
+        active_jobs: Vec::new(),
+        jobs_completed: 0,
+        chests_looted: 0,
+        equipped_item_id: item_ids::IRON_SWORD,
+        boss_fight_room: Pubkey::default(),
+        hp: PLAYER_MAX_HP,
+        active_buff_id: 0,
+        buff_expires_slot: 0,
+        loot_protected: false,
+        total_score: 0,
+        current_run_start_slot: 0,
+        runs_extracted: 0,
+        last_extraction_slot: 0,
+        season_seed: 1,
+        bump: 0,
+    }
+}
+
+/// Score each carried unit was granted at
+const UNIT_SCORE: u64 = 5;
+
+/// Stats `InventoryAccount::add_item` reads for a carried item
+fn carried_stats(stackable: bool) -> ItemStats {
+    ItemStats {
+        category: item_categories::UNKNOWN,
+        score_value: UNIT_SCORE,
+        base_durability: 0,
+        combat_dps: 0,
+        mining_speed: 0,
+        stackable,
+        tradeable: true,
+    }
+}
+
+/// A run's worth of carried items: a weapon, two treasure stacks and a buff
+fn carried_inventory(owner: Pubkey) -> InventoryAccount {
+    let mut inventory = InventoryAccount {
+        owner,
+        items: Vec::new(),
+        bump: 0,
+    };
+    inventory
+        .add_item(item_ids::IRON_SWORD, 1, 120, &carried_stats(false))
+        .unwrap();
+    inventory
+        .add_item(item_ids::GOLD_BAR, 3, 0, &carried_stats(true))
+        .unwrap();
+    inventory
+        .add_item(item_ids::DIAMOND, 2, 0, &carried_stats(true))
+        .unwrap();
+    inventory
+        .add_item(item_ids::MINOR_BUFF, 1, 0, &carried_stats(true))
+        .unwrap();
+    inventory
+}
+
+fn item_ids_in(inventory: &InventoryAccount) -> Vec<u16> {
+    inventory.items.iter().map(|item| item.item_id).collect()
+}
+
+#[test]
+fn void_shard_recalls_from_a_lower_floor_to_the_entrance() {
+    let layout = SeasonLayout::default();
+    let mut player = player_at(2, 0, 9);
+
+    player.recall_to_entrance(&layout).unwrap();
+    assert_eq!(player.current_floor, ENTRANCE_FLOOR);
+    assert!(player.is_at_room(ENTRANCE_FLOOR, layout.start_x, layout.start_y));
+
+    // Already there: the shard would be wasted.
+    assert_eq!(
+        player.recall_to_entrance(&layout).unwrap_err(),
+        error(ChainDepthError::AlreadyAtEntranceRoom)
+    );
+
+    // The start room of a lower floor is not the entrance.
+    let mut below = player_at(1, layout.start_x, layout.start_y);
+    below.recall_to_entrance(&layout).unwrap();
+    assert_eq!(below.current_floor, ENTRANCE_FLOOR);
+}
+
+#[test]
+fn void_shard_is_blocked_in_a_boss_fight_or_with_active_jobs() {
+    let layout = SeasonLayout::default();
+
+    let mut fighter = player_at(1, 3, 3);
+    fighter.boss_fight_room = Pubkey::new_unique();
+    assert_eq!(
+        fighter.recall_to_entrance(&layout).unwrap_err(),
+        error(ChainDepthError::InBossFight)
+    );
+    assert!(fighter.is_at_room(1, 3, 3));
+
+    let mut digger = player_at(0, 7, 2);
+    digger.active_jobs.push(ActiveJob {
+        room_x: 7,
+        room_y: 2,
+        direction: 0,
+    });
+    assert_eq!(
+        digger.recall_to_entrance(&layout).unwrap_err(),
+        error(ChainDepthError::CannotRecallWithActiveJobs)
+    );
+    assert!(digger.is_at_room(0, 7, 2));
+
+    digger.remove_job(7, 2, 0);
+    digger.recall_to_entrance(&layout).unwrap();
+}
+
+#[test]
+fn death_without_protection_drops_carried_treasure() {
+    let mut player = player_at(1, 2, 8);
+    let mut inventory = carried_inventory(player.owner);
+
+    assert_eq!(player.lose_carried_loot(&mut inventory), Some((2, 5)));
+    assert_eq!(
+        item_ids_in(&inventory),
+        vec![item_ids::IRON_SWORD, item_ids::MINOR_BUFF]
+    );
+}
+
+#[test]
+fn phoenix_feather_saves_loot_once() {
+    let mut player = player_at(1, 2, 8);
+    let mut inventory = carried_inventory(player.owner);
+    let carried = item_ids_in(&inventory);
+
+    player.loot_protected = true;
+    assert_eq!(player.lose_carried_loot(&mut inventory), None);
+    assert_eq!(item_ids_in(&inventory), carried);
+    assert!(!player.loot_protected);
+
+    // Spent: the next death costs the loot again.
+    assert_eq!(player.lose_carried_loot(&mut inventory), Some((2, 5)));
+    assert!(inventory
+        .items
+        .iter()
+        .all(|item| item.item_id != item_ids::GOLD_BAR));
+}
+
+#[test]
+fn respawn_after_a_saved_death_keeps_the_run_going_from_the_start_room() {
+    let layout = SeasonLayout::default();
+    let mut player = player_at(2, 9, 9);
+    let mut inventory = carried_inventory(player.owner);
+    player.loot_protected = true;
+    player.boss_fight_room = Pubkey::new_unique();
+
+    assert!(player.take_damage(u64::from(PLAYER_MAX_HP) + 10));
+    assert_eq!(player.lose_carried_loot(&mut inventory), None);
+    player.respawn(&layout, 500);
+
+    assert_eq!(player.hp, PLAYER_MAX_HP);
+    assert!(!player.is_in_boss_fight());
+    assert!(player.is_at_room(2, layout.start_x, layout.start_y));
+    assert!(inventory.has_item(item_ids::DIAMOND));
+
+    // Recall works straight after respawning on a lower floor.
+    player.recall_to_entrance(&layout).unwrap();
+    assert!(player.is_at_room(ENTRANCE_FLOOR, layout.start_x, layout.start_y));
+}
+
+const SEASON_SEED: u64 = 1;
+
+/// A season with one player standing at `(floor, x, y)` with a presence
+/// there and `items` in their inventory. The player also has a presence left
+/// in the entrance room: the test runtime cannot create accounts, so
+/// `use_void_shard` takes its `init_if_needed` path for an existing one.
+struct Dungeon {
+    runtime: TestRuntime,
+    wallet: Pubkey,
+    global: Pubkey,
+    player_account: Pubkey,
+    inventory: Pubkey,
+}
+
+impl Dungeon {
+    fn new(floor: u8, x: i8, y: i8, items: &[(u16, u32)]) -> Self {
+        let mut runtime = TestRuntime::new();
+        let wallet = Pubkey::new_unique();
+        runtime.set_account(wallet, TestAccount::wallet(1_000_000_000));
+
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        runtime.set_account(
+            global,
+            TestAccount::program_owned(&GlobalAccount {
+                season_seed: SEASON_SEED,
+                depth: 0,
+                skr_mint: Pubkey::new_unique(),
+                prize_pool: Pubkey::new_unique(),
+                admin: Pubkey::new_unique(),
+                end_slot: CLOCK_SLOT + GlobalAccount::SEASON_DURATION_SLOTS,
+                jobs_completed: 0,
+                config: GameConfig::default(),
+                layout: SeasonLayout::default(),
+                bump: global_bump,
+            }),
+        );
+
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        let mut player = player_at(floor, x, y);
+        player.owner = wallet;
+        player.bump = player_bump;
+        runtime.set_account(player_account, TestAccount::program_owned(&player));
+
+        let (inventory, inventory_bump) = Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        let mut carried = InventoryAccount {
+            owner: wallet,
+            items: Vec::new(),
+            bump: inventory_bump,
+        };
+        for &(item_id, amount) in items {
+            carried
+                .add_item(item_id, amount, 0, &carried_stats(true))
+                .unwrap();
+        }
+        runtime.set_account(inventory, TestAccount::program_owned(&carried));
+
+        let mut dungeon = Self {
+            runtime,
+            wallet,
+            global,
+            player_account,
+            inventory,
+        };
+        let layout = SeasonLayout::default();
+        dungeon.put_presence(ENTRANCE_FLOOR, layout.start_x, layout.start_y, false);
+        dungeon.put_presence(floor, x, y, true);
+        dungeon
+    }
+
+    fn presence(&self, floor: u8, x: i8, y: i8) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &SEASON_SEED.to_le_bytes(),
+                &[floor],
+                &[x as u8],
+                &[y as u8],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn put_presence(&mut self, floor: u8, x: i8, y: i8, is_current: bool) {
+        let key = self.presence(floor, x, y);
+        let (_, bump) = Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &SEASON_SEED.to_le_bytes(),
+                &[floor],
+                &[x as u8],
+                &[y as u8],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let mut presence = RoomPresence {
+            player: self.wallet,
+            season_seed: SEASON_SEED,
+            room_floor: floor,
+            room_x: x,
+            room_y: y,
+            skin_id: 3,
+            equipped_item_id: item_ids::IRON_SWORD,
+            activity: RoomPresence::ACTIVITY_IDLE,
+            activity_direction: 0,
+            is_current,
+            bump,
+        };
+        presence.set_idle();
+        self.runtime
+            .set_account(key, TestAccount::program_owned(&presence));
+    }
+
+    /// A session key for the wallet allowed to send `instruction_allowlist`
+    fn begin_session(&mut self, instruction_allowlist: u64) -> (Pubkey, Pubkey) {
+        let session_key = Pubkey::new_unique();
+        self.runtime
+            .set_account(session_key, TestAccount::wallet(1_000_000_000));
+        let (session_authority, bump) = Pubkey::find_program_address(
+            &[
+                SessionAuthority::SEED_PREFIX,
+                self.wallet.as_ref(),
+                session_key.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            session_authority,
+            TestAccount::program_owned(&SessionAuthority {
+                player: self.wallet,
+                session_key,
+                expires_at_slot: CLOCK_SLOT + 1_000,
+                expires_at_unix_timestamp: CLOCK_UNIX_TIMESTAMP + 3_600,
+                instruction_allowlist,
+                max_token_spend: 0,
+                spent_token_amount: 0,
+                is_active: true,
+                bump,
+            }),
+        );
+        (session_key, session_authority)
+    }
+
+    fn player(&self) -> PlayerAccount {
+        self.runtime.fetch(&self.player_account)
+    }
+
+    fn items(&self) -> Vec<(u16, u32)> {
+        let inventory: InventoryAccount = self.runtime.fetch(&self.inventory);
+        inventory
+            .items
+            .iter()
+            .map(|item| (item.item_id, item.amount))
+            .collect()
+    }
+
+    fn use_void_shard(
+        &mut self,
+        authority: Pubkey,
+        session_authority: Option<Pubkey>,
+    ) -> Result<(), ProgramError> {
+        let player = self.player();
+        let layout = SeasonLayout::default();
+        let accounts = accounts::UseVoidShard {
+            authority,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            inventory: self.inventory,
+            current_presence: self.presence(
+                player.current_floor,
+                player.current_room_x,
+                player.current_room_y,
+            ),
+            start_presence: self.presence(ENTRANCE_FLOOR, layout.start_x, layout.start_y),
+            session_authority,
+            system_program: system_program::ID,
+        };
+        self.runtime.process(accounts, instruction::UseVoidShard {})
+    }
+
+    fn use_phoenix_feather(
+        &mut self,
+        authority: Pubkey,
+        session_authority: Option<Pubkey>,
+    ) -> Result<(), ProgramError> {
+        let accounts = accounts::UsePhoenixFeather {
+            authority,
+            player: self.wallet,
+            player_account: self.player_account,
+            inventory: self.inventory,
+            session_authority,
+        };
+        self.runtime
+            .process(accounts, instruction::UsePhoenixFeather {})
+    }
+
+    /// Runs `exit_dungeon` from the entrance room, which has its stairs
+    fn exit_dungeon(&mut self) -> Result<(), ProgramError> {
+        let layout = SeasonLayout::default();
+        let (room, room_bump) =
+            RoomAccount::find_address(SEASON_SEED, ENTRANCE_FLOOR, layout.start_x, layout.start_y);
+        let mut walls = [0; 4];
+        walls[DIRECTION_SOUTH as usize] = WALL_ENTRANCE_STAIRS;
+        self.runtime.set_account(
+            room,
+            TestAccount::program_owned(&RoomAccount {
+                floor: ENTRANCE_FLOOR,
+                x: layout.start_x,
+                y: layout.start_y,
+                season_seed: SEASON_SEED,
+                walls,
+                bump: room_bump,
+                ..RoomAccount::default()
+            }),
+        );
+
+        let accounts = accounts::ExitDungeon {
+            authority: self.wallet,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            room,
+            inventory: self.inventory,
+            room_presence: self.presence(ENTRANCE_FLOOR, layout.start_x, layout.start_y),
+            session_authority: None,
+            system_program: system_program::ID,
+        };
+        self.runtime.process(accounts, instruction::ExitDungeon {})
+    }
+}
+
+#[test]
+fn use_void_shard_moves_the_presence_to_the_entrance_and_burns_the_shard() {
+    let layout = SeasonLayout::default();
+    let mut dungeon = Dungeon::new(
+        2,
+        0,
+        9,
+        &[(item_ids::VOID_SHARD, 2), (item_ids::GOLD_BAR, 2)],
+    );
+    let old_presence = dungeon.presence(2, 0, 9);
+    let start_presence = dungeon.presence(ENTRANCE_FLOOR, layout.start_x, layout.start_y);
+    let presence_rent = dungeon.runtime.account(&old_presence).lamports;
+    let global_lamports = dungeon.runtime.account(&dungeon.global).lamports;
+    let wallet_lamports = dungeon.runtime.account(&dungeon.wallet).lamports;
+
+    dungeon.use_void_shard(dungeon.wallet, None).unwrap();
+
+    let player = dungeon.player();
+    assert!(player.is_at_room(ENTRANCE_FLOOR, layout.start_x, layout.start_y));
+    assert_eq!(
+        dungeon.items(),
+        vec![(item_ids::VOID_SHARD, 1), (item_ids::GOLD_BAR, 2)]
+    );
+
+    // The old presence's rent goes to the treasury; the entrance presence is reused.
+    assert!(dungeon.runtime.account(&old_presence).is_closed());
+    assert_eq!(
+        dungeon.runtime.account(&dungeon.global).lamports,
+        global_lamports + presence_rent
+    );
+    assert_eq!(
+        dungeon.runtime.account(&dungeon.wallet).lamports,
+        wallet_lamports
+    );
+
+    let presence: RoomPresence = dungeon.runtime.fetch(&start_presence);
+    assert_eq!(presence.player, dungeon.wallet);
+    assert_eq!(
+        (presence.room_floor, presence.room_x, presence.room_y),
+        (ENTRANCE_FLOOR, layout.start_x, layout.start_y)
+    );
+    assert_eq!(presence.skin_id, 3);
+    assert!(presence.is_current);
+    assert_eq!(presence.activity, RoomPresence::ACTIVITY_IDLE);
+
+    // Already at the entrance: nothing is burned or closed.
+    assert_eq!(
+        dungeon.use_void_shard(dungeon.wallet, None).unwrap_err(),
+        program_error(ChainDepthError::AlreadyAtEntranceRoom)
+    );
+    assert_eq!(
+        dungeon.items(),
+        vec![(item_ids::VOID_SHARD, 1), (item_ids::GOLD_BAR, 2)]
+    );
+    assert!(!dungeon.runtime.account(&start_presence).is_closed());
+}
+
+#[test]
+fn recalled_loot_extracts_at_the_score_it_was_granted_at() {
+    let mut dungeon = Dungeon::new(
+        2,
+        0,
+        9,
+        &[
+            (item_ids::VOID_SHARD, 1),
+            (item_ids::GOLD_BAR, 2),
+            (item_ids::MINOR_BUFF, 1),
+        ],
+    );
+    dungeon.use_void_shard(dungeon.wallet, None).unwrap();
+
+    // No item definitions are passed: each stack carries its own score.
+    dungeon.exit_dungeon().unwrap();
+
+    let loot_score = 2 * UNIT_SCORE;
+    let player = dungeon.player();
+    assert_eq!(
+        player.total_score,
+        loot_score + compute_time_bonus(0, loot_score)
+    );
+    assert_eq!(player.runs_extracted, 1);
+    assert_eq!(dungeon.items(), vec![(item_ids::MINOR_BUFF, 1)]);
+}
+
+#[test]
+fn use_void_shard_without_a_shard_changes_nothing() {
+    let mut dungeon = Dungeon::new(2, 0, 9, &[(item_ids::GOLD_BAR, 1)]);
+    let old_presence = dungeon.presence(2, 0, 9);
+
+    assert_eq!(
+        dungeon.use_void_shard(dungeon.wallet, None).unwrap_err(),
+        program_error(ChainDepthError::InsufficientItemAmount)
+    );
+    assert!(dungeon.player().is_at_room(2, 0, 9));
+    assert!(!dungeon.runtime.account(&old_presence).is_closed());
+}
+
+#[test]
+fn use_phoenix_feather_burns_one_feather_and_protects_loot_once() {
+    let mut dungeon = Dungeon::new(1, 2, 8, &[(item_ids::PHOENIX_FEATHER, 2)]);
+
+    dungeon.use_phoenix_feather(dungeon.wallet, None).unwrap();
+    assert!(dungeon.player().loot_protected);
+    assert_eq!(dungeon.items(), vec![(item_ids::PHOENIX_FEATHER, 1)]);
+
+    assert_eq!(
+        dungeon
+            .use_phoenix_feather(dungeon.wallet, None)
+            .unwrap_err(),
+        program_error(ChainDepthError::LootAlreadyProtected)
+    );
+    assert_eq!(dungeon.items(), vec![(item_ids::PHOENIX_FEATHER, 1)]);
+}
+
+#[test]
+fn session_keys_need_each_item_instruction_bit() {
+    let mut dungeon = Dungeon::new(
+        2,
+        0,
+        9,
+        &[(item_ids::VOID_SHARD, 1), (item_ids::PHOENIX_FEATHER, 1)],
+    );
+
+    // Neither the wallet nor a session: rejected outright.
+    let stranger = Pubkey::new_unique();
+    dungeon
+        .runtime
+        .set_account(stranger, TestAccount::wallet(1_000_000_000));
+    assert_eq!(
+        dungeon.use_phoenix_feather(stranger, None).unwrap_err(),
+        program_error(ChainDepthError::Unauthorized)
+    );
+
+    let (session_key, session_authority) =
+        dungeon.begin_session(session_instruction_bits::USE_PHOENIX_FEATHER);
+    assert_eq!(
+        dungeon
+            .use_void_shard(session_key, Some(session_authority))
+            .unwrap_err(),
+        program_error(ChainDepthError::SessionInstructionNotAllowed)
+    );
+    dungeon
+        .use_phoenix_feather(session_key, Some(session_authority))
+        .unwrap();
+    assert!(dungeon.player().loot_protected);
+
+    let (session_key, session_authority) =
+        dungeon.begin_session(session_instruction_bits::USE_VOID_SHARD);
+    dungeon
+        .use_void_shard(session_key, Some(session_authority))
+        .unwrap();
+    assert_eq!(dungeon.player().current_floor, ENTRANCE_FLOOR);
+    assert!(dungeon.items().is_empty());
+}
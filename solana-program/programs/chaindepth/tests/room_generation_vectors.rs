@@ -0,0 +1,270 @@
+//! Golden vectors for room generation.
+//!
+//! `test-vectors/room_generation.json` pins what `initialize_discovered_room`
+//! produces for a spread of seeds, layouts, floors and rooms. Clients that
+//! regenerate rooms from `season_seed` (the Unity port) check themselves
+//! against the same file. When a generation change is intentional, rewrite
+//! the file with:
+//!
+//!     UPDATE_VECTORS=1 cargo test -p chaindepth --test room_generation_vectors
+//!
+//! and ship the new file to the client alongside the program upgrade.
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use chaindepth::state::{
+    generate_room_hash, generation_modes, initialize_discovered_room,
+    select_forced_key_chest_coords, select_stairs_down_coords, spanning_parent, FloorParams,
+    RoomAccount, RoomEntrance, SeasonLayout, DIRECTION_WEST,
+};
+use serde_json::{json, Value};
+
+const FORMAT_VERSION: u32 = 1;
+
+const SEEDS: [u64; 5] = [0, 1, 42, 0x9E37_79B9_7F4A_7C15, u64::MAX];
+
+const DESCRIPTION: &str = "Room generation golden vectors. Each room is generated by \
+initialize_discovered_room entered from `entrance` (0=north 1=south 2=east 3=west, the side \
+facing its spanning-tree parent). `hash` is generate_room_hash(floor_seed, x, y) as a decimal \
+string. walls: 0=solid 1=rubble 2=open 3=locked 4=entrance_stairs. door_lock_kinds: 0=none \
+1=skeleton 2=ruby 3=sapphire 4=emerald 5=boss_seal. center_type: 0=empty 1=chest 2=boss \
+3=stairs_down.";
+
+fn vectors_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-vectors/room_generation.json")
+}
+
+fn layouts() -> Vec<(&'static str, SeasonLayout)> {
+    vec![
+        ("default", SeasonLayout::default()),
+        (
+            "independent",
+            SeasonLayout {
+                generation_mode: generation_modes::INDEPENDENT,
+                ..SeasonLayout::default()
+            },
+        ),
+        (
+            "edge_spawn",
+            SeasonLayout {
+                width: 7,
+                height: 15,
+                start_x: 1,
+                start_y: 3,
+                ..SeasonLayout::default()
+            },
+        ),
+    ]
+}
+
+/// Rooms worth pinning on a floor: the start room's neighbours, the corners,
+/// a diagonal and every special room (stairs, forced key chests).
+fn sample_coords(params: &FloorParams, layout: &SeasonLayout) -> Vec<(i8, i8)> {
+    let (start_x, start_y) = layout.start();
+    let (max_x, max_y) = (layout.max_x(), layout.max_y());
+    let mut coords = vec![
+        (start_x, start_y + 1),
+        (start_x, start_y - 1),
+        (start_x + 1, start_y),
+        (start_x - 1, start_y),
+        (start_x + 1, start_y + 1),
+        (start_x - 2, start_y - 2),
+        (SeasonLayout::MIN_COORD, SeasonLayout::MIN_COORD),
+        (max_x, SeasonLayout::MIN_COORD),
+        (SeasonLayout::MIN_COORD, max_y),
+        (max_x, max_y),
+    ];
+    coords.extend(select_stairs_down_coords(params, layout));
+    coords.extend(
+        (1..=layout.max_ring())
+            .filter_map(|ring| select_forced_key_chest_coords(params.seed, layout, ring)),
+    );
+
+    let mut seen = Vec::new();
+    coords.retain(|&(x, y)| {
+        let keep = layout.contains(x, y) && !layout.is_start(x, y) && !seen.contains(&(x, y));
+        seen.push((x, y));
+        keep
+    });
+    coords
+}
+
+fn room_vector(season_seed: u64, layout: &SeasonLayout, floor: u8, x: i8, y: i8) -> Value {
+    let params = FloorParams::new(season_seed, layout, floor);
+    let parent = spanning_parent(params.seed, layout, x, y).unwrap();
+    let entrance = (0..=DIRECTION_WEST)
+        .find(|direction| RoomAccount::adjacent_coords(x, y, *direction) == parent)
+        .unwrap();
+
+    let mut room = RoomAccount::default();
+    initialize_discovered_room(
+        &mut room,
+        &params,
+        layout,
+        RoomEntrance {
+            x,
+            y,
+            direction: entrance,
+        },
+        Pubkey::default(),
+        0,
+        0,
+    );
+
+    json!({
+        "x": x,
+        "y": y,
+        "entrance": entrance,
+        "hash": generate_room_hash(params.seed, x, y).to_string(),
+        "depth": layout.depth(floor, x, y),
+        "walls": room.walls,
+        "door_lock_kinds": room.door_lock_kinds,
+        "center_type": room.center_type,
+        "center_id": room.center_id,
+        "forced_key_drop": room.forced_key_drop,
+        "boss_max_hp": room.boss_max_hp,
+    })
+}
+
+fn case_vector(season_seed: u64, layout_name: &str, layout: &SeasonLayout, floor: u8) -> Value {
+    let params = FloorParams::new(season_seed, layout, floor);
+    let rooms: Vec<Value> = sample_coords(&params, layout)
+        .into_iter()
+        .map(|(x, y)| room_vector(season_seed, layout, floor, x, y))
+        .collect();
+
+    json!({
+        "season_seed": season_seed.to_string(),
+        "layout_name": layout_name,
+        "layout": {
+            "width": layout.width,
+            "height": layout.height,
+            "start_x": layout.start_x,
+            "start_y": layout.start_y,
+            "generation_mode": layout.generation_mode,
+            "floor_count": layout.floor_count,
+            "max_locked_doors": layout.max_locked_doors,
+        },
+        "floor": floor,
+        "floor_seed": params.seed.to_string(),
+        "start_walls": RoomAccount::generate_start_walls(season_seed, layout),
+        "rooms": rooms,
+    })
+}
+
+fn build_vectors() -> Vec<Value> {
+    let mut cases = Vec::new();
+    for (layout_name, layout) in layouts() {
+        // Lower floors only change the seed and tuning, so one layout covers them.
+        let floors = if layout_name == "default" {
+            0..layout.floor_count
+        } else {
+            0..1
+        };
+        for floor in floors {
+            for season_seed in SEEDS {
+                cases.push(case_vector(season_seed, layout_name, &layout, floor));
+            }
+        }
+    }
+    cases
+}
+
+/// Pretty-print with one room per line so diffs stay reviewable.
+fn render_vectors(cases: &[Value]) -> String {
+    let mut out = String::from("{\n");
+    out.push_str(&format!("  \"version\": {FORMAT_VERSION},\n"));
+    out.push_str(&format!(
+        "  \"description\": {},\n",
+        serde_json::to_string(DESCRIPTION).unwrap()
+    ));
+    let rendered_cases: Vec<String> = cases
+        .iter()
+        .map(|case| {
+            let mut header = case.clone();
+            let rooms = header
+                .as_object_mut()
+                .unwrap()
+                .remove("rooms")
+                .unwrap_or_default();
+
+            let mut lines: Vec<String> = header
+                .as_object()
+                .unwrap()
+                .iter()
+                .map(|(key, value)| format!("      \"{key}\": {value},"))
+                .collect();
+            let rooms: Vec<String> = rooms
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|room| format!("        {room}"))
+                .collect();
+            lines.push(format!(
+                "      \"rooms\": [\n{}\n      ]",
+                rooms.join(",\n")
+            ));
+            format!("    {{\n{}\n    }}", lines.join("\n"))
+        })
+        .collect();
+
+    out.push_str(&format!(
+        "  \"cases\": [\n{}\n  ]\n}}\n",
+        rendered_cases.join(",\n")
+    ));
+    out
+}
+
+#[test]
+fn room_generation_matches_golden_vectors() {
+    let cases = build_vectors();
+    let path = vectors_path();
+
+    if std::env::var_os("UPDATE_VECTORS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, render_vectors(&cases)).unwrap();
+        return;
+    }
+
+    let golden: Value = serde_json::from_str(
+        &std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("reading {}: {err}", path.display())),
+    )
+    .unwrap();
+    assert_eq!(golden["version"], FORMAT_VERSION);
+
+    let golden_cases = golden["cases"].as_array().unwrap();
+    assert_eq!(golden_cases.len(), cases.len(), "case count changed");
+    for (expected, actual) in golden_cases.iter().zip(&cases) {
+        let label = format!(
+            "seed {} layout {} floor {}",
+            actual["season_seed"], actual["layout_name"], actual["floor"]
+        );
+        for key in ["layout", "floor_seed", "start_walls"] {
+            assert_eq!(expected[key], actual[key], "{label}: {key} changed");
+        }
+
+        let expected_rooms = expected["rooms"].as_array().unwrap();
+        let actual_rooms = actual["rooms"].as_array().unwrap();
+        assert_eq!(
+            expected_rooms.len(),
+            actual_rooms.len(),
+            "{label}: sampled rooms changed"
+        );
+        for (expected_room, actual_room) in expected_rooms.iter().zip(actual_rooms) {
+            assert_eq!(
+                expected_room, actual_room,
+                "{label}: room ({}, {}) changed",
+                actual_room["x"], actual_room["y"]
+            );
+        }
+    }
+}
+
+#[test]
+fn rendered_vectors_round_trip() {
+    let cases = build_vectors();
+    let parsed: Value = serde_json::from_str(&render_vectors(&cases)).unwrap();
+    assert_eq!(parsed["cases"], Value::Array(cases));
+}
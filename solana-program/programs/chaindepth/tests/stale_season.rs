@@ -0,0 +1,1034 @@
+//! Cleaning up after a season reset: stake recovery bookkeeping on
+//! `RoomAccount`, then `recover_stale_stake`, `respawn_stale_player`,
+//! `reveal_loot` of old-season receipts, the scouting items refused until the
+//! player respawns, and the `close_stale_*` cranks run on
+//! the test runtime. The runtime cannot run
+//! token transfers, so the payout amounts are covered through
+//! `RoomAccount::release_stale_stake`, which is what the instruction pays
+//! out, and the escrow sweep through the checks that run before it.
+
+mod common;
+
+use anchor_lang::prelude::{Error, ProgramError, Pubkey};
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::solana_program::sysvar::{self, slot_hashes};
+use anchor_spl::token::spl_token;
+use chaindepth::errors::ChainDepthError;
+use chaindepth::instructions::close_stale_accounts::STALE_CLOSE_CRANK_REWARD_LAMPORTS;
+use chaindepth::state::{
+    item_categories, item_ids, loot_kinds, ActiveJob, BossFightAccount, GameConfig, GlobalAccount,
+    HelperStake, InventoryAccount, InventoryItem, ItemDefinition, ItemStats, LootReceipt,
+    PlayerAccount, PlayerProfile, RoomAccount, RoomPresence, SeasonLayout, DIRECTION_NORTH,
+    ENTRANCE_FLOOR, EXPIRED_LOOT_DROP, PLAYER_MAX_HP,
+};
+use chaindepth::{accounts, instruction};
+use common::{error, program_error, TestAccount, TestRuntime, CLOCK_SLOT};
+
+const OLD_SEASON: u64 = 1;
+const NEW_SEASON: u64 = 2;
+const NORTH: usize = DIRECTION_NORTH as usize;
+
+fn helper(work_rate: u64, joined_slot: u64) -> HelperStake {
+    HelperStake {
+        player: Pubkey::new_unique(),
+        room: Pubkey::default(),
+        direction: DIRECTION_NORTH,
+        amount: RoomAccount::STAKE_AMOUNT,
+        joined_slot,
+        work_rate,
+        buff_bonus: 0,
+        buff_expires_slot: 0,
+        bump: 0,
+    }
+}
+
+/// An old-season room with `helpers` staked on its north door
+fn room_with_job(helpers: &[HelperStake]) -> RoomAccount {
+    let mut room = RoomAccount {
+        season_seed: OLD_SEASON,
+        ..RoomAccount::default()
+    };
+    room.base_slots[NORTH] = RoomAccount::BASE_SLOTS_DEPTH_0;
+    for helper_stake in helpers {
+        room.add_helper_work(NORTH, helper_stake, None).unwrap();
+        room.helper_counts[NORTH] += 1;
+        room.total_staked[NORTH] += helper_stake.amount;
+    }
+    room
+}
+
+fn program_error_code(code: anchor_lang::error::ErrorCode) -> ProgramError {
+    Error::from(code).into()
+}
+
+#[test]
+fn stale_stake_of_a_completed_job_pays_stake_plus_bonus_share() {
+    let early = helper(100, 100);
+    let late = helper(200, 200);
+    let mut room = room_with_job(&[early.clone(), late.clone()]);
+    room.record_job_completion(NORTH, 400).unwrap();
+    room.bonus_pool[NORTH] = 3_000_000;
+
+    // Contributions are 100 * 301 and 200 * 201 out of 70_300.
+    assert_eq!(
+        room.release_stale_stake(NORTH, &early).unwrap(),
+        RoomAccount::STAKE_AMOUNT + 1_284_495
+    );
+    assert_eq!(room.helper_counts[NORTH], 1);
+    assert_eq!(room.total_staked[NORTH], RoomAccount::STAKE_AMOUNT);
+    assert_eq!(room.bonus_paid[NORTH], 1_284_495);
+    assert!(room.job_completed[NORTH]);
+
+    // The last helper out clears the job; one token of dust stays in escrow.
+    assert_eq!(
+        room.release_stale_stake(NORTH, &late).unwrap(),
+        RoomAccount::STAKE_AMOUNT + 1_715_504
+    );
+    assert_eq!(room.helper_counts[NORTH], 0);
+    assert_eq!(room.total_staked[NORTH], 0);
+    assert!(!room.job_completed[NORTH]);
+    assert_eq!(room.bonus_pool[NORTH], 0);
+    assert_eq!(room.bonus_paid[NORTH], 0);
+}
+
+#[test]
+fn stale_stake_of_an_unfinished_job_is_refunded_in_full() {
+    let first = helper(100, 100);
+    let second = helper(300, 150);
+    let mut room = room_with_job(&[first.clone(), second.clone()]);
+    room.settle_job_progress(NORTH, None, 200).unwrap();
+    let progress = room.progress[NORTH];
+    assert!(progress > 0);
+
+    assert_eq!(
+        room.release_stale_stake(NORTH, &first).unwrap(),
+        RoomAccount::STAKE_AMOUNT
+    );
+    assert_eq!(room.helper_counts[NORTH], 1);
+    assert_eq!(room.total_staked[NORTH], RoomAccount::STAKE_AMOUNT);
+    assert_eq!(room.progress[NORTH], progress);
+    assert_eq!(room.bonus_paid[NORTH], 0);
+
+    assert_eq!(
+        room.release_stale_stake(NORTH, &second).unwrap(),
+        RoomAccount::STAKE_AMOUNT
+    );
+    assert_eq!(room.helper_counts[NORTH], 0);
+    assert_eq!(room.progress[NORTH], 0);
+    assert_eq!(room.total_work_rate[NORTH], 0);
+
+    // No helper left to release.
+    assert_eq!(
+        room.release_stale_stake(NORTH, &second).unwrap_err(),
+        error(ChainDepthError::Overflow)
+    );
+}
+
+/// A player whose data is still in `OLD_SEASON`, with the global in
+/// `global_season`
+struct Reset {
+    runtime: TestRuntime,
+    wallet: Pubkey,
+    global: Pubkey,
+    global_season: u64,
+    skr_mint: Pubkey,
+    prize_pool: Pubkey,
+    player_account: Pubkey,
+}
+
+impl Reset {
+    fn new(global_season: u64) -> Self {
+        let mut runtime = TestRuntime::new();
+        let wallet = Pubkey::new_unique();
+        runtime.set_account(wallet, TestAccount::wallet(1_000_000_000));
+
+        let skr_mint = Pubkey::new_unique();
+        let prize_pool = Pubkey::new_unique();
+        runtime.set_account(
+            prize_pool,
+            TestAccount::token_account(skr_mint, Pubkey::new_unique(), 0),
+        );
+        let (global, global_bump) =
+            Pubkey::find_program_address(&[GlobalAccount::SEED_PREFIX], &chaindepth::ID);
+        runtime.set_account(
+            global,
+            TestAccount::program_owned(&GlobalAccount {
+                season_seed: global_season,
+                depth: 0,
+                skr_mint,
+                prize_pool,
+                admin: Pubkey::new_unique(),
+                end_slot: CLOCK_SLOT + GlobalAccount::SEASON_DURATION_SLOTS,
+                jobs_completed: 0,
+                config: GameConfig::default(),
+                layout: SeasonLayout::default(),
+                bump: global_bump,
+            }),
+        );
+
+        let (player_account, player_bump) = Pubkey::find_program_address(
+            &[PlayerAccount::SEED_PREFIX, wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        runtime.set_account(
+            player_account,
+            TestAccount::program_owned(&PlayerAccount {
+                owner: wallet,
+                current_floor: 2,
+                current_room_x: 1,
+                current_room_y: 8,
+                active_jobs: vec![ActiveJob {
+                    room_x: 1,
+                    room_y: 8,
+                    direction: DIRECTION_NORTH,
+                }],
+                jobs_completed: 4,
+                chests_looted: 2,
+                equipped_item_id: item_ids::IRON_SWORD,
+                boss_fight_room: Pubkey::new_unique(),
+                hp: 35,
+                active_buff_id: item_ids::MINOR_BUFF,
+                buff_expires_slot: CLOCK_SLOT + 50,
+                loot_protected: true,
+                total_score: 900,
+                current_run_start_slot: 0,
+                runs_extracted: 1,
+                last_extraction_slot: 0,
+                season_seed: OLD_SEASON,
+                bump: player_bump,
+            }),
+        );
+
+        Self {
+            runtime,
+            wallet,
+            global,
+            global_season,
+            skr_mint,
+            prize_pool,
+            player_account,
+        }
+    }
+
+    fn player(&self) -> PlayerAccount {
+        self.runtime.fetch(&self.player_account)
+    }
+
+    /// An `OLD_SEASON` room on floor 2 with `helpers` staked on its north door
+    fn put_room(&mut self, x: i8, y: i8, helpers: &[HelperStake]) -> Pubkey {
+        let (room, room_bump) = RoomAccount::find_address(OLD_SEASON, 2, x, y);
+        let mut room_account = room_with_job(helpers);
+        room_account.floor = 2;
+        room_account.x = x;
+        room_account.y = y;
+        room_account.bump = room_bump;
+        self.runtime
+            .set_account(room, TestAccount::program_owned(&room_account));
+        room
+    }
+
+    /// The player's stake on the north door of an `OLD_SEASON` room, with
+    /// `escrowed` tokens in the job's escrow. Returns the room and the stake.
+    fn put_stake(&mut self, x: i8, y: i8, escrowed: u64) -> (Pubkey, Pubkey) {
+        let room = RoomAccount::find_address(OLD_SEASON, 2, x, y).0;
+        let (stake, stake_bump) = Pubkey::find_program_address(
+            &[
+                HelperStake::SEED_PREFIX,
+                room.as_ref(),
+                &[DIRECTION_NORTH],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        let helper_stake = HelperStake {
+            player: self.wallet,
+            room,
+            bump: stake_bump,
+            ..helper(100, 100)
+        };
+        self.put_room(x, y, std::slice::from_ref(&helper_stake));
+        self.runtime
+            .set_account(stake, TestAccount::program_owned(&helper_stake));
+        self.put_escrow(room, escrowed);
+        (room, stake)
+    }
+
+    fn put_escrow(&mut self, room: Pubkey, escrowed: u64) {
+        self.runtime.set_account(
+            Self::escrow(room),
+            TestAccount::token_account(self.skr_mint, Self::escrow(room), escrowed),
+        );
+    }
+
+    /// A funded wallet to crank closes from
+    fn closer(&mut self) -> Pubkey {
+        let closer = Pubkey::new_unique();
+        self.runtime
+            .set_account(closer, TestAccount::wallet(1_000_000));
+        closer
+    }
+
+    fn lamports(&self, key: &Pubkey) -> u64 {
+        self.runtime.account(key).lamports
+    }
+
+    fn escrow(room: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"escrow", room.as_ref(), &[DIRECTION_NORTH]],
+            &chaindepth::ID,
+        )
+        .0
+    }
+
+    fn recover_stale_stake(&mut self, room: Pubkey, season_seed: u64) -> Result<(), ProgramError> {
+        let player_token_account = Pubkey::new_unique();
+        self.runtime.set_account(
+            player_token_account,
+            TestAccount::token_account(self.skr_mint, self.wallet, 0),
+        );
+        let accounts = accounts::RecoverStaleStake {
+            authority: self.wallet,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            room,
+            escrow: Self::escrow(room),
+            helper_stake: Pubkey::find_program_address(
+                &[
+                    HelperStake::SEED_PREFIX,
+                    room.as_ref(),
+                    &[DIRECTION_NORTH],
+                    self.wallet.as_ref(),
+                ],
+                &chaindepth::ID,
+            )
+            .0,
+            player_token_account,
+            session_authority: None,
+            token_program: spl_token::ID,
+        };
+        self.runtime.process(
+            accounts,
+            instruction::RecoverStaleStake {
+                season_seed,
+                direction: DIRECTION_NORTH,
+            },
+        )
+    }
+
+    /// A profile and a presence in the current start room from an
+    /// earlier visit: the test runtime cannot create accounts, so
+    /// `respawn_stale_player` takes its `init_if_needed` path for an existing one.
+    fn respawn_stale_player(&mut self) -> Result<(), ProgramError> {
+        let (profile, profile_bump) = Pubkey::find_program_address(
+            &[PlayerProfile::SEED_PREFIX, self.wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            profile,
+            TestAccount::program_owned(&PlayerProfile {
+                owner: self.wallet,
+                skin_id: 7,
+                display_name: String::new(),
+                starter_pickaxe_granted: true,
+                bump: profile_bump,
+            }),
+        );
+
+        let start_presence = self.start_presence();
+        if self.runtime.account(&start_presence).data.is_empty() {
+            let layout = SeasonLayout::default();
+            let (_, presence_bump) = self.start_presence_address();
+            self.runtime.set_account(
+                start_presence,
+                TestAccount::program_owned(&RoomPresence {
+                    player: self.wallet,
+                    season_seed: self.global_season,
+                    room_floor: ENTRANCE_FLOOR,
+                    room_x: layout.start_x,
+                    room_y: layout.start_y,
+                    skin_id: 0,
+                    equipped_item_id: 0,
+                    activity: RoomPresence::ACTIVITY_DOOR_JOB,
+                    activity_direction: DIRECTION_NORTH,
+                    is_current: false,
+                    bump: presence_bump,
+                }),
+            );
+        }
+
+        let accounts = accounts::RespawnStalePlayer {
+            authority: self.wallet,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            profile,
+            start_presence,
+            session_authority: None,
+            system_program: system_program::ID,
+        };
+        self.runtime
+            .process(accounts, instruction::RespawnStalePlayer {})
+    }
+
+    fn start_presence_address(&self) -> (Pubkey, u8) {
+        let layout = SeasonLayout::default();
+        Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &self.global_season.to_le_bytes(),
+                &[ENTRANCE_FLOOR],
+                &[layout.start_x as u8],
+                &[layout.start_y as u8],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        )
+    }
+
+    fn start_presence(&self) -> Pubkey {
+        self.start_presence_address().0
+    }
+
+    fn close_stale_room(&mut self, closer: Pubkey, room: Pubkey) -> Result<(), ProgramError> {
+        let accounts = accounts::CloseStaleRoom {
+            closer,
+            global: self.global,
+            room,
+        };
+        self.runtime
+            .process(accounts, instruction::CloseStaleRoom {})
+    }
+
+    /// The player's presence in an `OLD_SEASON` room
+    fn put_stale_presence(&mut self, x: i8, y: i8) -> Pubkey {
+        let (presence, bump) = Pubkey::find_program_address(
+            &[
+                RoomPresence::SEED_PREFIX,
+                &OLD_SEASON.to_le_bytes(),
+                &[2],
+                &[x as u8],
+                &[y as u8],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            presence,
+            TestAccount::program_owned(&RoomPresence {
+                player: self.wallet,
+                season_seed: OLD_SEASON,
+                room_floor: 2,
+                room_x: x,
+                room_y: y,
+                skin_id: 0,
+                equipped_item_id: 0,
+                activity: RoomPresence::ACTIVITY_IDLE,
+                activity_direction: 255,
+                is_current: true,
+                bump,
+            }),
+        );
+        presence
+    }
+
+    fn close_stale_presence(
+        &mut self,
+        closer: Pubkey,
+        room_presence: Pubkey,
+    ) -> Result<(), ProgramError> {
+        let accounts = accounts::CloseStalePresence {
+            closer,
+            global: self.global,
+            room_presence,
+        };
+        self.runtime
+            .process(accounts, instruction::CloseStalePresence {})
+    }
+
+    /// The player's revealed chest receipt for an `OLD_SEASON` room
+    fn put_stale_loot_receipt(&mut self, x: i8, y: i8) -> Pubkey {
+        let (loot_receipt, bump) = Pubkey::find_program_address(
+            &[
+                LootReceipt::SEED_PREFIX,
+                &OLD_SEASON.to_le_bytes(),
+                &[2],
+                &[x as u8],
+                &[y as u8],
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            loot_receipt,
+            TestAccount::program_owned(&LootReceipt {
+                player: self.wallet,
+                season_seed: OLD_SEASON,
+                room_floor: 2,
+                room_x: x,
+                room_y: y,
+                kind: loot_kinds::CHEST,
+                source_id: 0,
+                commit_slot: 100,
+                damage_share_bps: 0,
+                last_hitter: false,
+                revealed: true,
+                bump,
+            }),
+        );
+        loot_receipt
+    }
+
+    /// Ends the current season and runs `reset_season` with the same layout
+    fn reset_season(&mut self) {
+        let admin = Pubkey::new_unique();
+        let mut global: GlobalAccount = self.runtime.fetch(&self.global);
+        global.admin = admin;
+        global.end_slot = CLOCK_SLOT;
+        self.runtime
+            .set_account(self.global, TestAccount::program_owned(&global));
+
+        let accounts = accounts::ResetSeason {
+            authority: admin,
+            global: self.global,
+        };
+        self.runtime
+            .process(accounts, instruction::ResetSeason { layout: None })
+            .unwrap();
+    }
+
+    /// Runs `reveal_loot` for the player's receipt once its slot hash has
+    /// left the window, so it settles with `EXPIRED_LOOT_DROP`. Returns the
+    /// player's inventory.
+    fn reveal_expired_loot(&mut self, loot_receipt: Pubkey) -> Result<Pubkey, ProgramError> {
+        let (inventory, inventory_bump) = Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, self.wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            inventory,
+            TestAccount::program_owned(&InventoryAccount {
+                owner: self.wallet,
+                items: Vec::new(),
+                bump: inventory_bump,
+            }),
+        );
+
+        // A single entry newer than every reveal slot used here
+        let mut slot_hashes_data = 1u64.to_le_bytes().to_vec();
+        slot_hashes_data.extend_from_slice(&CLOCK_SLOT.to_le_bytes());
+        slot_hashes_data.extend_from_slice(&[7; 32]);
+        self.runtime.set_account(
+            slot_hashes::ID,
+            TestAccount {
+                lamports: 1,
+                owner: sysvar::ID,
+                data: slot_hashes_data,
+                executable: false,
+            },
+        );
+
+        let (_, _, item_id) = EXPIRED_LOOT_DROP;
+        let (item_definition, item_definition_bump) = ItemDefinition::find_address(item_id);
+        self.runtime.set_account(
+            item_definition,
+            TestAccount::program_owned(&ItemDefinition {
+                item_id,
+                stats: ItemStats {
+                    category: item_categories::VALUABLE,
+                    score_value: 1,
+                    base_durability: 0,
+                    combat_dps: 0,
+                    mining_speed: 0,
+                    stackable: true,
+                    tradeable: true,
+                },
+                bump: item_definition_bump,
+            }),
+        );
+
+        let accounts = accounts::RevealLoot {
+            revealer: Pubkey::new_unique(),
+            player: self.wallet,
+            global: self.global,
+            loot_receipt,
+            inventory,
+            slot_hashes: slot_hashes::ID,
+        };
+        self.runtime.process_with_remaining_accounts(
+            accounts,
+            &[AccountMeta::new_readonly(item_definition, false)],
+            instruction::RevealLoot {},
+        )?;
+        Ok(inventory)
+    }
+
+    /// Gives the player one Rusted Compass and one Mystic Orb and uses each.
+    /// Returns the compass and orb results and the player's inventory.
+    fn use_scouting_items(
+        &mut self,
+    ) -> (Result<(), ProgramError>, Result<(), ProgramError>, Pubkey) {
+        let (inventory, inventory_bump) = Pubkey::find_program_address(
+            &[InventoryAccount::SEED_PREFIX, self.wallet.as_ref()],
+            &chaindepth::ID,
+        );
+        let items = [item_ids::RUSTED_COMPASS, item_ids::MYSTIC_ORB]
+            .into_iter()
+            .map(|item_id| InventoryItem {
+                item_id,
+                amount: 1,
+                durability: 0,
+                score_value: 0,
+            })
+            .collect();
+        self.runtime.set_account(
+            inventory,
+            TestAccount::program_owned(&InventoryAccount {
+                owner: self.wallet,
+                items,
+                bump: inventory_bump,
+            }),
+        );
+
+        let compass = accounts::UseRustedCompass {
+            authority: self.wallet,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            inventory,
+            session_authority: None,
+        };
+        let compass = self
+            .runtime
+            .process(compass, instruction::UseRustedCompass {});
+
+        let player = self.player();
+        let (target_room, _) = RoomAccount::find_address(
+            self.global_season,
+            player.current_floor,
+            player.current_room_x,
+            player.current_room_y + 1,
+        );
+        let orb = accounts::UseMysticOrb {
+            authority: self.wallet,
+            player: self.wallet,
+            global: self.global,
+            player_account: self.player_account,
+            inventory,
+            target_room,
+            session_authority: None,
+        };
+        let orb = self.runtime.process(
+            orb,
+            instruction::UseMysticOrb {
+                direction: DIRECTION_NORTH,
+            },
+        );
+        (compass, orb, inventory)
+    }
+
+    fn close_stale_loot_receipt(
+        &mut self,
+        closer: Pubkey,
+        loot_receipt: Pubkey,
+    ) -> Result<(), ProgramError> {
+        let accounts = accounts::CloseStaleLootReceipt {
+            closer,
+            global: self.global,
+            loot_receipt,
+        };
+        self.runtime
+            .process(accounts, instruction::CloseStaleLootReceipt {})
+    }
+
+    /// The player's boss fight marker in an `OLD_SEASON` room; the room
+    /// itself need not exist
+    fn put_stale_boss_fight(&mut self, x: i8, y: i8) -> Pubkey {
+        let room = RoomAccount::find_address(OLD_SEASON, 2, x, y).0;
+        let (boss_fight, bump) = Pubkey::find_program_address(
+            &[
+                BossFightAccount::SEED_PREFIX,
+                room.as_ref(),
+                self.wallet.as_ref(),
+            ],
+            &chaindepth::ID,
+        );
+        self.runtime.set_account(
+            boss_fight,
+            TestAccount::program_owned(&BossFightAccount {
+                player: self.wallet,
+                room,
+                dps: 1,
+                joined_slot: 100,
+                buff_bonus: 0,
+                buff_expires_slot: 0,
+                bump,
+            }),
+        );
+        boss_fight
+    }
+
+    fn close_stale_boss_fight(
+        &mut self,
+        closer: Pubkey,
+        boss_fight: Pubkey,
+        season_seed: u64,
+        x: i8,
+        y: i8,
+    ) -> Result<(), ProgramError> {
+        let accounts = accounts::CloseStaleBossFight {
+            closer,
+            global: self.global,
+            room: RoomAccount::find_address(season_seed, 2, x, y).0,
+            boss_fight,
+        };
+        self.runtime.process(
+            accounts,
+            instruction::CloseStaleBossFight {
+                season_seed,
+                room_floor: 2,
+                room_x: x,
+                room_y: y,
+            },
+        )
+    }
+
+    fn close_stale_escrow(
+        &mut self,
+        closer: Pubkey,
+        x: i8,
+        y: i8,
+        prize_pool: Pubkey,
+    ) -> Result<(), ProgramError> {
+        let room = RoomAccount::find_address(OLD_SEASON, 2, x, y).0;
+        let accounts = accounts::CloseStaleEscrow {
+            closer,
+            global: self.global,
+            room,
+            escrow: Self::escrow(room),
+            prize_pool,
+            token_program: spl_token::ID,
+        };
+        self.runtime.process(
+            accounts,
+            instruction::CloseStaleEscrow {
+                season_seed: OLD_SEASON,
+                room_floor: 2,
+                room_x: x,
+                room_y: y,
+                direction: DIRECTION_NORTH,
+            },
+        )
+    }
+}
+
+#[test]
+fn recover_stale_stake_is_rejected_while_the_season_is_current() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let (room, stake) = reset.put_stake(1, 8, RoomAccount::STAKE_AMOUNT);
+    let room_before = reset.runtime.account(&room);
+
+    assert_eq!(
+        reset.recover_stale_stake(room, OLD_SEASON).unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+    assert_eq!(reset.runtime.account(&room), room_before);
+    assert!(!reset.runtime.account(&stake).is_closed());
+    assert_eq!(reset.player().active_jobs.len(), 1);
+}
+
+#[test]
+fn recover_stale_stake_only_addresses_rooms_of_the_named_season() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let (room, _) = reset.put_stake(1, 8, RoomAccount::STAKE_AMOUNT);
+
+    // The room's seeds commit to its season, so a wrong season never reaches the payout.
+    assert_eq!(
+        reset.recover_stale_stake(room, NEW_SEASON + 1).unwrap_err(),
+        program_error_code(anchor_lang::error::ErrorCode::ConstraintSeeds)
+    );
+    let room_account: RoomAccount = reset.runtime.fetch(&room);
+    assert_eq!(room_account.helper_counts[NORTH], 1);
+}
+
+#[test]
+fn respawn_stale_player_resets_the_run_into_the_new_start_room() {
+    let layout = SeasonLayout::default();
+    let mut reset = Reset::new(NEW_SEASON);
+    let before = reset.player();
+
+    reset.respawn_stale_player().unwrap();
+
+    let player = reset.player();
+    assert_eq!(player.season_seed, NEW_SEASON);
+    assert!(player.is_at_room(ENTRANCE_FLOOR, layout.start_x, layout.start_y));
+    assert!(player.active_jobs.is_empty());
+    assert_eq!(player.hp, PLAYER_MAX_HP);
+    assert!(!player.is_in_boss_fight());
+    assert_eq!(player.active_buff_id, 0);
+    assert_eq!(player.buff_expires_slot, 0);
+    assert_eq!(player.current_run_start_slot, CLOCK_SLOT);
+    // Progress and items carried across seasons are untouched.
+    assert_eq!(player.total_score, before.total_score);
+    assert_eq!(player.jobs_completed, before.jobs_completed);
+    assert_eq!(player.equipped_item_id, before.equipped_item_id);
+
+    let presence: RoomPresence = reset.runtime.fetch(&reset.start_presence());
+    assert_eq!(presence.season_seed, NEW_SEASON);
+    assert_eq!(presence.skin_id, 7);
+    assert_eq!(presence.equipped_item_id, item_ids::IRON_SWORD);
+    assert!(presence.is_current);
+    assert_eq!(presence.activity, RoomPresence::ACTIVITY_IDLE);
+
+    // A second respawn in the same season is refused.
+    assert_eq!(
+        reset.respawn_stale_player().unwrap_err(),
+        program_error(ChainDepthError::PlayerAlreadyInCurrentSeason)
+    );
+}
+
+#[test]
+fn respawn_stale_player_is_rejected_while_the_season_is_current() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let before = reset.runtime.account(&reset.player_account);
+
+    assert_eq!(
+        reset.respawn_stale_player().unwrap_err(),
+        program_error(ChainDepthError::PlayerAlreadyInCurrentSeason)
+    );
+    assert_eq!(reset.runtime.account(&reset.player_account), before);
+}
+
+#[test]
+fn scouting_items_are_refused_until_the_player_respawns() {
+    let mut reset = Reset::new(NEW_SEASON);
+
+    // The old season's coordinates say nothing about the new season's floors.
+    let (compass, orb, inventory) = reset.use_scouting_items();
+    assert_eq!(
+        compass.unwrap_err(),
+        program_error(ChainDepthError::PlayerNotInCurrentSeason)
+    );
+    assert_eq!(
+        orb.unwrap_err(),
+        program_error(ChainDepthError::PlayerNotInCurrentSeason)
+    );
+    let inventory: InventoryAccount = reset.runtime.fetch(&inventory);
+    assert_eq!(inventory.items.len(), 2);
+
+    reset.respawn_stale_player().unwrap();
+    let (_, orb, inventory) = reset.use_scouting_items();
+    orb.unwrap();
+    let inventory: InventoryAccount = reset.runtime.fetch(&inventory);
+    assert!(inventory
+        .items
+        .iter()
+        .all(|item| item.item_id != item_ids::MYSTIC_ORB));
+}
+
+/// Closes `account` through `close` and checks the closer got the crank
+/// reward and the treasury the rest of its rent
+fn assert_cranked_closed(
+    reset: &mut Reset,
+    account: Pubkey,
+    close: impl FnOnce(&mut Reset, Pubkey) -> Result<(), ProgramError>,
+) {
+    let closer = reset.closer();
+    let rent = reset.lamports(&account);
+    let closer_lamports = reset.lamports(&closer);
+    let treasury_lamports = reset.lamports(&reset.global);
+
+    close(reset, closer).unwrap();
+
+    assert!(reset.runtime.account(&account).is_closed());
+    assert_eq!(
+        reset.lamports(&closer),
+        closer_lamports + STALE_CLOSE_CRANK_REWARD_LAMPORTS
+    );
+    assert_eq!(
+        reset.lamports(&reset.global),
+        treasury_lamports + rent - STALE_CLOSE_CRANK_REWARD_LAMPORTS
+    );
+}
+
+#[test]
+fn close_stale_room_pays_the_crank_and_returns_the_rent_to_the_treasury() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let room = reset.put_room(1, 8, &[]);
+
+    assert_cranked_closed(&mut reset, room, |reset, closer| {
+        reset.close_stale_room(closer, room)
+    });
+}
+
+#[test]
+fn close_stale_room_waits_for_every_stake_to_be_recovered() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let (room, _) = reset.put_stake(1, 8, RoomAccount::STAKE_AMOUNT);
+    let closer = reset.closer();
+    let room_before = reset.runtime.account(&room);
+
+    assert_eq!(
+        reset.close_stale_room(closer, room).unwrap_err(),
+        program_error(ChainDepthError::StaleStakesOutstanding)
+    );
+    assert_eq!(reset.runtime.account(&room), room_before);
+    assert_eq!(reset.lamports(&closer), 1_000_000);
+}
+
+#[test]
+fn close_stale_room_is_rejected_while_the_season_is_current() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let room = reset.put_room(1, 8, &[]);
+    let closer = reset.closer();
+
+    assert_eq!(
+        reset.close_stale_room(closer, room).unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+    assert!(!reset.runtime.account(&room).is_closed());
+}
+
+#[test]
+fn close_stale_presence_and_loot_receipt_pay_the_crank() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let presence = reset.put_stale_presence(1, 8);
+    let loot_receipt = reset.put_stale_loot_receipt(1, 8);
+
+    assert_cranked_closed(&mut reset, presence, |reset, closer| {
+        reset.close_stale_presence(closer, presence)
+    });
+    assert_cranked_closed(&mut reset, loot_receipt, |reset, closer| {
+        reset.close_stale_loot_receipt(closer, loot_receipt)
+    });
+}
+
+#[test]
+fn close_stale_presence_and_loot_receipt_are_rejected_while_the_season_is_current() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let presence = reset.put_stale_presence(1, 8);
+    let loot_receipt = reset.put_stale_loot_receipt(1, 8);
+    let closer = reset.closer();
+
+    assert_eq!(
+        reset.close_stale_presence(closer, presence).unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+    assert_eq!(
+        reset
+            .close_stale_loot_receipt(closer, loot_receipt)
+            .unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+    assert!(!reset.runtime.account(&presence).is_closed());
+    assert!(!reset.runtime.account(&loot_receipt).is_closed());
+}
+
+#[test]
+fn reveal_loot_settles_a_receipt_committed_before_the_season_reset() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let loot_receipt = reset.put_stale_loot_receipt(1, 8);
+    let mut receipt: LootReceipt = reset.runtime.fetch(&loot_receipt);
+    receipt.revealed = false;
+    reset
+        .runtime
+        .set_account(loot_receipt, TestAccount::program_owned(&receipt));
+
+    reset.reset_season();
+    let global: GlobalAccount = reset.runtime.fetch(&reset.global);
+    assert_ne!(global.season_seed, OLD_SEASON);
+
+    let inventory = reset.reveal_expired_loot(loot_receipt).unwrap();
+
+    let receipt: LootReceipt = reset.runtime.fetch(&loot_receipt);
+    assert!(receipt.revealed);
+    let (_, item_amount, item_id) = EXPIRED_LOOT_DROP;
+    let inventory: InventoryAccount = reset.runtime.fetch(&inventory);
+    assert_eq!(inventory.items.len(), 1);
+    assert_eq!(inventory.items[0].item_id, item_id);
+    assert_eq!(inventory.items[0].amount, u32::from(item_amount));
+}
+
+#[test]
+fn close_stale_boss_fight_works_after_the_room_is_gone() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let boss_fight = reset.put_stale_boss_fight(1, 8);
+    let closer = reset.closer();
+
+    assert_eq!(
+        reset
+            .close_stale_boss_fight(closer, boss_fight, OLD_SEASON, 1, 8)
+            .unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+
+    let mut reset = Reset::new(NEW_SEASON);
+    let boss_fight = reset.put_stale_boss_fight(1, 8);
+    let closer = reset.closer();
+    // The marker's seeds pin its room, so another room's coordinates miss it.
+    assert_eq!(
+        reset
+            .close_stale_boss_fight(closer, boss_fight, OLD_SEASON, 1, 7)
+            .unwrap_err(),
+        program_error_code(anchor_lang::error::ErrorCode::ConstraintSeeds)
+    );
+    assert_cranked_closed(&mut reset, boss_fight, |reset, closer| {
+        reset.close_stale_boss_fight(closer, boss_fight, OLD_SEASON, 1, 8)
+    });
+}
+
+#[test]
+fn close_stale_escrow_is_rejected_while_the_season_is_current() {
+    let mut reset = Reset::new(OLD_SEASON);
+    let room = reset.put_room(1, 8, &[]);
+    reset.put_escrow(room, 7);
+    let closer = reset.closer();
+    let prize_pool = reset.prize_pool;
+
+    assert_eq!(
+        reset
+            .close_stale_escrow(closer, 1, 8, prize_pool)
+            .unwrap_err(),
+        program_error(ChainDepthError::SeasonStillCurrent)
+    );
+    assert!(!reset.runtime.account(&Reset::escrow(room)).is_closed());
+}
+
+#[test]
+fn close_stale_escrow_waits_for_every_stake_on_its_door() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let (room, _) = reset.put_stake(1, 8, RoomAccount::STAKE_AMOUNT);
+    let closer = reset.closer();
+    let prize_pool = reset.prize_pool;
+    let escrow_before = reset.runtime.account(&Reset::escrow(room));
+
+    assert_eq!(
+        reset
+            .close_stale_escrow(closer, 1, 8, prize_pool)
+            .unwrap_err(),
+        program_error(ChainDepthError::StaleStakesOutstanding)
+    );
+    assert_eq!(reset.runtime.account(&Reset::escrow(room)), escrow_before);
+}
+
+#[test]
+fn close_stale_escrow_only_sweeps_into_the_prize_pool() {
+    let mut reset = Reset::new(NEW_SEASON);
+    let room = reset.put_room(1, 8, &[]);
+    reset.put_escrow(room, 7);
+    let closer = reset.closer();
+
+    let elsewhere = Pubkey::new_unique();
+    reset.runtime.set_account(
+        elsewhere,
+        TestAccount::token_account(reset.skr_mint, closer, 0),
+    );
+    assert_eq!(
+        reset
+            .close_stale_escrow(closer, 1, 8, elsewhere)
+            .unwrap_err(),
+        program_error_code(anchor_lang::error::ErrorCode::ConstraintRaw)
+    );
+    assert!(!reset.runtime.account(&Reset::escrow(room)).is_closed());
+}
@@ -15,6 +15,7 @@ export const AIRDROP_AMOUNT_LAMPORTS = 2_000_000_000n; // 2 SOL
 // Game configuration
 export const START_X = 5;
 export const START_Y = 5;
+export const ENTRANCE_FLOOR = 0;
 export const INITIAL_PRIZE_POOL_AMOUNT = 100 * SKR_MULTIPLIER; // 100 SKR
 export const DEFAULT_MINT_AMOUNT = 1000 * SKR_MULTIPLIER; // 1000 SKR
 export const DEFAULT_TEST_MINT_AMOUNT = 10 * SKR_MULTIPLIER; // 10 SKR
@@ -33,6 +34,10 @@ export const DEVNET_WS_URL = "wss://api.devnet.solana.com";
 export const GLOBAL_SEED = "global";
 export const PRIZE_POOL_SEED = "prize_pool";
 export const ROOM_SEED = "room";
+export const ITEM_DEF_SEED = "item_def";
+
+// Stats init-devnet registers as ItemDefinition accounts, relative to scripts/
+export const ITEM_CATALOG_PATH = "../programs/chaindepth/item-catalog.json";
 
 // Program ID (from Anchor.toml / deployed program)
 export const PROGRAM_ID = "3Ctc2FgnNHQtGAcZftMS4ykLhJYjLzBD3hELKy55DnKo";
@@ -35,7 +35,7 @@ async function main(): Promise<void> {
   console.log("Before end slot:", before.endSlot.toString());
 
   const signature = await program.methods
-    .forceResetSeason()
+    .forceResetSeason(null)
     .accountsPartial({
       authority: provider.wallet.publicKey,
       global: globalPda,
@@ -53,8 +53,9 @@ async function main(): Promise<void> {
         [
           Buffer.from("room"),
           afterReset.seasonSeed.toArrayLike(Buffer, "le", 8),
-          Buffer.from([5]),
-          Buffer.from([5]),
+          Buffer.from([0]), // entrance floor
+          Buffer.from([afterReset.layout.startX]),
+          Buffer.from([afterReset.layout.startY]),
         ],
         program.programId
       )[0],
@@ -5,6 +5,7 @@
  * 1. Creates a mock SKR token on devnet
  * 2. Initializes the global game state
  * 3. Funds the prize pool
+ * 4. Registers an item definition for every item in the item catalog
  *
  * Prerequisites:
  * - Solana CLI configured for devnet
@@ -37,11 +38,14 @@ import {
   AIRDROP_AMOUNT_LAMPORTS,
   START_X,
   START_Y,
+  ENTRANCE_FLOOR,
   INITIAL_PRIZE_POOL_AMOUNT,
   DEFAULT_MINT_AMOUNT,
   GLOBAL_SEED,
   PRIZE_POOL_SEED,
   ROOM_SEED,
+  ITEM_DEF_SEED,
+  ITEM_CATALOG_PATH,
   DEVNET_RPC_URL,
 } from "./constants";
 
@@ -147,6 +151,7 @@ async function main(): Promise<void> {
   const { pda: startRoomPda } = await connection.getPDAAndBump(programAddress, [
     ROOM_SEED,
     seasonSeed,
+    ENTRANCE_FLOOR,
     START_X,
     START_Y,
   ]);
@@ -167,7 +172,7 @@ async function main(): Promise<void> {
 
   try {
     const tx = await program.methods
-      .initGlobal(initialPrizePool, seasonSeedBN)
+      .initGlobal(initialPrizePool, seasonSeedBN, null)
       .accountsPartial({
         admin: adminPublicKey,
         global: globalPdaPubkey,
@@ -208,7 +213,52 @@ async function main(): Promise<void> {
     }
   }
 
-  // Step 5: Output configuration summary
+  // Step 5: Register every catalog item; the program has no stats of its own
+  console.log("\n--- Step 5: Registering item definitions ---");
+
+  const itemCatalog: {
+    items: {
+      itemId: number;
+      name: string;
+      category: number;
+      scoreValue: number;
+      baseDurability: number;
+      combatDps: number;
+      miningSpeed: number;
+      stackable: boolean;
+      tradeable: boolean;
+    }[];
+  } = JSON.parse(fs.readFileSync(path.join(__dirname, ITEM_CATALOG_PATH), "utf-8"));
+
+  for (const item of itemCatalog.items) {
+    const itemIdBytes = Buffer.alloc(2);
+    itemIdBytes.writeUInt16LE(item.itemId);
+    const [itemDefinitionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from(ITEM_DEF_SEED), itemIdBytes],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .upsertItemDefinition(item.itemId, {
+        category: item.category,
+        scoreValue: new anchor.BN(item.scoreValue),
+        baseDurability: item.baseDurability,
+        combatDps: new anchor.BN(item.combatDps),
+        miningSpeed: item.miningSpeed,
+        stackable: item.stackable,
+        tradeable: item.tradeable,
+      })
+      .accountsPartial({
+        admin: adminPublicKey,
+        global: globalPdaPubkey,
+        itemDefinition: itemDefinitionPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`✅ ${item.name} (${item.itemId}) registered:`, tx);
+  }
+
+  // Step 6: Output configuration summary
   console.log("\n=== Configuration Summary ===");
   console.log("\nAdd these to your Unity project:");
   console.log(`PROGRAM_ID=${program.programId.toBase58()}`);
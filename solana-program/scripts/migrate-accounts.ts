@@ -0,0 +1,158 @@
+/**
+ * Grow the global account and every player account, helper stake and
+ * inventory written by the launch program to the current layout (admin only). Run after upgrading the program
+ * in place; accounts already migrated are skipped.
+ *
+ * Usage:
+ *   npm run migrate-accounts
+ *
+ * Environment:
+ *   ANCHOR_PROVIDER_URL=https://api.devnet.solana.com
+ *   ANCHOR_WALLET=devnet-wallet.json
+ */
+
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import type { Chaindepth } from "../target/types/chaindepth";
+
+// Account sizes (discriminator included) the launch program allocated
+const LAUNCH_GLOBAL_SPACE = 133;
+const LAUNCH_PLAYER_SPACE = 117;
+const LAUNCH_HELPER_STAKE_SPACE = 90;
+const LAUNCH_INVENTORY_SPACE = 557;
+
+// PlayerAccount.owner follows the 8-byte discriminator
+const PLAYER_OWNER_OFFSET = 8;
+
+// InventoryAccount.owner follows the 8-byte discriminator
+const INVENTORY_OWNER_OFFSET = 8;
+
+// HelperStake.player, .room and .direction follow the 8-byte discriminator
+const STAKE_PLAYER_OFFSET = 8;
+const STAKE_ROOM_OFFSET = 40;
+const STAKE_DIRECTION_OFFSET = 72;
+
+async function main(): Promise<void> {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Chaindepth as Program<Chaindepth>;
+  const connection = provider.connection;
+  const [globalPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("global")],
+    program.programId
+  );
+
+  console.log("=== Migrate Accounts ===");
+  console.log("Program:", program.programId.toBase58());
+  console.log("Admin:", provider.wallet.publicKey.toBase58());
+  console.log("Global PDA:", globalPda.toBase58());
+
+  const globalInfo = await connection.getAccountInfo(globalPda, "confirmed");
+  if (globalInfo === null) {
+    throw new Error("Global account not found");
+  }
+  if (globalInfo.data.length === LAUNCH_GLOBAL_SPACE) {
+    const signature = await program.methods
+      .migrateGlobal()
+      .accountsPartial({
+        admin: provider.wallet.publicKey,
+        global: globalPda,
+      })
+      .rpc();
+    console.log("Migrated global:", signature);
+  } else {
+    console.log("Global already migrated");
+  }
+
+  const launchPlayers = await connection.getProgramAccounts(program.programId, {
+    commitment: "confirmed",
+    filters: [
+      { memcmp: program.coder.accounts.memcmp("playerAccount") },
+      { dataSize: LAUNCH_PLAYER_SPACE },
+    ],
+  });
+  console.log("Player accounts to migrate:", launchPlayers.length);
+
+  for (const { pubkey, account } of launchPlayers) {
+    const player = new anchor.web3.PublicKey(
+      account.data.subarray(PLAYER_OWNER_OFFSET, PLAYER_OWNER_OFFSET + 32)
+    );
+    const signature = await program.methods
+      .migratePlayer()
+      .accountsPartial({
+        admin: provider.wallet.publicKey,
+        global: globalPda,
+        player,
+        playerAccount: pubkey,
+      })
+      .rpc();
+    console.log("Migrated", pubkey.toBase58(), signature);
+  }
+
+  const launchStakes = await connection.getProgramAccounts(program.programId, {
+    commitment: "confirmed",
+    filters: [
+      { memcmp: program.coder.accounts.memcmp("helperStake") },
+      { dataSize: LAUNCH_HELPER_STAKE_SPACE },
+    ],
+  });
+  console.log("Helper stakes to migrate:", launchStakes.length);
+
+  for (const { pubkey, account } of launchStakes) {
+    const player = new anchor.web3.PublicKey(
+      account.data.subarray(STAKE_PLAYER_OFFSET, STAKE_PLAYER_OFFSET + 32)
+    );
+    const room = new anchor.web3.PublicKey(
+      account.data.subarray(STAKE_ROOM_OFFSET, STAKE_ROOM_OFFSET + 32)
+    );
+    const direction = account.data[STAKE_DIRECTION_OFFSET];
+    const signature = await program.methods
+      .migrateHelperStake(direction)
+      .accountsPartial({
+        admin: provider.wallet.publicKey,
+        global: globalPda,
+        room,
+        player,
+        helperStake: pubkey,
+      })
+      .rpc();
+    console.log("Migrated", pubkey.toBase58(), signature);
+  }
+
+  const launchInventories = await connection.getProgramAccounts(program.programId, {
+    commitment: "confirmed",
+    filters: [
+      { memcmp: program.coder.accounts.memcmp("inventoryAccount") },
+      { dataSize: LAUNCH_INVENTORY_SPACE },
+    ],
+  });
+  console.log("Inventories to migrate:", launchInventories.length);
+
+  for (const { pubkey, account } of launchInventories) {
+    const player = new anchor.web3.PublicKey(
+      account.data.subarray(INVENTORY_OWNER_OFFSET, INVENTORY_OWNER_OFFSET + 32)
+    );
+    const signature = await program.methods
+      .migrateInventory()
+      .accountsPartial({
+        admin: provider.wallet.publicKey,
+        global: globalPda,
+        player,
+        inventory: pubkey,
+      })
+      .rpc();
+    console.log("Migrated", pubkey.toBase58(), signature);
+  }
+
+  console.log("Run `npm run force-reset-season` to start a season on the new layout.");
+}
+
+main().catch((thrownObject: unknown) => {
+  const error =
+    thrownObject instanceof Error
+      ? thrownObject
+      : new Error(`Non-Error thrown: ${String(thrownObject)}`);
+  console.error("Error:", error.message);
+  process.exit(1);
+});
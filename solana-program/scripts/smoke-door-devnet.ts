@@ -42,6 +42,7 @@ const derivePlayerPda = function (
 const deriveRoomPda = function (
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number,
 ): anchor.web3.PublicKey {
@@ -49,6 +50,7 @@ const deriveRoomPda = function (
     [
       Buffer.from("room"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x & 0xff]),
       Buffer.from([y & 0xff]),
     ],
@@ -134,6 +136,7 @@ async function main(): Promise<void> {
   const roomPda = deriveRoomPda(
     program.programId,
     globalAccount.seasonSeed,
+    playerAccount.currentFloor,
     playerAccount.currentRoomX,
     playerAccount.currentRoomY,
   );
@@ -203,6 +206,7 @@ async function main(): Promise<void> {
   const adjacentRoomPda = deriveRoomPda(
     program.programId,
     globalAccount.seasonSeed,
+    playerAccount.currentFloor,
     adjX,
     adjY,
   );
@@ -241,6 +245,7 @@ async function main(): Promise<void> {
         escrow: escrowPda,
         helperStake: helperStakePda,
         playerTokenAccount,
+        prizePool: globalAccount.prizePool,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
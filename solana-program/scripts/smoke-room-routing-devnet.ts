@@ -9,6 +9,7 @@ const DIRECTION_SOUTH = 1;
 const DIRECTION_EAST = 2;
 const DIRECTION_WEST = 3;
 const WALL_OPEN = 2;
+const ENTRANCE_FLOOR = 0;
 
 const SESSION_MOVE_PLAYER_BIT = 1 << 6;
 const SESSION_FUNDING_LAMPORTS = 200_000_000;
@@ -75,6 +76,7 @@ const deriveProfilePda = function (
 const deriveRoomPda = function (
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number,
 ): anchor.web3.PublicKey {
@@ -82,6 +84,7 @@ const deriveRoomPda = function (
     [
       Buffer.from("room"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x & 0xff]),
       Buffer.from([y & 0xff]),
     ],
@@ -92,6 +95,7 @@ const deriveRoomPda = function (
 const deriveRoomPresencePda = function (
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number,
   player: anchor.web3.PublicKey,
@@ -100,6 +104,7 @@ const deriveRoomPresencePda = function (
     [
       Buffer.from("presence"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x & 0xff]),
       Buffer.from([y & 0xff]),
       player.toBuffer(),
@@ -178,6 +183,7 @@ const loadRoomContext = async function (
   const roomPda = deriveRoomPda(
     program.programId,
     seasonSeed,
+    playerAccount.currentFloor,
     playerAccount.currentRoomX,
     playerAccount.currentRoomY,
   );
@@ -209,6 +215,7 @@ const moveWithSession = async function (
   const currentPresencePda = deriveRoomPresencePda(
     program.programId,
     seasonSeed,
+    context.playerAccount.currentFloor,
     context.playerAccount.currentRoomX,
     context.playerAccount.currentRoomY,
     walletPubkey,
@@ -216,6 +223,7 @@ const moveWithSession = async function (
   const targetPresencePda = deriveRoomPresencePda(
     program.programId,
     seasonSeed,
+    context.playerAccount.currentFloor,
     targetCoordinates.x,
     targetCoordinates.y,
     walletPubkey,
@@ -223,12 +231,14 @@ const moveWithSession = async function (
   const currentRoomPda = deriveRoomPda(
     program.programId,
     seasonSeed,
+    context.playerAccount.currentFloor,
     context.playerAccount.currentRoomX,
     context.playerAccount.currentRoomY,
   );
   const targetRoomPda = deriveRoomPda(
     program.programId,
     seasonSeed,
+    context.playerAccount.currentFloor,
     targetCoordinates.x,
     targetCoordinates.y,
   );
@@ -290,6 +300,7 @@ async function main(): Promise<void> {
         roomPresence: deriveRoomPresencePda(
           program.programId,
           globalAccount.seasonSeed,
+          ENTRANCE_FLOOR,
           5,
           5,
           walletPubkey,
@@ -21,6 +21,7 @@ const SESSION_DURATION_SLOTS = 5_000;
 const SESSION_DURATION_SECONDS = 3_600;
 const START_ROOM_X = 5;
 const START_ROOM_Y = 5;
+const ENTRANCE_FLOOR = 0;
 const MAX_ROOM_SEARCH_STEPS = 24;
 
 type ActiveJob = {
@@ -64,6 +65,7 @@ const derivePlayerPda = function (
 const deriveRoomPda = function (
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number,
 ): anchor.web3.PublicKey {
@@ -71,6 +73,7 @@ const deriveRoomPda = function (
     [
       Buffer.from("room"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x & 0xff]),
       Buffer.from([y & 0xff]),
     ],
@@ -91,6 +94,7 @@ const deriveProfilePda = function (
 const deriveRoomPresencePda = function (
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number,
   player: anchor.web3.PublicKey,
@@ -99,6 +103,7 @@ const deriveRoomPresencePda = function (
     [
       Buffer.from("presence"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x & 0xff]),
       Buffer.from([y & 0xff]),
       player.toBuffer(),
@@ -205,6 +210,7 @@ const loadPlayerRoomContext = async function (
   const roomPda = deriveRoomPda(
     program.programId,
     seasonSeed,
+    playerAccount.currentFloor,
     playerAccount.currentRoomX,
     playerAccount.currentRoomY,
   );
@@ -230,6 +236,7 @@ async function main(): Promise<void> {
   const startRoomPresencePda = deriveRoomPresencePda(
     program.programId,
     globalAccount.seasonSeed,
+    ENTRANCE_FLOOR,
     START_ROOM_X,
     START_ROOM_Y,
     walletPubkey,
@@ -367,6 +374,7 @@ async function main(): Promise<void> {
     const currentPresencePda = deriveRoomPresencePda(
       program.programId,
       globalAccount.seasonSeed,
+      context.playerAccount.currentFloor,
       context.playerAccount.currentRoomX,
       context.playerAccount.currentRoomY,
       walletPubkey,
@@ -374,6 +382,7 @@ async function main(): Promise<void> {
     const targetPresencePda = deriveRoomPresencePda(
       program.programId,
       globalAccount.seasonSeed,
+      context.playerAccount.currentFloor,
       targetCoordinates.x,
       targetCoordinates.y,
       walletPubkey,
@@ -381,12 +390,14 @@ async function main(): Promise<void> {
     const currentRoomPda = deriveRoomPda(
       program.programId,
       globalAccount.seasonSeed,
+      context.playerAccount.currentFloor,
       context.playerAccount.currentRoomX,
       context.playerAccount.currentRoomY,
     );
     const targetRoomPda = deriveRoomPda(
       program.programId,
       globalAccount.seasonSeed,
+      context.playerAccount.currentFloor,
       targetCoordinates.x,
       targetCoordinates.y,
     );
@@ -439,6 +450,7 @@ async function main(): Promise<void> {
   const roomPresencePda = deriveRoomPresencePda(
     program.programId,
     globalAccount.seasonSeed,
+    context.playerAccount.currentFloor,
     context.playerAccount.currentRoomX,
     context.playerAccount.currentRoomY,
     walletPubkey,
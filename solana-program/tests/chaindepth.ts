@@ -31,6 +31,7 @@ describe("chaindepth", () => {
   let playerPda: anchor.web3.PublicKey;
 
   // Constants
+  const ENTRANCE_FLOOR = 0;
   const START_X = 5;
   const START_Y = 5;
   const DIRECTION_NORTH = 0;
@@ -115,6 +116,7 @@ describe("chaindepth", () => {
         [
           Buffer.from("room"),
           seasonSeed.toArrayLike(Buffer, "le", 8),
+          Buffer.from([ENTRANCE_FLOOR]),
           Buffer.from([START_X]),
           Buffer.from([START_Y]),
         ],
@@ -123,7 +125,7 @@ describe("chaindepth", () => {
 
       try {
         await program.methods
-          .initGlobal(initialPrizePool, seasonSeed)
+          .initGlobal(initialPrizePool, seasonSeed, null)
           .accounts({
             admin: admin.publicKey,
             global: globalPda,
@@ -169,6 +171,246 @@ describe("chaindepth", () => {
       // Actual test would require proper account setup
     });
   });
+
+  describe("recall and revive items", () => {
+    const PHOENIX_FEATHER = 218;
+    const VOID_SHARD = 219;
+    const USE_VOID_SHARD_BIT = new anchor.BN(1).shln(22);
+    const USE_PHOENIX_FEATHER_BIT = new anchor.BN(1).shln(23);
+
+    let seasonSeed: anchor.BN;
+    let startX: number;
+    let startY: number;
+    let profilePda: anchor.web3.PublicKey;
+    let inventoryPda: anchor.web3.PublicKey;
+
+    const presencePda = (floor: number, x: number, y: number) =>
+      deriveRoomPresencePda(program.programId, seasonSeed, floor, x, y, player.publicKey)[0];
+
+    const itemAmount = async (itemId: number) => {
+      const inventory = await program.account.inventoryAccount.fetch(inventoryPda);
+      return inventory.items
+        .filter((item) => item.itemId === itemId)
+        .reduce((total, item) => total + item.amount, 0);
+    };
+
+    const itemDefinitionPda = (itemId: number) =>
+      deriveItemDefinitionPda(program.programId, itemId)[0];
+
+    const addItem = (itemId: number) =>
+      program.methods
+        .addInventoryItem(itemId, 1, 0)
+        .accountsPartial({
+          player: player.publicKey,
+          inventory: inventoryPda,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: itemDefinitionPda(itemId), isSigner: false, isWritable: false },
+        ])
+        .signers([player])
+        .rpc();
+
+    const beginSession = async (instructionAllowlist: anchor.BN) => {
+      const sessionKey = anchor.web3.Keypair.generate();
+      const [sessionAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("session"), player.publicKey.toBuffer(), sessionKey.publicKey.toBuffer()],
+        program.programId
+      );
+      const slot = await provider.connection.getSlot();
+      await program.methods
+        .beginSession(
+          new anchor.BN(slot + 1_000),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 3_600),
+          instructionAllowlist,
+          new anchor.BN(0)
+        )
+        .accountsPartial({
+          player: player.publicKey,
+          sessionKey: sessionKey.publicKey,
+          playerAccount: playerPda,
+          global: globalPda,
+          playerTokenAccount,
+          sessionAuthority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player, sessionKey])
+        .rpc();
+      return { sessionKey, sessionAuthority };
+    };
+
+    const usePhoenixFeather = (
+      authority: anchor.web3.Keypair,
+      sessionAuthority: anchor.web3.PublicKey | null
+    ) =>
+      program.methods
+        .usePhoenixFeather()
+        .accountsPartial({
+          authority: authority.publicKey,
+          player: player.publicKey,
+          playerAccount: playerPda,
+          inventory: inventoryPda,
+          sessionAuthority,
+        })
+        .signers([authority])
+        .rpc();
+
+    const expectProgramError = async (call: Promise<unknown>, code: string) => {
+      try {
+        await call;
+      } catch (e: any) {
+        expect(e.error?.errorCode?.code).to.equal(code);
+        return;
+      }
+      expect.fail(`expected ${code}`);
+    };
+
+    before(async () => {
+      const airdrop = await provider.connection.requestAirdrop(
+        player.publicKey,
+        2 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdrop);
+
+      // Valuables from item-catalog.json: they stack and can be traded.
+      for (const [itemId, scoreValue] of [
+        [PHOENIX_FEATHER, 18],
+        [VOID_SHARD, 16],
+      ]) {
+        await program.methods
+          .upsertItemDefinition(itemId, {
+            category: 2,
+            scoreValue: new anchor.BN(scoreValue),
+            baseDurability: 0,
+            combatDps: new anchor.BN(0),
+            miningSpeed: 0,
+            stackable: true,
+            tradeable: true,
+          })
+          .accountsPartial({
+            admin: admin.publicKey,
+            global: globalPda,
+            itemDefinition: itemDefinitionPda(itemId),
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+      }
+
+      const globalAccount = await program.account.globalAccount.fetch(globalPda);
+      seasonSeed = globalAccount.seasonSeed;
+      startX = globalAccount.layout.startX;
+      startY = globalAccount.layout.startY;
+      [profilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("profile"), player.publicKey.toBuffer()],
+        program.programId
+      );
+      [inventoryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("inventory"), player.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initPlayer()
+        .accountsPartial({
+          player: player.publicKey,
+          global: globalPda,
+          playerAccount: playerPda,
+          profile: profilePda,
+          roomPresence: presencePda(ENTRANCE_FLOOR, startX, startY),
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+    });
+
+    it("use_void_shard closes the presence it leaves and creates the entrance presence", async () => {
+      await addItem(VOID_SHARD);
+
+      // The start room is open on every side; stepping north closes the entrance presence.
+      const startPresence = presencePda(ENTRANCE_FLOOR, startX, startY);
+      const northPresence = presencePda(ENTRANCE_FLOOR, startX, startY + 1);
+      await program.methods
+        .movePlayer(startX, startY + 1)
+        .accountsPartial({
+          authority: player.publicKey,
+          player: player.publicKey,
+          global: globalPda,
+          playerAccount: playerPda,
+          profile: profilePda,
+          currentRoom: deriveRoomPda(program.programId, seasonSeed, ENTRANCE_FLOOR, startX, startY)[0],
+          targetRoom: deriveRoomPda(program.programId, seasonSeed, ENTRANCE_FLOOR, startX, startY + 1)[0],
+          currentPresence: startPresence,
+          targetPresence: northPresence,
+          sessionAuthority: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(startPresence)).to.be.null;
+
+      const treasuryBefore = await provider.connection.getBalance(globalPda);
+      const presenceRent = await provider.connection.getBalance(northPresence);
+
+      await program.methods
+        .useVoidShard()
+        .accountsPartial({
+          authority: player.publicKey,
+          player: player.publicKey,
+          global: globalPda,
+          playerAccount: playerPda,
+          inventory: inventoryPda,
+          currentPresence: northPresence,
+          startPresence,
+          sessionAuthority: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([player])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(northPresence)).to.be.null;
+      expect(await provider.connection.getBalance(globalPda)).to.equal(
+        treasuryBefore + presenceRent
+      );
+
+      const presence = await program.account.roomPresence.fetch(startPresence);
+      expect(presence.isCurrent).to.equal(true);
+      expect([presence.roomFloor, presence.roomX, presence.roomY]).to.deep.equal([
+        ENTRANCE_FLOOR,
+        startX,
+        startY,
+      ]);
+
+      const playerAccount = await program.account.playerAccount.fetch(playerPda);
+      expect([
+        playerAccount.currentFloor,
+        playerAccount.currentRoomX,
+        playerAccount.currentRoomY,
+      ]).to.deep.equal([ENTRANCE_FLOOR, startX, startY]);
+      expect(await itemAmount(VOID_SHARD)).to.equal(0);
+    });
+
+    it("use_phoenix_feather needs its session bit and protects loot once", async () => {
+      await addItem(PHOENIX_FEATHER);
+
+      const recallOnly = await beginSession(USE_VOID_SHARD_BIT);
+      await expectProgramError(
+        usePhoenixFeather(recallOnly.sessionKey, recallOnly.sessionAuthority),
+        "SessionInstructionNotAllowed"
+      );
+      expect(await itemAmount(PHOENIX_FEATHER)).to.equal(1);
+
+      const featherOnly = await beginSession(USE_PHOENIX_FEATHER_BIT);
+      await usePhoenixFeather(featherOnly.sessionKey, featherOnly.sessionAuthority);
+      expect((await program.account.playerAccount.fetch(playerPda)).lootProtected).to.equal(true);
+      expect(await itemAmount(PHOENIX_FEATHER)).to.equal(0);
+
+      // A second feather is refused until the protection is spent.
+      await addItem(PHOENIX_FEATHER);
+      await expectProgramError(usePhoenixFeather(player, null), "LootAlreadyProtected");
+      expect(await itemAmount(PHOENIX_FEATHER)).to.equal(1);
+    });
+  });
 });
 
 // Helper functions for deriving PDAs
@@ -192,6 +434,7 @@ export function derivePlayerPda(
 export function deriveRoomPda(
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  floor: number,
   x: number,
   y: number
 ): [anchor.web3.PublicKey, number] {
@@ -199,6 +442,7 @@ export function deriveRoomPda(
     [
       Buffer.from("room"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
       Buffer.from([x]),
       Buffer.from([y]),
     ],
@@ -230,6 +474,7 @@ export function derivePrizePoolPda(
 export function deriveLootReceiptPda(
   programId: anchor.web3.PublicKey,
   seasonSeed: anchor.BN,
+  roomFloor: number,
   roomX: number,
   roomY: number,
   playerPubkey: anchor.web3.PublicKey
@@ -238,6 +483,7 @@ export function deriveLootReceiptPda(
     [
       Buffer.from("loot_receipt"),
       seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([roomFloor]),
       Buffer.from([roomX]),
       Buffer.from([roomY]),
       playerPubkey.toBuffer(),
@@ -245,3 +491,36 @@ export function deriveLootReceiptPda(
     programId
   );
 }
+
+export function deriveRoomPresencePda(
+  programId: anchor.web3.PublicKey,
+  seasonSeed: anchor.BN,
+  floor: number,
+  x: number,
+  y: number,
+  playerPubkey: anchor.web3.PublicKey
+): [anchor.web3.PublicKey, number] {
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("presence"),
+      seasonSeed.toArrayLike(Buffer, "le", 8),
+      Buffer.from([floor]),
+      Buffer.from([x]),
+      Buffer.from([y]),
+      playerPubkey.toBuffer(),
+    ],
+    programId
+  );
+}
+
+export function deriveItemDefinitionPda(
+  programId: anchor.web3.PublicKey,
+  itemId: number
+): [anchor.web3.PublicKey, number] {
+  const itemIdBytes = Buffer.alloc(2);
+  itemIdBytes.writeUInt16LE(itemId);
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("item_def"), itemIdBytes],
+    programId
+  );
+}
@@ -0,0 +1,181 @@
+//! Offline season map generator.
+//!
+//! Runs the program's room generation for a season seed over every room of
+//! every floor and prints an ASCII map with per-ring stats, or a JSON dump
+//! with `--json`. Nothing touches the network.
+
+mod render;
+mod simulate;
+
+use std::process::ExitCode;
+
+use chaindepth::state::{generation_modes, SeasonLayout};
+
+use render::{ascii_map, generation_mode_name, season_json, stats_report, LEGEND};
+use simulate::simulate_season;
+
+const USAGE: &str = "usage: dungeon-sim <season_seed> [options]
+
+options:
+  --width <n>        grid width (default 10)
+  --height <n>       grid height (default 10)
+  --start-x <x>      spawn x (default 5)
+  --start-y <y>      spawn y (default 5)
+  --mode <mode>      connected | independent (default connected)
+  --floors <n>       number of floors (default 3)
+  --max-locked-doors <n>
+                     locked doors per room on floor 0, one more below (default 1)
+  --json             print a JSON dump instead of the maps";
+
+struct Args {
+    season_seed: u64,
+    layout: SeasonLayout,
+    json: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut season_seed = None;
+    let mut layout = SeasonLayout::default();
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} needs a value"))
+                .cloned()
+        };
+        match arg.as_str() {
+            "--width" => layout.width = parse_number("--width", &value("--width")?)?,
+            "--height" => layout.height = parse_number("--height", &value("--height")?)?,
+            "--start-x" => layout.start_x = parse_number("--start-x", &value("--start-x")?)?,
+            "--start-y" => layout.start_y = parse_number("--start-y", &value("--start-y")?)?,
+            "--floors" => layout.floor_count = parse_number("--floors", &value("--floors")?)?,
+            "--max-locked-doors" => {
+                layout.max_locked_doors =
+                    parse_number("--max-locked-doors", &value("--max-locked-doors")?)?
+            }
+            "--mode" => {
+                layout.generation_mode = match value("--mode")?.as_str() {
+                    "connected" => generation_modes::CONNECTED,
+                    "independent" => generation_modes::INDEPENDENT,
+                    other => return Err(format!("unknown mode `{other}`")),
+                }
+            }
+            "--json" => json = true,
+            "-h" | "--help" => return Err(String::new()),
+            other if other.starts_with("--") => return Err(format!("unknown option `{other}`")),
+            other if season_seed.is_none() => {
+                season_seed = Some(parse_number("season_seed", other)?);
+            }
+            other => return Err(format!("unexpected argument `{other}`")),
+        }
+    }
+
+    let season_seed = season_seed.ok_or("missing season_seed")?;
+    if !layout.is_valid() {
+        return Err(format!(
+            "invalid layout: {}x{} grid ({}..={} per side) with spawn ({}, {}) inside, \
+             spawn y > 0, at least one floor and at most 3 locked doors per room",
+            layout.width,
+            layout.height,
+            SeasonLayout::MIN_DIMENSION,
+            SeasonLayout::MAX_DIMENSION,
+            layout.start_x,
+            layout.start_y
+        ));
+    }
+
+    Ok(Args {
+        season_seed,
+        layout,
+        json,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{name}: `{value}` is not a valid number"))
+}
+
+fn main() -> ExitCode {
+    let raw_args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse_args(&raw_args) {
+        Ok(args) => args,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("error: {message}\n");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let layout = args.layout;
+    let floors = simulate_season(args.season_seed, &layout);
+
+    if args.json {
+        let dump = season_json(args.season_seed, &layout, &floors);
+        println!("{}", serde_json::to_string_pretty(&dump).unwrap());
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "season {} | {}x{} grid, spawn ({}, {}) | {} | {} floor(s)\n",
+        args.season_seed,
+        layout.width,
+        layout.height,
+        layout.start_x,
+        layout.start_y,
+        generation_mode_name(layout.generation_mode),
+        layout.floor_count,
+    );
+    for map in &floors {
+        println!(
+            "floor {} (base depth {}, boss chance {}%, up to {} locked door(s) per room)",
+            map.floor, map.params.base_depth, map.params.boss_percent, map.params.max_locked_doors
+        );
+        println!("{}", ascii_map(map, &layout));
+        println!("{}", stats_report(map, &layout));
+    }
+    println!("{LEGEND}");
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Result<Args, String> {
+        parse_args(&raw.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parses_layout_overrides() {
+        let parsed = args(&[
+            "99",
+            "--width",
+            "20",
+            "--start-x",
+            "0",
+            "--mode",
+            "independent",
+            "--json",
+        ])
+        .unwrap();
+        assert_eq!(parsed.season_seed, 99);
+        assert_eq!(parsed.layout.width, 20);
+        assert_eq!(parsed.layout.start_x, 0);
+        assert_eq!(parsed.layout.generation_mode, generation_modes::INDEPENDENT);
+        assert!(parsed.json);
+    }
+
+    #[test]
+    fn rejects_missing_seed_and_bad_layouts() {
+        assert!(args(&[]).is_err());
+        assert!(args(&["1", "--start-y", "0"]).is_err());
+        assert!(args(&["1", "--width", "wide"]).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use chaindepth::state::{
+    generation_modes, SeasonLayout, CENTER_BOSS, CENTER_CHEST, CENTER_EMPTY, CENTER_STAIRS_DOWN,
+    DIRECTION_EAST, DIRECTION_NORTH, DIRECTION_SOUTH, DIRECTION_WEST, LOCK_KIND_BOSS_SEAL,
+    LOCK_KIND_EMERALD, LOCK_KIND_NONE, LOCK_KIND_RUBY, LOCK_KIND_SAPPHIRE, LOCK_KIND_SKELETON,
+    WALL_ENTRANCE_STAIRS, WALL_LOCKED, WALL_OPEN, WALL_RUBBLE, WALL_SOLID,
+};
+use serde_json::{json, Value};
+
+use crate::simulate::{floor_stats, FloorMap, SimRoom};
+
+pub const LEGEND: &str = "walls: # solid  % rubble  = entrance stairs  (blank) open\n\
+locks: L skeleton  R ruby  P sapphire  E emerald  X boss seal\n\
+centers: S start  C chest  K forced key chest  B boss  > stairs down  . empty  ? unreachable";
+
+/// Draw a floor as 3x3 character blocks per room, north up. Each block shows
+/// that room's own walls, so one-way edges (a wall rolled solid on one side
+/// only) stay visible.
+pub fn ascii_map(map: &FloorMap, layout: &SeasonLayout) -> String {
+    let width = usize::from(layout.width);
+    let mut out = String::new();
+
+    for y in (SeasonLayout::MIN_COORD..=layout.max_y()).rev() {
+        let row = &map.rooms[y as usize * width..(y as usize + 1) * width];
+        let mut lines = [String::new(), String::new(), String::new()];
+        for sim in row {
+            let block = room_block(sim, layout);
+            for (line, cells) in lines.iter_mut().zip(block) {
+                line.extend(cells);
+            }
+        }
+        for line in lines {
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn room_block(sim: &SimRoom, layout: &SeasonLayout) -> [[char; 3]; 3] {
+    if !sim.reachable {
+        return [[' ', ' ', ' '], [' ', '?', ' '], [' ', ' ', ' ']];
+    }
+
+    let room = &sim.room;
+    let wall = |direction: u8| {
+        wall_glyph(
+            room.walls[direction as usize],
+            room.door_lock_kinds[direction as usize],
+        )
+    };
+    let center = if layout.is_start(room.x, room.y) {
+        'S'
+    } else {
+        match room.center_type {
+            CENTER_CHEST if room.forced_key_drop => 'K',
+            CENTER_CHEST => 'C',
+            CENTER_BOSS => 'B',
+            CENTER_STAIRS_DOWN => '>',
+            _ => '.',
+        }
+    };
+
+    [
+        ['+', wall(DIRECTION_NORTH), '+'],
+        [wall(DIRECTION_WEST), center, wall(DIRECTION_EAST)],
+        ['+', wall(DIRECTION_SOUTH), '+'],
+    ]
+}
+
+fn wall_glyph(wall: u8, lock_kind: u8) -> char {
+    match (wall, lock_kind) {
+        (WALL_SOLID, _) => '#',
+        (WALL_RUBBLE, _) => '%',
+        (WALL_OPEN, _) => ' ',
+        (WALL_LOCKED, LOCK_KIND_RUBY) => 'R',
+        (WALL_LOCKED, LOCK_KIND_SAPPHIRE) => 'P',
+        (WALL_LOCKED, LOCK_KIND_EMERALD) => 'E',
+        (WALL_LOCKED, LOCK_KIND_BOSS_SEAL) => 'X',
+        (WALL_LOCKED, _) => 'L',
+        (WALL_ENTRANCE_STAIRS, _) => '=',
+        _ => '?',
+    }
+}
+
+/// Per-floor summary: totals, then the per-ring cadence table designers tune from.
+pub fn stats_report(map: &FloorMap, layout: &SeasonLayout) -> String {
+    let stats = floor_stats(map, layout);
+    let mut out = format!(
+        "reachable {}/{} rooms, max reachable depth {}, boss density {:.2}, \
+         {} chests, {} locked doors, {} rubble walls\n",
+        stats.reachable_rooms,
+        stats.rooms,
+        stats.max_reachable_depth,
+        stats.boss_density(),
+        stats.chests,
+        stats.locked_doors,
+        stats.rubble_walls,
+    );
+    match map.stairs_down {
+        Some((x, y)) => out.push_str(&format!("stairs down at ({x}, {y})\n")),
+        None => out.push_str("no stairs down (deepest floor)\n"),
+    }
+
+    out.push_str("ring depth rooms reachable bosses chests locked key_chest\n");
+    for ring in &stats.rings {
+        let key_chest = map
+            .key_chests
+            .iter()
+            .find(|chest| chest.ring == ring.ring)
+            .map(|chest| format!("({}, {})", chest.x, chest.y))
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "{:>4} {:>5} {:>5} {:>9} {:>6} {:>6} {:>6} {}\n",
+            ring.ring,
+            ring.depth,
+            ring.rooms,
+            ring.reachable_rooms,
+            ring.bosses,
+            ring.chests,
+            ring.locked_doors,
+            key_chest,
+        ));
+    }
+
+    out
+}
+
+pub fn season_json(season_seed: u64, layout: &SeasonLayout, floors: &[FloorMap]) -> Value {
+    json!({
+        "season_seed": season_seed,
+        "layout": {
+            "width": layout.width,
+            "height": layout.height,
+            "start_x": layout.start_x,
+            "start_y": layout.start_y,
+            "generation_mode": generation_mode_name(layout.generation_mode),
+            "floor_count": layout.floor_count,
+            "max_locked_doors": layout.max_locked_doors,
+        },
+        "floors": floors.iter().map(|map| floor_json(map, layout)).collect::<Vec<_>>(),
+    })
+}
+
+fn floor_json(map: &FloorMap, layout: &SeasonLayout) -> Value {
+    let stats = floor_stats(map, layout);
+    json!({
+        "floor": map.floor,
+        "base_depth": map.params.base_depth,
+        "boss_percent": map.params.boss_percent,
+        "max_locked_doors": map.params.max_locked_doors,
+        "stairs_down": map.stairs_down.map(|(x, y)| json!({ "x": x, "y": y })),
+        "forced_key_chests": map.key_chests.iter().map(|chest| json!({
+            "ring": chest.ring,
+            "x": chest.x,
+            "y": chest.y,
+        })).collect::<Vec<_>>(),
+        "stats": {
+            "rooms": stats.rooms,
+            "reachable_rooms": stats.reachable_rooms,
+            "max_reachable_depth": stats.max_reachable_depth,
+            "bosses": stats.bosses,
+            "boss_density": stats.boss_density(),
+            "chests": stats.chests,
+            "locked_doors": stats.locked_doors,
+            "rubble_walls": stats.rubble_walls,
+            "rings": stats.rings.iter().map(|ring| json!({
+                "ring": ring.ring,
+                "depth": ring.depth,
+                "rooms": ring.rooms,
+                "reachable_rooms": ring.reachable_rooms,
+                "bosses": ring.bosses,
+                "chests": ring.chests,
+                "locked_doors": ring.locked_doors,
+            })).collect::<Vec<_>>(),
+        },
+        "rooms": map.rooms.iter().map(room_json).collect::<Vec<_>>(),
+    })
+}
+
+fn room_json(sim: &SimRoom) -> Value {
+    let room = &sim.room;
+    json!({
+        "x": room.x,
+        "y": room.y,
+        "depth": sim.depth,
+        "reachable": sim.reachable,
+        "walls": room.walls.iter().map(|wall| wall_name(*wall)).collect::<Vec<_>>(),
+        "door_lock_kinds": room
+            .door_lock_kinds
+            .iter()
+            .map(|lock_kind| lock_kind_name(*lock_kind))
+            .collect::<Vec<_>>(),
+        "center": center_name(room.center_type),
+        "center_id": room.center_id,
+        "has_chest": room.has_chest,
+        "forced_key_drop": room.forced_key_drop,
+        "boss_max_hp": room.boss_max_hp,
+    })
+}
+
+fn wall_name(wall: u8) -> String {
+    match wall {
+        WALL_SOLID => "solid".to_string(),
+        WALL_RUBBLE => "rubble".to_string(),
+        WALL_OPEN => "open".to_string(),
+        WALL_LOCKED => "locked".to_string(),
+        WALL_ENTRANCE_STAIRS => "entrance_stairs".to_string(),
+        other => format!("unknown({other})"),
+    }
+}
+
+fn lock_kind_name(lock_kind: u8) -> String {
+    match lock_kind {
+        LOCK_KIND_NONE => "none".to_string(),
+        LOCK_KIND_SKELETON => "skeleton".to_string(),
+        LOCK_KIND_RUBY => "ruby".to_string(),
+        LOCK_KIND_SAPPHIRE => "sapphire".to_string(),
+        LOCK_KIND_EMERALD => "emerald".to_string(),
+        LOCK_KIND_BOSS_SEAL => "boss_seal".to_string(),
+        other => format!("unknown({other})"),
+    }
+}
+
+fn center_name(center_type: u8) -> String {
+    match center_type {
+        CENTER_EMPTY => "empty".to_string(),
+        CENTER_CHEST => "chest".to_string(),
+        CENTER_BOSS => "boss".to_string(),
+        CENTER_STAIRS_DOWN => "stairs_down".to_string(),
+        other => format!("unknown({other})"),
+    }
+}
+
+pub fn generation_mode_name(generation_mode: u8) -> String {
+    match generation_mode {
+        generation_modes::INDEPENDENT => "independent".to_string(),
+        generation_modes::CONNECTED => "connected".to_string(),
+        other => format!("unknown({other})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulate::simulate_floor;
+
+    #[test]
+    fn map_has_three_lines_per_row_and_marks_spawn() {
+        let layout = SeasonLayout::default();
+        let map = simulate_floor(7, &layout, 0);
+        let ascii = ascii_map(&map, &layout);
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), 3 * usize::from(layout.height));
+
+        // Row y = 5 is the fifth block row from the top; its middle line holds centers.
+        let start_line = lines[3 * (layout.max_y() - layout.start_y) as usize + 1];
+        assert_eq!(
+            start_line.chars().nth(3 * layout.start_x as usize + 1),
+            Some('S')
+        );
+        assert!(ascii.contains('='));
+    }
+
+    #[test]
+    fn json_lists_every_room() {
+        let layout = SeasonLayout::default();
+        let floors = vec![simulate_floor(7, &layout, 0)];
+        let dump = season_json(7, &layout, &floors);
+        assert_eq!(dump["floors"][0]["rooms"].as_array().unwrap().len(), 100);
+        assert_eq!(dump["layout"]["generation_mode"], "connected");
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use anchor_lang::prelude::Pubkey;
+use chaindepth::state::{
+    calculate_depth, enforce_special_room_topology, initialize_discovered_room,
+    initialize_start_room, select_forced_key_chest_coords, select_stairs_down_coords,
+    spanning_parent, FloorParams, RoomAccount, RoomEntrance, SeasonLayout, CENTER_BOSS,
+    CENTER_CHEST, DIRECTION_WEST, LOCK_KIND_BOSS_SEAL, WALL_ENTRANCE_STAIRS, WALL_LOCKED,
+    WALL_OPEN, WALL_RUBBLE, WALL_SOLID,
+};
+
+/// A generated room and whether players can reach it from the floor's start room.
+pub struct SimRoom {
+    pub room: RoomAccount,
+    pub depth: u32,
+    pub reachable: bool,
+}
+
+/// Forced key chest of one ring
+pub struct KeyChest {
+    pub ring: u32,
+    pub x: i8,
+    pub y: i8,
+}
+
+/// Every room of one floor, generated the way players would discover it.
+pub struct FloorMap {
+    pub floor: u8,
+    pub params: FloorParams,
+    /// Indexed by `y * width + x`
+    pub rooms: Vec<SimRoom>,
+    pub stairs_down: Option<(i8, i8)>,
+    pub key_chests: Vec<KeyChest>,
+}
+
+/// Counts over the reachable rooms of a floor, plus a per-ring breakdown.
+pub struct FloorStats {
+    pub rooms: usize,
+    pub reachable_rooms: usize,
+    pub max_reachable_depth: u32,
+    pub bosses: usize,
+    pub chests: usize,
+    pub locked_doors: usize,
+    pub rubble_walls: usize,
+    pub rings: Vec<RingStats>,
+}
+
+pub struct RingStats {
+    pub ring: u32,
+    pub depth: u32,
+    pub rooms: usize,
+    pub reachable_rooms: usize,
+    pub bosses: usize,
+    pub chests: usize,
+    pub locked_doors: usize,
+}
+
+impl FloorStats {
+    pub fn boss_density(&self) -> f64 {
+        if self.reachable_rooms == 0 {
+            return 0.0;
+        }
+        self.bosses as f64 / self.reachable_rooms as f64
+    }
+}
+
+pub fn simulate_season(season_seed: u64, layout: &SeasonLayout) -> Vec<FloorMap> {
+    (0..layout.floor_count)
+        .map(|floor| simulate_floor(season_seed, layout, floor))
+        .collect()
+}
+
+/// Explore the floor breadth-first from its start room through every wall a
+/// player could pass (open, rubble after a job, locked after a key, sealed
+/// once a boss on the sealed room's ring was reached), running the on-chain
+/// discovery code for each new room. Rooms that exploration
+/// cannot reach are still generated, entered from their spanning-tree parent,
+/// so the whole grid can be inspected.
+pub fn simulate_floor(season_seed: u64, layout: &SeasonLayout, floor: u8) -> FloorMap {
+    let width = usize::from(layout.width);
+    let index = |x: i8, y: i8| y as usize * width + x as usize;
+    let mut rooms: Vec<Option<SimRoom>> = (0..width * usize::from(layout.height))
+        .map(|_| None)
+        .collect();
+
+    let mut start_room = RoomAccount::default();
+    initialize_start_room(
+        &mut start_room,
+        season_seed,
+        layout,
+        floor,
+        Pubkey::default(),
+        0,
+        0,
+    );
+    rooms[index(layout.start_x, layout.start_y)] = Some(SimRoom {
+        room: start_room,
+        depth: calculate_depth(layout, floor, layout.start_x, layout.start_y),
+        reachable: true,
+    });
+
+    let params = FloorParams::new(season_seed, layout, floor);
+    let mut queue = VecDeque::from([layout.start()]);
+    let mut boss_rings = BTreeSet::new();
+    let mut sealed_rooms = Vec::new();
+    loop {
+        let Some((x, y)) = queue.pop_front() else {
+            // Revisit sealed rooms whose ring has a boss by now.
+            let (opened, still_sealed): (Vec<_>, Vec<_>) = sealed_rooms
+                .into_iter()
+                .partition(|(x, y)| boss_rings.contains(&layout.ring(*x, *y)));
+            if opened.is_empty() {
+                break;
+            }
+            queue.extend(opened);
+            sealed_rooms = still_sealed;
+            continue;
+        };
+        let room = &rooms[index(x, y)].as_ref().unwrap().room;
+        let (walls, lock_kinds) = (room.walls, room.door_lock_kinds);
+        for direction in 0..=DIRECTION_WEST {
+            let wall = walls[direction as usize];
+            if wall == WALL_SOLID || wall == WALL_ENTRANCE_STAIRS {
+                continue;
+            }
+            let (next_x, next_y) = RoomAccount::adjacent_coords(x, y, direction);
+            if !layout.contains(next_x, next_y) || rooms[index(next_x, next_y)].is_some() {
+                continue;
+            }
+            if wall == WALL_LOCKED
+                && lock_kinds[direction as usize] == LOCK_KIND_BOSS_SEAL
+                && !boss_rings.contains(&layout.ring(x, y))
+            {
+                sealed_rooms.push((x, y));
+                continue;
+            }
+
+            let entrance = RoomAccount::opposite_direction(direction);
+            let sim = discover_room(
+                &params,
+                layout,
+                RoomEntrance {
+                    x: next_x,
+                    y: next_y,
+                    direction: entrance,
+                },
+                true,
+            );
+            if sim.room.center_type == CENTER_BOSS {
+                boss_rings.insert(layout.ring(next_x, next_y));
+            }
+            rooms[index(next_x, next_y)] = Some(sim);
+            queue.push_back((next_x, next_y));
+        }
+    }
+
+    for y in SeasonLayout::MIN_COORD..=layout.max_y() {
+        for x in SeasonLayout::MIN_COORD..=layout.max_x() {
+            if rooms[index(x, y)].is_some() {
+                continue;
+            }
+            let (parent_x, parent_y) = spanning_parent(params.seed, layout, x, y)
+                .expect("only the start room has no parent");
+            let entrance = (0..=DIRECTION_WEST)
+                .find(|direction| {
+                    RoomAccount::adjacent_coords(x, y, *direction) == (parent_x, parent_y)
+                })
+                .unwrap();
+            rooms[index(x, y)] = Some(discover_room(
+                &params,
+                layout,
+                RoomEntrance {
+                    x,
+                    y,
+                    direction: entrance,
+                },
+                false,
+            ));
+        }
+    }
+
+    let key_chests = (1..=layout.max_ring())
+        .filter_map(|ring| {
+            let is_keyed = rooms
+                .iter()
+                .flatten()
+                .any(|sim| sim.room.forced_key_drop && layout.ring(sim.room.x, sim.room.y) == ring);
+            let (x, y) = select_forced_key_chest_coords(params.seed, layout, ring)?;
+            is_keyed.then_some(KeyChest { ring, x, y })
+        })
+        .collect();
+
+    FloorMap {
+        floor,
+        params,
+        rooms: rooms.into_iter().map(Option::unwrap).collect(),
+        stairs_down: select_stairs_down_coords(&params, layout),
+        key_chests,
+    }
+}
+
+fn discover_room(
+    params: &FloorParams,
+    layout: &SeasonLayout,
+    entrance: RoomEntrance,
+    reachable: bool,
+) -> SimRoom {
+    let mut room = RoomAccount::default();
+    initialize_discovered_room(&mut room, params, layout, entrance, Pubkey::default(), 0, 0);
+    // Same as move_player: the way in is always open.
+    room.walls[entrance.direction as usize] = WALL_OPEN;
+    enforce_special_room_topology(&mut room, layout);
+
+    SimRoom {
+        room,
+        depth: calculate_depth(layout, params.floor, entrance.x, entrance.y),
+        reachable,
+    }
+}
+
+pub fn floor_stats(map: &FloorMap, layout: &SeasonLayout) -> FloorStats {
+    let mut rings: Vec<RingStats> = (0..=layout.max_ring())
+        .map(|ring| RingStats {
+            ring,
+            depth: map.params.base_depth + ring,
+            rooms: 0,
+            reachable_rooms: 0,
+            bosses: 0,
+            chests: 0,
+            locked_doors: 0,
+        })
+        .collect();
+    let mut stats = FloorStats {
+        rooms: map.rooms.len(),
+        reachable_rooms: 0,
+        max_reachable_depth: 0,
+        bosses: 0,
+        chests: 0,
+        locked_doors: 0,
+        rubble_walls: 0,
+        rings: Vec::new(),
+    };
+
+    for sim in &map.rooms {
+        let ring = &mut rings[layout.ring(sim.room.x, sim.room.y) as usize];
+        ring.rooms += 1;
+        if !sim.reachable {
+            continue;
+        }
+
+        let is_boss = sim.room.center_type == CENTER_BOSS;
+        let is_chest = sim.room.center_type == CENTER_CHEST;
+        let locked_doors = count_walls(&sim.room, WALL_LOCKED);
+
+        ring.reachable_rooms += 1;
+        ring.bosses += usize::from(is_boss);
+        ring.chests += usize::from(is_chest);
+        ring.locked_doors += locked_doors;
+
+        stats.reachable_rooms += 1;
+        stats.max_reachable_depth = stats.max_reachable_depth.max(sim.depth);
+        stats.bosses += usize::from(is_boss);
+        stats.chests += usize::from(is_chest);
+        stats.locked_doors += locked_doors;
+        stats.rubble_walls += count_walls(&sim.room, WALL_RUBBLE);
+    }
+
+    stats.rings = rings;
+    stats
+}
+
+fn count_walls(room: &RoomAccount, wall_state: u8) -> usize {
+    room.walls
+        .iter()
+        .filter(|wall| **wall == wall_state)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chaindepth::state::{generation_modes, CENTER_STAIRS_DOWN};
+
+    #[test]
+    fn default_season_is_fully_reachable() {
+        let layout = SeasonLayout::default();
+        let floors = simulate_season(42, &layout);
+        assert_eq!(floors.len(), usize::from(layout.floor_count));
+
+        for map in &floors {
+            let stats = floor_stats(map, &layout);
+            assert_eq!(stats.reachable_rooms, stats.rooms);
+            assert_eq!(
+                stats.max_reachable_depth,
+                map.params.base_depth + layout.max_ring()
+            );
+            assert_eq!(
+                stats.rings.iter().map(|ring| ring.rooms).sum::<usize>(),
+                stats.rooms
+            );
+
+            let stairs = map
+                .rooms
+                .iter()
+                .find(|sim| sim.room.center_type == CENTER_STAIRS_DOWN)
+                .map(|sim| (sim.room.x, sim.room.y));
+            assert_eq!(stairs, map.stairs_down);
+        }
+    }
+
+    #[test]
+    fn boss_seals_never_strand_rooms() {
+        // This season once sealed floor 1's only reachable ring boss off.
+        let layout = SeasonLayout::default();
+        for map in simulate_season(11_978_766_303_238_853_878, &layout) {
+            let stats = floor_stats(&map, &layout);
+            assert_eq!(stats.reachable_rooms, stats.rooms, "floor {}", map.floor);
+        }
+    }
+
+    #[test]
+    fn unreachable_rooms_are_still_generated() {
+        let layout = SeasonLayout {
+            generation_mode: generation_modes::INDEPENDENT,
+            ..SeasonLayout::default()
+        };
+        let map = (0..100)
+            .map(|seed| simulate_floor(seed, &layout, 0))
+            .find(|map| map.rooms.iter().any(|sim| !sim.reachable))
+            .expect("independent walls strand rooms for some seed");
+
+        assert_eq!(map.rooms.len(), 100);
+        let stats = floor_stats(&map, &layout);
+        assert!(stats.reachable_rooms < stats.rooms);
+        for (index, sim) in map.rooms.iter().enumerate() {
+            assert_eq!(index, sim.room.y as usize * 10 + sim.room.x as usize);
+        }
+    }
+}